@@ -9,6 +9,18 @@ pub enum BamlFunction {
     Code(CodeRequest),
     Erc20Balance(Erc20BalanceRequest),
     Send(SendRequest),
+    Erc20Transfer(Erc20TransferRequest),
+    /// Chained query ("… then …"): run each step in order, stopping at the first failure
+    Sequence(Vec<BamlFunction>),
+    /// Declarative tool from the tools config: POST `body` to `endpoint` on the MCP server
+    Custom(CustomCall),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomCall {
+    pub tool: String,
+    pub endpoint: String,
+    pub body: serde_json::Value,
 }
 
 impl BamlFunction {
@@ -19,6 +31,9 @@ impl BamlFunction {
             BamlFunction::Code(_) => "code",
             BamlFunction::Erc20Balance(_) => "erc20_balance_of",
             BamlFunction::Send(_) => "send",
+            BamlFunction::Erc20Transfer(_) => "erc20_transfer",
+            BamlFunction::Sequence(_) => "sequence",
+            BamlFunction::Custom(_) => "custom",
         }
     }
 
@@ -29,6 +44,9 @@ impl BamlFunction {
             BamlFunction::Code(_) => "Check if address has deployed code",
             BamlFunction::Erc20Balance(_) => "Get ERC-20 token balance for holder",
             BamlFunction::Send(_) => "Send ETH from one address to another",
+            BamlFunction::Erc20Transfer(_) => "Send ERC-20 tokens from one address to another",
+            BamlFunction::Sequence(_) => "Run several functions in order",
+            BamlFunction::Custom(_) => "Call a configured MCP server endpoint",
         }
     }
 }
@@ -11,6 +11,7 @@ fn canonical_name(name: &str) -> &str {
         "IsDeployed" => "GetCode",
         "GetErc20Balance" => "GetFungibleBalance",
         "SendEth" => "SendNative",
+        "Erc20Transfer" => "TransferToken",
         other => other,
     }
 }
@@ -20,8 +21,7 @@ pub fn validate_and_to_baml_function(name: &str, input: &serde_json::Value) -> R
     match name {
         "GetNativeBalance" => {
             let who = input.get("who").and_then(|v| v.as_str()).ok_or_else(|| anyhow::anyhow!("Missing 'who' parameter"))?;
-            let who_s = who.to_string();
-            let addr_or_ens = if who_s.ends_with(".eth") { domain::AddressOrEns::from_ens(who_s) } else { domain::AddressOrEns::from_address(who_s) };
+            let addr_or_ens = domain::AddressOrEns::parse(who.to_string());
             Ok(BamlFunction::Balance(domain::BalanceRequest::new(addr_or_ens)))
         }
         "GetCode" => {
@@ -50,6 +50,22 @@ pub fn validate_and_to_baml_function(name: &str, input: &serde_json::Value) -> R
                     .build().map_err(|e| anyhow::anyhow!("{}", e))?
             ))
         }
+        "TransferToken" => {
+            let token = input.get("token").and_then(|v| v.as_str()).ok_or_else(|| anyhow::anyhow!("Missing 'token' parameter"))?;
+            let from = input.get("from").and_then(|v| v.as_str()).ok_or_else(|| anyhow::anyhow!("Missing 'from' parameter"))?;
+            let to = input.get("to").and_then(|v| v.as_str()).ok_or_else(|| anyhow::anyhow!("Missing 'to' parameter"))?;
+            let amount = input.get("amount").and_then(|v| v.as_str()).ok_or_else(|| anyhow::anyhow!("Missing 'amount' parameter"))?;
+            let simulate = input.get("simulate").and_then(|v| v.as_bool()).unwrap_or(true);
+            Ok(BamlFunction::Erc20Transfer(
+                domain::Erc20TransferRequest::builder()
+                    .token(domain::Address::new(token.to_string()))
+                    .from(domain::Address::new(from.to_string()))
+                    .to(domain::Address::new(to.to_string()))
+                    .amount(amount)
+                    .simulate(simulate)
+                    .build().map_err(|e| anyhow::anyhow!("{}", e))?
+            ))
+        }
         other => anyhow::bail!("Unknown function type: {}", other),
     }
 }
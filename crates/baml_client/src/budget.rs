@@ -0,0 +1,68 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use domain::http::TimedOut;
+
+/// Default wall-clock budget for one CLI invocation; `--timeout` overrides it
+pub const DEFAULT_TIMEOUT_SECS: u64 = 60;
+
+/// Wall-clock deadline shared by every layer that waits or retries during one CLI
+/// invocation, so stacked retries cannot add up past the user's `--timeout`.
+#[derive(Debug)]
+pub struct RetryBudget {
+    total: Duration,
+    deadline: Instant,
+}
+
+impl RetryBudget {
+    pub fn new(total: Duration) -> Arc<Self> {
+        Arc::new(Self { total, deadline: Instant::now() + total })
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.deadline.saturating_duration_since(Instant::now())
+    }
+
+    /// Run `fut` within whatever is left of the budget. Running out is a [`TimedOut`]
+    /// error for the whole operation, so callers can tell it apart with `downcast_ref`.
+    pub async fn run<T>(&self, fut: impl Future<Output = anyhow::Result<T>>) -> anyhow::Result<T> {
+        match tokio::time::timeout(self.remaining(), fut).await {
+            Ok(result) => result,
+            Err(_) => Err(TimedOut { target: "operation".to_string(), after: self.total }.into()),
+        }
+    }
+
+    /// A retry is only worth starting if its backoff leaves time for the request itself.
+    pub fn allows_retry_after(&self, delay: Duration) -> bool {
+        delay < self.remaining()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn budget_runs_down_and_refuses_long_backoffs() {
+        let budget = RetryBudget::new(Duration::from_millis(200));
+        assert!(!budget.remaining().is_zero());
+        assert!(budget.allows_retry_after(Duration::from_millis(10)));
+        assert!(!budget.allows_retry_after(Duration::from_secs(1)));
+
+        let spent = RetryBudget::new(Duration::ZERO);
+        assert!(spent.remaining().is_zero());
+        assert!(!spent.allows_retry_after(Duration::ZERO));
+    }
+
+    #[tokio::test]
+    async fn run_stops_work_that_outlives_the_budget() {
+        let budget = RetryBudget::new(Duration::from_millis(50));
+        let err = budget.run(async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        }).await.unwrap_err();
+        assert!(err.downcast_ref::<TimedOut>().is_some());
+        assert!(err.to_string().starts_with("operation timed out after"));
+    }
+}
@@ -0,0 +1,104 @@
+use std::io::{BufRead, Write};
+
+use baml_client::baml::BamlFunction;
+
+/// One line per transaction `function` would broadcast; empty when everything simulates.
+pub fn pending_broadcasts(function: &BamlFunction) -> Vec<String> {
+    match function {
+        BamlFunction::Send(req) if !req.simulate() => {
+            vec![format!("send {} ETH from {} to {}", req.amount_eth(), req.from().as_str(), req.to().as_str())]
+        }
+        BamlFunction::Erc20Transfer(req) if !req.simulate() => vec![format!(
+            "transfer {} of token {} from {} to {}",
+            req.amount(),
+            req.token().as_str(),
+            req.from().as_str(),
+            req.to().as_str()
+        )],
+        BamlFunction::Sequence(steps) => steps.iter().flat_map(pending_broadcasts).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Ask before anything broadcasts. `--dry-run` and `--yes` skip the question; without a
+/// terminal to ask on, a real broadcast is refused rather than sent unconfirmed.
+pub fn confirm_broadcasts(
+    function: &BamlFunction,
+    dry_run: bool,
+    yes: bool,
+    interactive: bool,
+    ask: impl FnOnce(&str) -> std::io::Result<bool>,
+) -> anyhow::Result<()> {
+    if dry_run || yes {
+        return Ok(());
+    }
+    let pending = pending_broadcasts(function);
+    if pending.is_empty() {
+        return Ok(());
+    }
+    if !interactive {
+        anyhow::bail!("refusing to broadcast without confirmation (stdin is not a terminal): {}; pass --yes to send", pending.join("; "));
+    }
+    let prompt = format!("About to broadcast:\n  {}\nProceed? [y/N] ", pending.join("\n  "));
+    if !ask(&prompt)? {
+        anyhow::bail!("aborted: nothing was broadcast");
+    }
+    Ok(())
+}
+
+/// Prompt on stderr (stdout may carry the `--json` document) and read a y/N answer.
+pub fn ask_stdin(prompt: &str) -> std::io::Result<bool> {
+    let mut stderr = std::io::stderr();
+    stderr.write_all(prompt.as_bytes())?;
+    stderr.flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn send(simulate: bool) -> BamlFunction {
+        BamlFunction::Send(
+            domain::SendRequest::builder()
+                .from(domain::Address::new("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".to_string()))
+                .to(domain::Address::new("0x70997970c51812dc3a010c7d01b50e0d17dc79c8".to_string()))
+                .amount_eth("1")
+                .simulate(simulate)
+                .build()
+                .unwrap(),
+        )
+    }
+
+    fn never_asked(_: &str) -> std::io::Result<bool> {
+        panic!("prompted when no prompt was expected")
+    }
+
+    #[test]
+    fn yes_and_dry_run_skip_the_prompt() {
+        confirm_broadcasts(&send(false), false, true, true, never_asked).unwrap();
+        confirm_broadcasts(&send(false), true, false, true, never_asked).unwrap();
+        confirm_broadcasts(&send(true), false, false, false, never_asked).unwrap();
+    }
+
+    #[test]
+    fn real_send_without_a_terminal_aborts() {
+        let err = confirm_broadcasts(&send(false), false, false, false, never_asked).unwrap_err().to_string();
+        assert!(err.contains("--yes") && err.contains("send 1 ETH"), "{}", err);
+    }
+
+    #[test]
+    fn prompt_lists_every_broadcast_and_no_aborts() {
+        let plan = BamlFunction::Sequence(vec![send(true), send(false)]);
+        let mut seen = String::new();
+        let err = confirm_broadcasts(&plan, false, false, true, |prompt| {
+            seen = prompt.to_string();
+            Ok(false)
+        });
+        assert!(err.is_err());
+        assert_eq!(seen.matches("send 1 ETH").count(), 1, "{}", seen);
+        confirm_broadcasts(&plan, false, false, true, |_| Ok(true)).unwrap();
+    }
+}
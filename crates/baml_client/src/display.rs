@@ -0,0 +1,35 @@
+use domain::units::{self, UnitError};
+
+/// Render integer base units for display: exact with trailing zeros trimmed when
+/// `precision` is `None`, otherwise rounded half-up to exactly `precision` places.
+pub fn format_amount(base_units: &str, decimals: u32, precision: Option<usize>) -> Result<String, UnitError> {
+    match precision {
+        Some(places) => units::format_units_rounded(base_units, decimals, places),
+        None => units::format_units(base_units, decimals),
+    }
+}
+
+pub fn format_eth(wei: &str, precision: Option<usize>) -> Result<String, UnitError> {
+    format_amount(wei, units::ETH_DECIMALS, precision)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 1.23456789 ETH
+    const WEI: &str = "1234567890000000000";
+
+    #[test]
+    fn precision_rounds_half_up() {
+        assert_eq!(format_eth(WEI, Some(4)).unwrap(), "1.2346");
+        assert_eq!(format_eth(WEI, Some(0)).unwrap(), "1");
+        assert_eq!(format_eth("1500000000000000000", Some(0)).unwrap(), "2");
+    }
+
+    #[test]
+    fn default_is_full_precision() {
+        assert_eq!(format_eth(WEI, None).unwrap(), "1.23456789");
+        assert_eq!(format_amount("1234500", 6, None).unwrap(), "1.2345");
+    }
+}
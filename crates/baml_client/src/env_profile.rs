@@ -0,0 +1,68 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+/// Env files to load in precedence order (lowest first): `.env`, then each profile
+/// from `--env-file`, or `ENV_FILE` (comma-separated) when no flag is given.
+pub fn profile_files(cli_files: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files = vec![PathBuf::from(".env")];
+    if !cli_files.is_empty() {
+        files.extend(cli_files.iter().cloned());
+    } else if let Ok(env_files) = std::env::var("ENV_FILE") {
+        files.extend(env_files.split(',').map(str::trim).filter(|f| !f.is_empty()).map(PathBuf::from));
+    }
+    files
+}
+
+/// Layer env files so later ones override earlier ones, while variables already
+/// exported in the shell always win. The default `.env` (first entry) may be
+/// missing; explicitly requested profiles must exist.
+pub fn load_env_files(files: &[PathBuf]) -> Result<()> {
+    // dotenvy never overwrites a variable that is already set, so loading the
+    // highest-precedence file first gives "later overrides earlier"
+    for (i, path) in files.iter().enumerate().rev() {
+        match dotenvy::from_path(path) {
+            Ok(()) => {}
+            Err(e) if i == 0 && is_not_found(&e) => {}
+            Err(e) => anyhow::bail!("failed to load env file {}: {}", path.display(), e),
+        }
+    }
+    Ok(())
+}
+
+fn is_not_found(err: &dotenvy::Error) -> bool {
+    matches!(err, dotenvy::Error::Io(io) if io.kind() == std::io::ErrorKind::NotFound)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn later_env_files_override_earlier_ones() {
+        let dir = std::env::temp_dir().join(format!("env-profiles-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let base = dir.join(".env");
+        let staging = dir.join(".env.staging");
+        std::fs::write(&base, "PROFILE_TEST_RPC=http://anvil\nPROFILE_TEST_ONLY_BASE=base\n").unwrap();
+        std::fs::write(&staging, "PROFILE_TEST_RPC=https://staging\n").unwrap();
+
+        load_env_files(&[base, staging]).unwrap();
+        assert_eq!(std::env::var("PROFILE_TEST_RPC").unwrap(), "https://staging");
+        assert_eq!(std::env::var("PROFILE_TEST_ONLY_BASE").unwrap(), "base");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn missing_profile_is_an_error_but_missing_default_is_not() {
+        let missing = PathBuf::from("/nonexistent/.env.prod");
+        assert!(load_env_files(&[PathBuf::from("/nonexistent/.env")]).is_ok());
+        let err = load_env_files(&[PathBuf::from("/nonexistent/.env"), missing]).unwrap_err();
+        assert!(err.to_string().contains(".env.prod"));
+    }
+
+    #[test]
+    fn cli_profiles_come_after_default_env() {
+        let files = profile_files(&[PathBuf::from(".env.staging"), PathBuf::from(".env.local")]);
+        assert_eq!(files, vec![PathBuf::from(".env"), PathBuf::from(".env.staging"), PathBuf::from(".env.local")]);
+    }
+}
@@ -0,0 +1,52 @@
+//! Pull addresses and ENS names out of free-form text, shared by the parser fallback
+//! and the mock provider.
+
+/// Strip surrounding punctuation and a trailing possessive: `(0xabc…'s),` → `0xabc…`
+fn clean_word(word: &str) -> &str {
+    let trimmed = word.trim_matches(|c: char| matches!(c, '?' | '!' | '"' | '\'' | '`' | ',' | '.' | ')' | '(' | ':' | ';'));
+    trimmed.strip_suffix("'s").or_else(|| trimmed.strip_suffix("’s")).unwrap_or(trimmed)
+}
+
+/// First well-formed 20-byte hex address, with the prefix normalized to lowercase `0x`
+/// (`0X…` is accepted) and the digits left as written.
+pub fn first_address(text: &str) -> Option<String> {
+    text.split_whitespace().map(clean_word).find_map(|word| {
+        let digits = word.strip_prefix("0x").or_else(|| word.strip_prefix("0X"))?;
+        let candidate = format!("0x{}", digits);
+        domain::looks_like_address(&candidate).then_some(candidate)
+    })
+}
+
+/// First ENS-looking name such as `vitalik.eth` or `name.cb.id`.
+pub fn first_ens(text: &str) -> Option<String> {
+    text.split_whitespace().map(clean_word).find(|word| domain::looks_like_ens(word)).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOWER: &str = "0xabc0000000000000000000000000000000000def";
+    const UPPER: &str = "0xABC0000000000000000000000000000000000DEF";
+
+    #[test]
+    fn trailing_punctuation_and_possessives_are_stripped() {
+        assert_eq!(first_address(&format!("send to {}, please", LOWER)).as_deref(), Some(LOWER));
+        assert_eq!(first_address(&format!("what is {}'s balance?", UPPER)).as_deref(), Some(UPPER));
+        assert_eq!(first_address(&format!("balance of ({})", LOWER)).as_deref(), Some(LOWER));
+    }
+
+    #[test]
+    fn uppercase_prefix_is_normalized_and_bad_hex_rejected() {
+        let shouted = format!("0X{}", &UPPER[2..]);
+        assert_eq!(first_address(&shouted).as_deref(), Some(UPPER));
+        assert_eq!(first_address("0xzzz0000000000000000000000000000000000def"), None);
+        assert_eq!(first_address("0xabc"), None);
+    }
+
+    #[test]
+    fn ens_names_skip_amounts() {
+        assert_eq!(first_ens("send 0.1 to vitalik.eth's wallet").as_deref(), Some("vitalik.eth"));
+        assert_eq!(first_ens("send 0.1 eth"), None);
+    }
+}
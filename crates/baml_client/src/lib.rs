@@ -0,0 +1,11 @@
+//! Natural-language → tool-call core shared by the CLI and the MCP server's `/chat`.
+
+pub mod baml;
+pub mod baml_bindings;
+pub mod extract;
+pub mod logging;
+pub mod parser;
+pub mod provider;
+pub mod provider_select;
+pub mod tools;
+pub mod transport;
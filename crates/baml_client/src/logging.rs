@@ -0,0 +1,49 @@
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::EnvFilter;
+
+/// Log line format chosen by `LOG_FORMAT`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    Pretty,
+    Json,
+}
+
+impl LogFormat {
+    /// `json` (any case) selects one JSON object per line; anything else, including
+    /// unset, keeps the human-readable format.
+    pub fn parse(value: Option<&str>) -> Self {
+        match value.map(|v| v.trim().to_ascii_lowercase()) {
+            Some(v) if v == "json" => Self::Json,
+            _ => Self::Pretty,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::parse(std::env::var("LOG_FORMAT").ok().as_deref())
+    }
+}
+
+/// Install the global subscriber. `RUST_LOG` filters as usual; `to_stderr` keeps
+/// stdout free for machine-readable output.
+pub fn init(format: LogFormat, to_stderr: bool) {
+    let writer = if to_stderr { BoxMakeWriter::new(std::io::stderr) } else { BoxMakeWriter::new(std::io::stdout) };
+    let builder = tracing_subscriber::fmt().with_env_filter(EnvFilter::from_default_env()).with_writer(writer);
+    match format {
+        LogFormat::Json => builder.json().init(),
+        LogFormat::Pretty => builder.init(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_json_selects_json() {
+        assert_eq!(LogFormat::parse(Some("json")), LogFormat::Json);
+        assert_eq!(LogFormat::parse(Some(" JSON ")), LogFormat::Json);
+        assert_eq!(LogFormat::parse(Some("pretty")), LogFormat::Pretty);
+        assert_eq!(LogFormat::parse(Some("yaml")), LogFormat::Pretty);
+        assert_eq!(LogFormat::parse(None), LogFormat::Pretty);
+    }
+}
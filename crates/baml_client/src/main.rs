@@ -1,17 +1,21 @@
 use clap::Parser;
+use std::io::IsTerminal;
 use tracing::info;
 
-mod baml;
+mod budget;
+mod confirm;
+mod display;
+mod env_profile;
 mod mcp;
-mod parser;
-mod provider;
-mod tools;
-mod baml_bindings;
+mod offline;
 
-use baml::BamlFunction;
+use baml_client::baml::BamlFunction;
+use baml_client::parser::{self, NlParser};
+use baml_client::provider;
+use baml_client::provider_select::ProviderKind;
+use baml_client::transport;
 use mcp::McpClient;
-use parser::NlParser;
-use provider::{MockProvider, AnthropicProvider};
+use offline::ToolBackend;
 
 #[derive(Parser)]
 #[command(name = "baml-client")]
@@ -25,6 +29,22 @@ struct Cli {
     #[arg(short, long, default_value = "http://localhost:3000")]
     server: String,
 
+    /// Retries for idempotent reads (balance/code/erc20/token lookup); sends are never retried
+    #[arg(long, default_value_t = mcp::DEFAULT_RETRIES)]
+    retries: u32,
+
+    /// Max concurrent requests to the MCP server when a command fans out
+    #[arg(long, default_value_t = mcp::DEFAULT_CONCURRENCY)]
+    concurrency: usize,
+
+    /// Decimal places for formatted ETH amounts (half-up); full precision if unset
+    #[arg(long)]
+    precision: Option<usize>,
+
+    /// Total seconds for the whole invocation, shared by every request and retry
+    #[arg(long, default_value_t = budget::DEFAULT_TIMEOUT_SECS)]
+    timeout: u64,
+
     /// Enable debug logging
     #[arg(short, long)]
     debug: bool,
@@ -33,31 +53,82 @@ struct Cli {
     #[arg(long)]
     session: Option<String>,
 
-    /// Use mock provider instead of real LLM
+    /// Use mock provider instead of real LLM (shorthand for `--provider mock`)
     #[arg(short, long)]
     mock: bool,
 
+    /// Run tools in-process against canned values instead of the MCP server; with --mock nothing leaves the process
+    #[arg(long, default_value_t = false)]
+    offline: bool,
+
+    /// LLM backend; can also set PROVIDER. Defaults to Anthropic, or OpenAI for a non-`claude*` --model
+    #[arg(long, value_enum)]
+    provider: Option<ProviderKind>,
+
+    /// LLM model; defaults to the chosen provider's default model
+    #[arg(long)]
+    model: Option<String>,
+
+    /// Read the agent system prompt from a file (overrides SYSTEM_PROMPT)
+    #[arg(long)]
+    system_prompt_file: Option<std::path::PathBuf>,
+
+    /// JSON file of declarative tools exposed to the LLM (overrides TOOLS_CONFIG)
+    #[arg(long)]
+    tools_config: Option<std::path::PathBuf>,
+
+    /// Read ETH balances as of this block number instead of latest
+    #[arg(long)]
+    block: Option<u64>,
+
     /// Simulate-only; do not broadcast state-changing transactions
     #[arg(long, default_value_t = false)]
     dry_run: bool,
 
+    /// Broadcast without asking for confirmation (needed when stdin is not a terminal)
+    #[arg(short, long, default_value_t = false)]
+    yes: bool,
+
     /// Enable bonus tools (swap, token lookup, RAG); can also set BONUS=1
     #[arg(long, default_value_t = false)]
     enable_bonus: bool,
 
+    /// Print a single machine-readable JSON object (`function`, `result`, `usage`, or `error`) to stdout
+    #[arg(long, default_value_t = false)]
+    json: bool,
+
+    /// Extra env file layered over `.env` (repeatable; later wins). Can also set ENV_FILE
+    #[arg(long = "env-file")]
+    env_files: Vec<std::path::PathBuf>,
+
     /// Enable BAML validation (schema-first). Can also set ENABLE_BAML=1
     #[arg(long, default_value_t = false)]
     enable_baml: bool,
 }
 
 #[tokio::main]
-async fn main() -> anyhow::Result<()> {
-    // Load .env so CLI runs without exporting variables in the shell
-    let _ = dotenvy::dotenv();
+async fn main() -> std::process::ExitCode {
     let cli = Cli::parse();
+    let json = cli.json;
+    match run(cli).await {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(e) => {
+            if json {
+                println!("{}", error_json(&e));
+            } else {
+                eprintln!("Error: {:?}", e);
+            }
+            std::process::ExitCode::FAILURE
+        }
+    }
+}
 
-    // Initialize logging
-    tracing_subscriber::fmt::init();
+async fn run(cli: Cli) -> anyhow::Result<()> {
+    // Load .env plus any profiles so CLI runs without exporting variables in the shell
+    env_profile::load_env_files(&env_profile::profile_files(&cli.env_files))?;
+
+    // Initialize logging; keep stdout clean for the JSON document in --json mode
+    baml_client::logging::init(baml_client::logging::LogFormat::from_env(), cli.json);
 
     // Bonus flag/env
     let bonus_env = std::env::var("BONUS").ok().map(|v| v == "1").unwrap_or(false);
@@ -73,90 +144,530 @@ async fn main() -> anyhow::Result<()> {
         info!("BAML validation enabled");
     }
 
+    // Per-request limits for the MCP client; the LLM transport reads the same variables
+    let http_timeouts = domain::http::HttpTimeouts::from_env();
+
+    // One deadline for the whole invocation, however many layers retry underneath
+    let retry_budget = budget::RetryBudget::new(std::time::Duration::from_secs(cli.timeout));
+
     info!("Processing query: {}", cli.query);
     info!("MCP server: {}", cli.server);
 
     // 3.0 Optional: load session history
     let mut _history: Vec<provider::ChatMessage> = Vec::new();
-    if let Some(session_id) = &cli.session {
-        let client = McpClient::new(cli.server.clone());
+    if let Some(session_id) = cli.session.as_ref().filter(|_| !cli.offline) {
+        let client = McpClient::new(cli.server.clone()).with_timeouts(http_timeouts).with_retries(cli.retries).with_budget(retry_budget.clone()).with_concurrency(cli.concurrency);
         if let Ok(h) = client.session_get(session_id).await { _history = h; }
     }
 
+    let system_prompt = parser::resolve_system_prompt(cli.system_prompt_file.as_deref())?;
+
     // 3.1 Parse NL input and choose BAML function
-    let function = if cli.mock {
-        let provider = MockProvider::new();
-        let parser = NlParser::new_with_baml(provider, baml_enabled);
-        parser.parse_query(&cli.query).await?
-    } else {
-        let api_key = std::env::var("ANTHROPIC_API_KEY")
-            .expect("ANTHROPIC_API_KEY environment variable required");
-        let provider = AnthropicProvider::new(api_key);
-        let parser = NlParser::new_with_baml(provider, baml_enabled);
-        parser.parse_query(&cli.query).await?
-    };
+    let kind = ProviderKind::resolve(cli.mock, cli.provider, std::env::var("PROVIDER").ok().as_deref(), cli.model.as_deref())?;
+    let model = cli.model.clone().unwrap_or_else(|| kind.default_model().to_string());
+    let provider = kind.build(|var| std::env::var(var).ok(), transport::RecordReplayTransport::from_env()?)?;
+    info!("LLM provider: {} (model {})", provider.name(), model);
+    let mut parser = NlParser::new_with_baml(provider, baml_enabled).with_system_prompt(system_prompt).with_model(model);
+    if let Some(path) = cli.tools_config.clone().or_else(|| std::env::var("TOOLS_CONFIG").ok().map(Into::into)) {
+        let mut registry = baml_client::tools::ToolRegistry::with_default_tools();
+        let count = registry.register_declarative_file(&path)?;
+        info!("Loaded {} declarative tool(s) from {}", count, path.display());
+        parser = parser.with_tool_registry(registry);
+    }
+    let session_client = cli
+        .session
+        .as_ref()
+        .filter(|_| !cli.offline)
+        .map(|id| (McpClient::new(cli.server.clone()).with_timeouts(http_timeouts).with_retries(cli.retries).with_budget(retry_budget.clone()), id.as_str()));
+    let session = session_client.as_ref().map(|(client, id)| (client, *id));
+    let function = at_block(retry_budget.run(parse_turn(&parser, session, &cli.query)).await?, cli.block);
     info!("Selected function: {}", function.name());
 
     // 3.2 Validate via BAML schema (implicit in our type system)
     info!("Function validated: {}", function.description());
 
-    // 3.3 Invoke MCP server
-    let client = McpClient::new(cli.server.clone());
-    let result = match function {
-        BamlFunction::Chat(ref text) => {
+    // 3.3 Invoke MCP server (chat-only turns never touch it, so skip the preflight)
+    let backend = if cli.offline {
+        info!("Offline: tools answer from the in-process stub toolbox");
+        ToolBackend::offline()
+    } else {
+        ToolBackend::Mcp(McpClient::new(cli.server.clone()).with_timeouts(http_timeouts).with_retries(cli.retries).with_budget(retry_budget.clone()).with_concurrency(cli.concurrency))
+    };
+    if !matches!(function, BamlFunction::Chat(_)) {
+        backend.health().await?;
+    }
+    if let BamlFunction::Chat(ref text) = function {
+        if !cli.json {
             println!("Chat: {}", text);
-            serde_json::json!({ "message": text })
         }
-        BamlFunction::Balance(ref req) => {
-            let balance = client.balance(req).await?;
-            serde_json::json!({ "balance": balance })
+    }
+    confirm::confirm_broadcasts(&function, cli.dry_run, cli.yes, std::io::stdin().is_terminal(), confirm::ask_stdin)?;
+    let progress = Progress::default();
+    let result = retry_budget
+        .run(invoke(&backend, &function, cli.dry_run, cli.precision, &progress))
+        .await
+        .map_err(|e| timeout_report(e, &progress, cli.dry_run))?;
+
+    // 3.4 Echo typed call and pretty-print JSON response
+    println!("{}", render(&function, &result, cli.json, &parser.usage())?);
+
+    // 3.5 Append turns to session if enabled
+    if let Some(session_id) = cli.session.as_ref().filter(|_| !cli.offline) {
+        let client = McpClient::new(cli.server.clone()).with_timeouts(http_timeouts).with_retries(cli.retries).with_budget(retry_budget.clone()).with_concurrency(cli.concurrency);
+        // Append user input
+        let _ = client.session_append(session_id, "user", &cli.query).await;
+        // Append assistant/tool reply summary
+        let summary = match &function {
+            BamlFunction::Chat(text) => text.clone(),
+            _ => serde_json::to_string(&result).unwrap_or_default(),
+        };
+        let _ = client.session_append(session_id, "assistant", &summary).await;
+    }
+
+    Ok(())
+}
+
+/// Parse one turn. With a session, a stored partial intent is completed from this
+/// message when it supplies any of its fields; the session then keeps the new partial
+/// intent while one is pending and clears it once the call is complete.
+async fn parse_turn<P: provider::ChatProvider>(parser: &NlParser<P>, session: Option<(&McpClient, &str)>, query: &str) -> anyhow::Result<BamlFunction> {
+    let Some((client, session_id)) = session else { return parser.parse_query(query).await };
+    let stored = client.session_partial_get(session_id).await.ok().flatten();
+    let resumed = match &stored {
+        Some(partial) => parser.resume_partial(partial, query)?,
+        None => None,
+    };
+    let function = match resumed {
+        Some(function) => function,
+        None => parser.parse_query(query).await?,
+    };
+    let pending = match &function {
+        BamlFunction::Chat(text) => parser::partial_intent(text),
+        _ => None,
+    };
+    if pending.is_some() || stored.is_some() {
+        let _ = client.session_partial_set(session_id, pending.unwrap_or(serde_json::Value::Null)).await;
+    }
+    Ok(function)
+}
+
+/// Point balance reads (including those inside a sequence) at `--block`
+fn at_block(function: BamlFunction, block: Option<u64>) -> BamlFunction {
+    match function {
+        BamlFunction::Balance(req) if block.is_some() => BamlFunction::Balance(req.with_block(block)),
+        BamlFunction::Sequence(steps) => BamlFunction::Sequence(steps.into_iter().map(|step| at_block(step, block)).collect()),
+        other => other,
+    }
+}
+
+/// Steps `invoke` has finished and the one it is waiting on. It outlives the `invoke`
+/// future, so a `--timeout` that drops the call midway can still say what went out.
+#[derive(Default)]
+struct Progress {
+    completed: std::sync::Mutex<Vec<serde_json::Value>>,
+    /// Name of the running step and the broadcasts it would make
+    in_flight: std::sync::Mutex<Option<(String, Vec<String>)>>,
+}
+
+impl Progress {
+    async fn track(&self, step: &BamlFunction, call: impl std::future::Future<Output = anyhow::Result<serde_json::Value>>) -> anyhow::Result<serde_json::Value> {
+        *self.in_flight.lock().unwrap() = Some((step.name().to_string(), confirm::pending_broadcasts(step)));
+        let result = call.await?;
+        *self.in_flight.lock().unwrap() = None;
+        self.completed.lock().unwrap().push(serde_json::json!({ "function": step.name(), "result": result.clone() }));
+        Ok(result)
+    }
+}
+
+/// Explain a `--timeout`: list the transactions already broadcast, and warn when a
+/// broadcast was in flight, since it may have reached the chain with no reply. Other
+/// errors pass through unchanged.
+fn timeout_report(err: anyhow::Error, progress: &Progress, dry_run: bool) -> anyhow::Error {
+    if err.downcast_ref::<domain::http::TimedOut>().is_none() {
+        return err;
+    }
+    let mut report = err.to_string();
+    let sent: Vec<String> = progress
+        .completed
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|step| Some(format!("{} {}", step["function"].as_str()?, step["result"]["tx_hash"].as_str().filter(|hash| !hash.is_empty())?)))
+        .collect();
+    if !sent.is_empty() {
+        report.push_str(&format!("; already broadcast: {}", sent.join(", ")));
+    }
+    if let Some((name, broadcasts)) = progress.in_flight.lock().unwrap().as_ref() {
+        if !dry_run && !broadcasts.is_empty() {
+            report.push_str(&format!("; {} was in flight and may have been broadcast, so check the sender's recent transactions before retrying", name));
+        }
+    }
+    anyhow::anyhow!(report)
+}
+
+/// Run the selected function against `backend`; `dry_run` forces sends to simulate.
+/// A sequence runs its steps in order and replies with each step's result; the first
+/// failing step stops it, and the error names that step and carries the results so far.
+/// Each step is recorded in `progress` as it starts and finishes.
+async fn invoke(backend: &ToolBackend, function: &BamlFunction, dry_run: bool, precision: Option<usize>, progress: &Progress) -> anyhow::Result<serde_json::Value> {
+    let BamlFunction::Sequence(steps) = function else {
+        return progress.track(function, invoke_step(backend, function, dry_run, precision)).await;
+    };
+    let mut done = Vec::with_capacity(steps.len());
+    for (i, step) in steps.iter().enumerate() {
+        info!("Step {}/{}: {}", i + 1, steps.len(), step.name());
+        match progress.track(step, invoke_step(backend, step, dry_run, precision)).await {
+            Ok(result) => done.push(serde_json::json!({ "function": step.name(), "result": result })),
+            Err(e) => anyhow::bail!(
+                "step {} of {} ({}) failed: {}; completed steps: {}",
+                i + 1,
+                steps.len(),
+                step.name(),
+                e,
+                serde_json::Value::Array(done)
+            ),
+        }
+    }
+    Ok(serde_json::json!({ "steps": done }))
+}
+
+async fn invoke_step(backend: &ToolBackend, function: &BamlFunction, dry_run: bool, precision: Option<usize>) -> anyhow::Result<serde_json::Value> {
+    Ok(match function {
+        BamlFunction::Chat(text) => serde_json::json!({ "message": text }),
+        BamlFunction::Balance(req) => {
+            let balance = backend.balance(req).await?;
+            let formatted = display::format_eth(&balance, precision).map_err(|e| anyhow::anyhow!("{}", e))?;
+            serde_json::json!({ "balance": balance, "formatted": formatted })
         }
-        BamlFunction::Code(ref req) => {
-            let (deployed, bytecode_len) = client.code(req).await?;
+        BamlFunction::Code(req) => {
+            let (deployed, bytecode_len) = backend.code(req).await?;
             serde_json::json!({
                 "deployed": deployed,
                 "bytecode_len": bytecode_len
             })
         }
-        BamlFunction::Erc20Balance(ref req) => {
-            let amount = client.erc20_balance_of(req).await?;
+        BamlFunction::Erc20Balance(req) => {
+            let amount = backend.erc20_balance_of(req).await?;
             serde_json::json!({ "amount": amount })
         }
-        BamlFunction::Send(ref req) => {
+        BamlFunction::Send(req) => {
             // Honor --dry-run by forcing simulate=true
             let req_overridden = domain::SendRequest::builder()
                 .from(req.from().clone())
                 .to(req.to().clone())
                 .amount_eth(req.amount_eth().to_string())
-                .simulate(cli.dry_run || req.simulate())
+                .simulate(dry_run || req.simulate())
                 .fork_block(req.fork_block())
+                .confirmations(req.confirmations())
+                .tx_type(req.tx_type())
                 .build()
                 .map_err(|e| anyhow::anyhow!("{}", e))?;
-            let tx_result = client.send(&req_overridden).await?;
-            serde_json::json!({
-                "tx_hash": tx_result.tx_hash(),
-                "success": tx_result.status().unwrap_or(false)
-            })
+            let sim_result = backend.send(&req_overridden).await?;
+            serde_json::to_value(&sim_result)?
         }
-    };
+        BamlFunction::Erc20Transfer(req) => {
+            let req_overridden = domain::Erc20TransferRequest::builder()
+                .token(req.token().clone())
+                .from(req.from().clone())
+                .to(req.to().clone())
+                .amount(req.amount())
+                .simulate(dry_run || req.simulate())
+                .build()
+                .map_err(|e| anyhow::anyhow!("{}", e))?;
+            serde_json::to_value(backend.erc20_transfer(&req_overridden).await?)?
+        }
+        BamlFunction::Custom(call) => backend.call_endpoint(&call.endpoint, &call.body).await?,
+        BamlFunction::Sequence(_) => anyhow::bail!("nested sequences are not supported"),
+    })
+}
 
-    // 3.4 Echo typed call and pretty-print JSON response
-    println!("Function: {}", function.name());
-    println!("Response: {}", serde_json::to_string_pretty(&result)?);
+/// What the CLI prints for a result: the `--json` document, or the function name and pretty response.
+fn render(function: &BamlFunction, result: &serde_json::Value, json: bool, usage: &provider::Usage) -> anyhow::Result<String> {
+    if json {
+        Ok(serde_json::to_string(&json_output(function, result.clone(), usage))?)
+    } else {
+        let mut out = format!("Function: {}\nResponse: {}", function.name(), serde_json::to_string_pretty(result)?);
+        if let Some(diff) = balance_diff(result)? {
+            out.push('\n');
+            out.push_str(&diff);
+        }
+        if let Some(fee_wei) = result.get("fee_wei").and_then(|v| v.as_str()) {
+            out.push_str(&format!("\nFee paid: {} ETH", domain::units::wei_to_eth(fee_wei)?));
+        }
+        Ok(out)
+    }
+}
 
-    // 3.5 Append turns to session if enabled
-    if let Some(session_id) = &cli.session {
-        let client = McpClient::new(cli.server.clone());
-        // Append user input
-        let _ = client.session_append(session_id, "user", &cli.query).await;
-        // Append assistant/tool reply summary
-        let summary = match &function {
-            BamlFunction::Chat(text) => text.clone(),
-            _ => serde_json::to_string(&result).unwrap_or_default(),
-        };
-        let _ = client.session_append(session_id, "assistant", &summary).await;
+/// Dry-run sends: one `address: before -> after ETH (delta)` line per projected balance change
+fn balance_diff(result: &serde_json::Value) -> anyhow::Result<Option<String>> {
+    let Some(changes) = result.get("balance_changes") else { return Ok(None) };
+    let changes: Vec<domain::BalanceChange> = serde_json::from_value(changes.clone())?;
+    let mut lines = vec!["Balance changes (projected):".to_string()];
+    for change in changes {
+        let (sign, amount) = change.delta.split_at(1);
+        lines.push(format!(
+            "  {}: {} -> {} ETH ({}{})",
+            change.address,
+            domain::units::wei_to_eth(&change.before)?,
+            domain::units::wei_to_eth(&change.after)?,
+            sign,
+            domain::units::wei_to_eth(amount)?,
+        ));
     }
+    Ok(Some(lines.join("\n")))
+}
 
-    Ok(())
+/// The `--json` document: the function name, its result, and the LLM tokens spent choosing it.
+fn json_output(function: &BamlFunction, result: serde_json::Value, usage: &provider::Usage) -> serde_json::Value {
+    serde_json::json!({ "function": function.name(), "result": result, "usage": usage })
+}
+
+/// The `--json` document for a failed run, with the error and its causes in one line.
+fn error_json(err: &anyhow::Error) -> serde_json::Value {
+    serde_json::json!({ "error": format!("{:#}", err) })
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn hello_in_json_mode_is_a_structured_chat_result() {
+        let parser = NlParser::new(provider::MockProvider::new());
+        let function = parser.parse_query("hello").await.unwrap();
+        let BamlFunction::Chat(ref text) = function else { panic!("expected chat, got {}", function.name()) };
+        let output = json_output(&function, serde_json::json!({ "message": text }), &parser.usage());
+        assert_eq!(output["function"], "chat");
+        assert_eq!(output["result"]["message"], serde_json::json!(text));
+        assert_eq!(output["usage"]["total_tokens"], 0);
+        assert!(output.get("response").is_none());
+    }
+
+    /// Replies with one canned LLM response and reports the tokens it cost
+    struct Metered(&'static str, u32);
+
+    #[async_trait::async_trait]
+    impl provider::ChatProvider for Metered {
+        async fn chat(&self, _request: provider::ChatRequest) -> anyhow::Result<provider::ChatResponse> {
+            let usage = provider::Usage { prompt_tokens: self.1, completion_tokens: 1, total_tokens: self.1 + 1 };
+            Ok(provider::ChatResponse { content: self.0.to_string(), usage: Some(usage) })
+        }
+    }
+
+    #[tokio::test]
+    async fn json_mode_prints_one_parseable_document_for_results_and_errors() {
+        let parser = NlParser::new(Metered(r#"{"function": {"type": "GetEthBalance", "who": "vitalik.eth"}}"#, 10));
+        let function = parser.parse_query("balance of vitalik.eth then balance of vitalik.eth").await.unwrap();
+        let result = invoke(&ToolBackend::offline(), &function, false, None, &Progress::default()).await.unwrap();
+        let printed = render(&function, &result, true, &parser.usage()).unwrap();
+        assert!(!printed.contains('\n'), "{}", printed);
+        let output: serde_json::Value = serde_json::from_str(&printed).unwrap();
+        assert_eq!(output["function"], "sequence");
+        assert_eq!(output["result"]["steps"][0]["result"]["balance"], offline::STUB_BALANCE_WEI);
+        assert_eq!(output["usage"]["total_tokens"], 22);
+
+        let err = anyhow::anyhow!("connection refused").context("MCP server not reachable");
+        let printed = error_json(&err).to_string();
+        let output: serde_json::Value = serde_json::from_str(&printed).unwrap();
+        assert_eq!(output["error"], "MCP server not reachable: connection refused");
+    }
+
+    #[tokio::test]
+    async fn balance_query_runs_fully_offline() {
+        let parser = NlParser::new(provider::MockProvider::new());
+        let function = parser.parse_query("What's vitalik.eth's balance?").await.unwrap();
+        assert!(matches!(function, BamlFunction::Balance(_)), "got {}", function.name());
+
+        let backend = ToolBackend::offline();
+        backend.health().await.unwrap();
+        let result = invoke(&backend, &function, false, None, &Progress::default()).await.unwrap();
+        let printed = render(&function, &result, false, &Default::default()).unwrap();
+        assert!(printed.contains(offline::STUB_BALANCE_WEI), "{}", printed);
+        assert_eq!(result["formatted"], "1");
+    }
+
+    #[tokio::test]
+    async fn dry_run_send_prints_balance_diff() {
+        let req = domain::SendRequest::builder()
+            .from(domain::Address::new("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".to_string()))
+            .to(domain::Address::new("0x70997970c51812dc3a010c7d01b50e0d17dc79c8".to_string()))
+            .amount_eth("0.5")
+            .simulate(false)
+            .build()
+            .unwrap();
+        let function = BamlFunction::Send(req);
+        let result = invoke(&ToolBackend::offline(), &function, true, None, &Progress::default()).await.unwrap();
+        let printed = render(&function, &result, false, &Default::default()).unwrap();
+        // 0.5 ETH plus 21000 gas at 1 gwei leaves the sender, the recipient gains the value
+        assert!(printed.contains("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266: 1 -> 0.499979 ETH (-0.500021)"), "{}", printed);
+        assert!(printed.contains("0x70997970c51812dc3a010c7d01b50e0d17dc79c8: 1 -> 1.5 ETH (+0.5)"), "{}", printed);
+        assert!(!printed.contains("Fee paid"), "{}", printed);
+    }
+
+    #[tokio::test]
+    async fn sequence_runs_steps_in_order_and_names_the_failing_one() {
+        let balance = domain::BalanceRequest::new(domain::AddressOrEns::parse("vitalik.eth".to_string()));
+        let function = BamlFunction::Sequence(vec![
+            BamlFunction::Balance(balance.clone()),
+            BamlFunction::Code(domain::CodeRequest::new(domain::Address::new("0x0000000000000000000000000000000000000000".to_string()))),
+        ]);
+        let result = invoke(&ToolBackend::offline(), &function, false, None, &Progress::default()).await.unwrap();
+        assert_eq!(result["steps"][0]["function"], "balance");
+        assert_eq!(result["steps"][0]["result"]["balance"], offline::STUB_BALANCE_WEI);
+        assert_eq!(result["steps"][1]["result"]["deployed"], false);
+
+        // Declarative endpoints need an MCP server, so the offline backend fails step 2
+        let failing = BamlFunction::Sequence(vec![
+            BamlFunction::Balance(balance),
+            BamlFunction::Custom(baml_client::baml::CustomCall { tool: "GetGasPrice".into(), endpoint: "/gas_price".into(), body: serde_json::json!({}) }),
+        ]);
+        let err = invoke(&ToolBackend::offline(), &failing, false, None, &Progress::default()).await.unwrap_err().to_string();
+        assert!(err.starts_with("step 2 of 2 (custom) failed"), "{}", err);
+        assert!(err.contains(offline::STUB_BALANCE_WEI), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn block_flag_reaches_every_balance_read() {
+        let server = httpmock::MockServer::start_async().await;
+        let historical = server.mock(|when, then| {
+            when.method(httpmock::Method::POST).path("/balance").json_body_partial(r#"{"block":17000000}"#);
+            then.status(200).json_body(serde_json::json!({ "balance": "42" }));
+        });
+        let balance = domain::BalanceRequest::new(domain::AddressOrEns::parse("vitalik.eth".to_string()));
+        let function = at_block(BamlFunction::Sequence(vec![BamlFunction::Balance(balance.clone()), BamlFunction::Balance(balance)]), Some(17_000_000));
+        let backend = ToolBackend::Mcp(McpClient::new(server.base_url()).with_retries(0));
+        let result = invoke(&backend, &function, false, None, &Progress::default()).await.unwrap();
+        assert_eq!(result["steps"][1]["result"]["balance"], "42");
+        historical.assert_hits(2);
+    }
+
+    /// Replies with one canned LLM response
+    struct Scripted(&'static str);
+
+    #[async_trait::async_trait]
+    impl provider::ChatProvider for Scripted {
+        async fn chat(&self, _request: provider::ChatRequest) -> anyhow::Result<provider::ChatResponse> {
+            Ok(provider::ChatResponse { content: self.0.to_string(), usage: None })
+        }
+    }
+
+    #[tokio::test]
+    async fn session_partial_intent_is_stored_then_completed() {
+        let server = httpmock::MockServer::start_async().await;
+        let client = McpClient::new(server.base_url()).with_retries(0);
+
+        // Turn 1: "send to Bob" lacks the sender and amount, so the partial is stored
+        let mut empty = server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/session/partial_intent/get");
+            then.status(200).json_body(serde_json::json!({ "partial_intent": null }));
+        });
+        let store = server.mock(|when, then| {
+            when.method(httpmock::Method::POST)
+                .path("/session/partial_intent/set")
+                .json_body(serde_json::json!({ "session_id": "s1", "intent": { "type": "SendNative", "to": "Bob" } }));
+            then.status(200).json_body(serde_json::json!({ "ok": true }));
+        });
+        let parser = NlParser::new(Scripted(r#"{"function": {"type": "SendNative", "to": "Bob"}}"#));
+        let first = parse_turn(&parser, Some((&client, "s1")), "send to Bob").await.unwrap();
+        assert!(matches!(first, BamlFunction::Chat(_)), "got {}", first.name());
+        store.assert();
+        empty.delete();
+
+        // Turn 2: the follow-up fills in the rest without asking the LLM, and the partial is cleared
+        server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/session/partial_intent/get");
+            then.status(200).json_body(serde_json::json!({ "partial_intent": { "type": "SendNative", "to": "Bob" } }));
+        });
+        let clear = server.mock(|when, then| {
+            when.method(httpmock::Method::POST)
+                .path("/session/partial_intent/set")
+                .json_body(serde_json::json!({ "session_id": "s1", "intent": null }));
+            then.status(200).json_body(serde_json::json!({ "ok": true }));
+        });
+        let parser = NlParser::new(Scripted("the LLM is not consulted"));
+        let second = parse_turn(&parser, Some((&client, "s1")), "1 ETH from Alice").await.unwrap();
+        let BamlFunction::Send(req) = second else { panic!("expected send, got {}", second.name()) };
+        assert_eq!((req.from().as_str(), req.to().as_str(), req.amount_eth()), ("Alice", "Bob", "1"));
+        clear.assert();
+    }
+
+    /// An LLM that answers long after any sensible deadline
+    struct Sleepy;
+
+    #[async_trait::async_trait]
+    impl provider::ChatProvider for Sleepy {
+        async fn chat(&self, _request: provider::ChatRequest) -> anyhow::Result<provider::ChatResponse> {
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+            Ok(provider::ChatResponse { content: "too late".to_string(), usage: None })
+        }
+    }
+
+    #[tokio::test]
+    async fn timeout_stops_a_slow_provider() {
+        let budget = budget::RetryBudget::new(std::time::Duration::from_millis(50));
+        let parser = NlParser::new(Sleepy);
+        let err = budget.run(parse_turn(&parser, None, "hello")).await.unwrap_err();
+        let err = timeout_report(err, &Progress::default(), false).to_string();
+        assert_eq!(err, "operation timed out after 0.05s");
+    }
+
+    #[tokio::test]
+    async fn timeout_mid_sequence_reports_what_was_broadcast() {
+        let server = httpmock::MockServer::start_async().await;
+        let sent = domain::SimResult {
+            mode: domain::SendMode::Broadcast,
+            tx_hash: Some("0xfeed".to_string()),
+            estimated_gas: None,
+            gas_used: Some(21000),
+            success: true,
+            estimated_seconds: None,
+            balance_changes: Vec::new(),
+            effective_gas_price: None,
+            fee_wei: None,
+        };
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).path("/send").json_body_partial(r#"{"to":"0x70997970c51812dc3a010c7d01b50e0d17dc79c8"}"#);
+            then.status(200).json_body(serde_json::to_value(&sent).unwrap());
+        });
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).path("/send").json_body_partial(r#"{"to":"0x3c44cdddb6a900fa2b585dd299e03d12fa4293bc"}"#);
+            then.status(200).delay(std::time::Duration::from_secs(5)).json_body(serde_json::to_value(&sent).unwrap());
+        });
+        let send = |to: &str| {
+            BamlFunction::Send(
+                domain::SendRequest::builder()
+                    .from(domain::Address::new("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".to_string()))
+                    .to(domain::Address::new(to.to_string()))
+                    .amount_eth("0.1")
+                    .simulate(false)
+                    .build()
+                    .unwrap(),
+            )
+        };
+        let function = BamlFunction::Sequence(vec![send("0x70997970c51812dc3a010c7d01b50e0d17dc79c8"), send("0x3c44cdddb6a900fa2b585dd299e03d12fa4293bc")]);
+        let backend = ToolBackend::Mcp(McpClient::new(server.base_url()).with_retries(0));
+        let progress = Progress::default();
+        let budget = budget::RetryBudget::new(std::time::Duration::from_millis(500));
+        let err = budget.run(invoke(&backend, &function, false, None, &progress)).await.unwrap_err();
+        let err = timeout_report(err, &progress, false).to_string();
+        assert!(err.starts_with("operation timed out after 0.5s"), "{}", err);
+        assert!(err.contains("already broadcast: send 0xfeed"), "{}", err);
+        assert!(err.contains("send was in flight and may have been broadcast"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn broadcast_send_prints_fee_paid() {
+        let req = domain::SendRequest::builder()
+            .from(domain::Address::new("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".to_string()))
+            .to(domain::Address::new("0x70997970c51812dc3a010c7d01b50e0d17dc79c8".to_string()))
+            .amount_eth("0.5")
+            .simulate(false)
+            .build()
+            .unwrap();
+        let function = BamlFunction::Send(req);
+        let result = invoke(&ToolBackend::offline(), &function, false, None, &Progress::default()).await.unwrap();
+        assert_eq!(result["fee_wei"], "21000000000000");
+        let printed = render(&function, &result, false, &Default::default()).unwrap();
+        assert!(printed.contains("Fee paid: 0.000021 ETH"), "{}", printed);
+    }
+}
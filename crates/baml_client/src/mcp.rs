@@ -1,26 +1,200 @@
 use anyhow::Result;
 use domain::*;
+use domain::http::{HttpTimeouts, TimedOut};
 use serde_json::{json, Value};
-use tracing::info;
-use crate::provider::ChatMessage;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+use crate::budget::RetryBudget;
+use baml_client::provider::ChatMessage;
 
+/// Default number of retries for idempotent reads (on top of the first attempt)
+pub const DEFAULT_RETRIES: u32 = 2;
+/// Default cap on in-flight requests when a command fans out over many inputs
+pub const DEFAULT_CONCURRENCY: usize = 8;
+const RETRY_BASE_DELAY_MS: u64 = 100;
+/// Pause before the single retry of a refused connection, for a server that is still starting
+const CONNECT_RETRY_DELAY_MS: u64 = 500;
+
+/// Failures reported by the MCP server, kept distinct from transport errors so a
+/// server-side error is never mistaken for an empty result.
+#[derive(Debug, thiserror::Error)]
+pub enum McpError {
+    #[error("{path} failed ({status}): {message}")]
+    Server { path: String, status: u16, code: Option<String>, message: String },
+
+    #[error("{path} response is missing `{field}`")]
+    MissingField { path: String, field: &'static str },
+
+    #[error(transparent)]
+    Timeout(#[from] TimedOut),
+}
+
+/// Turn a response into its JSON body, or an `McpError::Server` when the status is
+/// not a success or the body carries an `error` (either `{"error": "msg"}` or
+/// `{"error": {"code", "message"}}`).
+async fn read_json(path: &str, response: reqwest::Response) -> Result<Value> {
+    let status = response.status();
+    let text = response.text().await?;
+    let body: Option<Value> = serde_json::from_str(&text).ok();
+    let error = body.as_ref().and_then(|b| b.get("error")).filter(|e| !e.is_null());
+    if let Some(error) = error {
+        let (code, message) = match error {
+            Value::String(message) => (None, message.clone()),
+            other => (
+                other.get("code").and_then(Value::as_str).map(str::to_string),
+                other.get("message").and_then(Value::as_str).unwrap_or("unknown error").to_string(),
+            ),
+        };
+        return Err(McpError::Server { path: path.to_string(), status: status.as_u16(), code, message }.into());
+    }
+    match body {
+        Some(body) if status.is_success() => Ok(body),
+        _ => {
+            let message = if text.trim().is_empty() { status.canonical_reason().unwrap_or("error").to_string() } else { text };
+            Err(McpError::Server { path: path.to_string(), status: status.as_u16(), code: None, message }.into())
+        }
+    }
+}
+
+fn http_client(timeouts: HttpTimeouts) -> reqwest::Client {
+    reqwest::Client::builder()
+        .connect_timeout(timeouts.connect)
+        .timeout(timeouts.request)
+        .build()
+        .expect("reqwest client with timeouts")
+}
+
+fn required_str(path: &str, result: &Value, field: &'static str) -> Result<String> {
+    result[field]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| McpError::MissingField { path: path.to_string(), field }.into())
+}
+
+#[derive(Clone)]
 pub struct McpClient {
     server_url: String,
     http_client: reqwest::Client,
+    timeouts: HttpTimeouts,
+    retries: u32,
+    budget: Option<Arc<RetryBudget>>,
+    concurrency: usize,
 }
 
 impl McpClient {
     pub fn new(server_url: String) -> Self {
         Self {
             server_url,
-            http_client: reqwest::Client::new(),
+            http_client: http_client(HttpTimeouts::default()),
+            timeouts: HttpTimeouts::default(),
+            retries: DEFAULT_RETRIES,
+            budget: None,
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+
+    /// Connect and per-request timeouts; a request that hits one fails with `McpError::Timeout`
+    pub fn with_timeouts(mut self, timeouts: HttpTimeouts) -> Self {
+        self.http_client = http_client(timeouts);
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Retries apply to idempotent reads only; `/send` is always attempted once.
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Upper bound on concurrent requests for fan-out helpers such as `balances`
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Share the invocation-wide budget: requests time out when it runs out and
+    /// retries stop once the next backoff would overrun it.
+    pub fn with_budget(mut self, budget: Arc<RetryBudget>) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    fn bounded(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.budget {
+            Some(budget) => request.timeout(budget.remaining()),
+            None => request,
+        }
+    }
+
+    /// Timeouts become `McpError::Timeout`; other transport errors pass through
+    fn transport_error(&self, path: &str, e: reqwest::Error) -> anyhow::Error {
+        if e.is_timeout() {
+            return McpError::from(self.timeouts.timed_out(format!("{}{}", self.server_url, path), e.is_connect())).into();
+        }
+        e.into()
+    }
+
+    /// Send `request` within the budget. A refused connection is tried once more after a
+    /// short pause: nothing reached the server, so this is safe even for sends.
+    async fn send_request(&self, path: &str, request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        let again = request.try_clone();
+        match self.bounded(request).send().await {
+            Err(e) if e.is_connect() && !e.is_timeout() && again.is_some() => {
+                warn!("{} connection refused ({}); retrying once", path, e);
+                tokio::time::sleep(Duration::from_millis(CONNECT_RETRY_DELAY_MS)).await;
+                let again = again.expect("checked above");
+                self.bounded(again).send().await.map_err(|e| self.transport_error(path, e))
+            }
+            result => result.map_err(|e| self.transport_error(path, e)),
+        }
+    }
+
+    /// POST a read-only request, retrying with exponential backoff on connection
+    /// errors, 5xx and 429 responses.
+    async fn post_read(&self, path: &str, body: &Value) -> Result<Value> {
+        let url = format!("{}{}", self.server_url, path);
+        let mut attempt = 0;
+        loop {
+            let retryable = match self.send_request(path, self.http_client.post(&url).json(body)).await {
+                Ok(response) if response.status().is_server_error() || response.status().as_u16() == 429 => {
+                    anyhow::anyhow!("{} returned {}", path, response.status())
+                }
+                Ok(response) => return read_json(path, response).await,
+                Err(e) => e,
+            };
+            let delay = Duration::from_millis(RETRY_BASE_DELAY_MS << attempt);
+            if attempt >= self.retries {
+                return Err(retryable);
+            }
+            if self.budget.as_ref().is_some_and(|b| !b.allows_retry_after(delay)) {
+                return Err(retryable.context("retry budget exhausted"));
+            }
+            attempt += 1;
+            warn!("{} failed ({}); retry {}/{}", path, retryable, attempt, self.retries);
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Preflight: fail fast with a friendly message when the MCP server is not reachable
+    pub async fn health(&self) -> Result<()> {
+        let url = format!("{}/health", self.server_url.trim_end_matches('/'));
+        match self.send_request("/health", self.http_client.get(&url)).await {
+            Ok(response) if response.status().is_success() => Ok(()),
+            Ok(response) => anyhow::bail!(
+                "MCP server at {} failed its health check ({}); is it running?",
+                self.server_url,
+                response.status()
+            ),
+            Err(e) if matches!(e.downcast_ref::<McpError>(), Some(McpError::Timeout(_))) => Err(e),
+            Err(_) => anyhow::bail!("MCP server unreachable at {}; is it running?", self.server_url),
         }
     }
 
     pub async fn session_get(&self, session_id: &str) -> Result<Vec<ChatMessage>> {
         let url = format!("{}/session/get?session_id={}", self.server_url, urlencoding::encode(session_id));
-        let response = self.http_client.get(&url).send().await?;
-        let result: Value = response.json().await?;
+        let response = self.send_request("/session/get", self.http_client.get(&url)).await?;
+        let result = read_json("/session/get", response).await?;
         let mut turns: Vec<ChatMessage> = Vec::new();
         if let Some(arr) = result.get("turns").and_then(|v| v.as_array()) {
             for t in arr {
@@ -35,131 +209,439 @@ impl McpClient {
     }
 
     pub async fn session_append(&self, session_id: &str, role: &str, content: &str) -> Result<()> {
-        let _ = self
-            .http_client
-            .post(&format!("{}/session/append", self.server_url))
-            .json(&json!({
-                "session_id": session_id,
-                "role": role,
-                "content": content,
-            }))
-            .send()
-            .await?;
+        let request = self.http_client.post(format!("{}/session/append", self.server_url)).json(&json!({
+            "session_id": session_id,
+            "role": role,
+            "content": content,
+        }));
+        read_json("/session/append", self.send_request("/session/append", request).await?).await?;
         Ok(())
     }
 
-    #[allow(dead_code)]
     pub async fn session_partial_get(&self, session_id: &str) -> Result<Option<Value>> {
         let url = format!("{}/session/partial_intent/get?session_id={}", self.server_url, urlencoding::encode(session_id));
-        let response = self.http_client.get(&url).send().await?;
-        let result: Value = response.json().await?;
-        Ok(result.get("partial_intent").cloned())
+        let response = self.send_request("/session/partial_intent/get", self.http_client.get(&url)).await?;
+        let result = read_json("/session/partial_intent/get", response).await?;
+        Ok(result.get("partial_intent").filter(|intent| !intent.is_null()).cloned())
     }
 
-    #[allow(dead_code)]
+    /// Store the unfinished tool call; `Value::Null` clears it
     pub async fn session_partial_set(&self, session_id: &str, intent: Value) -> Result<()> {
-        let _ = self
-            .http_client
-            .post(&format!("{}/session/partial_intent/set", self.server_url))
-            .json(&json!({ "session_id": session_id, "intent": intent }))
-            .send()
-            .await?;
+        let request = self.http_client.post(format!("{}/session/partial_intent/set", self.server_url)).json(&json!({ "session_id": session_id, "intent": intent }));
+        read_json("/session/partial_intent/set", self.send_request("/session/partial_intent/set", request).await?).await?;
         Ok(())
     }
 
     pub async fn balance(&self, req: &BalanceRequest) -> Result<String> {
-        let response = self
-            .http_client
-            .post(&format!("{}/balance", self.server_url))
-            .json(&json!({
+        let result = self
+            .post_read("/balance", &json!({
                 "who": match req.who() {
                     domain::AddressOrEns::Address(addr) => addr.as_str(),
                     domain::AddressOrEns::Ens(ens) => ens.as_str(),
-                }
+                },
+                "block": req.block(),
             }))
-            .send()
             .await?;
-
-        let result: Value = response.json().await?;
         info!("Balance response: {}", serde_json::to_string_pretty(&result)?);
-        
-        Ok(result["balance"].as_str().unwrap_or("0").to_string())
+        required_str("/balance", &result, "balance")
+    }
+
+    /// Balances for many holders with at most `concurrency` requests in flight.
+    /// Results come back in input order; one failure does not affect the others.
+    #[allow(dead_code)]
+    pub async fn balances(&self, reqs: Vec<BalanceRequest>) -> Vec<Result<String>> {
+        let permits = Arc::new(tokio::sync::Semaphore::new(self.concurrency));
+        let mut tasks = tokio::task::JoinSet::new();
+        for (i, req) in reqs.into_iter().enumerate() {
+            let client = self.clone();
+            let permits = permits.clone();
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await.expect("semaphore is never closed");
+                (i, client.balance(&req).await)
+            });
+        }
+        let mut results: Vec<Option<Result<String>>> = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            let (i, result) = joined.expect("balance task panicked");
+            if results.len() <= i {
+                results.resize_with(i + 1, || None);
+            }
+            results[i] = Some(result);
+        }
+        results.into_iter().map(|r| r.expect("every index is filled")).collect()
     }
 
     pub async fn code(&self, req: &CodeRequest) -> Result<(bool, u64)> {
-        let response = self
-            .http_client
-            .post(&format!("{}/code", self.server_url))
-            .json(&json!({
+        let result = self
+            .post_read("/code", &json!({
                 "addr": req.addr().as_str()
             }))
-            .send()
             .await?;
-
-        let result: Value = response.json().await?;
         info!("Code response: {}", serde_json::to_string_pretty(&result)?);
         
-        let deployed = result["deployed"].as_bool().unwrap_or(false);
-        let bytecode_len = result["bytecode_len"].as_u64().unwrap_or(0);
+        let deployed = result["deployed"]
+            .as_bool()
+            .ok_or_else(|| McpError::MissingField { path: "/code".to_string(), field: "deployed" })?;
+        let bytecode_len = result["bytecode_len"]
+            .as_u64()
+            .ok_or_else(|| McpError::MissingField { path: "/code".to_string(), field: "bytecode_len" })?;
         Ok((deployed, bytecode_len))
     }
 
     pub async fn erc20_balance_of(&self, req: &Erc20BalanceRequest) -> Result<String> {
-        let response = self
-            .http_client
-            .post(&format!("{}/erc20_balance_of", self.server_url))
-            .json(&json!({
+        let result = self
+            .post_read("/erc20_balance_of", &json!({
                 "token": req.token().as_str(),
                 "holder": req.holder().as_str()
             }))
-            .send()
             .await?;
-
-        let result: Value = response.json().await?;
         info!("ERC20 balance response: {}", serde_json::to_string_pretty(&result)?);
-        
-        Ok(result["amount"].as_str().unwrap_or("0").to_string())
+        required_str("/erc20_balance_of", &result, "amount")
     }
 
-    pub async fn send(&self, req: &SendRequest) -> Result<TxResult> {
-        let response = self
+    pub async fn send(&self, req: &SendRequest) -> Result<SimResult> {
+        let request = self
             .http_client
-            .post(&format!("{}/send", self.server_url))
+            .post(format!("{}/send", self.server_url))
             .json(&json!({
                 "from": req.from().as_str(),
                 "to": req.to().as_str(),
                 "amount_eth": req.amount_eth(),
                 "simulate": req.simulate(),
-                "fork_block": req.fork_block()
-            }))
-            .send()
-            .await?;
+                "fork_block": req.fork_block(),
+                "confirmations": req.confirmations(),
+                "tx_type": req.tx_type()
+            }));
+        let response = self.send_request("/send", request).await?;
 
-        let result: Value = response.json().await?;
+        let result = read_json("/send", response).await?;
         info!("Send response: {}", serde_json::to_string_pretty(&result)?);
-        
-        Ok(TxResult::new(
-            result["tx_hash"].as_str().unwrap_or("").to_string(),
-            None, // gas_used
-            result["success"].as_bool(), // status
-        ))
+        Ok(serde_json::from_value(result)?)
+    }
+
+    pub async fn erc20_transfer(&self, req: &Erc20TransferRequest) -> Result<SimResult> {
+        let request = self
+            .http_client
+            .post(format!("{}/erc20_transfer", self.server_url))
+            .json(&json!({
+                "token": req.token().as_str(),
+                "from": req.from().as_str(),
+                "to": req.to().as_str(),
+                "amount": req.amount(),
+                "simulate": req.simulate()
+            }));
+        let response = self.send_request("/erc20_transfer", request).await?;
+
+        let result = read_json("/erc20_transfer", response).await?;
+        info!("ERC20 transfer response: {}", serde_json::to_string_pretty(&result)?);
+        Ok(serde_json::from_value(result)?)
+    }
+
+    /// POST a declarative tool's body to its endpoint; attempted once since the
+    /// endpoint's side effects are unknown.
+    pub async fn call_endpoint(&self, endpoint: &str, body: &Value) -> Result<Value> {
+        let path = if endpoint.starts_with('/') { endpoint.to_string() } else { format!("/{}", endpoint) };
+        let request = self.http_client.post(format!("{}{}", self.server_url, path)).json(body);
+        let response = self.send_request(&path, request).await?;
+        read_json(&path, response).await
     }
 
     // Bonus: external API token lookup (kept for BONUS workflows)
     #[cfg(feature = "bonus_uniswap_v2")]
+    #[allow(dead_code)]
     pub async fn token_lookup_address(&self, symbol: &str, chain: &str) -> Result<Option<String>> {
-        let response = self
-            .http_client
-            .post(&format!("{}/token_lookup", self.server_url))
-            .json(&json!({
+        let result = self
+            .post_read("/token_lookup", &json!({
                 "symbol": symbol,
                 "chain": chain
             }))
-            .send()
             .await?;
-
-        let result: Value = response.json().await?;
         info!("Token lookup response: {}", serde_json::to_string_pretty(&result)?);
         Ok(result["address"].as_str().map(|s| s.to_string()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn health_reports_friendly_message_when_server_is_down() {
+        // Port 1 is never bound in test environments, so the connection is refused
+        let client = McpClient::new("http://127.0.0.1:1".to_string());
+        let err = client.health().await.unwrap_err();
+        assert_eq!(err.to_string(), "MCP server unreachable at http://127.0.0.1:1; is it running?");
+    }
+
+    #[tokio::test]
+    async fn structured_error_body_is_surfaced_not_defaulted() {
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).path("/balance");
+            then.status(400).json_body(json!({ "error": { "code": "invalid_address", "message": "bad address 0x12" } }));
+        });
+        let client = McpClient::new(server.base_url()).with_retries(0);
+        let req = BalanceRequest::new(AddressOrEns::Address(Address::new("0x12".to_string())));
+        let err = client.balance(&req).await.unwrap_err();
+        match err.downcast_ref::<McpError>() {
+            Some(McpError::Server { status, code, message, .. }) => {
+                assert_eq!(*status, 400);
+                assert_eq!(code.as_deref(), Some("invalid_address"));
+                assert_eq!(message, "bad address 0x12");
+            }
+            other => panic!("expected McpError::Server, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn server_error_on_a_session_read_is_not_an_empty_history() {
+        let server = httpmock::MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/session/get");
+            then.status(500).json_body(json!({ "error": "session store unavailable" }));
+        });
+        let client = McpClient::new(server.base_url()).with_retries(0);
+        let err = client.session_get("s1").await.unwrap_err();
+        match err.downcast_ref::<McpError>() {
+            Some(McpError::Server { status, message, .. }) => {
+                assert_eq!(*status, 500);
+                assert_eq!(message, "session store unavailable");
+            }
+            other => panic!("expected McpError::Server, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn refused_connection_is_retried_once_while_the_server_starts() {
+        use std::io::{Read, Write};
+        // Reserve a free port, then leave it closed until shortly after the first attempt
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let late_server = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(CONNECT_RETRY_DELAY_MS / 5));
+            let listener = std::net::TcpListener::bind(("127.0.0.1", port)).unwrap();
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 4096];
+            let _ = stream.read(&mut request).unwrap();
+            let body = json!({ "mode": "broadcast", "tx_hash": "0xfeed", "estimated_gas": null, "gas_used": 21000, "success": true }).to_string();
+            write!(stream, "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}", body.len(), body).unwrap();
+        });
+
+        // `/send` is never retried on failure, but a refused connection never reached the server
+        let client = McpClient::new(format!("http://127.0.0.1:{}", port)).with_retries(0);
+        let req = SendRequest::builder()
+            .from(Address::new("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".to_string()))
+            .to(Address::new("0x70997970c51812dc3a010c7d01b50e0d17dc79c8".to_string()))
+            .amount_eth("0.1")
+            .simulate(false)
+            .build()
+            .unwrap();
+        let result = client.send(&req).await.unwrap();
+        assert_eq!(result.tx_hash.as_deref(), Some("0xfeed"));
+        late_server.join().unwrap();
+
+        // Still down after the retry: the error surfaces
+        let err = McpClient::new("http://127.0.0.1:1".to_string()).with_retries(0).send(&req).await.unwrap_err();
+        assert!(err.downcast_ref::<reqwest::Error>().is_some_and(|e| e.is_connect()), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn slow_server_fails_with_a_timeout_error() {
+        let server = httpmock::MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).path("/balance");
+            then.status(200).delay(Duration::from_secs(2)).json_body(json!({ "balance": "42" }));
+        });
+        let timeouts = HttpTimeouts { connect: Duration::from_secs(1), request: Duration::from_millis(200) };
+        let client = McpClient::new(server.base_url()).with_retries(0).with_timeouts(timeouts);
+        let req = BalanceRequest::new(AddressOrEns::from_ens("vitalik.eth".to_string()));
+        let err = client.balance(&req).await.unwrap_err();
+        match err.downcast_ref::<McpError>() {
+            Some(McpError::Timeout(timed_out)) => {
+                assert_eq!(timed_out.after, timeouts.request);
+                assert_eq!(timed_out.target, format!("{}/balance", server.base_url()));
+            }
+            other => panic!("expected McpError::Timeout, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn error_in_ok_body_or_missing_field_is_an_error() {
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).path("/erc20_balance_of");
+            then.status(200).json_body(json!({ "error": "token call reverted" }));
+        });
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).path("/code");
+            then.status(200).json_body(json!({ "deployed": true }));
+        });
+        let client = McpClient::new(server.base_url()).with_retries(0);
+
+        let req = Erc20BalanceRequest::new(Address::new("0xa".to_string()), Address::new("0xb".to_string()));
+        let err = client.erc20_balance_of(&req).await.unwrap_err();
+        assert!(err.to_string().contains("token call reverted"), "{}", err);
+
+        let err = client.code(&CodeRequest::new(Address::new("0xa".to_string()))).await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<McpError>(), Some(McpError::MissingField { field: "bytecode_len", .. })), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn balance_retries_after_transient_server_error() {
+        let server = httpmock::MockServer::start_async().await;
+        let failing = server.mock(|when, then| {
+            when.method(httpmock::Method::POST).path("/balance");
+            then.status(500);
+        });
+        let client = McpClient::new(server.base_url()).with_retries(2);
+        let req = BalanceRequest::new(AddressOrEns::from_ens("vitalik.eth".to_string()));
+
+        // Swap the failing mock for a healthy one while the client is backing off
+        let retrying = client.balance(&req);
+        let recover = async {
+            while failing.hits_async().await == 0 {
+                tokio::task::yield_now().await;
+            }
+            failing.delete_async().await;
+            server.mock_async(|when, then| {
+                when.method(httpmock::Method::POST).path("/balance");
+                then.status(200).json_body(json!({ "balance": "42" }));
+            }).await
+        };
+        let (balance, _ok) = tokio::join!(retrying, recover);
+        assert_eq!(balance.unwrap(), "42");
+    }
+
+    #[tokio::test]
+    async fn send_is_not_retried() {
+        let server = httpmock::MockServer::start();
+        let m = server.mock(|when, then| {
+            when.method(httpmock::Method::POST).path("/send");
+            then.status(500);
+        });
+        let client = McpClient::new(server.base_url()).with_retries(3);
+        let req = SendRequest::builder()
+            .from(Address::new("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".to_string()))
+            .to(Address::new("0x70997970c51812dc3a010c7d01b50e0d17dc79c8".to_string()))
+            .amount_eth("0.1")
+            .simulate(false)
+            .build()
+            .unwrap();
+        assert!(client.send(&req).await.is_err());
+        m.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn shared_budget_bounds_total_retry_time() {
+        let server = httpmock::MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).path("/balance");
+            then.status(503);
+        });
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).path("/code");
+            then.status(200).delay(Duration::from_secs(10)).json_body(json!({ "deployed": true }));
+        });
+        let budget = RetryBudget::new(Duration::from_secs(1));
+        // Unbounded, these retry counts alone would back off for minutes
+        let flaky = McpClient::new(server.base_url()).with_retries(12).with_budget(budget.clone());
+        let slow = McpClient::new(server.base_url()).with_retries(12).with_budget(budget.clone());
+
+        let start = std::time::Instant::now();
+        let balance = flaky.balance(&BalanceRequest::new(AddressOrEns::from_ens("vitalik.eth".to_string()))).await;
+        let code = slow.code(&CodeRequest::new(Address::new("0x0000000000000000000000000000000000000000".to_string()))).await;
+        assert!(balance.is_err());
+        assert!(code.is_err());
+        assert!(start.elapsed() < Duration::from_millis(1500), "took {:?}", start.elapsed());
+    }
+
+    #[tokio::test]
+    async fn send_parses_simulated_and_broadcast_modes() {
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).path("/send").json_body_partial(r#"{"simulate": true}"#);
+            then.status(200).json_body(json!({
+                "mode": "simulated", "tx_hash": null, "estimated_gas": 21000, "gas_used": null, "success": false
+            }));
+        });
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).path("/send").json_body_partial(r#"{"simulate": false}"#);
+            then.status(200).json_body(json!({
+                "mode": "broadcast", "tx_hash": "0xabc", "estimated_gas": null, "gas_used": 21000, "success": true
+            }));
+        });
+        let client = McpClient::new(server.base_url());
+        let send = |simulate| SendRequest::builder()
+            .from(Address::new("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".to_string()))
+            .to(Address::new("0x70997970c51812dc3a010c7d01b50e0d17dc79c8".to_string()))
+            .amount_eth("0.1")
+            .simulate(simulate)
+            .build()
+            .unwrap();
+
+        let simulated = client.send(&send(true)).await.unwrap();
+        assert_eq!(simulated.mode, SendMode::Simulated);
+        assert_eq!(simulated.tx_hash, None);
+        assert_eq!(simulated.estimated_gas, Some(21_000));
+
+        let broadcast = client.send(&send(false)).await.unwrap();
+        assert_eq!(broadcast.mode, SendMode::Broadcast);
+        assert_eq!(broadcast.tx_hash.as_deref(), Some("0xabc"));
+        assert!(broadcast.success);
+    }
+
+    #[tokio::test]
+    async fn balances_fan_out_respects_concurrency_limit() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // Counting server: tracks how many /balance requests are in flight at once
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let app_state = (in_flight.clone(), peak.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let (in_flight, peak) = app_state.clone();
+                tokio::spawn(async move {
+                    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+                    let mut buf = [0u8; 4096];
+                    let _ = socket.read(&mut buf).await;
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    let body = r#"{"balance":"1"}"#;
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+
+        let client = McpClient::new(format!("http://{}", addr)).with_concurrency(3);
+        let reqs = (0..12)
+            .map(|_| BalanceRequest::new(AddressOrEns::from_ens("vitalik.eth".to_string())))
+            .collect();
+        let results = client.balances(reqs).await;
+        assert_eq!(results.len(), 12);
+        assert!(results.iter().all(|r| r.as_deref().ok() == Some("1")));
+        let peak = peak.load(Ordering::SeqCst);
+        assert!(peak <= 3, "saw {} concurrent requests", peak);
+        assert!(peak >= 2, "fan-out should actually run concurrently");
+    }
+
+    #[tokio::test]
+    async fn health_passes_when_server_responds() {
+        let server = httpmock::MockServer::start();
+        server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/health");
+            then.status(200).json_body(json!({ "status": "ok" }));
+        });
+        let client = McpClient::new(server.base_url());
+        assert!(client.health().await.is_ok());
+    }
+}
@@ -0,0 +1,126 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use domain::*;
+use serde_json::Value;
+
+use crate::mcp::McpClient;
+
+/// Canned balance for every holder: 1 ETH
+pub const STUB_BALANCE_WEI: &str = "1000000000000000000";
+/// Canned ERC-20 balance: 1 token at 6 decimals (USDC-style)
+pub const STUB_ERC20_AMOUNT: &str = "1000000";
+const STUB_TRANSFER_GAS: u64 = 21_000;
+/// Typical gas for an ERC-20 `transfer`
+const STUB_TOKEN_TRANSFER_GAS: u64 = 65_000;
+/// Canned gas price for dry-run diffs: 1 gwei
+const STUB_GAS_PRICE_WEI: u128 = 1_000_000_000;
+
+/// In-process toolbox for `--offline`: answers every tool call with canned values so
+/// demos run without an MCP server or an RPC node.
+#[derive(Debug, Default)]
+pub struct StubToolbox;
+
+#[async_trait]
+impl Toolbox for StubToolbox {
+    async fn balance(&self, _req: BalanceRequest) -> Result<BalanceResponse> {
+        Ok(BalanceResponse::new(STUB_BALANCE_WEI.to_string()))
+    }
+
+    async fn code(&self, _req: CodeRequest) -> Result<CodeResponse> {
+        Ok(CodeResponse::new(false, 0))
+    }
+
+    async fn erc20_balance_of(&self, _req: Erc20BalanceRequest) -> Result<Erc20BalanceResponse> {
+        Ok(Erc20BalanceResponse::new(STUB_ERC20_AMOUNT.to_string()))
+    }
+
+    async fn send(&self, req: SendRequest) -> Result<TxResult> {
+        let result = TxResult::new(format!("0x{}", "0".repeat(64)), Some(STUB_TRANSFER_GAS), Some(true));
+        if !req.simulate() {
+            return Ok(result.with_effective_gas_price(Some(STUB_GAS_PRICE_WEI as u64)));
+        }
+        let balance: u128 = STUB_BALANCE_WEI.parse()?;
+        let value: u128 = units::eth_to_wei(req.amount_eth())?.parse()?;
+        let spent = value + STUB_TRANSFER_GAS as u128 * STUB_GAS_PRICE_WEI;
+        let change = |address: &Address, after: u128, delta: String| BalanceChange {
+            address: address.as_str().to_string(),
+            before: balance.to_string(),
+            after: after.to_string(),
+            delta,
+        };
+        Ok(result.with_balance_changes(vec![
+            change(req.from(), balance.saturating_sub(spent), format!("-{}", spent)),
+            change(req.to(), balance + value, format!("+{}", value)),
+        ]))
+    }
+
+    async fn erc20_transfer(&self, req: Erc20TransferRequest) -> Result<TxResult> {
+        let result = TxResult::new(format!("0x{}", "0".repeat(64)), Some(STUB_TOKEN_TRANSFER_GAS), Some(true));
+        Ok(if req.simulate() { result } else { result.with_effective_gas_price(Some(STUB_GAS_PRICE_WEI as u64)) })
+    }
+}
+
+/// Where tool calls go: the MCP server over HTTP, or an in-process toolbox.
+pub enum ToolBackend {
+    Mcp(McpClient),
+    InProcess(Box<dyn Toolbox>),
+}
+
+impl ToolBackend {
+    pub fn offline() -> Self {
+        Self::InProcess(Box::new(StubToolbox))
+    }
+
+    /// Preflight; only the MCP server can be down.
+    pub async fn health(&self) -> Result<()> {
+        match self {
+            Self::Mcp(client) => client.health().await,
+            Self::InProcess(_) => Ok(()),
+        }
+    }
+
+    pub async fn balance(&self, req: &BalanceRequest) -> Result<String> {
+        match self {
+            Self::Mcp(client) => client.balance(req).await,
+            Self::InProcess(toolbox) => Ok(toolbox.balance(req.clone()).await?.wei().to_string()),
+        }
+    }
+
+    pub async fn code(&self, req: &CodeRequest) -> Result<(bool, u64)> {
+        match self {
+            Self::Mcp(client) => client.code(req).await,
+            Self::InProcess(toolbox) => {
+                let code = toolbox.code(req.clone()).await?;
+                Ok((code.deployed(), code.bytecode_len()))
+            }
+        }
+    }
+
+    pub async fn erc20_balance_of(&self, req: &Erc20BalanceRequest) -> Result<String> {
+        match self {
+            Self::Mcp(client) => client.erc20_balance_of(req).await,
+            Self::InProcess(toolbox) => Ok(toolbox.erc20_balance_of(req.clone()).await?.amount().to_string()),
+        }
+    }
+
+    pub async fn send(&self, req: &SendRequest) -> Result<SimResult> {
+        match self {
+            Self::Mcp(client) => client.send(req).await,
+            Self::InProcess(toolbox) => Ok(SimResult::from_tx(&toolbox.send(req.clone()).await?, req.simulate())),
+        }
+    }
+
+    pub async fn erc20_transfer(&self, req: &Erc20TransferRequest) -> Result<SimResult> {
+        match self {
+            Self::Mcp(client) => client.erc20_transfer(req).await,
+            Self::InProcess(toolbox) => Ok(SimResult::from_tx(&toolbox.erc20_transfer(req.clone()).await?, req.simulate())),
+        }
+    }
+
+    pub async fn call_endpoint(&self, endpoint: &str, body: &Value) -> Result<Value> {
+        match self {
+            Self::Mcp(client) => client.call_endpoint(endpoint, body).await,
+            Self::InProcess(_) => anyhow::bail!("declarative tool endpoint {} needs an MCP server; drop --offline", endpoint),
+        }
+    }
+}
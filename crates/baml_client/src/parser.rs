@@ -1,37 +1,23 @@
 use anyhow::Result;
+use std::path::Path;
+use std::sync::OnceLock;
 use tracing::{debug, info};
 use crate::baml::BamlFunction;
-use crate::provider::{ChatProvider, ChatMessage, ChatRequest};
+use crate::provider::{ChatProvider, ChatMessage, ChatRequest, ToolDef, Usage};
 use crate::tools::ToolRegistry;
 
-pub struct NlParser<P: ChatProvider> {
-    provider: P,
-    baml_validation_enabled: bool,
-}
-
-impl<P: ChatProvider> NlParser<P> {
-    #[allow(dead_code)]
-    pub fn new(provider: P) -> Self {
-        Self { provider, baml_validation_enabled: false }
-    }
+/// Default LLM model; `--model` overrides it
+pub const DEFAULT_MODEL: &str = "claude-sonnet-4-20250514";
 
-    pub fn new_with_baml(provider: P, enabled: bool) -> Self {
-        Self { provider, baml_validation_enabled: enabled }
-    }
-
-    #[allow(dead_code)]
-    pub async fn parse_query_with_history(&self, query: &str, history: &[ChatMessage]) -> Result<BamlFunction> {
-        info!("Parsing query with LLM (with history): {}", query);
-        let mut messages = vec![
-            ChatMessage {
-                role: "system".to_string(),
-                content: r#"You are an EVM toolbox agent that can help with blockchain operations or casual conversation.
+/// Default agent instructions; `--system-prompt-file` or `SYSTEM_PROMPT` override them
+pub const DEFAULT_SYSTEM_PROMPT: &str = r#"You are an EVM toolbox agent that can help with blockchain operations or casual conversation.
 
 Available blockchain functions:
 - GetNativeBalance: Get native token balance of an address or name
 - GetFungibleBalance: Get fungible token balance for a holder address  
 - GetCode: Check if an address has deployed code
 - SendNative: Send native token from one address to another
+- TransferToken: Send fungible (ERC-20) tokens from one address to another
 
 For blockchain-related queries, use the appropriate function with the correct parameters.
 For addresses, prefer ENS names when available (e.g., "vitalik.eth").
@@ -39,71 +25,183 @@ For send operations, default to simulate=true unless explicitly requested to sen
 
 For casual conversation (greetings, general questions), respond naturally without using any tools.
 If you use a tool, return a JSON object with the function type and parameters.
-If it's casual conversation, just respond normally."#.to_string(),
-            },
-        ];
-        messages.extend_from_slice(history);
-        messages.push(ChatMessage { role: "user".to_string(), content: query.to_string() });
+If it's casual conversation, just respond normally."#;
 
-        let request = ChatRequest {
-            messages,
-            model: "claude-sonnet-4-20250514".to_string(),
-            temperature: Some(0.0),
-            tools: Some(self.native_tools_schema()),
-        };
+/// Tool schemas from the default `ToolRegistry`, built once per process and shared
+/// by every request.
+fn default_tool_defs() -> &'static [ToolDef] {
+    static DEFS: OnceLock<Vec<ToolDef>> = OnceLock::new();
+    DEFS.get_or_init(|| ToolRegistry::with_default_tools().tool_defs())
+}
 
-        let response = self.provider.chat(request).await?;
-        debug!("LLM response: {}", response.content);
+/// Pick the system prompt: `--system-prompt-file`, then `SYSTEM_PROMPT`, then the default.
+pub fn resolve_system_prompt(file: Option<&Path>) -> Result<String> {
+    if let Some(path) = file {
+        return std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("failed to read system prompt file {}: {}", path.display(), e));
+    }
+    match std::env::var("SYSTEM_PROMPT") {
+        Ok(prompt) if !prompt.trim().is_empty() => Ok(prompt),
+        _ => Ok(DEFAULT_SYSTEM_PROMPT.to_string()),
+    }
+}
 
-        match self.parse_llm_response(&response.content) {
-            Ok(func) => Ok(func),
-            Err(_) => Ok(BamlFunction::Chat(response.content)),
+/// Clauses of a chained query: "check X, then send Y" and "check X and then send Y" both
+/// give ["check X", "send Y"]. A query without "then" is a single clause.
+fn split_steps(query: &str) -> Vec<String> {
+    let mut clauses: Vec<Vec<&str>> = vec![Vec::new()];
+    for word in query.split_whitespace() {
+        let current = clauses.last_mut().expect("at least one clause");
+        if word.eq_ignore_ascii_case("then") && !current.is_empty() {
+            if current.last().is_some_and(|w| w.eq_ignore_ascii_case("and")) {
+                current.pop();
+            }
+            clauses.push(Vec::new());
+        } else {
+            current.push(word);
         }
     }
+    clauses
+        .into_iter()
+        .map(|words| words.join(" ").trim_end_matches([',', ';']).to_string())
+        .filter(|clause| !clause.is_empty())
+        .collect()
+}
 
-    fn native_tools_schema(&self) -> Vec<crate::provider::ToolDef> {
-        // Generate from ToolRegistry so tools can be added dynamically
-        ToolRegistry::with_default_tools().tool_defs()
+/// The unfinished tool call embedded in a clarification's `[[PARTIAL_INTENT]]` block
+pub fn partial_intent(message: &str) -> Option<serde_json::Value> {
+    let (_, rest) = message.split_once("[[PARTIAL_INTENT]]")?;
+    let (block, _) = rest.split_once("[[/PARTIAL_INTENT]]")?;
+    let intent: serde_json::Value = serde_json::from_str(block.trim()).ok()?;
+    // Accept both the bare function object and the `{"function": {...}}` envelope
+    Some(intent.get("function").cloned().unwrap_or(intent))
+}
+
+/// Fields a built-in tool needs before it can run
+fn required_fields(function_type: &str) -> &'static [&'static str] {
+    match function_type {
+        "GetNativeBalance" | "GetEthBalance" => &["who"],
+        "GetCode" | "IsDeployed" => &["addr"],
+        "GetFungibleBalance" | "GetErc20Balance" => &["token", "holder"],
+        "SendNative" | "SendEth" => &["from", "to", "amount_eth"],
+        "TransferToken" | "Erc20Transfer" => &["token", "from", "to", "amount"],
+        _ => &[],
     }
+}
 
-    pub async fn parse_query(&self, query: &str) -> Result<BamlFunction> {
-        info!("Parsing query with LLM: {}", query);
+/// Values a follow-up message supplies for `required`: "from X" and "to Y", the first
+/// number as the amount, and bare addresses or ENS names for the remaining address fields.
+fn fields_from_text(required: &[&str], text: &str) -> serde_json::Map<String, serde_json::Value> {
+    let mut fields = serde_json::Map::new();
+    let mut names = Vec::new();
+    let mut words = text.split_whitespace().map(|w| w.trim_matches(|c: char| matches!(c, ',' | '?' | '!' | '"' | '\'')));
+    while let Some(word) = words.next() {
+        let keyword = word.to_lowercase();
+        if matches!(keyword.as_str(), "from" | "to") && required.contains(&keyword.as_str()) {
+            if let Some(value) = words.next() {
+                fields.insert(keyword, value.trim_end_matches('.').into());
+            }
+        } else if word.parse::<f64>().is_ok() {
+            if let Some(amount) = required.iter().find(|f| f.starts_with("amount")) {
+                fields.entry(amount.to_string()).or_insert_with(|| word.into());
+            }
+        } else {
+            let word = word.trim_end_matches('.');
+            if domain::looks_like_address(word) || domain::looks_like_ens(word) {
+                names.push(word);
+            }
+        }
+    }
+    let open = required.iter().filter(|f| matches!(**f, "who" | "addr" | "token" | "holder"));
+    for (field, name) in open.zip(names) {
+        fields.insert(field.to_string(), name.into());
+    }
+    fields
+}
 
-        // Create BAML agent prompt
-        let messages = vec![
-            ChatMessage {
-                role: "system".to_string(),
-                content: r#"You are an EVM toolbox agent that can help with blockchain operations or casual conversation.
+pub struct NlParser<P: ChatProvider> {
+    provider: P,
+    baml_validation_enabled: bool,
+    model: String,
+    system_prompt: String,
+    /// Set when the tools config adds declarative tools; otherwise the shared defaults
+    tools: Option<ToolRegistry>,
+    /// Tokens spent by every LLM call this parser has made
+    usage: std::sync::Mutex<Usage>,
+}
 
-Available blockchain functions:
-- GetNativeBalance: Get native token balance of an address or name
-- GetFungibleBalance: Get fungible token balance for a holder address  
-- GetCode: Check if an address has deployed code
-- SendNative: Send native token from one address to another
+impl<P: ChatProvider> NlParser<P> {
+    #[allow(dead_code)]
+    pub fn new(provider: P) -> Self {
+        Self::new_with_baml(provider, false)
+    }
 
-For blockchain-related queries, use the appropriate function with the correct parameters.
-For addresses, prefer ENS names when available (e.g., "vitalik.eth").
-For send operations, default to simulate=true unless explicitly requested to send.
+    pub fn new_with_baml(provider: P, enabled: bool) -> Self {
+        Self { provider, baml_validation_enabled: enabled, model: DEFAULT_MODEL.to_string(), system_prompt: DEFAULT_SYSTEM_PROMPT.to_string(), tools: None, usage: Default::default() }
+    }
 
-For casual conversation (greetings, general questions), respond naturally without using any tools.
-If you use a tool, return a JSON object with the function type and parameters.
-If it's casual conversation, just respond normally."#.to_string(),
-            },
-            ChatMessage {
-                role: "user".to_string(),
-                content: query.to_string(),
-            },
-        ];
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// Advertise and dispatch this registry's tools instead of the defaults.
+    pub fn with_tool_registry(mut self, tools: ToolRegistry) -> Self {
+        self.tools = Some(tools);
+        self
+    }
+
+    pub fn with_system_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.system_prompt = prompt.into();
+        self
+    }
+
+    /// Token usage summed over the LLM calls so far; zero for providers that don't report it.
+    pub fn usage(&self) -> Usage {
+        self.usage.lock().unwrap().clone()
+    }
+
+    /// A query chaining steps with "then" parses clause by clause into a `Sequence`; each
+    /// clause sees the earlier ones (and what they parsed to) as history, so it can refer
+    /// back to them. A clause that needs clarification is returned instead of the plan.
+    pub async fn parse_query_with_history(&self, query: &str, history: &[ChatMessage]) -> Result<BamlFunction> {
+        let clauses = split_steps(query);
+        if clauses.len() < 2 {
+            return self.parse_step(query, history).await;
+        }
+        let mut context = history.to_vec();
+        let mut steps = Vec::with_capacity(clauses.len());
+        for clause in clauses {
+            let step = self.parse_step(&clause, &context).await?;
+            if let BamlFunction::Chat(_) = step {
+                return Ok(step);
+            }
+            context.push(ChatMessage { role: "user".to_string(), content: clause });
+            context.push(ChatMessage { role: "assistant".to_string(), content: serde_json::to_string(&step)? });
+            steps.push(step);
+        }
+        info!("Parsed a {}-step plan", steps.len());
+        Ok(BamlFunction::Sequence(steps))
+    }
+
+    async fn parse_step(&self, query: &str, history: &[ChatMessage]) -> Result<BamlFunction> {
+        info!("Parsing query with LLM: {}", query);
+        let mut messages = vec![ChatMessage { role: "system".to_string(), content: self.system_prompt.clone() }];
+        messages.extend_from_slice(history);
+        messages.push(ChatMessage { role: "user".to_string(), content: query.to_string() });
 
         let request = ChatRequest {
             messages,
-            model: "claude-sonnet-4-20250514".to_string(),
+            model: self.model.clone(),
             temperature: Some(0.0),
             // Pass native tool schemas so the LLM can select tools or decline
             tools: Some(self.native_tools_schema()),
         };
 
         let response = self.provider.chat(request).await?;
+        if let Some(usage) = &response.usage {
+            self.usage.lock().unwrap().add(usage);
+        }
         debug!("LLM response: {}", response.content);
 
         // Parse the response. If it's not a tool call JSON, treat it as plain chat.
@@ -113,6 +211,43 @@ If it's casual conversation, just respond normally."#.to_string(),
         }
     }
 
+    /// Tell the user the model asked for a tool we don't have instead of failing silently.
+    fn unknown_tool_message(&self, attempted: &str) -> String {
+        let names: Vec<String> = self.native_tools_schema().into_iter().map(|def| def.name).collect();
+        format!(
+            "I can't run '{}': it isn't one of my tools. Supported tools: {}.",
+            attempted,
+            names.join(", ")
+        )
+    }
+
+    fn native_tools_schema(&self) -> Vec<ToolDef> {
+        match &self.tools {
+            Some(tools) => tools.tool_defs(),
+            None => default_tool_defs().to_vec(),
+        }
+    }
+
+    pub async fn parse_query(&self, query: &str) -> Result<BamlFunction> {
+        self.parse_query_with_history(query, &[]).await
+    }
+
+    /// Merge a follow-up message into a stored partial intent and re-validate it: the
+    /// result is the completed call, or another clarification if fields are still
+    /// missing. `None` when the message supplies none of its fields, so the caller can
+    /// treat it as a new query.
+    pub fn resume_partial(&self, partial: &serde_json::Value, message: &str) -> Result<Option<BamlFunction>> {
+        let Some(function_type) = partial.get("type").and_then(|t| t.as_str()) else { return Ok(None) };
+        let fields = fields_from_text(required_fields(function_type), message);
+        if fields.is_empty() {
+            return Ok(None);
+        }
+        let mut merged = partial.as_object().cloned().unwrap_or_default();
+        merged.extend(fields);
+        debug!("Resuming partial intent: {}", serde_json::Value::Object(merged.clone()));
+        self.parse_function_json(&serde_json::Value::Object(merged)).map(Some)
+    }
+
     fn parse_llm_response(&self, response: &str) -> Result<BamlFunction> {
         // Try to parse as JSON first
         if let Ok(json) = serde_json::from_str::<serde_json::Value>(response) {
@@ -142,16 +277,11 @@ If it's casual conversation, just respond normally."#.to_string(),
             "GetNativeBalance" | "GetEthBalance" => {
                 let who_opt = function.get("who").and_then(|w| w.as_str());
                 if who_opt.is_none() {
-                    let msg = format!("I need 'who' to get a balance. Please provide an address or ENS/name.\n[[PARTIAL_INTENT]]\n{}\n[[/PARTIAL_INTENT]]", function.to_string());
+                    let msg = format!("I need 'who' to get a balance. Please provide an address or ENS/name.\n[[PARTIAL_INTENT]]\n{}\n[[/PARTIAL_INTENT]]", function);
                     return Ok(BamlFunction::Chat(msg));
                 }
                 let who = who_opt.unwrap();
-                let input = who.to_string();
-                let addr_or_ens = if input.ends_with(".eth") {
-                    domain::AddressOrEns::from_ens(input)
-                } else {
-                    domain::AddressOrEns::from_address(input)
-                };
+                let addr_or_ens = domain::AddressOrEns::parse(who.to_string());
                 Ok(BamlFunction::Balance(
                     domain::BalanceRequest::new(addr_or_ens)
                 ))
@@ -160,7 +290,7 @@ If it's casual conversation, just respond normally."#.to_string(),
             "GetCode" | "IsDeployed" => {
                 let addr_opt = function.get("addr").and_then(|a| a.as_str());
                 if addr_opt.is_none() {
-                    let msg = format!("I need 'addr' to check code. Please provide an address.\n[[PARTIAL_INTENT]]\n{}\n[[/PARTIAL_INTENT]]", function.to_string());
+                    let msg = format!("I need 'addr' to check code. Please provide an address.\n[[PARTIAL_INTENT]]\n{}\n[[/PARTIAL_INTENT]]", function);
                     return Ok(BamlFunction::Chat(msg));
                 }
                 let addr = addr_opt.unwrap();
@@ -173,7 +303,7 @@ If it's casual conversation, just respond normally."#.to_string(),
                 let token_opt = function.get("token").and_then(|t| t.as_str());
                 let holder_opt = function.get("holder").and_then(|h| h.as_str());
                 if token_opt.is_none() || holder_opt.is_none() {
-                    let msg = format!("I need 'token' and 'holder' to get token balance. Please provide both.\n[[PARTIAL_INTENT]]\n{}\n[[/PARTIAL_INTENT]]", function.to_string());
+                    let msg = format!("I need 'token' and 'holder' to get token balance. Please provide both.\n[[PARTIAL_INTENT]]\n{}\n[[/PARTIAL_INTENT]]", function);
                     return Ok(BamlFunction::Chat(msg));
                 }
                 let token = token_opt.unwrap();
@@ -191,7 +321,7 @@ If it's casual conversation, just respond normally."#.to_string(),
                 let to_opt = function.get("to").and_then(|t| t.as_str());
                 let amount_opt = function.get("amount_eth").and_then(|a| a.as_str());
                 if from_opt.is_none() || to_opt.is_none() || amount_opt.is_none() {
-                    let msg = format!("I need 'from', 'to', and 'amount_eth' to send. Please provide missing fields.\n[[PARTIAL_INTENT]]\n{}\n[[/PARTIAL_INTENT]]", function.to_string());
+                    let msg = format!("I need 'from', 'to', and 'amount_eth' to send. Please provide missing fields.\n[[PARTIAL_INTENT]]\n{}\n[[/PARTIAL_INTENT]]", function);
                     return Ok(BamlFunction::Chat(msg));
                 }
                 let from = from_opt.unwrap();
@@ -208,7 +338,43 @@ If it's casual conversation, just respond normally."#.to_string(),
                         .build().map_err(|e| anyhow::anyhow!("{}", e))?
                 ))
             }
-            _ => anyhow::bail!("Unknown function type: {}", function_type),
+            "TransferToken" | "Erc20Transfer" => {
+                let token_opt = function.get("token").and_then(|t| t.as_str());
+                let from_opt = function.get("from").and_then(|f| f.as_str());
+                let to_opt = function.get("to").and_then(|t| t.as_str());
+                let amount_opt = function.get("amount").and_then(|a| a.as_str());
+                let (Some(token), Some(from), Some(to), Some(amount)) = (token_opt, from_opt, to_opt, amount_opt) else {
+                    let msg = format!("I need 'token', 'from', 'to', and 'amount' to transfer tokens. Please provide missing fields.\n[[PARTIAL_INTENT]]\n{}\n[[/PARTIAL_INTENT]]", function);
+                    return Ok(BamlFunction::Chat(msg));
+                };
+                let simulate = function.get("simulate").and_then(|s| s.as_bool()).unwrap_or(true);
+
+                Ok(BamlFunction::Erc20Transfer(
+                    domain::Erc20TransferRequest::builder()
+                        .token(domain::Address::new(token.to_string()))
+                        .from(domain::Address::new(from.to_string()))
+                        .to(domain::Address::new(to.to_string()))
+                        .amount(amount)
+                        .simulate(simulate)
+                        .build().map_err(|e| anyhow::anyhow!("{}", e))?
+                ))
+            }
+            // Declarative tools dispatch through the registry
+            _ => {
+                let defaults;
+                let tools = match &self.tools {
+                    Some(tools) => tools,
+                    None => {
+                        defaults = ToolRegistry::with_default_tools();
+                        &defaults
+                    }
+                };
+                if tools.contains(function_type) {
+                    tools.to_baml_function(function_type, function)
+                } else {
+                    Ok(BamlFunction::Chat(self.unknown_tool_message(function_type)))
+                }
+            }
         }
     }
 
@@ -220,11 +386,7 @@ If it's casual conversation, just respond normally."#.to_string(),
         if response_lower.contains("balance") && response_lower.contains("eth") {
             let who = self.extract_address_or_ens(response)?;
             debug!("Parsed balance request for: {}", who);
-            let addr_or_ens = if who.ends_with(".eth") {
-                domain::AddressOrEns::from_ens(who)
-            } else {
-                domain::AddressOrEns::from_address(who)
-            };
+            let addr_or_ens = domain::AddressOrEns::parse(who);
             return Ok(BamlFunction::Balance(
                 domain::BalanceRequest::new(addr_or_ens)
             ));
@@ -271,22 +433,15 @@ If it's casual conversation, just respond normally."#.to_string(),
         if query.contains("vitalik") {
             return Ok("vitalik.eth".to_string());
         }
-        if query.contains("0x") {
-            return Ok(self.extract_address(query)?);
+        if let Some(addr) = crate::extract::first_address(query) {
+            return Ok(addr);
         }
         anyhow::bail!("No address or ENS found in query")
     }
 
     #[allow(dead_code)]
     fn extract_address(&self, query: &str) -> Result<String> {
-        // Extract first 0x-prefixed string
-        let words: Vec<&str> = query.split_whitespace().collect();
-        for word in words {
-            if word.starts_with("0x") && word.len() == 42 {
-                return Ok(word.to_string());
-            }
-        }
-        anyhow::bail!("No valid address found in query")
+        crate::extract::first_address(query).ok_or_else(|| anyhow::anyhow!("No valid address found in query"))
     }
 
     #[allow(dead_code)]
@@ -312,7 +467,131 @@ If it's casual conversation, just respond normally."#.to_string(),
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::provider::MockProvider;
+    use crate::provider::{ChatResponse, MockProvider};
+    use async_trait::async_trait;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn unknown_tool_becomes_chat_listing_supported_tools() {
+        let parser = NlParser::new(MockProvider::new());
+        let function = parser.parse_llm_response(r#"{"function": {"type": "Teleport", "to": "mars"}}"#).unwrap();
+        let BamlFunction::Chat(message) = function else { panic!("expected chat, got {}", function.name()) };
+        assert!(message.contains("'Teleport'"), "{}", message);
+        for tool in ["GetNativeBalance", "GetCode", "GetFungibleBalance", "SendNative"] {
+            assert!(message.contains(tool), "missing {} in: {}", tool, message);
+        }
+    }
+
+    #[test]
+    fn transfer_token_missing_fields_asks_with_a_partial_intent() {
+        let parser = NlParser::new(MockProvider::new());
+        let response = r#"{"function": {"type": "TransferToken", "token": "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", "to": "0xBob"}}"#;
+        let BamlFunction::Chat(message) = parser.parse_llm_response(response).unwrap() else { panic!("expected a clarification") };
+        assert!(message.contains("'amount'"), "{}", message);
+        assert!(message.contains("[[PARTIAL_INTENT]]") && message.contains("0xBob"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn token_transfer_phrasing_selects_erc20_transfer() {
+        let parser = NlParser::new(MockProvider::new());
+        let function = parser.parse_query("Transfer 25 USDC from 0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266 to 0x70997970c51812dc3a010c7d01b50e0d17dc79c8").await.unwrap();
+        let BamlFunction::Erc20Transfer(req) = function else { panic!("expected erc20_transfer, got {}", function.name()) };
+        assert_eq!(req.token().as_str(), "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+        assert_eq!(req.to().as_str(), "0x70997970c51812dc3a010c7d01b50e0d17dc79c8");
+        assert_eq!(req.amount(), "25");
+        assert!(req.simulate());
+    }
+
+    #[test]
+    fn chained_queries_split_on_then() {
+        assert_eq!(split_steps("check vitalik.eth's balance, then send 0.1 ETH"), vec!["check vitalik.eth's balance", "send 0.1 ETH"]);
+        assert_eq!(split_steps("check X and then send Y Then check Z"), vec!["check X", "send Y", "check Z"]);
+        assert_eq!(split_steps("what's vitalik.eth's balance?"), vec!["what's vitalik.eth's balance?"]);
+        assert_eq!(split_steps("then what?"), vec!["then what?"]);
+    }
+
+    #[tokio::test]
+    async fn two_step_query_becomes_an_ordered_sequence() {
+        let parser = NlParser::new(MockProvider::new());
+        let function = parser.parse_query("check vitalik.eth's balance then send 0.1 ETH to bob").await.unwrap();
+        let BamlFunction::Sequence(steps) = function else { panic!("expected a sequence, got {}", function.name()) };
+        assert_eq!(steps.len(), 2);
+        assert!(matches!(steps[0], BamlFunction::Balance(_)), "got {}", steps[0].name());
+        assert!(matches!(steps[1], BamlFunction::Send(_)), "got {}", steps[1].name());
+    }
+
+    #[test]
+    fn follow_up_fields_complete_a_partial_send() {
+        let parser = NlParser::new(MockProvider::new());
+        let BamlFunction::Chat(ask) = parser.parse_llm_response(r#"{"function": {"type": "SendNative", "to": "Bob"}}"#).unwrap() else { panic!("expected a clarification") };
+        let partial = partial_intent(&ask).expect("clarification carries the partial intent");
+        assert_eq!(partial, serde_json::json!({ "type": "SendNative", "to": "Bob" }));
+
+        let function = parser.resume_partial(&partial, "1 ETH from Alice").unwrap().expect("fields were supplied");
+        let BamlFunction::Send(req) = function else { panic!("expected send, got {}", function.name()) };
+        assert_eq!((req.from().as_str(), req.to().as_str(), req.amount_eth()), ("Alice", "Bob", "1"));
+        assert!(req.simulate());
+
+        // Still missing `from`: ask again with the merged intent
+        let BamlFunction::Chat(again) = parser.resume_partial(&partial, "make it 2.5").unwrap().unwrap() else { panic!("expected a clarification") };
+        assert_eq!(partial_intent(&again).unwrap()["amount_eth"], "2.5");
+        assert!(parser.resume_partial(&partial, "never mind").unwrap().is_none());
+    }
+
+    /// Records the system message of every request it receives
+    struct SystemPromptSpy {
+        seen: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl ChatProvider for SystemPromptSpy {
+        async fn chat(&self, request: ChatRequest) -> Result<ChatResponse> {
+            let system = request.messages.iter().find(|m| m.role == "system").map(|m| m.content.clone());
+            self.seen.lock().unwrap().push(system.unwrap_or_default());
+            Ok(ChatResponse { content: "ok".to_string(), usage: None })
+        }
+    }
+
+    #[tokio::test]
+    async fn custom_system_prompt_reaches_provider() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let parser = NlParser::new(SystemPromptSpy { seen: seen.clone() }).with_system_prompt("Only answer in haiku.");
+        parser.parse_query("hello").await.unwrap();
+        parser.parse_query_with_history("hello", &[]).await.unwrap();
+        assert_eq!(*seen.lock().unwrap(), vec!["Only answer in haiku.", "Only answer in haiku."]);
+    }
+
+    #[tokio::test]
+    async fn parse_query_matches_empty_history_path() {
+        let parser = NlParser::new(MockProvider::new());
+        for query in ["balance", "code", "send", "hello", "what is gas?"] {
+            let direct = parser.parse_query(query).await.unwrap();
+            let with_history = parser.parse_query_with_history(query, &[]).await.unwrap();
+            assert_eq!(format!("{:?}", direct), format!("{:?}", with_history), "diverged on {:?}", query);
+        }
+    }
+
+    #[test]
+    fn tool_schemas_are_built_once_and_stable() {
+        let first = default_tool_defs();
+        let second = default_tool_defs();
+        assert!(std::ptr::eq(first, second), "registry should be built once and reused");
+
+        let parser = NlParser::new(MockProvider::new());
+        let a = serde_json::to_value(parser.native_tools_schema()).unwrap();
+        let b = serde_json::to_value(parser.native_tools_schema()).unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a, serde_json::to_value(ToolRegistry::with_default_tools().tool_defs()).unwrap());
+    }
+
+    #[test]
+    fn system_prompt_file_overrides_default() {
+        let path = std::env::temp_dir().join(format!("system-prompt-{}.txt", std::process::id()));
+        std::fs::write(&path, "custom prompt").unwrap();
+        assert_eq!(resolve_system_prompt(Some(&path)).unwrap(), "custom prompt");
+        assert!(resolve_system_prompt(Some(Path::new("/nonexistent/prompt.txt"))).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
 
     #[tokio::test]
     async fn test_golden_prompts() {
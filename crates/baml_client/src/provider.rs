@@ -1,6 +1,8 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use crate::transport::{HttpReply, HttpTransport, ReqwestTransport};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
@@ -24,13 +26,21 @@ pub struct ChatResponse {
     pub usage: Option<Usage>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Usage {
     pub prompt_tokens: u32,
     pub completion_tokens: u32,
     pub total_tokens: u32,
 }
 
+impl Usage {
+    pub fn add(&mut self, other: &Usage) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.total_tokens += other.total_tokens;
+    }
+}
+
 /// Minimal tool definition for native tool registration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolDef {
@@ -43,24 +53,81 @@ pub struct ToolDef {
 #[async_trait]
 pub trait ChatProvider: Send + Sync {
     async fn chat(&self, request: ChatRequest) -> Result<ChatResponse>;
+
+    /// Human-readable backend name, used in logs and error messages
+    fn name(&self) -> &str {
+        "custom"
+    }
+}
+
+#[async_trait]
+impl ChatProvider for Box<dyn ChatProvider> {
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse> {
+        (**self).chat(request).await
+    }
+
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+}
+
+/// Public Anthropic API endpoint; override with `ANTHROPIC_BASE_URL` for gateways/proxies
+pub const DEFAULT_ANTHROPIC_BASE_URL: &str = "https://api.anthropic.com";
+/// Public OpenAI API endpoint; override with `OPENAI_BASE_URL` for gateways/proxies
+pub const DEFAULT_OPENAI_BASE_URL: &str = "https://api.openai.com";
+/// Local Ollama server; override with `OLLAMA_BASE_URL`
+pub const DEFAULT_OLLAMA_BASE_URL: &str = "http://localhost:11434";
+/// Gemini's OpenAI-compatible endpoint host; override with `GEMINI_BASE_URL`
+pub const DEFAULT_GEMINI_BASE_URL: &str = "https://generativelanguage.googleapis.com";
+
+fn env_or(var: &str, default: &str) -> String {
+    match std::env::var(var) {
+        Ok(url) if !url.trim().is_empty() => url,
+        _ => default.to_string(),
+    }
 }
 
 pub struct AnthropicProvider {
     api_key: String,
-    client: reqwest::Client,
+    base_url: String,
+    transport: Arc<dyn HttpTransport>,
 }
 
 impl AnthropicProvider {
     pub fn new(api_key: String) -> Self {
+        Self::with_base_url(api_key, DEFAULT_ANTHROPIC_BASE_URL)
+    }
+
+    /// Build a provider that sends requests to `base_url` instead of the public API
+    pub fn with_base_url(api_key: String, base_url: impl Into<String>) -> Self {
         Self {
             api_key,
-            client: reqwest::Client::new(),
+            base_url: base_url.into(),
+            transport: Arc::new(ReqwestTransport::new()),
+        }
+    }
+
+    /// Route HTTP through a custom transport (e.g. record/replay in tests)
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Build from env: `ANTHROPIC_BASE_URL` overrides the default endpoint when set
+    pub fn from_env(api_key: String) -> Self {
+        match std::env::var("ANTHROPIC_BASE_URL") {
+            Ok(url) if !url.trim().is_empty() => Self::with_base_url(api_key, url),
+            _ => Self::new(api_key),
         }
     }
 }
 
 #[async_trait]
 impl ChatProvider for AnthropicProvider {
+    fn name(&self) -> &str {
+        "Anthropic"
+    }
+
     async fn chat(&self, request: ChatRequest) -> Result<ChatResponse> {
         // Separate system message from user messages
         let (system_message, user_messages): (Option<String>, Vec<ChatMessage>) = {
@@ -82,25 +149,22 @@ impl ChatProvider for AnthropicProvider {
             "max_tokens": 1000,
             "messages": user_messages,
             "temperature": request.temperature.unwrap_or(0.0),
-            // Native tool registration (if provided)
-            "tools": request.tools.unwrap_or_default(),
         });
 
+        // Native tool registration; omit the key entirely when there are no tools
+        if let Some(tools) = request.tools.filter(|t| !t.is_empty()) {
+            body["tools"] = serde_json::to_value(tools)?;
+        }
+
         // Add system message as separate parameter if present
         if let Some(system) = system_message {
             body["system"] = serde_json::Value::String(system);
         }
 
-        let response = self
-            .client
-            .post("https://api.anthropic.com/v1/messages")
-            .header("x-api-key", &self.api_key)
-            .header("anthropic-version", "2023-06-01")
-            .json(&body)
-            .send()
-            .await?;
-
-        let result: serde_json::Value = response.json().await?;
+        let url = format!("{}/v1/messages", self.base_url.trim_end_matches('/'));
+        let headers = [("x-api-key", self.api_key.as_str()), ("anthropic-version", "2023-06-01")];
+        let reply = self.transport.post_json(&url, &headers, &body).await?;
+        let result = ensure_success("Anthropic", reply)?;
         
         // Prefer native tool_use blocks if present and convert them into the
         // function JSON our parser already understands: { "function": { "type": name, ...input } }
@@ -108,18 +172,7 @@ impl ChatProvider for AnthropicProvider {
             if let Some(tool_block) = content_blocks.iter().find(|b| b.get("type").and_then(|t| t.as_str()) == Some("tool_use")) {
                 let name = tool_block.get("name").and_then(|n| n.as_str()).unwrap_or("");
                 let input = tool_block.get("input").cloned().unwrap_or(serde_json::json!({}));
-                let function_json = serde_json::json!({
-                    "function": {
-                        "type": name,
-                        // Merge input fields directly under function
-                    }
-                });
-                // Manually merge input object into function_json["function"]
-                let mut function_obj = function_json["function"].as_object().cloned().unwrap_or_default();
-                if let Some(map) = input.as_object() {
-                    for (k, v) in map.iter() { function_obj.insert(k.clone(), v.clone()); }
-                }
-                let final_json = serde_json::json!({ "function": serde_json::Value::Object(function_obj) });
+                let final_json = tool_call_to_function_json(name, &input);
 
                 return Ok(ChatResponse {
                     content: final_json.to_string(),
@@ -147,10 +200,169 @@ impl ChatProvider for AnthropicProvider {
     }
 }
 
+/// OpenAI chat completions, also used for OpenAI-compatible backends (Ollama, Gemini)
+pub struct OpenAIProvider {
+    api_key: String,
+    base_url: String,
+    label: &'static str,
+    completions_path: &'static str,
+    transport: Arc<dyn HttpTransport>,
+}
+
+impl OpenAIProvider {
+    pub fn new(api_key: String) -> Self {
+        Self::with_base_url(api_key, DEFAULT_OPENAI_BASE_URL)
+    }
+
+    /// Build a provider that sends requests to `base_url` instead of the public API
+    pub fn with_base_url(api_key: String, base_url: impl Into<String>) -> Self {
+        Self {
+            api_key,
+            base_url: base_url.into(),
+            label: "OpenAI",
+            completions_path: "/v1/chat/completions",
+            transport: Arc::new(ReqwestTransport::new()),
+        }
+    }
+
+    /// Route HTTP through a custom transport (e.g. record/replay in tests)
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Build from env: `OPENAI_BASE_URL` overrides the default endpoint when set
+    pub fn from_env(api_key: String) -> Self {
+        match std::env::var("OPENAI_BASE_URL") {
+            Ok(url) if !url.trim().is_empty() => Self::with_base_url(api_key, url),
+            _ => Self::new(api_key),
+        }
+    }
+
+    /// Local Ollama via its OpenAI-compatible API; no key needed
+    pub fn ollama() -> Self {
+        Self { label: "Ollama", ..Self::with_base_url(String::new(), env_or("OLLAMA_BASE_URL", DEFAULT_OLLAMA_BASE_URL)) }
+    }
+
+    /// Gemini via its OpenAI-compatible API
+    pub fn gemini(api_key: String) -> Self {
+        Self {
+            label: "Gemini",
+            completions_path: "/v1beta/openai/chat/completions",
+            ..Self::with_base_url(api_key, env_or("GEMINI_BASE_URL", DEFAULT_GEMINI_BASE_URL))
+        }
+    }
+}
+
+#[async_trait]
+impl ChatProvider for OpenAIProvider {
+    fn name(&self) -> &str {
+        self.label
+    }
+
+    async fn chat(&self, request: ChatRequest) -> Result<ChatResponse> {
+        // OpenAI function tools wrap the same name/description/schema triple
+        let tools: Vec<serde_json::Value> = request
+            .tools
+            .unwrap_or_default()
+            .into_iter()
+            .map(|t| serde_json::json!({
+                "type": "function",
+                "function": {
+                    "name": t.name,
+                    "description": t.description,
+                    "parameters": t.input_schema,
+                }
+            }))
+            .collect();
+
+        let mut body = serde_json::json!({
+            "model": request.model,
+            "messages": request.messages,
+            "temperature": request.temperature.unwrap_or(0.0),
+        });
+        // Some OpenAI-compatible endpoints reject an empty `tools` array
+        if !tools.is_empty() {
+            body["tools"] = serde_json::Value::Array(tools);
+        }
+
+        let url = format!("{}{}", self.base_url.trim_end_matches('/'), self.completions_path);
+        let auth = format!("Bearer {}", self.api_key);
+        // Keyless backends (Ollama) get no authorization header at all
+        let headers: &[(&str, &str)] = if self.api_key.is_empty() { &[] } else { &[("authorization", auth.as_str())] };
+        let reply = self.transport.post_json(&url, headers, &body).await?;
+        let result = ensure_success(self.label, reply)?;
+        let usage = result.get("usage").map(|u| Usage {
+            prompt_tokens: u["prompt_tokens"].as_u64().unwrap_or(0) as u32,
+            completion_tokens: u["completion_tokens"].as_u64().unwrap_or(0) as u32,
+            total_tokens: u["total_tokens"].as_u64().unwrap_or(0) as u32,
+        });
+        let message = &result["choices"][0]["message"];
+
+        // Normalize the first function tool call into the parser's function JSON
+        if let Some(call) = message["tool_calls"].as_array().and_then(|calls| calls.first()) {
+            let name = call["function"]["name"].as_str().unwrap_or("");
+            let input = call["function"]["arguments"]
+                .as_str()
+                .and_then(|args| serde_json::from_str::<serde_json::Value>(args).ok())
+                .unwrap_or(serde_json::json!({}));
+            return Ok(ChatResponse {
+                content: tool_call_to_function_json(name, &input).to_string(),
+                usage,
+            });
+        }
+
+        // Fallback: plain assistant text
+        let text = message["content"].as_str().unwrap_or("").to_string();
+        Ok(ChatResponse { content: text, usage })
+    }
+}
+
+/// Parse 2xx bodies as JSON; otherwise read the provider error body and fail with a
+/// readable message like "Anthropic API error (401): invalid x-api-key"
+fn ensure_success(provider: &str, reply: HttpReply) -> Result<serde_json::Value> {
+    if !reply.is_success() {
+        anyhow::bail!("{}", format_api_error(provider, reply.status, &reply.body));
+    }
+    Ok(serde_json::from_str(&reply.body)?)
+}
+
+/// Both Anthropic and OpenAI report failures as `{ "error": { "type", "message" } }`
+fn format_api_error(provider: &str, status: u16, body: &str) -> String {
+    let parsed = serde_json::from_str::<serde_json::Value>(body).ok();
+    let error = parsed.as_ref().map(|v| &v["error"]);
+    let message = error.and_then(|e| e["message"].as_str()).filter(|m| !m.is_empty());
+    let kind = error.and_then(|e| e["type"].as_str()).filter(|t| !t.is_empty());
+    let detail = match (message, kind) {
+        (Some(message), _) => message.to_string(),
+        (None, Some(kind)) => kind.to_string(),
+        (None, None) if body.trim().is_empty() => "no error body".to_string(),
+        (None, None) => body.trim().chars().take(200).collect(),
+    };
+    format!("{} API error ({}): {}", provider, status, detail)
+}
+
+/// Merge a native tool call (name + input object) into the function JSON the parser
+/// understands: `{ "function": { "type": name, ...input } }`
+fn tool_call_to_function_json(name: &str, input: &serde_json::Value) -> serde_json::Value {
+    let mut function_obj = serde_json::Map::new();
+    function_obj.insert("type".to_string(), serde_json::Value::String(name.to_string()));
+    if let Some(map) = input.as_object() {
+        for (k, v) in map.iter() { function_obj.insert(k.clone(), v.clone()); }
+    }
+    serde_json::json!({ "function": serde_json::Value::Object(function_obj) })
+}
+
 pub struct MockProvider {
     responses: std::collections::HashMap<String, String>,
 }
 
+impl Default for MockProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MockProvider {
     pub fn new() -> Self {
         let mut responses = std::collections::HashMap::new();
@@ -177,6 +389,10 @@ impl MockProvider {
 
 #[async_trait]
 impl ChatProvider for MockProvider {
+    fn name(&self) -> &str {
+        "Mock"
+    }
+
     async fn chat(&self, _request: ChatRequest) -> Result<ChatResponse> {
         // Simple keyword-based response for testing
         let last = &_request.messages.last().unwrap().content;
@@ -193,7 +409,7 @@ impl ChatProvider for MockProvider {
         // Prefer code/deployed queries first → IsDeployed
         let lower = last.to_lowercase();
         if lower.contains("code") || lower.contains("deployed") {
-            if let Some(addr) = extract_first_address(last) {
+            if let Some(addr) = crate::extract::first_address(last) {
                 let json = serde_json::json!({
                     "function": { "type": "IsDeployed", "addr": addr }
                 });
@@ -201,8 +417,25 @@ impl ChatProvider for MockProvider {
             }
         }
 
+        // "send/transfer N USDC from A to B" → TransferToken; missing parts stay null
+        if lower.contains("usdc") && (lower.contains("send") || lower.contains("transfer")) {
+            let (from_part, to_part) = last.split_once(" to ").unwrap_or((last.as_str(), ""));
+            let amount = last.split_whitespace().find(|w| w.parse::<f64>().is_ok());
+            let json = serde_json::json!({
+                "function": {
+                    "type": "TransferToken",
+                    "token": "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48",
+                    "from": crate::extract::first_address(from_part),
+                    "to": crate::extract::first_address(to_part),
+                    "amount": amount,
+                    "simulate": true
+                }
+            });
+            return Ok(ChatResponse { content: json.to_string(), usage: None });
+        }
+
         // If the query includes a 0x address, default to GetNativeBalance for testing
-        if let Some(addr) = extract_first_address(last) {
+        if let Some(addr) = crate::extract::first_address(last) {
             let json = serde_json::json!({
                 "function": { "type": "GetNativeBalance", "who": addr }
             });
@@ -210,7 +443,7 @@ impl ChatProvider for MockProvider {
         }
 
         // If the query includes an ENS-like token, pass it as who (balance)
-        if let Some(ens) = extract_first_ens(last) {
+        if let Some(ens) = crate::extract::first_ens(last) {
             let json = serde_json::json!({
                 "function": { "type": "GetNativeBalance", "who": ens }
             });
@@ -236,31 +469,175 @@ impl ChatProvider for MockProvider {
     }
 }
 
-// Helpers for MockProvider only
-fn extract_first_address(text: &str) -> Option<String> {
-    for word in text.split_whitespace() {
-        let trimmed = word.trim_matches(|c: char|
-            c == '?' || c == '"' || c == '\'' || c == ',' || c == '.' || c == ')' || c == '(' || c == ':' || c == ';'
-        );
-        let mut candidate = trimmed.to_string();
-        if candidate.ends_with("'s") || candidate.ends_with("’s") {
-            candidate.truncate(candidate.len().saturating_sub(2));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+    use std::sync::Mutex;
+
+    /// Captures outgoing bodies and answers with a fixed reply
+    struct CaptureTransport {
+        reply: String,
+        bodies: Mutex<Vec<serde_json::Value>>,
+    }
+
+    impl CaptureTransport {
+        fn new(reply: serde_json::Value) -> Arc<Self> {
+            Arc::new(Self { reply: reply.to_string(), bodies: Mutex::new(Vec::new()) })
         }
-        if candidate.starts_with("0x") && candidate.len() == 42 { return Some(candidate); }
     }
-    None
-}
 
-fn extract_first_ens(text: &str) -> Option<String> {
-    for word in text.split_whitespace() {
-        let trimmed = word.trim_matches(|c: char|
-            c == '?' || c == '"' || c == '\'' || c == ',' || c == '.' || c == ')' || c == '(' || c == ':' || c == ';'
-        );
-        let mut candidate = trimmed.to_string();
-        if candidate.ends_with("'s") || candidate.ends_with("’s") {
-            candidate.truncate(candidate.len().saturating_sub(2));
+    #[async_trait]
+    impl HttpTransport for CaptureTransport {
+        async fn post_json(&self, _url: &str, _headers: &[(&str, &str)], body: &serde_json::Value) -> Result<HttpReply> {
+            self.bodies.lock().unwrap().push(body.clone());
+            Ok(HttpReply { status: 200, body: self.reply.clone() })
+        }
+    }
+
+    fn balance_tool() -> ToolDef {
+        ToolDef {
+            name: "GetNativeBalance".to_string(),
+            description: "Get native token balance".to_string(),
+            input_schema: serde_json::json!({ "type": "object" }),
         }
-        if candidate.ends_with(".eth") { return Some(candidate); }
     }
-    None
+
+    #[tokio::test]
+    async fn anthropic_body_omits_tools_when_none_provided() {
+        let transport = CaptureTransport::new(serde_json::json!({ "content": [{ "type": "text", "text": "hi" }] }));
+        let provider = AnthropicProvider::new("key".to_string()).with_transport(transport.clone());
+        provider.chat(request(None)).await.unwrap();
+        provider.chat(request(Some(vec![]))).await.unwrap();
+        provider.chat(request(Some(vec![balance_tool()]))).await.unwrap();
+
+        let bodies = transport.bodies.lock().unwrap();
+        assert!(bodies[0].get("tools").is_none());
+        assert!(bodies[1].get("tools").is_none());
+        assert_eq!(bodies[2]["tools"][0]["name"], "GetNativeBalance");
+    }
+
+    #[tokio::test]
+    async fn openai_body_omits_tools_when_none_provided() {
+        let transport = CaptureTransport::new(serde_json::json!({ "choices": [{ "message": { "content": "hi" } }] }));
+        let provider = OpenAIProvider::new("key".to_string()).with_transport(transport.clone());
+        provider.chat(request(None)).await.unwrap();
+        provider.chat(request(Some(vec![]))).await.unwrap();
+        provider.chat(request(Some(vec![balance_tool()]))).await.unwrap();
+
+        let bodies = transport.bodies.lock().unwrap();
+        assert!(bodies[0].get("tools").is_none());
+        assert!(bodies[1].get("tools").is_none());
+        assert_eq!(bodies[2]["tools"][0]["function"]["name"], "GetNativeBalance");
+    }
+
+    fn request(tools: Option<Vec<ToolDef>>) -> ChatRequest {
+        ChatRequest {
+            messages: vec![ChatMessage { role: "user".to_string(), content: "hello".to_string() }],
+            model: "test-model".to_string(),
+            temperature: Some(0.0),
+            tools,
+        }
+    }
+
+    #[tokio::test]
+    async fn anthropic_provider_uses_overridden_base_url() {
+        let server = MockServer::start();
+        let m = server.mock(|when, then| {
+            when.method(POST).path("/v1/messages").header("x-api-key", "test-key");
+            then.status(200).json_body(serde_json::json!({
+                "content": [{ "type": "text", "text": "hi from gateway" }],
+                "usage": { "input_tokens": 3, "output_tokens": 4 }
+            }));
+        });
+
+        let provider = AnthropicProvider::with_base_url("test-key".to_string(), server.base_url());
+        let resp = provider.chat(request(None)).await.unwrap();
+        m.assert_hits(1);
+        assert_eq!(resp.content, "hi from gateway");
+        assert_eq!(resp.usage.unwrap().total_tokens, 7);
+    }
+
+    #[tokio::test]
+    async fn anthropic_provider_surfaces_api_error_message() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(POST).path("/v1/messages");
+            then.status(401).json_body(serde_json::json!({
+                "type": "error",
+                "error": { "type": "authentication_error", "message": "invalid x-api-key" }
+            }));
+        });
+
+        let provider = AnthropicProvider::with_base_url("bad-key".to_string(), server.base_url());
+        let err = provider.chat(request(None)).await.unwrap_err();
+        assert_eq!(err.to_string(), "Anthropic API error (401): invalid x-api-key");
+    }
+
+    #[tokio::test]
+    async fn openai_provider_surfaces_api_error_message() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(POST).path("/v1/chat/completions");
+            then.status(401).json_body(serde_json::json!({
+                "error": { "type": "invalid_request_error", "message": "Incorrect API key provided" }
+            }));
+        });
+
+        let provider = OpenAIProvider::with_base_url("bad-key".to_string(), server.base_url());
+        let err = provider.chat(request(None)).await.unwrap_err();
+        assert_eq!(err.to_string(), "OpenAI API error (401): Incorrect API key provided");
+    }
+
+    #[tokio::test]
+    async fn gemini_and_ollama_use_compatible_paths_and_auth() {
+        let server = MockServer::start();
+        let reply = serde_json::json!({ "choices": [{ "message": { "content": "hi" } }] });
+        let gemini = server.mock(|when, then| {
+            when.method(POST).path("/v1beta/openai/chat/completions").header("authorization", "Bearer g-key");
+            then.status(200).json_body(reply.clone());
+        });
+        let ollama = server.mock(|when, then| {
+            when.method(POST).path("/v1/chat/completions").matches(|req| {
+                !req.headers.iter().flatten().any(|(name, _)| name.eq_ignore_ascii_case("authorization"))
+            });
+            then.status(200).json_body(reply.clone());
+        });
+
+        let provider = OpenAIProvider { base_url: server.base_url(), ..OpenAIProvider::gemini("g-key".to_string()) };
+        assert_eq!(provider.chat(request(None)).await.unwrap().content, "hi");
+        let provider = OpenAIProvider { base_url: server.base_url(), ..OpenAIProvider::ollama() };
+        assert_eq!(provider.chat(request(None)).await.unwrap().content, "hi");
+        gemini.assert_hits(1);
+        ollama.assert_hits(1);
+    }
+
+    #[test]
+    fn format_api_error_falls_back_to_raw_body() {
+        assert_eq!(format_api_error("OpenAI", 502, "Bad Gateway"), "OpenAI API error (502): Bad Gateway");
+        assert_eq!(format_api_error("Anthropic", 500, ""), "Anthropic API error (500): no error body");
+    }
+
+    #[tokio::test]
+    async fn openai_provider_uses_overridden_base_url_and_normalizes_tool_calls() {
+        let server = MockServer::start();
+        let m = server.mock(|when, then| {
+            when.method(POST).path("/v1/chat/completions").header("authorization", "Bearer test-key");
+            then.status(200).json_body(serde_json::json!({
+                "choices": [{ "message": { "tool_calls": [{
+                    "type": "function",
+                    "function": { "name": "GetNativeBalance", "arguments": "{\"who\":\"vitalik.eth\"}" }
+                }]}}],
+                "usage": { "prompt_tokens": 5, "completion_tokens": 2, "total_tokens": 7 }
+            }));
+        });
+
+        // Trailing slash on the gateway URL must not produce a double slash
+        let provider = OpenAIProvider::with_base_url("test-key".to_string(), format!("{}/", server.base_url()));
+        let resp = provider.chat(request(None)).await.unwrap();
+        m.assert_hits(1);
+        let json: serde_json::Value = serde_json::from_str(&resp.content).unwrap();
+        assert_eq!(json["function"]["type"], "GetNativeBalance");
+        assert_eq!(json["function"]["who"], "vitalik.eth");
+    }
 }
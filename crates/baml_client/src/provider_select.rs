@@ -0,0 +1,148 @@
+use anyhow::Result;
+use std::sync::Arc;
+
+use crate::parser::DEFAULT_MODEL;
+use crate::provider::{AnthropicProvider, ChatProvider, MockProvider, OpenAIProvider};
+use crate::transport::HttpTransport;
+
+/// LLM backend chosen by `--provider` / `PROVIDER`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum ProviderKind {
+    Mock,
+    Anthropic,
+    Openai,
+    Ollama,
+    Gemini,
+}
+
+impl ProviderKind {
+    /// Precedence: `--mock`, then `--provider`, then `PROVIDER`, then the model name
+    /// (`claude*` → Anthropic, any other explicit model → OpenAI), then Anthropic.
+    pub fn resolve(mock: bool, flag: Option<ProviderKind>, env: Option<&str>, model: Option<&str>) -> Result<Self> {
+        if mock {
+            return Ok(Self::Mock);
+        }
+        if let Some(kind) = flag {
+            return Ok(kind);
+        }
+        if let Some(raw) = env.map(str::trim).filter(|v| !v.is_empty()) {
+            return <Self as clap::ValueEnum>::from_str(raw, true).map_err(|_| {
+                anyhow::anyhow!("PROVIDER must be one of mock, anthropic, openai, ollama, gemini; got '{}'", raw)
+            });
+        }
+        Ok(match model {
+            Some(m) if !m.starts_with("claude") => Self::Openai,
+            _ => Self::Anthropic,
+        })
+    }
+
+    /// Env var holding the API key this provider needs, if any
+    pub fn required_key(self) -> Option<&'static str> {
+        match self {
+            Self::Anthropic => Some("ANTHROPIC_API_KEY"),
+            Self::Openai => Some("OPENAI_API_KEY"),
+            Self::Gemini => Some("GEMINI_API_KEY"),
+            Self::Mock | Self::Ollama => None,
+        }
+    }
+
+    pub fn default_model(self) -> &'static str {
+        match self {
+            Self::Mock | Self::Anthropic => DEFAULT_MODEL,
+            Self::Openai => "gpt-4o-mini",
+            Self::Ollama => "llama3.1",
+            Self::Gemini => "gemini-2.0-flash",
+        }
+    }
+
+    /// Build the provider, failing early with a clear message when its key is missing.
+    /// `lookup` reads env vars (injectable for tests).
+    pub fn build(
+        self,
+        lookup: impl Fn(&str) -> Option<String>,
+        transport: Option<Arc<dyn HttpTransport>>,
+    ) -> Result<Box<dyn ChatProvider>> {
+        let key = match self.required_key() {
+            Some(var) => match lookup(var).filter(|k| !k.trim().is_empty()) {
+                Some(key) => key,
+                None => anyhow::bail!("provider '{}' requires {} to be set", self.as_str(), var),
+            },
+            None => String::new(),
+        };
+        let with_transport = |p: OpenAIProvider| match &transport {
+            Some(t) => p.with_transport(t.clone()),
+            None => p,
+        };
+        Ok(match self {
+            Self::Mock => Box::new(MockProvider::new()),
+            Self::Anthropic => {
+                let provider = AnthropicProvider::from_env(key);
+                Box::new(match &transport {
+                    Some(t) => provider.with_transport(t.clone()),
+                    None => provider,
+                })
+            }
+            Self::Openai => Box::new(with_transport(OpenAIProvider::from_env(key))),
+            Self::Ollama => Box::new(with_transport(OpenAIProvider::ollama())),
+            Self::Gemini => Box::new(with_transport(OpenAIProvider::gemini(key))),
+        })
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Mock => "mock",
+            Self::Anthropic => "anthropic",
+            Self::Openai => "openai",
+            Self::Ollama => "ollama",
+            Self::Gemini => "gemini",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(var: &str) -> Option<String> {
+        match var {
+            "ANTHROPIC_API_KEY" | "OPENAI_API_KEY" | "GEMINI_API_KEY" => Some("test-key".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn resolve_follows_precedence() {
+        use ProviderKind::*;
+        assert_eq!(ProviderKind::resolve(true, Some(Openai), Some("gemini"), None).unwrap(), Mock);
+        assert_eq!(ProviderKind::resolve(false, Some(Ollama), Some("gemini"), None).unwrap(), Ollama);
+        assert_eq!(ProviderKind::resolve(false, None, Some("Gemini"), Some("gpt-4o")).unwrap(), Gemini);
+        assert_eq!(ProviderKind::resolve(false, None, None, Some("gpt-4o")).unwrap(), Openai);
+        assert_eq!(ProviderKind::resolve(false, None, None, Some("claude-3-5-haiku")).unwrap(), Anthropic);
+        assert_eq!(ProviderKind::resolve(false, None, Some(" "), None).unwrap(), Anthropic);
+        assert!(ProviderKind::resolve(false, None, Some("bard"), None).is_err());
+    }
+
+    #[test]
+    fn each_provider_builds_the_matching_impl() {
+        let cases = [
+            (ProviderKind::Mock, "Mock"),
+            (ProviderKind::Anthropic, "Anthropic"),
+            (ProviderKind::Openai, "OpenAI"),
+            (ProviderKind::Ollama, "Ollama"),
+            (ProviderKind::Gemini, "Gemini"),
+        ];
+        for (kind, name) in cases {
+            let provider = kind.build(keys, None).unwrap();
+            assert_eq!(provider.name(), name);
+        }
+    }
+
+    #[test]
+    fn missing_key_is_reported_for_the_chosen_provider() {
+        let no_keys = |_: &str| None;
+        let err = ProviderKind::Gemini.build(no_keys, None).err().unwrap();
+        assert_eq!(err.to_string(), "provider 'gemini' requires GEMINI_API_KEY to be set");
+        assert!(ProviderKind::Ollama.build(no_keys, None).is_ok());
+        assert!(ProviderKind::Mock.build(no_keys, None).is_ok());
+    }
+}
@@ -1,15 +1,19 @@
 use anyhow::Result;
-use crate::baml::BamlFunction;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use crate::baml::{BamlFunction, CustomCall};
 
 #[allow(dead_code)]
 pub trait Tool: Send + Sync {
-    fn name(&self) -> &'static str;
-    fn description(&self) -> &'static str;
+    fn name(&self) -> &str;
+    fn description(&self) -> &str;
     fn input_schema(&self) -> serde_json::Value;
     fn to_baml_function(&self, input: &serde_json::Value) -> Result<BamlFunction>;
 }
 
 #[allow(dead_code)]
+#[derive(Default)]
 pub struct ToolRegistry {
     tools: Vec<Box<dyn Tool>>,    
 }
@@ -24,6 +28,7 @@ impl ToolRegistry {
         r.register(GetCodeTool);
         r.register(GetFungibleBalanceTool);
         r.register(SendNativeTool);
+        r.register(TransferTokenTool);
         r
     }
 
@@ -31,6 +36,30 @@ impl ToolRegistry {
         self.tools.push(Box::new(tool));
     }
 
+    /// Register every declarative tool in a JSON array of `DeclarativeTool` entries.
+    pub fn register_declarative_json(&mut self, raw: &str) -> Result<usize> {
+        let tools: Vec<DeclarativeTool> = serde_json::from_str(raw)?;
+        let count = tools.len();
+        for tool in tools {
+            if self.contains(&tool.name) {
+                anyhow::bail!("declarative tool '{}' clashes with an existing tool", tool.name);
+            }
+            self.register(tool);
+        }
+        Ok(count)
+    }
+
+    pub fn register_declarative_file(&mut self, path: &Path) -> Result<usize> {
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("failed to read tools config {}: {}", path.display(), e))?;
+        self.register_declarative_json(&raw)
+            .map_err(|e| anyhow::anyhow!("invalid tools config {}: {}", path.display(), e))
+    }
+
+    pub fn contains(&self, tool_name: &str) -> bool {
+        self.tools.iter().any(|t| t.name() == tool_name)
+    }
+
     pub fn tool_defs(&self) -> Vec<crate::provider::ToolDef> {
         self.tools.iter().map(|t| crate::provider::ToolDef {
             name: t.name().to_string(),
@@ -49,10 +78,41 @@ impl ToolRegistry {
     }
 }
 
+/// A tool defined in config rather than code: the LLM sees `name`, `description` and
+/// `input_schema`, and a call becomes a POST to `endpoint` whose body is built from
+/// `fields` (request field → tool input field).
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeclarativeTool {
+    pub name: String,
+    pub description: String,
+    pub input_schema: serde_json::Value,
+    pub endpoint: String,
+    #[serde(default)]
+    pub fields: BTreeMap<String, String>,
+}
+
+impl Tool for DeclarativeTool {
+    fn name(&self) -> &str { &self.name }
+    fn description(&self) -> &str { &self.description }
+    fn input_schema(&self) -> serde_json::Value { self.input_schema.clone() }
+    fn to_baml_function(&self, input: &serde_json::Value) -> Result<BamlFunction> {
+        let mut body = serde_json::Map::new();
+        for (request_field, input_field) in &self.fields {
+            let value = input.get(input_field).ok_or_else(|| anyhow::anyhow!("Missing '{}' parameter", input_field))?;
+            body.insert(request_field.clone(), value.clone());
+        }
+        Ok(BamlFunction::Custom(CustomCall {
+            tool: self.name.clone(),
+            endpoint: self.endpoint.clone(),
+            body: serde_json::Value::Object(body),
+        }))
+    }
+}
+
 struct GetNativeBalanceTool;
 impl Tool for GetNativeBalanceTool {
-    fn name(&self) -> &'static str { "GetNativeBalance" }
-    fn description(&self) -> &'static str { "Get native token balance of an address or name" }
+    fn name(&self) -> &str { "GetNativeBalance" }
+    fn description(&self) -> &str { "Get native token balance of an address or name" }
     fn input_schema(&self) -> serde_json::Value {
         serde_json::json!({
             "type": "object",
@@ -62,16 +122,15 @@ impl Tool for GetNativeBalanceTool {
     }
     fn to_baml_function(&self, input: &serde_json::Value) -> Result<BamlFunction> {
         let who = input.get("who").and_then(|v| v.as_str()).ok_or_else(|| anyhow::anyhow!("Missing 'who' parameter"))?;
-        let who_s = who.to_string();
-        let addr_or_ens = if who_s.ends_with(".eth") { domain::AddressOrEns::from_ens(who_s) } else { domain::AddressOrEns::from_address(who_s) };
+        let addr_or_ens = domain::AddressOrEns::parse(who.to_string());
         Ok(BamlFunction::Balance(domain::BalanceRequest::new(addr_or_ens)))
     }
 }
 
 struct GetCodeTool;
 impl Tool for GetCodeTool {
-    fn name(&self) -> &'static str { "GetCode" }
-    fn description(&self) -> &'static str { "Get code/deployment status for an address" }
+    fn name(&self) -> &str { "GetCode" }
+    fn description(&self) -> &str { "Get code/deployment status for an address" }
     fn input_schema(&self) -> serde_json::Value {
         serde_json::json!({
             "type": "object",
@@ -87,8 +146,8 @@ impl Tool for GetCodeTool {
 
 struct GetFungibleBalanceTool;
 impl Tool for GetFungibleBalanceTool {
-    fn name(&self) -> &'static str { "GetFungibleBalance" }
-    fn description(&self) -> &'static str { "Get fungible token balance for holder" }
+    fn name(&self) -> &str { "GetFungibleBalance" }
+    fn description(&self) -> &str { "Get fungible token balance for holder" }
     fn input_schema(&self) -> serde_json::Value {
         serde_json::json!({
             "type": "object",
@@ -108,8 +167,8 @@ impl Tool for GetFungibleBalanceTool {
 
 struct SendNativeTool;
 impl Tool for SendNativeTool {
-    fn name(&self) -> &'static str { "SendNative" }
-    fn description(&self) -> &'static str { "Send native token from one address to another" }
+    fn name(&self) -> &str { "SendNative" }
+    fn description(&self) -> &str { "Send native token from one address to another" }
     fn input_schema(&self) -> serde_json::Value {
         serde_json::json!({
             "type": "object",
@@ -138,4 +197,86 @@ impl Tool for SendNativeTool {
     }
 }
 
+struct TransferTokenTool;
+impl Tool for TransferTokenTool {
+    fn name(&self) -> &str { "TransferToken" }
+    fn description(&self) -> &str { "Send fungible (ERC-20) tokens from one address to another; amount is in whole tokens" }
+    fn input_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "token": {"type": "string"},
+                "from": {"type": "string"},
+                "to": {"type": "string"},
+                "amount": {"type": "string"},
+                "simulate": {"type": "boolean"}
+            },
+            "required": ["token", "from", "to", "amount"],
+        })
+    }
+    fn to_baml_function(&self, input: &serde_json::Value) -> Result<BamlFunction> {
+        let token = input.get("token").and_then(|v| v.as_str()).ok_or_else(|| anyhow::anyhow!("Missing 'token' parameter"))?;
+        let from = input.get("from").and_then(|v| v.as_str()).ok_or_else(|| anyhow::anyhow!("Missing 'from' parameter"))?;
+        let to = input.get("to").and_then(|v| v.as_str()).ok_or_else(|| anyhow::anyhow!("Missing 'to' parameter"))?;
+        let amount = input.get("amount").and_then(|v| v.as_str()).ok_or_else(|| anyhow::anyhow!("Missing 'amount' parameter"))?;
+        let simulate = input.get("simulate").and_then(|v| v.as_bool()).unwrap_or(true);
+        Ok(BamlFunction::Erc20Transfer(
+            domain::Erc20TransferRequest::builder()
+                .token(domain::Address::new(token.to_string()))
+                .from(domain::Address::new(from.to_string()))
+                .to(domain::Address::new(to.to_string()))
+                .amount(amount)
+                .simulate(simulate)
+                .build().map_err(|e| anyhow::anyhow!("{}", e))?
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"[{
+        "name": "GetGasPrice",
+        "description": "Current gas price on a chain",
+        "input_schema": { "type": "object", "properties": { "chain": { "type": "string" } }, "required": ["chain"] },
+        "endpoint": "/gas_price",
+        "fields": { "network": "chain" }
+    }]"#;
+
+    #[test]
+    fn declarative_tool_is_advertised_and_builds_the_call() {
+        let mut registry = ToolRegistry::with_default_tools();
+        assert_eq!(registry.register_declarative_json(CONFIG).unwrap(), 1);
+
+        let defs = registry.tool_defs();
+        let def = defs.iter().find(|d| d.name == "GetGasPrice").expect("declarative tool in tool_defs");
+        assert_eq!(def.description, "Current gas price on a chain");
+        assert_eq!(def.input_schema["required"][0], "chain");
+
+        let call = registry.to_baml_function("GetGasPrice", &serde_json::json!({ "chain": "base" })).unwrap();
+        let BamlFunction::Custom(call) = call else { panic!("expected a custom call") };
+        assert_eq!(call.endpoint, "/gas_price");
+        assert_eq!(call.body, serde_json::json!({ "network": "base" }));
+    }
+
+    #[test]
+    fn transfer_token_maps_to_an_erc20_transfer() {
+        let registry = ToolRegistry::with_default_tools();
+        assert!(registry.tool_defs().iter().any(|d| d.name == "TransferToken"));
+        let input = serde_json::json!({ "token": "0xA0b8", "from": "0xAlice", "to": "0xBob", "amount": "2.5" });
+        let BamlFunction::Erc20Transfer(req) = registry.to_baml_function("TransferToken", &input).unwrap() else { panic!("expected a token transfer") };
+        assert_eq!((req.token().as_str(), req.from().as_str(), req.to().as_str()), ("0xA0b8", "0xAlice", "0xBob"));
+        assert_eq!(req.amount(), "2.5");
+        assert!(req.simulate());
+        assert!(registry.to_baml_function("TransferToken", &serde_json::json!({ "token": "0xA0b8" })).is_err());
+    }
+
+    #[test]
+    fn declarative_tool_cannot_shadow_a_builtin() {
+        let mut registry = ToolRegistry::with_default_tools();
+        let clash = CONFIG.replace("GetGasPrice", "GetCode");
+        assert!(registry.register_declarative_json(&clash).is_err());
+    }
+}
 
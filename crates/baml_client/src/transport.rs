@@ -0,0 +1,263 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use domain::http::HttpTimeouts;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Raw HTTP reply handed back to providers for status checks and parsing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpReply {
+    pub status: u16,
+    pub body: String,
+}
+
+impl HttpReply {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// The single HTTP operation LLM providers need: POST a JSON body to a URL.
+/// Swapping the transport lets tests record and replay real provider traffic.
+#[async_trait]
+pub trait HttpTransport: Send + Sync {
+    async fn post_json(&self, url: &str, headers: &[(&str, &str)], body: &Value) -> Result<HttpReply>;
+}
+
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+    timeouts: HttpTimeouts,
+}
+
+impl ReqwestTransport {
+    /// Timeouts from `HTTP_CONNECT_TIMEOUT_SECS` / `HTTP_TIMEOUT_SECS`, else the defaults
+    pub fn new() -> Self {
+        Self::with_timeouts(HttpTimeouts::from_env())
+    }
+
+    pub fn with_timeouts(timeouts: HttpTimeouts) -> Self {
+        let client = reqwest::Client::builder()
+            .connect_timeout(timeouts.connect)
+            .timeout(timeouts.request)
+            .build()
+            .expect("reqwest client with timeouts");
+        Self { client, timeouts }
+    }
+}
+
+impl Default for ReqwestTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl HttpTransport for ReqwestTransport {
+    async fn post_json(&self, url: &str, headers: &[(&str, &str)], body: &Value) -> Result<HttpReply> {
+        let mut request = self.client.post(url).json(body);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let timed_out = |e: reqwest::Error| -> anyhow::Error {
+            if e.is_timeout() { self.timeouts.timed_out(url, e.is_connect()).into() } else { e.into() }
+        };
+        let response = request.send().await.map_err(timed_out)?;
+        let status = response.status().as_u16();
+        let body = response.text().await.map_err(timed_out)?;
+        Ok(HttpReply { status, body })
+    }
+}
+
+/// One recorded request/response pair. Headers are never stored so cassettes
+/// cannot leak API keys; URLs are stored as paths so replay ignores the host.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub path: String,
+    pub request: Value,
+    pub response: Value,
+    pub status: u16,
+}
+
+enum Mode {
+    Record(Box<dyn HttpTransport>),
+    Replay,
+}
+
+/// Records interactions to a cassette file, or serves them back in order.
+///
+/// Replay checks each outgoing body against the recorded one, so a cassette
+/// doubles as a regression test for the provider's request shaping.
+pub struct RecordReplayTransport {
+    path: PathBuf,
+    mode: Mode,
+    interactions: Mutex<Vec<Interaction>>,
+    cursor: Mutex<usize>,
+}
+
+impl RecordReplayTransport {
+    /// Forward to `inner` and append every interaction to the cassette at `path`.
+    pub fn record(path: impl Into<PathBuf>, inner: Box<dyn HttpTransport>) -> Self {
+        Self { path: path.into(), mode: Mode::Record(inner), interactions: Mutex::new(Vec::new()), cursor: Mutex::new(0) }
+    }
+
+    /// Serve interactions from an existing cassette without touching the network.
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let raw = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow::anyhow!("failed to read cassette {}: {}", path.display(), e))?;
+        let interactions: Vec<Interaction> = serde_json::from_str(&raw)?;
+        Ok(Self { path, mode: Mode::Replay, interactions: Mutex::new(interactions), cursor: Mutex::new(0) })
+    }
+
+    /// `LLM_CASSETTE=<path>` with `LLM_CASSETTE_MODE=record|replay` (default replay)
+    pub fn from_env() -> Result<Option<Arc<dyn HttpTransport>>> {
+        let Ok(path) = std::env::var("LLM_CASSETTE") else { return Ok(None) };
+        let mode = std::env::var("LLM_CASSETTE_MODE").unwrap_or_else(|_| "replay".to_string());
+        let transport: Arc<dyn HttpTransport> = match mode.as_str() {
+            "record" => Arc::new(Self::record(path, Box::new(ReqwestTransport::new()))),
+            "replay" => Arc::new(Self::replay(path)?),
+            other => anyhow::bail!("LLM_CASSETTE_MODE must be 'record' or 'replay', got '{}'", other),
+        };
+        Ok(Some(transport))
+    }
+
+    fn save(path: &Path, interactions: &[Interaction]) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(interactions)?)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl HttpTransport for RecordReplayTransport {
+    async fn post_json(&self, url: &str, headers: &[(&str, &str)], body: &Value) -> Result<HttpReply> {
+        let path = url_path(url);
+        match &self.mode {
+            Mode::Record(inner) => {
+                let reply = inner.post_json(url, headers, body).await?;
+                let response = serde_json::from_str(&reply.body).unwrap_or(Value::String(reply.body.clone()));
+                let mut interactions = self.interactions.lock().unwrap();
+                interactions.push(Interaction { path, request: body.clone(), response, status: reply.status });
+                Self::save(&self.path, &interactions)?;
+                Ok(reply)
+            }
+            Mode::Replay => {
+                let interactions = self.interactions.lock().unwrap();
+                let mut cursor = self.cursor.lock().unwrap();
+                let recorded = interactions.get(*cursor).ok_or_else(|| {
+                    anyhow::anyhow!("cassette {} exhausted after {} interactions", self.path.display(), *cursor)
+                })?;
+                if recorded.path != path || recorded.request != *body {
+                    anyhow::bail!(
+                        "cassette mismatch at interaction {}: expected POST {} {}, got POST {} {}",
+                        *cursor, recorded.path, recorded.request, path, body
+                    );
+                }
+                *cursor += 1;
+                let body = match &recorded.response {
+                    Value::String(raw) => raw.clone(),
+                    other => other.to_string(),
+                };
+                Ok(HttpReply { status: recorded.status, body })
+            }
+        }
+    }
+}
+
+/// Strip scheme and host: "https://api.anthropic.com/v1/messages" -> "/v1/messages"
+fn url_path(url: &str) -> String {
+    let without_scheme = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    match without_scheme.find('/') {
+        Some(idx) => without_scheme[idx..].to_string(),
+        None => "/".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::{AnthropicProvider, ChatMessage, ChatProvider, ChatRequest, ToolDef};
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures").join(name)
+    }
+
+    fn balance_request() -> ChatRequest {
+        ChatRequest {
+            messages: vec![ChatMessage { role: "user".to_string(), content: "What's vitalik.eth's balance?".to_string() }],
+            model: "claude-sonnet-4-20250514".to_string(),
+            temperature: Some(0.0),
+            tools: Some(vec![ToolDef {
+                name: "GetNativeBalance".to_string(),
+                description: "Get native token balance of an address or name".to_string(),
+                input_schema: serde_json::json!({
+                    "type": "object",
+                    "properties": { "who": { "type": "string" } },
+                    "required": ["who"],
+                }),
+            }]),
+        }
+    }
+
+    #[tokio::test]
+    async fn replays_recorded_anthropic_tool_use() {
+        let transport = RecordReplayTransport::replay(fixture("anthropic_tool_use.json")).unwrap();
+        let provider = AnthropicProvider::new("unused".to_string()).with_transport(Arc::new(transport));
+
+        let resp = provider.chat(balance_request()).await.unwrap();
+        let json: Value = serde_json::from_str(&resp.content).unwrap();
+        assert_eq!(json["function"]["type"], "GetNativeBalance");
+        assert_eq!(json["function"]["who"], "vitalik.eth");
+        assert_eq!(resp.usage.unwrap().total_tokens, 420);
+    }
+
+    #[tokio::test]
+    async fn replay_rejects_changed_request_body() {
+        let transport = RecordReplayTransport::replay(fixture("anthropic_tool_use.json")).unwrap();
+        let provider = AnthropicProvider::new("unused".to_string()).with_transport(Arc::new(transport));
+
+        let mut request = balance_request();
+        request.model = "claude-other".to_string();
+        let err = provider.chat(request).await.unwrap_err();
+        assert!(err.to_string().contains("cassette mismatch"));
+    }
+
+    #[tokio::test]
+    async fn records_then_replays_round_trip() {
+        let server = httpmock::MockServer::start();
+        let m = server.mock(|when, then| {
+            when.method(httpmock::Method::POST).path("/v1/messages");
+            then.status(200).json_body(serde_json::json!({
+                "content": [{ "type": "text", "text": "recorded hello" }],
+                "usage": { "input_tokens": 1, "output_tokens": 2 }
+            }));
+        });
+        let path = std::env::temp_dir().join(format!("cassette-{}.json", std::process::id()));
+
+        let recorder = RecordReplayTransport::record(&path, Box::new(ReqwestTransport::new()));
+        let provider = AnthropicProvider::with_base_url("key".to_string(), server.base_url()).with_transport(Arc::new(recorder));
+        assert_eq!(provider.chat(balance_request()).await.unwrap().content, "recorded hello");
+
+        let player = RecordReplayTransport::replay(&path).unwrap();
+        let provider = AnthropicProvider::new("key".to_string()).with_transport(Arc::new(player));
+        assert_eq!(provider.chat(balance_request()).await.unwrap().content, "recorded hello");
+        m.assert_hits(1);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn slow_provider_fails_with_a_timeout_error() {
+        let server = httpmock::MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).path("/v1/messages");
+            then.status(200).delay(std::time::Duration::from_secs(2)).json_body(serde_json::json!({ "content": [] }));
+        });
+        let timeouts = HttpTimeouts { connect: std::time::Duration::from_secs(1), request: std::time::Duration::from_millis(200) };
+        let provider = AnthropicProvider::with_base_url("key".to_string(), server.base_url()).with_transport(Arc::new(ReqwestTransport::with_timeouts(timeouts)));
+        let err = provider.chat(balance_request()).await.unwrap_err();
+        let timed_out = err.downcast_ref::<domain::http::TimedOut>().unwrap_or_else(|| panic!("expected a timeout, got {:#}", err));
+        assert_eq!(timed_out.after, timeouts.request);
+        assert!(timed_out.target.ends_with("/v1/messages"), "{}", timed_out);
+    }
+}
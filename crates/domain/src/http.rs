@@ -0,0 +1,65 @@
+//! Timeouts shared by the outbound HTTP clients (LLM providers, the MCP client,
+//! explorer and token APIs, JSON-RPC over HTTP).
+//!
+//! Each client builds its own `reqwest::Client` from these; the domain crate only
+//! carries the numbers and the error, so it stays free of an HTTP dependency.
+
+use std::time::Duration;
+
+use thiserror::Error;
+
+pub const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+pub const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+
+/// How long to wait for a connection and for a whole request (connect through body)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HttpTimeouts {
+    pub connect: Duration,
+    pub request: Duration,
+}
+
+impl Default for HttpTimeouts {
+    fn default() -> Self {
+        Self { connect: Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SECS), request: Duration::from_secs(DEFAULT_REQUEST_TIMEOUT_SECS) }
+    }
+}
+
+impl HttpTimeouts {
+    /// `HTTP_CONNECT_TIMEOUT_SECS` and `HTTP_TIMEOUT_SECS`; unset or invalid values keep the defaults
+    pub fn from_env() -> Self {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    pub fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let secs = |name: &str| var(name).and_then(|v| v.trim().parse().ok()).map(Duration::from_secs);
+        let defaults = Self::default();
+        Self { connect: secs("HTTP_CONNECT_TIMEOUT_SECS").unwrap_or(defaults.connect), request: secs("HTTP_TIMEOUT_SECS").unwrap_or(defaults.request) }
+    }
+
+    /// The error for a call to `target` that timed out, while connecting or afterwards
+    pub fn timed_out(&self, target: impl Into<String>, connecting: bool) -> TimedOut {
+        TimedOut { target: target.into(), after: if connecting { self.connect } else { self.request } }
+    }
+}
+
+/// An outbound call that got no answer within its timeout
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("{target} timed out after {}s", after.as_secs_f64())]
+pub struct TimedOut {
+    pub target: String,
+    pub after: Duration,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeouts_come_from_env_with_defaults() {
+        let vars = std::collections::HashMap::from([("HTTP_TIMEOUT_SECS", "5"), ("HTTP_CONNECT_TIMEOUT_SECS", "soon")]);
+        let timeouts = HttpTimeouts::from_vars(|name| vars.get(name).map(|v| v.to_string()));
+        assert_eq!(timeouts.request, Duration::from_secs(5));
+        assert_eq!(timeouts.connect, Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SECS));
+        assert_eq!(timeouts.timed_out("https://api.example", false).to_string(), "https://api.example timed out after 5s");
+    }
+}
@@ -1,6 +1,9 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
+pub mod http;
+pub mod units;
+
 /// Represents an Ethereum address as a checksummed string.
 /// Fields are private; use constructors and getters.
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -46,6 +49,31 @@ pub enum AddressOrEns {
 impl AddressOrEns {
     pub fn from_address(address: String) -> Self { Self::Address(Address::new(address)) }
     pub fn from_ens(name: String) -> Self { Self::Ens(EnsName::new(name)) }
+
+    /// Classify free-form input: ENS-looking names become `Ens`, everything else `Address`.
+    pub fn parse(input: String) -> Self {
+        if looks_like_ens(&input) { Self::from_ens(input) } else { Self::from_address(input) }
+    }
+}
+
+/// Shape check only (0x + 40 hex chars); checksum validation stays in the adapter.
+pub fn looks_like_address(value: &str) -> bool {
+    value.len() == 42 && value.starts_with("0x") && value[2..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Any dotted name that is not a hex address: `vitalik.eth`, `foo.xyz`, `name.cb.id`,
+/// DNS-imported names. Labels must be non-empty and the TLD must contain a letter,
+/// so amounts like `0.1` are not mistaken for names.
+pub fn looks_like_ens(input: &str) -> bool {
+    let input = input.trim();
+    if looks_like_address(input) || !input.contains('.') {
+        return false;
+    }
+    let valid_labels = input
+        .split('.')
+        .all(|label| !label.is_empty() && label.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_'));
+    let tld_has_letter = input.rsplit('.').next().is_some_and(|tld| tld.chars().any(|c| c.is_alphabetic()));
+    valid_labels && tld_has_letter
 }
 
 /// Request/Response types for tools
@@ -53,11 +81,16 @@ impl AddressOrEns {
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct BalanceRequest {
     who: AddressOrEns,
+    /// Historical block number; latest when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    block: Option<u64>,
 }
 
 impl BalanceRequest {
-    pub fn new(who: AddressOrEns) -> Self { Self { who } }
+    pub fn new(who: AddressOrEns) -> Self { Self { who, block: None } }
+    pub fn with_block(mut self, block: Option<u64>) -> Self { self.block = block; self }
     pub fn who(&self) -> &AddressOrEns { &self.who }
+    pub fn block(&self) -> Option<u64> { self.block }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -114,6 +147,15 @@ impl Erc20BalanceResponse {
     pub fn amount(&self) -> &str { &self.amount }
 }
 
+/// Transaction envelope for a send; unset means the adapter picks EIP-1559 when the
+/// chain reports a base fee and legacy otherwise.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TxType {
+    Legacy,
+    Eip1559,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct SendRequest {
     from: Address,
@@ -121,6 +163,10 @@ pub struct SendRequest {
     amount_eth: String,
     simulate: bool,
     fork_block: Option<u64>,
+    #[serde(default)]
+    confirmations: Option<u64>,
+    #[serde(default)]
+    tx_type: Option<TxType>,
 }
 
 impl SendRequest {
@@ -130,6 +176,10 @@ impl SendRequest {
     pub fn amount_eth(&self) -> &str { &self.amount_eth }
     pub fn simulate(&self) -> bool { self.simulate }
     pub fn fork_block(&self) -> Option<u64> { self.fork_block }
+    /// Blocks to wait for after broadcast; `None` uses the chain's default
+    pub fn confirmations(&self) -> Option<u64> { self.confirmations }
+    /// Forced transaction type; `None` auto-detects from the chain's base fee
+    pub fn tx_type(&self) -> Option<TxType> { self.tx_type }
 }
 
 #[derive(Default)]
@@ -139,6 +189,8 @@ pub struct SendRequestBuilder {
     amount_eth: Option<String>,
     simulate: Option<bool>,
     fork_block: Option<u64>,
+    confirmations: Option<u64>,
+    tx_type: Option<TxType>,
 }
 
 impl SendRequestBuilder {
@@ -147,6 +199,8 @@ impl SendRequestBuilder {
     pub fn amount_eth(mut self, amount_eth: impl Into<String>) -> Self { self.amount_eth = Some(amount_eth.into()); self }
     pub fn simulate(mut self, simulate: bool) -> Self { self.simulate = Some(simulate); self }
     pub fn fork_block(mut self, fork_block: Option<u64>) -> Self { self.fork_block = fork_block; self }
+    pub fn confirmations(mut self, confirmations: Option<u64>) -> Self { self.confirmations = confirmations; self }
+    pub fn tx_type(mut self, tx_type: Option<TxType>) -> Self { self.tx_type = tx_type; self }
     pub fn build(self) -> Result<SendRequest, &'static str> {
         Ok(SendRequest {
             from: self.from.ok_or("from required")?,
@@ -154,6 +208,54 @@ impl SendRequestBuilder {
             amount_eth: self.amount_eth.ok_or("amount_eth required")?,
             simulate: self.simulate.unwrap_or(true),
             fork_block: self.fork_block,
+            confirmations: self.confirmations,
+            tx_type: self.tx_type,
+        })
+    }
+}
+
+/// ERC-20 `transfer`; `amount` is in whole tokens (e.g. "1.5") and is scaled by the
+/// token's decimals when sent
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Erc20TransferRequest {
+    token: Address,
+    from: Address,
+    to: Address,
+    amount: String,
+    simulate: bool,
+}
+
+impl Erc20TransferRequest {
+    pub fn builder() -> Erc20TransferRequestBuilder { Erc20TransferRequestBuilder::default() }
+    pub fn token(&self) -> &Address { &self.token }
+    pub fn from(&self) -> &Address { &self.from }
+    pub fn to(&self) -> &Address { &self.to }
+    pub fn amount(&self) -> &str { &self.amount }
+    pub fn simulate(&self) -> bool { self.simulate }
+}
+
+#[derive(Default)]
+pub struct Erc20TransferRequestBuilder {
+    token: Option<Address>,
+    from: Option<Address>,
+    to: Option<Address>,
+    amount: Option<String>,
+    simulate: Option<bool>,
+}
+
+impl Erc20TransferRequestBuilder {
+    pub fn token(mut self, token: Address) -> Self { self.token = Some(token); self }
+    pub fn from(mut self, from: Address) -> Self { self.from = Some(from); self }
+    pub fn to(mut self, to: Address) -> Self { self.to = Some(to); self }
+    pub fn amount(mut self, amount: impl Into<String>) -> Self { self.amount = Some(amount.into()); self }
+    pub fn simulate(mut self, simulate: bool) -> Self { self.simulate = Some(simulate); self }
+    pub fn build(self) -> Result<Erc20TransferRequest, &'static str> {
+        Ok(Erc20TransferRequest {
+            token: self.token.ok_or("token required")?,
+            from: self.from.ok_or("from required")?,
+            to: self.to.ok_or("to required")?,
+            amount: self.amount.ok_or("amount required")?,
+            simulate: self.simulate.unwrap_or(true),
         })
     }
 }
@@ -163,17 +265,249 @@ pub struct TxResult {
     tx_hash: String,
     gas_used: Option<u64>,
     status: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    estimated_seconds: Option<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    balance_changes: Vec<BalanceChange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    effective_gas_price: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fee_wei: Option<String>,
+}
+
+/// Projected effect of a simulated send on one address, in wei. `delta` is signed
+/// (`-…` / `+…`); for the sender it includes the estimated gas cost.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BalanceChange {
+    pub address: String,
+    pub before: String,
+    pub after: String,
+    pub delta: String,
 }
 
 impl TxResult {
     pub fn new(tx_hash: String, gas_used: Option<u64>, status: Option<bool>) -> Self {
-        Self { tx_hash, gas_used, status }
+        Self { tx_hash, gas_used, status, estimated_seconds: None, balance_changes: Vec::new(), effective_gas_price: None, fee_wei: None }
+    }
+    /// Price from a mined receipt; with `gas_used` known this also sets the total fee
+    pub fn with_effective_gas_price(mut self, effective_gas_price: Option<u64>) -> Self {
+        self.effective_gas_price = effective_gas_price;
+        self.fee_wei = effective_gas_price.zip(self.gas_used).map(|(price, used)| (price as u128 * used as u128).to_string());
+        self
     }
+    pub fn effective_gas_price(&self) -> Option<u64> { self.effective_gas_price }
+    /// Wei actually paid for gas: `effective_gas_price * gas_used`
+    pub fn fee_wei(&self) -> Option<&str> { self.fee_wei.as_deref() }
+    /// Dry-run diff attached to simulations
+    pub fn with_balance_changes(mut self, balance_changes: Vec<BalanceChange>) -> Self {
+        self.balance_changes = balance_changes;
+        self
+    }
+    pub fn balance_changes(&self) -> &[BalanceChange] { &self.balance_changes }
+    /// Best-effort time-to-inclusion estimate attached to simulations
+    pub fn with_estimated_seconds(mut self, estimated_seconds: Option<u64>) -> Self {
+        self.estimated_seconds = estimated_seconds;
+        self
+    }
+    pub fn estimated_seconds(&self) -> Option<u64> { self.estimated_seconds }
     pub fn tx_hash(&self) -> &str { &self.tx_hash }
     pub fn gas_used(&self) -> Option<u64> { self.gas_used }
     pub fn status(&self) -> Option<bool> { self.status }
 }
 
+/// Read-only `eth_call` against a contract with raw ABI-encoded calldata (0x-hex)
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ContractCallRequest {
+    to: Address,
+    data: String,
+}
+
+impl ContractCallRequest {
+    pub fn new(to: Address, data: impl Into<String>) -> Self { Self { to, data: data.into() } }
+    pub fn to(&self) -> &Address { &self.to }
+    pub fn data(&self) -> &str { &self.data }
+}
+
+/// Raw return data of a contract call (0x-hex); decoding is left to the caller
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ContractCallResponse {
+    data: String,
+}
+
+impl ContractCallResponse {
+    pub fn new(data: impl Into<String>) -> Self { Self { data: data.into() } }
+    pub fn data(&self) -> &str { &self.data }
+}
+
+/// Whether a send was only simulated or actually broadcast
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SendMode {
+    Simulated,
+    Broadcast,
+}
+
+/// Wire shape of a send outcome. `tx_hash` is `None` for simulations instead of an
+/// empty string, and `mode` says whether anything reached the chain.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SimResult {
+    pub mode: SendMode,
+    pub tx_hash: Option<String>,
+    pub estimated_gas: Option<u64>,
+    pub gas_used: Option<u64>,
+    pub success: bool,
+    /// Simulations only: rough seconds until inclusion; omitted when it can't be computed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_seconds: Option<u64>,
+    /// Simulations only: projected balances of sender and recipient
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub balance_changes: Vec<BalanceChange>,
+    /// Broadcasts only: price per gas the receipt reports
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_gas_price: Option<u64>,
+    /// Broadcasts only: total wei paid for gas
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_wei: Option<String>,
+}
+
+impl SimResult {
+    /// Adapters report the gas estimate as `gas_used` for simulations; split it out here.
+    pub fn from_tx(result: &TxResult, simulate: bool) -> Self {
+        if simulate {
+            Self {
+                mode: SendMode::Simulated,
+                tx_hash: None,
+                estimated_gas: result.gas_used(),
+                gas_used: None,
+                success: result.status().unwrap_or(false),
+                estimated_seconds: result.estimated_seconds(),
+                balance_changes: result.balance_changes().to_vec(),
+                effective_gas_price: None,
+                fee_wei: None,
+            }
+        } else {
+            Self {
+                mode: SendMode::Broadcast,
+                tx_hash: Some(result.tx_hash().to_string()).filter(|h| !h.is_empty()),
+                estimated_gas: None,
+                gas_used: result.gas_used(),
+                success: result.status().unwrap_or(false),
+                estimated_seconds: None,
+                balance_changes: Vec::new(),
+                effective_gas_price: result.effective_gas_price(),
+                fee_wei: result.fee_wei().map(str::to_string),
+            }
+        }
+    }
+}
+
+/// Contract creation: `bytecode` is the init code (0x-hex) and `constructor_args` the
+/// ABI-encoded arguments appended to it (0x-hex, may be empty)
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DeployRequest {
+    from: Address,
+    bytecode: String,
+    #[serde(default)]
+    constructor_args: String,
+    value_eth: String,
+    simulate: bool,
+    #[serde(default)]
+    confirmations: Option<u64>,
+}
+
+impl DeployRequest {
+    pub fn builder() -> DeployRequestBuilder { DeployRequestBuilder::default() }
+    pub fn from(&self) -> &Address { &self.from }
+    pub fn bytecode(&self) -> &str { &self.bytecode }
+    pub fn constructor_args(&self) -> &str { &self.constructor_args }
+    /// ETH sent to a payable constructor
+    pub fn value_eth(&self) -> &str { &self.value_eth }
+    pub fn simulate(&self) -> bool { self.simulate }
+    /// Blocks to wait for after broadcast; `None` uses the chain's default
+    pub fn confirmations(&self) -> Option<u64> { self.confirmations }
+}
+
+#[derive(Default)]
+pub struct DeployRequestBuilder {
+    from: Option<Address>,
+    bytecode: Option<String>,
+    constructor_args: Option<String>,
+    value_eth: Option<String>,
+    simulate: Option<bool>,
+    confirmations: Option<u64>,
+}
+
+impl DeployRequestBuilder {
+    pub fn from(mut self, from: Address) -> Self { self.from = Some(from); self }
+    pub fn bytecode(mut self, bytecode: impl Into<String>) -> Self { self.bytecode = Some(bytecode.into()); self }
+    pub fn constructor_args(mut self, args: impl Into<String>) -> Self { self.constructor_args = Some(args.into()); self }
+    pub fn value_eth(mut self, value_eth: impl Into<String>) -> Self { self.value_eth = Some(value_eth.into()); self }
+    pub fn simulate(mut self, simulate: bool) -> Self { self.simulate = Some(simulate); self }
+    pub fn confirmations(mut self, confirmations: Option<u64>) -> Self { self.confirmations = confirmations; self }
+    pub fn build(self) -> Result<DeployRequest, &'static str> {
+        Ok(DeployRequest {
+            from: self.from.ok_or("from required")?,
+            bytecode: self.bytecode.ok_or("bytecode required")?,
+            constructor_args: self.constructor_args.unwrap_or_default(),
+            value_eth: self.value_eth.unwrap_or_else(|| "0".to_string()),
+            simulate: self.simulate.unwrap_or(true),
+            confirmations: self.confirmations,
+        })
+    }
+}
+
+/// Outcome of a deployment. `address` comes from sender + nonce for simulations and
+/// from the receipt once broadcast.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DeployResult {
+    pub mode: SendMode,
+    pub address: String,
+    pub tx_hash: Option<String>,
+    pub estimated_gas: Option<u64>,
+    pub gas_used: Option<u64>,
+    pub success: bool,
+}
+
+/// One transaction of a bundle preview. Amounts follow the matching endpoint: ETH for
+/// `Send`, base units for `Approve`, whole tokens for `Transfer`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BundleStep {
+    Send { from: Address, to: Address, amount_eth: String },
+    Approve { token: Address, owner: Address, spender: Address, amount: String },
+    Transfer { token: Address, from: Address, to: Address, amount: String },
+    /// Any other contract call, such as a router swap; `data` is 0x-hex calldata
+    Call { from: Address, to: Address, data: String, value_eth: String },
+}
+
+impl BundleStep {
+    pub fn op(&self) -> &'static str {
+        match self {
+            BundleStep::Send { .. } => "send",
+            BundleStep::Approve { .. } => "approve",
+            BundleStep::Transfer { .. } => "transfer",
+            BundleStep::Call { .. } => "call",
+        }
+    }
+
+    /// The account the step's transaction is sent from
+    pub fn sender(&self) -> &Address {
+        match self {
+            BundleStep::Send { from, .. } | BundleStep::Transfer { from, .. } | BundleStep::Call { from, .. } => from,
+            BundleStep::Approve { owner, .. } => owner,
+        }
+    }
+}
+
+/// How one bundle step fared; `error` explains a step that could not run or reverted
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BundleStepResult {
+    pub op: String,
+    pub success: bool,
+    pub gas_used: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct UniswapV2SwapRequest {
     amount_eth: String,
@@ -244,6 +578,17 @@ pub trait Toolbox: Send + Sync {
     async fn code(&self, req: CodeRequest) -> anyhow::Result<CodeResponse>;
     async fn erc20_balance_of(&self, req: Erc20BalanceRequest) -> anyhow::Result<Erc20BalanceResponse>;
     async fn send(&self, req: SendRequest) -> anyhow::Result<TxResult>;
+
+    /// Generic read-only contract call. Defaults to an error so toolboxes that
+    /// predate it keep compiling.
+    async fn call(&self, req: ContractCallRequest) -> anyhow::Result<ContractCallResponse> {
+        anyhow::bail!("contract calls are not supported by this toolbox (to {})", req.to().as_str())
+    }
+
+    /// ERC-20 `transfer`; defaults to an error like `call`.
+    async fn erc20_transfer(&self, req: Erc20TransferRequest) -> anyhow::Result<TxResult> {
+        anyhow::bail!("token transfers are not supported by this toolbox (token {})", req.token().as_str())
+    }
 }
 
 /// Chain-agnostic provider interface for agent tools.
@@ -264,6 +609,7 @@ pub trait BlockchainProvider: Send + Sync {
     /// Send native token with optional simulation/fork controls (where supported).
     async fn send_native(&self, req: SendRequest) -> anyhow::Result<TxResult>;
 }
+pub fn placeholder_domain() {}
 
 #[cfg(test)]
 mod tests {
@@ -279,6 +625,97 @@ mod tests {
             .unwrap();
         assert!(req.simulate());
     }
+
+    #[test]
+    fn sim_result_distinguishes_simulated_and_broadcast() {
+        let simulated = SimResult::from_tx(&TxResult::new(String::new(), Some(21_000), None), true);
+        assert_eq!(simulated.mode, SendMode::Simulated);
+        assert_eq!(simulated.tx_hash, None);
+        assert_eq!(simulated.estimated_gas, Some(21_000));
+        let json = serde_json::to_value(&simulated).unwrap();
+        assert_eq!(json["mode"], "simulated");
+        assert!(json["tx_hash"].is_null());
+
+        let broadcast = SimResult::from_tx(&TxResult::new("0xabc".into(), Some(21_000), Some(true)), false);
+        assert_eq!(broadcast.mode, SendMode::Broadcast);
+        assert_eq!(broadcast.tx_hash.as_deref(), Some("0xabc"));
+        assert_eq!(broadcast.gas_used, Some(21_000));
+        assert!(broadcast.success);
+        assert_eq!(serde_json::to_value(&broadcast).unwrap()["mode"], "broadcast");
+        assert_eq!(broadcast.fee_wei, None);
+    }
+
+    #[test]
+    fn fee_is_effective_price_times_gas_used() {
+        let paid = TxResult::new("0xabc".into(), Some(21_000), Some(true)).with_effective_gas_price(Some(2_000_000_000));
+        assert_eq!(paid.fee_wei(), Some("42000000000000"));
+        let broadcast = SimResult::from_tx(&paid, false);
+        assert_eq!(broadcast.effective_gas_price, Some(2_000_000_000));
+        assert_eq!(broadcast.fee_wei.as_deref(), Some("42000000000000"));
+
+        // Simulations never report what was paid
+        let simulated = serde_json::to_value(SimResult::from_tx(&paid, true)).unwrap();
+        assert!(simulated.get("fee_wei").is_none() && simulated.get("effective_gas_price").is_none());
+    }
+
+    #[test]
+    fn estimated_seconds_is_carried_for_simulations_and_omitted_when_unknown() {
+        let tx = TxResult::new(String::new(), Some(21_000), None).with_estimated_seconds(Some(12));
+        assert_eq!(SimResult::from_tx(&tx, true).estimated_seconds, Some(12));
+
+        let unknown = serde_json::to_value(SimResult::from_tx(&TxResult::new(String::new(), Some(21_000), None), true)).unwrap();
+        assert!(unknown.get("estimated_seconds").is_none());
+    }
+
+    struct LegacyToolbox;
+
+    #[async_trait]
+    impl Toolbox for LegacyToolbox {
+        async fn balance(&self, _req: BalanceRequest) -> anyhow::Result<BalanceResponse> { unreachable!() }
+        async fn code(&self, _req: CodeRequest) -> anyhow::Result<CodeResponse> { unreachable!() }
+        async fn erc20_balance_of(&self, _req: Erc20BalanceRequest) -> anyhow::Result<Erc20BalanceResponse> { unreachable!() }
+        async fn send(&self, _req: SendRequest) -> anyhow::Result<TxResult> { unreachable!() }
+    }
+
+    #[tokio::test]
+    async fn toolbox_call_defaults_to_unsupported_error() {
+        let req = ContractCallRequest::new(Address::new("0xToken".into()), "0x18160ddd");
+        let err = LegacyToolbox.call(req).await.unwrap_err();
+        assert_eq!(err.to_string(), "contract calls are not supported by this toolbox (to 0xToken)");
+    }
+
+    struct CallingToolbox;
+
+    #[async_trait]
+    impl Toolbox for CallingToolbox {
+        async fn balance(&self, _req: BalanceRequest) -> anyhow::Result<BalanceResponse> { unreachable!() }
+        async fn code(&self, _req: CodeRequest) -> anyhow::Result<CodeResponse> { unreachable!() }
+        async fn erc20_balance_of(&self, _req: Erc20BalanceRequest) -> anyhow::Result<Erc20BalanceResponse> { unreachable!() }
+        async fn send(&self, _req: SendRequest) -> anyhow::Result<TxResult> { unreachable!() }
+        async fn call(&self, req: ContractCallRequest) -> anyhow::Result<ContractCallResponse> {
+            assert_eq!(req.data(), "0x18160ddd");
+            Ok(ContractCallResponse::new(format!("0x{:064x}", 1_000u64)))
+        }
+    }
+
+    #[tokio::test]
+    async fn toolbox_call_can_be_overridden() {
+        let toolbox: &dyn Toolbox = &CallingToolbox;
+        let resp = toolbox.call(ContractCallRequest::new(Address::new("0xToken".into()), "0x18160ddd")).await.unwrap();
+        assert!(resp.data().ends_with("3e8"));
+    }
+
+    #[test]
+    fn looks_like_ens_accepts_any_dotted_name() {
+        assert!(looks_like_ens("vitalik.eth"));
+        assert!(looks_like_ens("foo.xyz"));
+        assert!(looks_like_ens("alice.cb.id"));
+        assert!(!looks_like_ens("0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045"));
+        assert!(!looks_like_ens("vitalik"));
+        assert!(!looks_like_ens("0.1"));
+        assert!(!looks_like_ens("foo..eth"));
+        assert!(matches!(AddressOrEns::parse("foo.xyz".into()), AddressOrEns::Ens(_)));
+        assert!(matches!(AddressOrEns::parse("0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045".into()), AddressOrEns::Address(_)));
+    }
 }
-pub fn placeholder_domain() {}
 
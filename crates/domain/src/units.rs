@@ -0,0 +1,290 @@
+//! Wei/gwei/eth conversions on decimal strings.
+//!
+//! Amounts are kept as base-10 digit strings so the domain crate does not need a
+//! big-integer dependency; adapters parse the results into `U256` at the edge.
+
+use thiserror::Error;
+
+pub const ETH_DECIMALS: u32 = 18;
+pub const GWEI_DECIMALS: u32 = 9;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum UnitError {
+    #[error("invalid amount: {0:?}")]
+    InvalidAmount(String),
+
+    #[error("amount {amount:?} has more than {max} decimal places")]
+    TooManyDecimals { amount: String, max: u32 },
+
+    #[error("amount {0:?} is ambiguous; use '.' for decimals (1.5) or group thousands in threes (1,000)")]
+    AmbiguousAmount(String),
+}
+
+/// Convert a decimal amount (e.g. "1.5") into integer base units with `decimals` places.
+pub fn parse_units(amount: &str, decimals: u32) -> Result<String, UnitError> {
+    let trimmed = amount.trim();
+    let (int_part, frac_part) = match trimmed.split_once('.') {
+        Some((i, f)) => (i, f),
+        None => (trimmed, ""),
+    };
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if (int_part.is_empty() && frac_part.is_empty()) || !is_digits(int_part) || !is_digits(frac_part) {
+        return Err(UnitError::InvalidAmount(amount.to_string()));
+    }
+    if frac_part.len() > decimals as usize {
+        return Err(UnitError::TooManyDecimals { amount: amount.to_string(), max: decimals });
+    }
+    let mut digits = String::with_capacity(int_part.len() + decimals as usize);
+    digits.push_str(int_part);
+    digits.push_str(frac_part);
+    digits.extend(std::iter::repeat_n('0', decimals as usize - frac_part.len()));
+    Ok(strip_leading_zeros(&digits))
+}
+
+/// Like `parse_units`, but first accepts human-formatted amounts (see `normalize_amount`).
+pub fn parse_amount(amount: &str, decimals: u32) -> Result<String, UnitError> {
+    parse_units(&normalize_amount(amount)?, decimals)
+}
+
+/// Rewrite a pasted amount into plain `digits[.digits]` form:
+/// - spaces (incl. NBSP and thin spaces) and `_` are digit grouping: `1 000,5` → `1000,5`
+/// - with both `,` and `.`, the last one is the decimal point: `1,000.5`, `1.000,5` → `1000.5`
+/// - a lone `,` is a decimal point only after space grouping (`1 000,5`); commas that
+///   split digits into groups of three are thousands separators (`1,000,000`)
+///
+/// Anything else with a comma, such as `1,5`, is rejected as ambiguous.
+pub fn normalize_amount(amount: &str) -> Result<String, UnitError> {
+    let trimmed = amount.trim();
+    let grouped_with_spaces = trimmed.chars().any(|c| matches!(c, ' ' | '\u{a0}' | '\u{202f}' | '\u{2009}'));
+    let compact: String = trimmed.chars().filter(|c| !matches!(c, ' ' | '\u{a0}' | '\u{202f}' | '\u{2009}' | '_')).collect();
+    let ambiguous = || UnitError::AmbiguousAmount(amount.to_string());
+
+    let (last_comma, last_dot) = (compact.rfind(','), compact.rfind('.'));
+    let (thousands, decimal_at) = match (last_comma, last_dot) {
+        (None, _) => return Ok(compact),
+        (Some(c), Some(d)) if d > c => (',', Some(d)),
+        (Some(c), Some(_)) => ('.', Some(c)),
+        (Some(c), None) if grouped_with_spaces && compact.matches(',').count() == 1 => (',', Some(c)),
+        (Some(_), None) => (',', None),
+    };
+
+    let (int_part, frac_part) = match decimal_at {
+        Some(at) => (&compact[..at], Some(&compact[at + 1..])),
+        None => (compact.as_str(), None),
+    };
+    if frac_part.is_some_and(|f| f.contains([',', '.'])) {
+        return Err(ambiguous());
+    }
+    let groups: Vec<&str> = int_part.split(thousands).collect();
+    if groups.len() > 1 && (groups[0].is_empty() || groups[0].len() > 3 || groups[1..].iter().any(|g| g.len() != 3)) {
+        return Err(ambiguous());
+    }
+    let int_digits = groups.concat();
+    Ok(match frac_part {
+        Some(frac) => format!("{}.{}", int_digits, frac),
+        None => int_digits,
+    })
+}
+
+/// Render integer base units as an exact decimal, trimming trailing fractional zeros.
+pub fn format_units(value: &str, decimals: u32) -> Result<String, UnitError> {
+    let digits = parse_integer(value)?;
+    let (int_part, frac_part) = split_at_decimals(&digits, decimals as usize);
+    let frac_part = frac_part.trim_end_matches('0');
+    if frac_part.is_empty() {
+        Ok(int_part)
+    } else {
+        Ok(format!("{}.{}", int_part, frac_part))
+    }
+}
+
+/// Render integer base units rounded half-up to exactly `precision` fractional digits.
+pub fn format_units_rounded(value: &str, decimals: u32, precision: usize) -> Result<String, UnitError> {
+    let digits = parse_integer(value)?;
+    let decimals = decimals as usize;
+    if precision >= decimals {
+        let (int_part, frac_part) = split_at_decimals(&digits, decimals);
+        let padding = "0".repeat(precision - decimals);
+        return Ok(join_decimal(&int_part, &format!("{}{}", frac_part, padding)));
+    }
+    // Drop the digits below `precision`, rounding on the first dropped digit
+    let dropped = decimals - precision;
+    let padded = format!("{:0>width$}", digits, width = dropped + 1);
+    let (kept, rest) = padded.split_at(padded.len() - dropped);
+    let kept = if rest.as_bytes()[0] >= b'5' { increment(kept) } else { kept.to_string() };
+    let (int_part, frac_part) = split_at_decimals(&kept, precision);
+    Ok(join_decimal(&int_part, &frac_part))
+}
+
+pub fn eth_to_wei(eth: &str) -> Result<String, UnitError> {
+    parse_amount(eth, ETH_DECIMALS)
+}
+
+pub fn gwei_to_wei(gwei: &str) -> Result<String, UnitError> {
+    parse_units(gwei, GWEI_DECIMALS)
+}
+
+pub fn wei_to_eth(wei: &str) -> Result<String, UnitError> {
+    format_units(wei, ETH_DECIMALS)
+}
+
+pub fn wei_to_gwei(wei: &str) -> Result<String, UnitError> {
+    format_units(wei, GWEI_DECIMALS)
+}
+
+/// Format wei as ETH with exactly `decimals` fractional digits (half-up rounding).
+pub fn format_wei(wei: &str, decimals: usize) -> Result<String, UnitError> {
+    format_units_rounded(wei, ETH_DECIMALS, decimals)
+}
+
+/// Sum integer wei amounts of any size with digit-string addition, so whale aggregates
+/// cannot overflow the way `u128` would.
+pub fn sum_wei(values: &[&str]) -> Result<String, UnitError> {
+    let mut total = "0".to_string();
+    for value in values {
+        total = add_digits(&total, &parse_integer(value)?);
+    }
+    Ok(total)
+}
+
+fn parse_integer(value: &str) -> Result<String, UnitError> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() || !trimmed.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(UnitError::InvalidAmount(value.to_string()));
+    }
+    Ok(strip_leading_zeros(trimmed))
+}
+
+fn strip_leading_zeros(digits: &str) -> String {
+    let stripped = digits.trim_start_matches('0');
+    if stripped.is_empty() { "0".to_string() } else { stripped.to_string() }
+}
+
+/// Split an integer digit string into (integer, fraction) with `decimals` fraction digits.
+fn split_at_decimals(digits: &str, decimals: usize) -> (String, String) {
+    let padded = format!("{:0>width$}", digits, width = decimals + 1);
+    let (int_part, frac_part) = padded.split_at(padded.len() - decimals);
+    (strip_leading_zeros(int_part), frac_part.to_string())
+}
+
+fn join_decimal(int_part: &str, frac_part: &str) -> String {
+    if frac_part.is_empty() { int_part.to_string() } else { format!("{}.{}", int_part, frac_part) }
+}
+
+/// Schoolbook addition of two canonical decimal digit strings.
+fn add_digits(a: &str, b: &str) -> String {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let mut out = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0u8;
+    let (mut i, mut j) = (a.len(), b.len());
+    while i > 0 || j > 0 || carry > 0 {
+        let mut digit = carry;
+        if i > 0 { i -= 1; digit += a[i] - b'0'; }
+        if j > 0 { j -= 1; digit += b[j] - b'0'; }
+        out.push(b'0' + digit % 10);
+        carry = digit / 10;
+    }
+    out.reverse();
+    strip_leading_zeros(&String::from_utf8(out).expect("ascii digits"))
+}
+
+/// Add one to a decimal digit string, carrying as needed.
+fn increment(digits: &str) -> String {
+    let mut bytes = digits.as_bytes().to_vec();
+    for b in bytes.iter_mut().rev() {
+        if *b == b'9' {
+            *b = b'0';
+        } else {
+            *b += 1;
+            return String::from_utf8(bytes).expect("ascii digits");
+        }
+    }
+    format!("1{}", String::from_utf8(bytes).expect("ascii digits"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eth_to_wei_handles_fractions_and_integers() {
+        assert_eq!(eth_to_wei("1").unwrap(), "1000000000000000000");
+        assert_eq!(eth_to_wei("0.1").unwrap(), "100000000000000000");
+        assert_eq!(eth_to_wei(".5").unwrap(), "500000000000000000");
+        assert_eq!(eth_to_wei("0").unwrap(), "0");
+        assert_eq!(eth_to_wei("0.000000000000000001").unwrap(), "1");
+        assert_eq!(gwei_to_wei("1.5").unwrap(), "1500000000");
+    }
+
+    #[test]
+    fn eth_to_wei_rejects_bad_input() {
+        assert!(matches!(eth_to_wei("abc"), Err(UnitError::InvalidAmount(_))));
+        assert!(matches!(eth_to_wei("-1"), Err(UnitError::InvalidAmount(_))));
+        assert!(matches!(eth_to_wei(""), Err(UnitError::InvalidAmount(_))));
+        assert!(matches!(eth_to_wei("."), Err(UnitError::InvalidAmount(_))));
+        assert!(matches!(eth_to_wei("0.0000000000000000001"), Err(UnitError::TooManyDecimals { .. })));
+    }
+
+    #[test]
+    fn locale_separators_are_normalized() {
+        assert_eq!(normalize_amount("1,000.5").unwrap(), "1000.5");
+        assert_eq!(normalize_amount("1000.5").unwrap(), "1000.5");
+        assert_eq!(normalize_amount("1 000,5").unwrap(), "1000.5");
+        assert_eq!(normalize_amount("1.000,5").unwrap(), "1000.5");
+        assert_eq!(normalize_amount("1,000,000").unwrap(), "1000000");
+        assert_eq!(eth_to_wei("1,000.5").unwrap(), "1000500000000000000000");
+    }
+
+    #[test]
+    fn ambiguous_commas_are_rejected() {
+        assert!(matches!(eth_to_wei("1,5"), Err(UnitError::AmbiguousAmount(_))));
+        assert!(matches!(normalize_amount("1,00.5"), Err(UnitError::AmbiguousAmount(_))));
+        assert!(matches!(normalize_amount("1.000,5,0"), Err(UnitError::AmbiguousAmount(_))));
+        let message = eth_to_wei("1,5").unwrap_err().to_string();
+        assert!(message.contains("ambiguous"), "{}", message);
+    }
+
+    #[test]
+    fn wei_to_eth_is_exact_and_trims_zeros() {
+        assert_eq!(wei_to_eth("1500000000000000000").unwrap(), "1.5");
+        assert_eq!(wei_to_eth("1").unwrap(), "0.000000000000000001");
+        assert_eq!(wei_to_eth("0").unwrap(), "0");
+        assert_eq!(wei_to_gwei("1500000000").unwrap(), "1.5");
+    }
+
+    #[test]
+    fn format_wei_rounds_half_up() {
+        assert_eq!(format_wei("1234500000000000000", 4).unwrap(), "1.2345");
+        assert_eq!(format_wei("1234550000000000000", 4).unwrap(), "1.2346");
+        assert_eq!(format_wei("1234549999999999999", 4).unwrap(), "1.2345");
+        assert_eq!(format_wei("1999999999999999999", 4).unwrap(), "2.0000");
+        assert_eq!(format_wei("499999999999999999", 0).unwrap(), "0");
+        assert_eq!(format_wei("500000000000000000", 0).unwrap(), "1");
+        assert_eq!(format_wei("1", 20).unwrap(), "0.00000000000000000100");
+    }
+
+    #[test]
+    fn sum_wei_adds_small_values_and_rejects_bad_input() {
+        assert_eq!(sum_wei(&[]).unwrap(), "0");
+        assert_eq!(sum_wei(&["1", "999", "0"]).unwrap(), "1000");
+        assert_eq!(sum_wei(&["007", "3"]).unwrap(), "10");
+        assert!(matches!(sum_wei(&["1", "1.5"]), Err(UnitError::InvalidAmount(_))));
+    }
+
+    #[test]
+    fn sum_wei_exceeds_u128_without_overflow() {
+        let max = u128::MAX.to_string();
+        assert_eq!(sum_wei(&[&max, "1"]).unwrap(), "340282366920938463463374607431768211456");
+        assert_eq!(sum_wei(&[&max, &max, &max]).unwrap(), "1020847100762815390390123822295304634365");
+    }
+
+    #[test]
+    fn handles_values_beyond_u128() {
+        // 2^256 - 1 wei, well past any native integer type
+        let max = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+        let eth = wei_to_eth(max).unwrap();
+        assert_eq!(eth, "115792089237316195423570985008687907853269984665640564039457.584007913129639935");
+        assert_eq!(eth_to_wei(&eth).unwrap(), max);
+        assert_eq!(format_wei(max, 2).unwrap(), "115792089237316195423570985008687907853269984665640564039457.58");
+    }
+}
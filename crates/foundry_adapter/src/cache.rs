@@ -1,5 +1,12 @@
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
+use domain::http::HttpTimeouts;
+use tokio::sync::OnceCell;
+
+use crate::constants::{
+    BLOCKSCOUT_APIS, CACHE_TTL_SECONDS, EXPLORER_APIS, EXPLORER_RATE_LIMIT_BACKOFF_MS, EXPLORER_RATE_LIMIT_RETRIES, LRU_CACHE_SIZE,
+};
 
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -18,28 +25,109 @@ pub struct CachedContract {
     pub cached_at: Instant,
 }
 
+/// What an explorer's `getsourcecode` says about a contract
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractSource {
+    pub name: Option<String>,
+    /// Only verified contracts have one
+    pub abi: Option<String>,
+    pub verified: bool,
+}
+
+/// Sizes and TTLs for the ABI and contract caches. Defaults come from
+/// `LRU_CACHE_SIZE`/`CACHE_TTL_SECONDS`; `from_env` lets a deployment tune them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CacheConfig {
+    pub abi_size: usize,
+    pub abi_ttl: Duration,
+    pub contract_size: usize,
+    pub contract_ttl: Duration,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            abi_size: LRU_CACHE_SIZE,
+            abi_ttl: Duration::from_secs(CACHE_TTL_SECONDS),
+            contract_size: LRU_CACHE_SIZE,
+            contract_ttl: Duration::from_secs(CACHE_TTL_SECONDS),
+        }
+    }
+}
+
+impl CacheConfig {
+    pub fn with_abi_size(mut self, size: usize) -> Self {
+        self.abi_size = size;
+        self
+    }
+
+    pub fn with_abi_ttl(mut self, ttl: Duration) -> Self {
+        self.abi_ttl = ttl;
+        self
+    }
+
+    pub fn with_contract_size(mut self, size: usize) -> Self {
+        self.contract_size = size;
+        self
+    }
+
+    pub fn with_contract_ttl(mut self, ttl: Duration) -> Self {
+        self.contract_ttl = ttl;
+        self
+    }
+
+    /// Defaults overridden by `ABI_CACHE_SIZE`, `ABI_CACHE_TTL_SECS`, `CONTRACT_CACHE_SIZE`
+    /// and `CONTRACT_CACHE_TTL_SECS`; unparseable values keep the default.
+    pub fn from_env() -> Self {
+        Self::from_vars(|var| std::env::var(var).ok())
+    }
+
+    pub fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let num = |name: &str| var(name).and_then(|v| v.trim().parse::<u64>().ok());
+        let mut config = Self::default();
+        if let Some(size) = num("ABI_CACHE_SIZE") {
+            config.abi_size = size as usize;
+        }
+        if let Some(secs) = num("ABI_CACHE_TTL_SECS") {
+            config.abi_ttl = Duration::from_secs(secs);
+        }
+        if let Some(size) = num("CONTRACT_CACHE_SIZE") {
+            config.contract_size = size as usize;
+        }
+        if let Some(secs) = num("CONTRACT_CACHE_TTL_SECS") {
+            config.contract_ttl = Duration::from_secs(secs);
+        }
+        config
+    }
+}
+
 #[allow(dead_code)]
+#[derive(Debug)]
 pub struct LruCache {
     abis: HashMap<String, CachedAbi>,
     contracts: HashMap<String, CachedContract>,
-    max_size: usize,
-    ttl: Duration,
+    config: CacheConfig,
 }
 
 #[allow(dead_code)]
 impl LruCache {
+    /// Same size and TTL for both caches
     pub fn new(max_size: usize, ttl_seconds: u64) -> Self {
-        Self {
-            abis: HashMap::new(),
-            contracts: HashMap::new(),
-            max_size,
-            ttl: Duration::from_secs(ttl_seconds),
-        }
+        let ttl = Duration::from_secs(ttl_seconds);
+        Self::from_config(CacheConfig { abi_size: max_size, abi_ttl: ttl, contract_size: max_size, contract_ttl: ttl })
+    }
+
+    pub fn from_config(config: CacheConfig) -> Self {
+        Self { abis: HashMap::new(), contracts: HashMap::new(), config }
+    }
+
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
     }
 
     pub fn get_abi(&self, key: &str) -> Option<&CachedAbi> {
         self.abis.get(key).and_then(|cached| {
-            if cached.cached_at.elapsed() < self.ttl {
+            if cached.cached_at.elapsed() < self.config.abi_ttl {
                 Some(cached)
             } else {
                 None
@@ -48,9 +136,12 @@ impl LruCache {
     }
 
     pub fn set_abi(&mut self, key: String, abi: String, verified: bool) {
-        if self.abis.len() >= self.max_size {
+        if self.config.abi_size == 0 {
+            return;
+        }
+        if self.abis.len() >= self.config.abi_size && !self.abis.contains_key(&key) {
             // Simple LRU: remove oldest entry
-            let oldest_key = self.abis.keys().next().cloned();
+            let oldest_key = self.abis.iter().min_by_key(|(_, cached)| cached.cached_at).map(|(k, _)| k.clone());
             if let Some(old_key) = oldest_key {
                 self.abis.remove(&old_key);
             }
@@ -65,7 +156,7 @@ impl LruCache {
 
     pub fn get_contract(&self, address: &str) -> Option<&CachedContract> {
         self.contracts.get(address).and_then(|cached| {
-            if cached.cached_at.elapsed() < self.ttl {
+            if cached.cached_at.elapsed() < self.config.contract_ttl {
                 Some(cached)
             } else {
                 None
@@ -74,9 +165,12 @@ impl LruCache {
     }
 
     pub fn set_contract(&mut self, address: String, name: String, abi: Option<String>) {
-        if self.contracts.len() >= self.max_size {
+        if self.config.contract_size == 0 {
+            return;
+        }
+        if self.contracts.len() >= self.config.contract_size && !self.contracts.contains_key(&address) {
             // Simple LRU: remove oldest entry
-            let oldest_key = self.contracts.keys().next().cloned();
+            let oldest_key = self.contracts.iter().min_by_key(|(_, cached)| cached.cached_at).map(|(k, _)| k.clone());
             if let Some(key) = oldest_key {
                 self.contracts.remove(&key);
             }
@@ -92,34 +186,142 @@ impl LruCache {
 
     pub fn clear_expired(&mut self) {
         let now = Instant::now();
-        self.abis.retain(|_, cached| now.duration_since(cached.cached_at) < self.ttl);
-        self.contracts.retain(|_, cached| now.duration_since(cached.cached_at) < self.ttl);
+        self.abis.retain(|_, cached| now.duration_since(cached.cached_at) < self.config.abi_ttl);
+        self.contracts.retain(|_, cached| now.duration_since(cached.cached_at) < self.config.contract_ttl);
     }
 }
 
-// Etherscan API interface for fallback
-pub struct EtherscanClient {
+// Block explorer API interface for fallback
+/// Etherscan-compatible API for `chain_id`, if the chain has a known explorer
+pub fn explorer_api_url(chain_id: u64) -> Option<&'static str> {
+    EXPLORER_APIS.iter().find(|(id, _)| *id == chain_id).map(|(_, url)| *url)
+}
+
+/// Request and reply shapes an explorer API speaks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExplorerDialect {
+    /// `?module=contract&action=…` with `{status, message, result}` replies (Etherscan and its L2 forks)
+    Etherscan,
+    /// Blockscout's REST API, `/v2/smart-contracts/{address}`; needs no API key
+    Blockscout,
+}
+
+pub struct ExplorerClient {
     api_key: String,
     base_url: String,
+    dialect: ExplorerDialect,
+    rate_limit_backoff: Duration,
+    http: reqwest::Client,
+    timeouts: HttpTimeouts,
 }
 
 #[allow(dead_code)]
-impl EtherscanClient {
+impl ExplorerClient {
     pub fn new(api_key: String) -> Self {
         Self {
             api_key,
             base_url: "https://api.etherscan.io/api".to_string(),
+            dialect: ExplorerDialect::Etherscan,
+            rate_limit_backoff: Duration::from_millis(EXPLORER_RATE_LIMIT_BACKOFF_MS),
+            http: http_client(HttpTimeouts::default()),
+            timeouts: HttpTimeouts::default(),
+        }
+    }
+
+    /// A request that hits a timeout fails with `domain::http::TimedOut`
+    pub fn with_timeouts(mut self, timeouts: HttpTimeouts) -> Self {
+        self.http = http_client(timeouts);
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// A Blockscout instance, e.g. `https://gnosis.blockscout.com/api` or a self-hosted one
+    pub fn blockscout(base_url: impl Into<String>) -> Self {
+        Self { dialect: ExplorerDialect::Blockscout, ..Self::new(String::new()).with_base_url(base_url) }
+    }
+
+    /// Client for the chain's explorer (Optimism, Base, Arbitrum, ...). Etherscan-family
+    /// APIs need `api_key`; chains served only by Blockscout work without one. `None`
+    /// when no known explorer can be used.
+    pub fn for_chain(api_key: Option<String>, chain_id: u64) -> Option<Self> {
+        if let (Some(url), Some(api_key)) = (explorer_api_url(chain_id), api_key) {
+            return Some(Self::new(api_key).with_base_url(url));
         }
+        BLOCKSCOUT_APIS.iter().find(|(id, _)| *id == chain_id).map(|(_, url)| Self::blockscout(*url))
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn dialect(&self) -> ExplorerDialect {
+        self.dialect
+    }
+
+    /// Point at a different Etherscan-compatible API (mock servers, explorers)
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Initial delay before retrying a rate-limited request; doubles on each retry
+    pub fn with_rate_limit_backoff(mut self, backoff: Duration) -> Self {
+        self.rate_limit_backoff = backoff;
+        self
+    }
+
+    /// GET `url` and parse the body. Rate limits are retried with backoff: Etherscan
+    /// signals them with a 200 whose `result` says "Max rate limit reached", Blockscout
+    /// with a 429. A 404 (Blockscout's reply for an unknown contract) is `Null`.
+    async fn get_json(&self, url: &str) -> anyhow::Result<serde_json::Value> {
+        let mut delay = self.rate_limit_backoff;
+        let mut attempt = 0;
+        loop {
+            // Name the API rather than the URL, which carries the key
+            let timed_out = |e: reqwest::Error| -> anyhow::Error {
+                if e.is_timeout() { self.timeouts.timed_out(self.base_url.clone(), e.is_connect()).into() } else { e.into() }
+            };
+            let response = self.http.get(url).send().await.map_err(timed_out)?;
+            let status = response.status();
+            if status == reqwest::StatusCode::NOT_FOUND {
+                return Ok(serde_json::Value::Null);
+            }
+            let throttled = status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+            let result: serde_json::Value = if throttled { serde_json::Value::Null } else { response.json().await.map_err(timed_out)? };
+            if !throttled && !is_rate_limited(&result) {
+                return Ok(result);
+            }
+            if attempt >= EXPLORER_RATE_LIMIT_RETRIES {
+                anyhow::bail!("explorer API rate limited: {}", result["result"].as_str().unwrap_or(status.as_str()));
+            }
+            attempt += 1;
+            tracing::warn!("explorer API rate limited, retry {} in {:?}", attempt, delay);
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+        }
+    }
+
+    /// Blockscout's smart-contract record for `address`; unknown contracts read as unverified
+    async fn blockscout_source(&self, address: &str) -> anyhow::Result<ContractSource> {
+        let body = self.get_json(&format!("{}/v2/smart-contracts/{}", self.base_url, address)).await?;
+        let verified = body["is_verified"].as_bool().unwrap_or(false);
+        Ok(ContractSource {
+            name: body["name"].as_str().filter(|name| !name.is_empty()).map(str::to_string),
+            abi: body["abi"].as_array().filter(|_| verified).map(|abi| serde_json::Value::Array(abi.clone()).to_string()),
+            verified,
+        })
     }
 
     pub async fn get_contract_abi(&self, address: &str) -> anyhow::Result<Option<String>> {
+        if self.dialect == ExplorerDialect::Blockscout {
+            return Ok(self.blockscout_source(address).await?.abi);
+        }
         let url = format!(
             "{}?module=contract&action=getabi&address={}&apikey={}",
             self.base_url, address, self.api_key
         );
         
-        let response = reqwest::get(&url).await?;
-        let result: serde_json::Value = response.json().await?;
+        let result = self.get_json(&url).await?;
         
         if result["status"] == "1" {
             Ok(Some(result["result"].as_str().unwrap_or("").to_string()))
@@ -128,14 +330,45 @@ impl EtherscanClient {
         }
     }
 
+    /// Source details from `getsourcecode`. Unverified contracts and plain accounts come
+    /// back with `verified: false`; an error reply (such as a malformed address) is an `Err`.
+    pub async fn get_source_code(&self, address: &str) -> anyhow::Result<ContractSource> {
+        if self.dialect == ExplorerDialect::Blockscout {
+            return self.blockscout_source(address).await;
+        }
+        let url = format!(
+            "{}?module=contract&action=getsourcecode&address={}&apikey={}",
+            self.base_url, address, self.api_key
+        );
+        let result = self.get_json(&url).await?;
+        let entry = &result["result"][0];
+        if result["status"] != "1" || !entry.is_object() {
+            anyhow::bail!("explorer getsourcecode failed: {}", result["result"].as_str().or(result["message"].as_str()).unwrap_or("unexpected response"));
+        }
+        // Unverified contracts have an empty SourceCode and a placeholder message as ABI
+        let verified = entry["SourceCode"].as_str().is_some_and(|source| !source.is_empty());
+        Ok(ContractSource {
+            name: entry["ContractName"].as_str().filter(|name| !name.is_empty()).map(str::to_string),
+            abi: entry["ABI"].as_str().filter(|_| verified).map(str::to_string),
+            verified,
+        })
+    }
+
+    /// Whether the explorer has verified source for `address`
+    pub async fn is_verified(&self, address: &str) -> anyhow::Result<bool> {
+        Ok(self.get_source_code(address).await?.verified)
+    }
+
     pub async fn get_contract_name(&self, address: &str) -> anyhow::Result<Option<String>> {
+        if self.dialect == ExplorerDialect::Blockscout {
+            return Ok(self.blockscout_source(address).await?.name);
+        }
         let url = format!(
             "{}?module=contract&action=getcontractcreation&contractaddresses={}&apikey={}",
             self.base_url, address, self.api_key
         );
         
-        let response = reqwest::get(&url).await?;
-        let result: serde_json::Value = response.json().await?;
+        let result = self.get_json(&url).await?;
         
         if result["status"] == "1" {
             let contracts = result["result"].as_array();
@@ -150,17 +383,33 @@ impl EtherscanClient {
     }
 }
 
+fn http_client(timeouts: HttpTimeouts) -> reqwest::Client {
+    reqwest::Client::builder()
+        .connect_timeout(timeouts.connect)
+        .timeout(timeouts.request)
+        .build()
+        .expect("reqwest client with timeouts")
+}
+
+fn is_rate_limited(body: &serde_json::Value) -> bool {
+    body["status"] == "0"
+        && body["result"].as_str().is_some_and(|r| r.to_lowercase().contains("rate limit"))
+}
+
 // Interface for future L2Beat-style discovery
-pub trait ContractDiscovery {
+#[allow(dead_code)]
+#[async_trait::async_trait]
+pub trait ContractDiscovery: Send + Sync {
     async fn get_contract_info(&self, address: &str) -> anyhow::Result<Option<CachedContract>>;
     async fn get_abi(&self, address: &str) -> anyhow::Result<Option<String>>;
 }
 
-impl ContractDiscovery for EtherscanClient {
+#[async_trait::async_trait]
+impl ContractDiscovery for ExplorerClient {
+    /// One source lookup in either dialect gives both the name and the ABI
     async fn get_contract_info(&self, address: &str) -> anyhow::Result<Option<CachedContract>> {
-        let name = self.get_contract_name(address).await?;
-        let abi = self.get_contract_abi(address).await?;
-        
+        let ContractSource { name, abi, .. } = self.get_source_code(address).await?;
+
         if name.is_some() || abi.is_some() {
             Ok(Some(CachedContract {
                 address: address.to_string(),
@@ -178,6 +427,50 @@ impl ContractDiscovery for EtherscanClient {
     }
 }
 
+/// ABI lookups backed by the LRU cache with single-flight coalescing: concurrent
+/// misses for the same address share one discovery request instead of each
+/// hitting Etherscan.
+pub struct AbiResolver<D: ContractDiscovery> {
+    discovery: D,
+    cache: Mutex<LruCache>,
+    inflight: Mutex<HashMap<String, Arc<OnceCell<Option<String>>>>>,
+}
+
+impl<D: ContractDiscovery> AbiResolver<D> {
+    pub fn new(discovery: D) -> Self {
+        Self::with_cache(discovery, LruCache::from_config(CacheConfig::default()))
+    }
+
+    pub fn with_cache(discovery: D, cache: LruCache) -> Self {
+        Self { discovery, cache: Mutex::new(cache), inflight: Mutex::new(HashMap::new()) }
+    }
+
+    pub async fn get_abi(&self, address: &str) -> anyhow::Result<Option<String>> {
+        let key = crate::validation::normalize(address);
+        if let Some(cached) = self.cache.lock().unwrap().get_abi(&key) {
+            return Ok(Some(cached.abi.clone()));
+        }
+
+        let cell = self.inflight.lock().unwrap().entry(key.clone()).or_default().clone();
+        let result = cell.get_or_try_init(|| self.discovery.get_abi(&key)).await.cloned();
+
+        // The first finisher retires the slot; later callers go through the cache
+        {
+            let mut inflight = self.inflight.lock().unwrap();
+            if inflight.get(&key).is_some_and(|current| Arc::ptr_eq(current, &cell)) {
+                inflight.remove(&key);
+            }
+        }
+
+        let abi = result?;
+        if let Some(abi) = &abi {
+            // Etherscan only serves ABIs for verified sources
+            self.cache.lock().unwrap().set_abi(key, abi.clone(), true);
+        }
+        Ok(abi)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -198,6 +491,217 @@ mod tests {
         assert!(cache.get_abi("key3").is_some(), "Newest key should be present");
     }
 
+    #[tokio::test]
+    async fn test_concurrent_abi_lookups_coalesce() {
+        let server = httpmock::MockServer::start_async().await;
+        let abi_mock = server.mock(|when, then| {
+            when.method(httpmock::Method::GET)
+                .path("/api")
+                .query_param("action", "getabi");
+            then.status(200)
+                .delay(Duration::from_millis(200))
+                .json_body(serde_json::json!({ "status": "1", "result": "[{\"type\":\"function\"}]" }));
+        });
+
+        let client = ExplorerClient::new("key".to_string()).with_base_url(server.url("/api"));
+        let resolver = AbiResolver::new(client);
+        let addr = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+        let lower = addr.to_lowercase();
+        let (a, b, c, d) = tokio::join!(
+            resolver.get_abi(addr),
+            resolver.get_abi(addr),
+            resolver.get_abi(&lower),
+            resolver.get_abi(addr),
+        );
+        for abi in [a, b, c, d] {
+            assert_eq!(abi.unwrap().as_deref(), Some("[{\"type\":\"function\"}]"));
+        }
+        abi_mock.assert_hits(1);
+
+        // Later lookups are served from the LRU cache
+        assert!(resolver.get_abi(addr).await.unwrap().is_some());
+        abi_mock.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn test_verification_status_from_getsourcecode() {
+        let server = httpmock::MockServer::start_async().await;
+        let (verified, unverified) = ("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2", "0x28c6c06298d514db089934071355e5743bf21d60");
+        server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/api").query_param("action", "getsourcecode").query_param("address", verified);
+            then.status(200).json_body(serde_json::json!({
+                "status": "1",
+                "message": "OK",
+                "result": [{ "SourceCode": "contract WETH9 { }", "ABI": "[{\"type\":\"function\",\"name\":\"deposit\"}]", "ContractName": "WETH9" }]
+            }));
+        });
+        server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/api").query_param("action", "getsourcecode").query_param("address", unverified);
+            then.status(200).json_body(serde_json::json!({
+                "status": "1",
+                "message": "OK",
+                "result": [{ "SourceCode": "", "ABI": "Contract source code not verified", "ContractName": "" }]
+            }));
+        });
+        server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/api").query_param("address", "0x12");
+            then.status(200).json_body(serde_json::json!({ "status": "0", "message": "NOTOK", "result": "Invalid Address format" }));
+        });
+        let client = ExplorerClient::new("key".to_string()).with_base_url(server.url("/api"));
+
+        assert!(client.is_verified(verified).await.unwrap());
+        let source = client.get_source_code(verified).await.unwrap();
+        assert_eq!(source.name.as_deref(), Some("WETH9"));
+        assert!(source.abi.unwrap().contains("deposit"));
+
+        assert!(!client.is_verified(unverified).await.unwrap());
+        assert_eq!(client.get_source_code(unverified).await.unwrap(), ContractSource { name: None, abi: None, verified: false });
+
+        let err = client.is_verified("0x12").await.unwrap_err();
+        assert!(err.to_string().contains("Invalid Address format"), "{}", err);
+    }
+
+    #[test]
+    fn test_explorer_selected_by_chain_id() {
+        assert_eq!(ExplorerClient::for_chain(Some("k".into()), 1).unwrap().base_url(), "https://api.etherscan.io/api");
+        assert_eq!(ExplorerClient::for_chain(Some("k".into()), 8453).unwrap().base_url(), "https://api.basescan.org/api");
+        assert_eq!(explorer_api_url(42161), Some("https://api.arbiscan.io/api"));
+        assert!(ExplorerClient::for_chain(Some("k".into()), 31337).is_none());
+        // Etherscan-family APIs need a key; Blockscout-only chains do not
+        assert!(ExplorerClient::for_chain(None, 1).is_none());
+        let gnosis = ExplorerClient::for_chain(None, 100).unwrap();
+        assert_eq!((gnosis.base_url(), gnosis.dialect()), ("https://gnosis.blockscout.com/api", ExplorerDialect::Blockscout));
+    }
+
+    #[tokio::test]
+    async fn test_both_dialects_parse_into_the_same_contract() {
+        let weth = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+        let abi = serde_json::json!([{ "type": "function", "name": "deposit", "inputs": [], "outputs": [], "stateMutability": "payable" }]);
+        let server = httpmock::MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/etherscan/api").query_param("action", "getsourcecode").query_param("address", weth);
+            then.status(200).json_body(serde_json::json!({
+                "status": "1",
+                "message": "OK",
+                "result": [{ "SourceCode": "contract WETH9 { }", "ABI": abi.to_string(), "ContractName": "WETH9" }]
+            }));
+        });
+        server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path(format!("/blockscout/api/v2/smart-contracts/{}", weth));
+            then.status(200).json_body(serde_json::json!({ "name": "WETH9", "is_verified": true, "abi": abi, "source_code": "contract WETH9 { }" }));
+        });
+        // Blockscout answers 404 for addresses it has no contract record for
+        server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/blockscout/api/v2/smart-contracts/0x0000000000000000000000000000000000000001");
+            then.status(404).json_body(serde_json::json!({ "message": "Not found" }));
+        });
+
+        let etherscan = ExplorerClient::new("key".to_string()).with_base_url(server.url("/etherscan/api"));
+        let blockscout = ExplorerClient::blockscout(server.url("/blockscout/api"));
+        let (a, b) = (etherscan.get_contract_info(weth).await.unwrap().unwrap(), blockscout.get_contract_info(weth).await.unwrap().unwrap());
+        assert_eq!((&a.address, &a.name, &a.abi), (&b.address, &b.name, &b.abi));
+        assert_eq!(a.name, "WETH9");
+        let parsed: serde_json::Value = serde_json::from_str(b.abi.as_deref().unwrap()).unwrap();
+        assert_eq!(parsed, abi);
+
+        assert!(blockscout.is_verified(weth).await.unwrap());
+        let missing = "0x0000000000000000000000000000000000000001";
+        assert!(!blockscout.is_verified(missing).await.unwrap());
+        assert!(blockscout.get_contract_info(missing).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_custom_base_url_receives_requests() {
+        let server = httpmock::MockServer::start_async().await;
+        let abi_mock = server.mock(|when, then| {
+            when.method(httpmock::Method::GET)
+                .path("/optimism/api")
+                .query_param("action", "getabi")
+                .query_param("apikey", "op-key");
+            then.status(200).json_body(serde_json::json!({ "status": "1", "result": "[]" }));
+        });
+
+        let client = ExplorerClient::for_chain(Some("op-key".to_string()), 10)
+            .unwrap()
+            .with_base_url(server.url("/optimism/api"));
+        let abi = client.get_contract_abi("0x4200000000000000000000000000000000000006").await.unwrap();
+        assert_eq!(abi.as_deref(), Some("[]"));
+        abi_mock.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_request_is_retried() {
+        let server = httpmock::MockServer::start_async().await;
+        let mut limited = server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/api").query_param("action", "getabi");
+            then.status(200).json_body(serde_json::json!({
+                "status": "0", "message": "NOTOK", "result": "Max rate limit reached"
+            }));
+        });
+
+        let client = ExplorerClient::new("key".to_string())
+            .with_base_url(server.url("/api"))
+            .with_rate_limit_backoff(Duration::from_millis(200));
+        let addr = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+        let lookup = tokio::spawn(async move { client.get_contract_abi(addr).await });
+
+        // Swap the rate-limit reply for a success while the client is backing off
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        limited.assert_hits(1);
+        limited.delete();
+        let ok = server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/api").query_param("action", "getabi");
+            then.status(200).json_body(serde_json::json!({ "status": "1", "result": "[]" }));
+        });
+
+        assert_eq!(lookup.await.unwrap().unwrap().as_deref(), Some("[]"));
+        ok.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn test_persistent_rate_limit_is_an_error() {
+        let server = httpmock::MockServer::start_async().await;
+        let limited = server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/api");
+            then.status(200).json_body(serde_json::json!({
+                "status": "0", "message": "NOTOK", "result": "Max rate limit reached"
+            }));
+        });
+
+        let client = ExplorerClient::new("key".to_string())
+            .with_base_url(server.url("/api"))
+            .with_rate_limit_backoff(Duration::from_millis(1));
+        let err = client.get_contract_abi("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48").await.unwrap_err();
+        assert!(err.to_string().contains("Max rate limit reached"));
+        limited.assert_hits(1 + EXPLORER_RATE_LIMIT_RETRIES as usize);
+    }
+
+    #[tokio::test]
+    async fn test_slow_explorer_is_a_timeout_error() {
+        let server = httpmock::MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/api");
+            then.status(200).delay(Duration::from_secs(2)).json_body(serde_json::json!({ "status": "1", "result": "[]" }));
+        });
+
+        let timeouts = HttpTimeouts { connect: Duration::from_secs(1), request: Duration::from_millis(200) };
+        let client = ExplorerClient::new("secret".to_string()).with_base_url(server.url("/api")).with_timeouts(timeouts);
+        let err = client.get_contract_abi("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48").await.unwrap_err();
+        let timed_out = err.downcast_ref::<domain::http::TimedOut>().unwrap_or_else(|| panic!("expected a timeout, got {:#}", err));
+        assert_eq!(timed_out.target, server.url("/api"));
+        assert!(!err.to_string().contains("secret"), "{}", err);
+    }
+
+    #[test]
+    fn test_cache_config_from_env_vars() {
+        let vars: HashMap<&str, &str> = [("ABI_CACHE_SIZE", "5"), ("CONTRACT_CACHE_TTL_SECS", "30"), ("ABI_CACHE_TTL_SECS", "soon")].into();
+        let config = CacheConfig::from_vars(|name| vars.get(name).map(|v| v.to_string()));
+        assert_eq!(config.abi_size, 5);
+        assert_eq!(config.contract_ttl, Duration::from_secs(30));
+        assert_eq!(config.abi_ttl, Duration::from_secs(CACHE_TTL_SECONDS));
+        assert_eq!(config.contract_size, LRU_CACHE_SIZE);
+    }
+
     #[test]
     fn test_cache_expiration() {
         let mut cache = LruCache::new(10, 1); // 1 second TTL
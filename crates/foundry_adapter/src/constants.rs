@@ -3,7 +3,9 @@ use ethers_core::types::Address;
 // Mainnet contract addresses
 #[allow(dead_code)] pub const USDC_MAINNET: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
 #[allow(dead_code)] pub const UNISWAP_V2_ROUTER: &str = "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D";
-#[allow(dead_code)] pub const WETH_MAINNET: &str = "0xC02aaA39b223FE8D0A0E5C4F27eAD9083C756Cc2";
+pub const WETH_MAINNET: &str = "0xC02aaA39b223FE8D0A0E5C4F27eAD9083C756Cc2";
+// Multicall3 lives at the same address on mainnet and most L2s/testnets
+pub const MULTICALL3: &str = "0xcA11bde05977b3631167028862bE2a173976CA11";
 
 // Anvil default accounts (seeded with 10000 ETH each)
 pub const ANVIL_ACCOUNT_0: &str = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266"; // Alice
@@ -12,18 +14,84 @@ pub const ANVIL_ACCOUNT_2: &str = "0x3c44cdddb6a900fa2b585dd299e03d12fa4293bc";
 pub const ANVIL_ACCOUNT_3: &str = "0x90f79bf6eb2c4f870365e785982e1f101e93b906"; // David
 pub const ANVIL_ACCOUNT_4: &str = "0x15d34aaf54267db7d7c367839aaf71a00a2c6a65"; // Eve
 
+// Anvil default private keys, index-aligned with the accounts above
+pub const ANVIL_PRIVATE_KEYS: [&str; 5] = [
+    "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+    "0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d",
+    "0x5de4111afa1a4b94908f83103eb1f1706367c2e68ca870fc3fb9a804cdab365a",
+    "0x7c852118294e51e653712a81e05800f419141751be58f605c371e15141b007a6",
+    "0x47e179ec197488593b187f80a00eb0da91f1b9d0b13f8733639f19c30a34926a",
+];
+
 // Default configuration
 pub const DEFAULT_GAS_CAP: u64 = 30_000_000; // 30M gas
+pub const DEFAULT_GAS_BUFFER_BPS: u64 = 1_000; // +10% over the estimate
 #[allow(dead_code)] pub const DEFAULT_CHAIN_ID: u64 = 1; // Mainnet
 #[allow(dead_code)] pub const DEFAULT_RPC_URL: &str = "http://127.0.0.1:8545"; // Anvil default
 
+// wait_for_balance polling: starts fast, backs off to the max interval
+pub const BALANCE_POLL_INITIAL_MS: u64 = 100;
+pub const BALANCE_POLL_MAX_MS: u64 = 2_000;
+
+// Widest eth_getLogs window per query; many providers reject larger ranges
+pub const MAX_LOG_BLOCK_RANGE: u64 = 10_000;
+
+// Fee suggestion fallback: median of this percentile of priority fees over recent blocks
+pub const FEE_HISTORY_BLOCKS: u64 = 10;
+pub const FEE_HISTORY_PERCENTILE: f64 = 50.0;
+
+// Block subscriptions: resubscribe backoff (doubling up to the max) and per-subscriber buffer
+pub const BLOCK_STREAM_BACKOFF_MS: u64 = 1_000;
+pub const BLOCK_STREAM_MAX_BACKOFF_MS: u64 = 30_000;
+pub const BLOCK_STREAM_BUFFER: usize = 64;
+
+// Inclusion estimate: average block time over this many recent blocks
+pub const BLOCK_TIME_SAMPLE: u64 = 10;
+
+// Confirmations to wait for when a send doesn't specify any; unknown chains use the fallback
+pub const DEFAULT_CONFIRMATIONS: &[(u64, usize)] = &[
+    (1, 3),        // Ethereum mainnet
+    (10, 1),       // Optimism
+    (8453, 1),     // Base
+    (42161, 1),    // Arbitrum One
+    (31337, 1),    // Anvil
+];
+pub const FALLBACK_CONFIRMATIONS: usize = 3;
+// How long a broadcast waits for its confirmations before giving up
+pub const DEFAULT_CONFIRMATION_TIMEOUT_SECS: u64 = 300;
+
+// Chains where the well-known Anvil/Hardhat dev keys may sign; anywhere else they are public secrets
+pub const LOCAL_CHAIN_IDS: [u64; 2] = [31337, 1337];
+
+// Etherscan-compatible explorer APIs by chain id
+pub const EXPLORER_APIS: &[(u64, &str)] = &[
+    (1, "https://api.etherscan.io/api"),
+    (10, "https://api-optimistic.etherscan.io/api"),
+    (137, "https://api.polygonscan.com/api"),
+    (8453, "https://api.basescan.org/api"),
+    (42161, "https://api.arbiscan.io/api"),
+    (11155111, "https://api-sepolia.etherscan.io/api"),
+];
+
+// Blockscout instances for chains without an Etherscan-family API above
+pub const BLOCKSCOUT_APIS: &[(u64, &str)] = &[
+    (100, "https://gnosis.blockscout.com/api"),
+    (7777777, "https://explorer.zora.energy/api"),
+];
+
+// Etherscan rate limiting: retries after a "Max rate limit reached" reply, doubling the delay
+pub const EXPLORER_RATE_LIMIT_RETRIES: u32 = 3;
+pub const EXPLORER_RATE_LIMIT_BACKOFF_MS: u64 = 1_000;
+
 // ENS resolution
-#[allow(dead_code)] pub const ENS_REGISTRY: &str = "0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e";
+pub const ENS_REGISTRY: &str = "0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e";
+// Chains with a canonical ENS deployment (mainnet, Sepolia, Holesky)
+pub const ENS_CHAIN_IDS: [u64; 3] = [1, 11155111, 17000];
 #[allow(dead_code)] pub const ENS_RESOLVER: &str = "0x4976fb03C32e5B8cfe2b6cCB31c09Ba78EBaBa41";
 
-// Cache configuration
-#[allow(dead_code)] pub const LRU_CACHE_SIZE: usize = 1000;
-#[allow(dead_code)] pub const CACHE_TTL_SECONDS: u64 = 3600; // 1 hour
+// Cache defaults; CacheConfig::from_env overrides them per deployment
+pub const LRU_CACHE_SIZE: usize = 1000;
+pub const CACHE_TTL_SECONDS: u64 = 3600; // 1 hour
 
 pub fn get_anvil_accounts() -> Vec<Address> {
     vec![
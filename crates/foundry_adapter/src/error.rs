@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::transport::RpcTimedOut;
+
 #[derive(Debug, Error)]
 pub enum AdapterError {
     #[error("unexpected chain id: got {got} expected {expected}")]
@@ -8,17 +10,86 @@ pub enum AdapterError {
     #[error("estimated gas {estimated} exceeds cap {cap}")]
     GasCapExceeded { estimated: u64, cap: u64 },
 
+    #[error("fork_block {fork_block} is ahead of the chain head {head}")]
+    ForkBlockAhead { fork_block: u64, head: u64 },
+
+    #[error("fork_block {fork_block} only applies to simulations; transactions are broadcast against the chain head")]
+    ForkBlockOnBroadcast { fork_block: u64 },
+
+    #[error("block {block} is in the future; the chain head is {head}")]
+    BlockInFuture { block: u64, head: u64 },
+
+    #[error("block subscriptions need a WebSocket RPC (ws:// or wss://)")]
+    SubscriptionsNeedWs,
+
     #[error("no local key for from address {0}")]
     MissingLocalKey(String),
 
+    #[error("PRIVATE_KEYS entry {index} is not a valid private key")]
+    InvalidPrivateKey { index: usize },
+
+    #[error("ENS is not deployed on chain {chain_id}; ENS names only resolve against Ethereum mainnet, so use a mainnet RPC or pass a 0x address")]
+    EnsUnsupported { chain_id: u64 },
+
+    #[error("WETH is not deployed on chain {chain_id}; wrapping uses the mainnet WETH contract, so use a mainnet RPC or a mainnet fork")]
+    WethUnavailable { chain_id: u64 },
+
+    #[error("recipient {to} is a contract that rejects ETH transfers (simulated call reverted)")]
+    RecipientRejectsEth { to: String },
+
+    #[error("timed out waiting for balance >= {min_wei} wei; last seen {last_wei} wei")]
+    BalanceTimeout { min_wei: String, last_wei: String },
+
+    #[error("transaction {tx_hash} was broadcast but not confirmed {confirmations} time(s) within {waited_secs}s")]
+    ConfirmationTimeout { tx_hash: String, confirmations: usize, waited_secs: u64 },
+
+    #[error("{token} is not an ERC-20 token: balanceOf returned no decodable data")]
+    NotAnErc20 { token: String },
+
+    #[error("invalid contract bytecode: {reason}")]
+    InvalidBytecode { reason: String },
+
+    #[error("invalid raw transaction: {reason}")]
+    InvalidRawTx { reason: String },
+
+    #[error("invalid amount {amount}: sends must move a positive amount")]
+    InvalidAmount { amount: String },
+
+    #[error("refusing self-transfer: from and to are both {address}")]
+    SelfTransfer { address: String },
+
+    #[error("insufficient funds: have {have} wei, need {need} wei (value + gas)")]
+    InsufficientFunds { have: String, need: String },
+
+    #[error("invalid block range {from_block}..={to_block}: must be ascending and span at most {max} blocks")]
+    InvalidBlockRange { from_block: u64, to_block: u64, max: u64 },
+
+    #[error("chain has no base fee (pre-London); EIP-1559 fees are unavailable")]
+    NoBaseFee,
+
+    #[error("execution reverted: {reason}")]
+    Reverted { reason: String },
+
+    #[error("{method} needs a dev node such as Anvil: {reason}")]
+    DevMethodUnsupported { method: &'static str, reason: String },
+
+    #[error("impersonation is disabled; enable it with with_impersonation(true) on a dev node such as Anvil")]
+    ImpersonationDisabled,
+
+    #[error("adapter is read-only; refusing to send transactions")]
+    ReadOnly,
+
     #[error(transparent)]
-    Provider(#[from] ethers_providers::ProviderError),
+    Timeout(#[from] domain::http::TimedOut),
+
+    #[error(transparent)]
+    Provider(ethers_providers::ProviderError),
 
     #[error(transparent)]
     Abi(#[from] ethers_core::abi::Error),
 
     #[error(transparent)]
-    Contract(#[from] ethers_contract::ContractError<ethers_providers::Provider<ethers_providers::Http>>),
+    Contract(#[from] ethers_contract::ContractError<ethers_providers::Provider<crate::Transport>>),
 
     #[error(transparent)]
     Signer(#[from] ethers_signers::WalletError),
@@ -30,3 +101,15 @@ pub enum AdapterError {
     Other(#[from] anyhow::Error),
 }
 
+/// Transport timeouts arrive wrapped in a `ProviderError`; lift them into `Timeout`
+impl From<ethers_providers::ProviderError> for AdapterError {
+    fn from(e: ethers_providers::ProviderError) -> Self {
+        if let ethers_providers::ProviderError::JsonRpcClientError(inner) = &e {
+            let inner: &(dyn std::error::Error + 'static) = inner.as_ref();
+            if let Some(RpcTimedOut(timed_out)) = inner.downcast_ref::<RpcTimedOut>() {
+                return Self::Timeout(timed_out.clone());
+            }
+        }
+        Self::Provider(e)
+    }
+}
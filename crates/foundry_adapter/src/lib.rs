@@ -2,32 +2,57 @@ mod error;
 mod constants;
 mod validation;
 mod cache;
+mod revert;
+mod transport;
+mod subscribe;
+#[cfg(feature = "test-anvil")]
+pub mod test_anvil;
 // use anyhow::anyhow; // reserved for future error conversions
-use error::AdapterError;
+pub use error::AdapterError;
 use constants::*;
+pub use revert::decode_revert_reason;
+pub use transport::Transport;
+pub use cache::{explorer_api_url, AbiResolver, CacheConfig, ContractDiscovery, ContractSource, ExplorerClient, ExplorerDialect, LruCache};
 
+use domain::http::HttpTimeouts;
 use domain::{
+    units,
     Address,
     AddressOrEns,
+    BalanceChange,
+    BundleStep,
+    BundleStepResult,
     BalanceRequest,
     BalanceResponse,
     BlockchainProvider,
     CodeRequest,
     CodeResponse,
+    ContractCallRequest,
+    DeployRequest,
+    DeployResult,
     Erc20BalanceRequest,
+    Erc20TransferRequest,
     Erc20BalanceResponse,
+    SendMode,
     SendRequest,
     TxResult,
+    TxType,
+};
+use ethers_contract::{Contract, ContractError, Multicall};
+use ethers_core::abi::{parse_abi_str, Token};
+use ethers_core::types::{
+    transaction::eip2718::TypedTransaction, Address as EthAddress, BlockId, BlockNumber, Bytes, Eip1559TransactionRequest, Filter, Log, TransactionReceipt, TransactionRequest,
+    H256, U256,
 };
-use ethers_contract::Contract;
-use ethers_core::abi::parse_abi_str;
-use ethers_core::types::{transaction::eip2718::TypedTransaction, Address as EthAddress, Bytes, TransactionRequest, U256};
-use ethers_core::utils::parse_ether;
 use ethers_middleware::SignerMiddleware;
-use ethers_providers::{Http, Middleware, Provider};
+use ethers_providers::{Middleware, PendingTransaction, Provider, ProviderError, RpcError, Ws};
 use ethers_signers::{LocalWallet, Signer};
-use std::collections::HashMap;
+use futures_util::Stream;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::warn;
 
 #[allow(dead_code)]
 pub fn is_checksum_address(_addr: &str) -> bool {
@@ -35,34 +60,124 @@ pub fn is_checksum_address(_addr: &str) -> bool {
     true
 }
 
+/// ERC-20 `name()`/`symbol()`/`decimals()`; immutable per token, so cached for the
+/// adapter's lifetime. `name`/`symbol` are optional in the standard (and some tokens
+/// return `bytes32`), so they are `None` when missing or undecodable.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Erc20Metadata {
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub decimals: u8,
+}
+
+/// Current EIP-1559 fee conditions in wei: `max_fee = 2 * base_fee + priority_fee`,
+/// which stays valid through several blocks of rising base fees.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+pub struct FeeSuggestion {
+    pub base_fee: String,
+    pub priority_fee: String,
+    pub max_fee: String,
+}
+
+/// One ERC-20 `Transfer` event; addresses are checksummed and `value` is in base units.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+pub struct TransferLog {
+    pub from: String,
+    pub to: String,
+    pub value: String,
+    pub tx_hash: String,
+    pub block: u64,
+}
+
+/// A native transfer simulated and priced by `prepare_send`. `send_prepared` broadcasts
+/// it with the same gas, fees and envelope; only the nonce is assigned at broadcast.
+#[derive(Clone, Debug)]
+pub struct PreparedTransfer {
+    from: Address,
+    tx: TypedTransaction,
+    estimated_gas: u64,
+    confirmations: Option<u64>,
+}
+
+/// A transfer built, estimated, priced and simulated by `fill_transfer`
+struct FilledTransfer {
+    from_addr: EthAddress,
+    to_addr: EthAddress,
+    value: U256,
+    typed: TypedTransaction,
+    est: U256,
+    expected_fee_per_gas: U256,
+    balance: Option<U256>,
+    /// `fork_block` of the request; balances are read there too
+    block: Option<BlockId>,
+}
+
 #[derive(Clone, Debug)]
 pub struct FoundryAdapter {
-    provider: Provider<Http>,
+    provider: Provider<Transport>,
     gas_cap: u64,
+    gas_buffer_bps: u64,
     expected_chain_id: Option<u64>,
     known_wallets: HashMap<String, LocalWallet>,
+    /// Anvil dev keys; only sign on `LOCAL_CHAIN_IDS`
+    anvil_wallets: HashMap<String, LocalWallet>,
+    read_only: bool,
+    erc20_metadata: Arc<Mutex<HashMap<String, Erc20Metadata>>>,
+    /// One lock per sender (normalized address), shared by clones, so broadcasts from an
+    /// account never race on the pending nonce
+    send_locks: Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>,
+    cache: Arc<Mutex<LruCache>>,
+    ens_registry: Option<EthAddress>,
+    simulate_funds_check: bool,
+    reject_self_transfers: bool,
+    /// Read RPCs: total attempts (1 = no retries) and the first backoff delay
+    retry_attempts: u32,
+    retry_base_delay: Duration,
+    /// Broadcasts fail with `ConfirmationTimeout` if their confirmations take longer
+    confirmation_timeout: Duration,
+    /// Set by `new_ws`; subscriptions reconnect to it when the socket drops
+    ws_url: Option<String>,
+    /// Bundle previews snapshot and revert the whole node, so they hold this exclusively;
+    /// broadcasts share it until confirmed, so a revert never drops them
+    node_lock: Arc<tokio::sync::RwLock<()>>,
+    /// Off unless `with_impersonation(true)`, so production RPCs never see `anvil_*` calls
+    allow_impersonation: bool,
+    /// Senders (normalized) the node signs for after `impersonate`, shared by clones
+    impersonated: Arc<Mutex<HashSet<String>>>,
 }
 
 impl FoundryAdapter {
     pub async fn new(rpc_url: impl Into<String>) -> Result<Self, AdapterError> {
+        Self::new_with_timeouts(rpc_url, HttpTimeouts::default()).await
+    }
+
+    /// Like `new`, with connect and per-call timeouts; a call that hits one fails with
+    /// `AdapterError::Timeout`.
+    pub async fn new_with_timeouts(rpc_url: impl Into<String>, timeouts: HttpTimeouts) -> Result<Self, AdapterError> {
         let rpc_url = rpc_url.into();
-        let provider = Provider::<Http>::try_from(rpc_url).map_err(|e| AdapterError::Other(e.into()))?;
-        let mut known_wallets = HashMap::new();
-        let accounts = get_anvil_accounts();
-        let private_keys = vec![
-            "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
-            "0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d",
-            "0x5de4111afa1a4b94908f83103eb1f1706367c2e68ca870fc3fb9a804cdab365a",
-            "0x7c852118e8d7e3b58184ae9b0c2aa26a2d4f9b6c3b6b6b6b6b6b6b6b6b6b6b6b",
-            "0x47e179ec197488593b187f80a00eb0da91f1b9d0b13f8733639f19c30a34926a",
-        ];
-        
-        for (addr, key) in accounts.iter().zip(private_keys.iter()) {
-            let wallet = LocalWallet::from_str(key)?;
-            known_wallets.insert(normalize(&addr.to_string()), wallet);
-        }
+        Ok(Self::from_transport(Transport::http(&rpc_url, timeouts)?))
+    }
+
+    /// Connect over a WebSocket (`ws://` / `wss://`), which subscriptions need; all other
+    /// methods and builders behave exactly as with `new`.
+    pub async fn new_ws(ws_url: impl Into<String>) -> Result<Self, AdapterError> {
+        Self::new_ws_with_timeouts(ws_url, HttpTimeouts::default()).await
+    }
+
+    pub async fn new_ws_with_timeouts(ws_url: impl Into<String>, timeouts: HttpTimeouts) -> Result<Self, AdapterError> {
+        let ws_url = ws_url.into();
+        let connect = tokio::time::timeout(timeouts.connect, Ws::connect(ws_url.as_str()))
+            .await
+            .map_err(|_| timeouts.timed_out(ws_url.clone(), true))?;
+        let ws = connect.map_err(ProviderError::from)?;
+        Ok(Self { ws_url: Some(ws_url), ..Self::from_transport(Transport::Ws(ws, timeouts)) })
+    }
+
+    fn from_transport(transport: Transport) -> Self {
+        let provider = Provider::new(transport);
+        let anvil_wallets = load_known_wallets(&get_anvil_accounts(), &ANVIL_PRIVATE_KEYS);
         
-        Ok(Self { provider, gas_cap: DEFAULT_GAS_CAP, expected_chain_id: None, known_wallets })
+        Self { provider, gas_cap: DEFAULT_GAS_CAP, gas_buffer_bps: DEFAULT_GAS_BUFFER_BPS, expected_chain_id: None, known_wallets: HashMap::new(), anvil_wallets, read_only: false, erc20_metadata: Arc::default(), send_locks: Arc::default(), cache: Arc::new(Mutex::new(LruCache::from_config(CacheConfig::default()))), ens_registry: None, simulate_funds_check: true, reject_self_transfers: true, retry_attempts: 1, retry_base_delay: Duration::ZERO, confirmation_timeout: Duration::from_secs(DEFAULT_CONFIRMATION_TIMEOUT_SECS), ws_url: None, node_lock: Arc::default(), allow_impersonation: false, impersonated: Arc::default() }
     }
 
     pub fn with_expected_chain_id(mut self, chain_id: u64) -> Self {
@@ -75,6 +190,243 @@ impl FoundryAdapter {
         self
     }
 
+    /// Headroom added to every gas estimate, in basis points (1000 = +10%)
+    pub fn with_gas_buffer_bps(mut self, bps: u64) -> Self {
+        self.gas_buffer_bps = bps;
+        self
+    }
+
+    /// Resolve ENS names through a custom registry (testnets, forks with their own
+    /// deployment) instead of the canonical one.
+    pub fn with_ens_registry(mut self, registry: &Address) -> Result<Self, AdapterError> {
+        let registry = EthAddress::from_str(registry.as_str()).map_err(|_| AdapterError::AddrParse(registry.as_str().into()))?;
+        self.provider = self.provider.ens(registry);
+        self.ens_registry = Some(registry);
+        Ok(self)
+    }
+
+    /// Replace the ABI/contract cache with an empty one sized by `config`
+    pub fn with_cache_config(mut self, config: CacheConfig) -> Self {
+        self.cache = Arc::new(Mutex::new(LruCache::from_config(config)));
+        self
+    }
+
+    pub fn cache_config(&self) -> CacheConfig {
+        *self.cache.lock().unwrap().config()
+    }
+
+    /// Remember a contract's ABI (keyed by normalized address) for later lookups
+    pub fn cache_abi(&self, address: &str, abi: String, verified: bool) {
+        self.cache.lock().unwrap().set_abi(validation::normalize(address), abi, verified);
+    }
+
+    /// A cached, unexpired ABI for `address`
+    pub fn cached_abi(&self, address: &str) -> Option<String> {
+        self.cache.lock().unwrap().get_abi(&validation::normalize(address)).map(|cached| cached.abi.clone())
+    }
+
+    /// Whether simulations also fail with `InsufficientFunds`; broadcasts are always checked.
+    pub fn with_simulate_funds_check(mut self, enabled: bool) -> Self {
+        self.simulate_funds_check = enabled;
+        self
+    }
+
+    /// Sign for the key's address on any chain. Ignored by read-only adapters.
+    pub fn with_signer_from_private_key(mut self, hex_key: &str) -> Result<Self, AdapterError> {
+        let wallet = LocalWallet::from_str(hex_key)?;
+        self.add_signer(wallet);
+        Ok(self)
+    }
+
+    /// Sign with the key in an encrypted JSON keystore (geth/`cast wallet` format)
+    pub fn with_keystore(mut self, path: impl AsRef<std::path::Path>, password: &str) -> Result<Self, AdapterError> {
+        let wallet = LocalWallet::decrypt_keystore(path, password)?;
+        self.add_signer(wallet);
+        Ok(self)
+    }
+
+    /// Register the comma-separated hex keys in `PRIVATE_KEYS`, if set
+    pub fn with_env_signers(self) -> Result<Self, AdapterError> {
+        self.with_signers_from_vars(|name| std::env::var(name).ok())
+    }
+
+    fn with_signers_from_vars(mut self, var: impl Fn(&str) -> Option<String>) -> Result<Self, AdapterError> {
+        let Some(keys) = var("PRIVATE_KEYS") else { return Ok(self) };
+        for (index, key) in keys.split(',').map(str::trim).filter(|k| !k.is_empty()).enumerate() {
+            // Never echo the key itself, only its position
+            let wallet = LocalWallet::from_str(key).map_err(|_| AdapterError::InvalidPrivateKey { index })?;
+            self.add_signer(wallet);
+        }
+        Ok(self)
+    }
+
+    fn add_signer(&mut self, wallet: LocalWallet) {
+        if !self.read_only {
+            self.known_wallets.insert(normalize(&format!("{:?}", wallet.address())), wallet);
+        }
+    }
+
+    /// The key that signs for `from` on `chain_id`: configured signers first, then the
+    /// Anvil dev keys on local chains only.
+    fn wallet_for(&self, from: &str, chain_id: u64) -> Result<LocalWallet, AdapterError> {
+        let key = normalize(from);
+        self.known_wallets
+            .get(&key)
+            .or_else(|| LOCAL_CHAIN_IDS.contains(&chain_id).then(|| self.anvil_wallets.get(&key)).flatten())
+            .map(|wallet| wallet.clone().with_chain_id(chain_id))
+            .ok_or_else(|| AdapterError::MissingLocalKey(from.to_string()))
+    }
+
+    /// Retry reads that fail in transport (timeouts, gateway errors) up to `max_attempts`
+    /// times in total, doubling the delay from `base_delay`. Errors the node itself
+    /// returns, such as reverts, are never retried. The default is no retries.
+    pub fn with_retry(mut self, max_attempts: u32, base_delay: Duration) -> Self {
+        self.retry_attempts = max_attempts.max(1);
+        self.retry_base_delay = base_delay;
+        self
+    }
+
+    async fn read_with_retry<T, F, Fut>(&self, mut read: F) -> Result<T, ProviderError>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = Result<T, ProviderError>>,
+    {
+        let mut delay = self.retry_base_delay;
+        let mut attempt = 1;
+        loop {
+            match read().await {
+                Err(e) if attempt < self.retry_attempts && is_transient(&e) => {
+                    warn!("transient RPC error on attempt {} of {}, retrying in {:?}: {}", attempt, self.retry_attempts, delay, e);
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// How often pending transactions are polled for their receipt (ethers default: 7s)
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.provider = self.provider.interval(interval);
+        self
+    }
+
+    /// Upper bound on waiting for a broadcast's confirmations; the tx stays in flight
+    /// after a timeout, so the error carries its hash.
+    pub fn with_confirmation_timeout(mut self, timeout: Duration) -> Self {
+        self.confirmation_timeout = timeout;
+        self
+    }
+
+    async fn await_confirmations(&self, pending: PendingTransaction<'_, Transport>, confirmations: usize) -> Result<Option<TransactionReceipt>, AdapterError> {
+        let tx_hash = format!("0x{:x}", *pending);
+        match tokio::time::timeout(self.confirmation_timeout, pending.confirmations(confirmations)).await {
+            Ok(receipt) => Ok(receipt?),
+            Err(_) => Err(AdapterError::ConfirmationTimeout { tx_hash, confirmations, waited_secs: self.confirmation_timeout.as_secs() }),
+        }
+    }
+
+    /// Self-transfers (from == to) only burn gas and usually mean the request was
+    /// mis-parsed, so they are rejected unless this is turned off.
+    pub fn with_self_transfer_check(mut self, enabled: bool) -> Self {
+        self.reject_self_transfers = enabled;
+        self
+    }
+
+    fn send_lock(&self, from: &str) -> Arc<tokio::sync::Mutex<()>> {
+        self.send_locks.lock().unwrap().entry(normalize(from)).or_default().clone()
+    }
+
+    /// Allow `impersonate` (Anvil's `anvil_impersonateAccount`). Only for dev nodes and forks.
+    pub fn with_impersonation(mut self, enabled: bool) -> Self {
+        self.allow_impersonation = enabled;
+        self
+    }
+
+    /// Let the node sign for `addr` until `stop_impersonate`: sends from it skip the local
+    /// key lookup and go out as plain `eth_sendTransaction`. Needs `with_impersonation(true)`.
+    pub async fn impersonate(&self, addr: &Address) -> Result<(), AdapterError> {
+        if self.read_only {
+            return Err(AdapterError::ReadOnly);
+        }
+        if !self.allow_impersonation {
+            return Err(AdapterError::ImpersonationDisabled);
+        }
+        let account = EthAddress::from_str(addr.as_str()).map_err(|_| AdapterError::AddrParse(addr.as_str().into()))?;
+        self.provider
+            .request::<_, ()>("anvil_impersonateAccount", [account])
+            .await
+            .map_err(|e| dev_method_error("anvil_impersonateAccount", e))?;
+        self.impersonated.lock().unwrap().insert(normalize(addr.as_str()));
+        Ok(())
+    }
+
+    /// Undo `impersonate`; sends from `addr` need a local key again
+    pub async fn stop_impersonate(&self, addr: &Address) -> Result<(), AdapterError> {
+        if !self.allow_impersonation {
+            return Err(AdapterError::ImpersonationDisabled);
+        }
+        let account = EthAddress::from_str(addr.as_str()).map_err(|_| AdapterError::AddrParse(addr.as_str().into()))?;
+        self.provider
+            .request::<_, ()>("anvil_stopImpersonatingAccount", [account])
+            .await
+            .map_err(|e| dev_method_error("anvil_stopImpersonatingAccount", e))?;
+        self.impersonated.lock().unwrap().remove(&normalize(addr.as_str()));
+        Ok(())
+    }
+
+    fn is_impersonated(&self, from: &str) -> bool {
+        self.impersonated.lock().unwrap().contains(&normalize(from))
+    }
+
+    /// Broadcast `typed` from `from`: through the node when it is impersonated, otherwise
+    /// signed with the local key for `from`. Retries once on "nonce too low".
+    async fn broadcast(&self, from: &str, from_addr: EthAddress, chain_id: u64, typed: TypedTransaction) -> Result<PendingTransaction<'_, Transport>, AdapterError> {
+        let pending_nonce = || async { Ok(self.provider.get_transaction_count(from_addr, Some(BlockNumber::Pending.into())).await?) };
+        if self.is_impersonated(from) {
+            return send_with_nonce_retry(typed, |tx| self.provider.send_transaction(tx, None), pending_nonce).await;
+        }
+        let client = SignerMiddleware::new(self.provider.clone(), self.wallet_for(from, chain_id)?);
+        let client = &client;
+        let pending = send_with_nonce_retry(typed, |tx| client.send_transaction(tx, None), pending_nonce).await?;
+        // The pending tx borrows the signer's provider; rebind it to ours (same transport)
+        Ok(PendingTransaction::new(*pending, &self.provider))
+    }
+
+    /// Read-only adapters drop all signing keys and refuse every send, simulated or not.
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        if read_only {
+            self.known_wallets.clear();
+            self.anvil_wallets.clear();
+        }
+        self
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    pub fn is_ws(&self) -> bool {
+        self.provider.as_ref().is_ws()
+    }
+
+    /// New block numbers as they are mined. The stream survives dropped sockets by
+    /// resubscribing with backoff, and stops once it is dropped. WebSocket adapters only.
+    pub async fn subscribe_blocks(&self) -> Result<impl Stream<Item = u64> + Send + use<>, AdapterError> {
+        let ws_url = self.ws_url.clone().ok_or(AdapterError::SubscriptionsNeedWs)?;
+        let (tx, rx) = tokio::sync::mpsc::channel(BLOCK_STREAM_BUFFER);
+        let (ready_tx, ready) = tokio::sync::oneshot::channel();
+        tokio::spawn(subscribe::forward_blocks(ws_url, tx, ready_tx));
+        ready.await.map_err(|_| AdapterError::Other(anyhow::anyhow!("block subscription task stopped")))??;
+        Ok(futures_util::stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|number| (number, rx)) }))
+    }
+
+    pub async fn chain_id(&self) -> Result<u64, AdapterError> {
+        Ok(self.read_with_retry(|| self.provider.get_chainid()).await?.as_u64())
+    }
+
     pub async fn resolve_address_or_ens(&self, input: &AddressOrEns) -> Result<Address, AdapterError> {
         eprintln!("DEBUG: resolve_address_or_ens called with: {:?}", input);
         match input {
@@ -91,6 +443,7 @@ impl FoundryAdapter {
             }
             AddressOrEns::Ens(name) => {
                 eprintln!("DEBUG: Resolving ENS: {}", name.as_str());
+                self.ensure_ens_supported().await?;
                 let resolved: EthAddress = self.provider.resolve_name(name.as_str()).await?;
                 eprintln!("DEBUG: ENS resolved to: {}", resolved);
                 let checksummed = ethers_core::utils::to_checksum(&resolved, None);
@@ -99,100 +452,2168 @@ impl FoundryAdapter {
         }
     }
 
+    /// Fail with `EnsUnsupported` on chains without the ENS registry (e.g. an L2 fork)
+    /// instead of letting `resolve_name` surface an opaque provider error.
+    async fn ensure_ens_supported(&self) -> Result<(), AdapterError> {
+        let chain_id = self.chain_id().await?;
+        if self.ens_registry.is_none() && ENS_CHAIN_IDS.contains(&chain_id) {
+            return Ok(());
+        }
+        // Devnets and forks with custom chain ids may still carry the registry
+        let registry = self.ens_registry.unwrap_or_else(|| EthAddress::from_str(ENS_REGISTRY).expect("valid ENS registry address"));
+        let code: Bytes = self.provider.get_code(registry, None).await?;
+        if code.0.is_empty() {
+            return Err(AdapterError::EnsUnsupported { chain_id });
+        }
+        Ok(())
+    }
+
+    /// Resolve an ENS name without side effects. Unregistered names (no resolver or a
+    /// zero address record) are `Ok(None)`; RPC failures are still errors.
+    pub async fn resolve_ens(&self, name: &str) -> Result<Option<Address>, AdapterError> {
+        self.ensure_ens_supported().await?;
+        match self.provider.resolve_name(name).await {
+            Ok(resolved) if resolved.is_zero() => Ok(None),
+            Ok(resolved) => Ok(Some(Address::new(ethers_core::utils::to_checksum(&resolved, None)))),
+            Err(ethers_providers::ProviderError::EnsError(_) | ethers_providers::ProviderError::EnsNotOwned(_)) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
     pub async fn get_balance(&self, req: &BalanceRequest) -> Result<String, AdapterError> {
         eprintln!("DEBUG: get_balance called for: {:?}", req.who());
         let addr = self.resolve_address_or_ens(req.who()).await?;
         eprintln!("DEBUG: Address resolved to: {}", addr.as_str());
         let addr = EthAddress::from_str(addr.as_str()).map_err(|_| AdapterError::AddrParse(addr.as_str().into()))?;
+        let block = self.past_block(req.block()).await?;
         eprintln!("DEBUG: About to call provider.get_balance for: {}", addr);
-        let bal: U256 = self.provider.get_balance(addr, None).await?;
+        let bal: U256 = self.read_with_retry(|| self.provider.get_balance(addr, block)).await?;
         eprintln!("DEBUG: Balance retrieved: {}", bal);
         Ok(bal.to_string())
     }
 
+    /// Block tag for a historical read: `block` when set (`BlockInFuture` past the head),
+    /// otherwise the node's default of latest
+    async fn past_block(&self, block: Option<u64>) -> Result<Option<BlockId>, AdapterError> {
+        let Some(block) = block else { return Ok(None) };
+        let head = self.read_with_retry(|| self.provider.get_block_number()).await?.as_u64();
+        if block > head {
+            return Err(AdapterError::BlockInFuture { block, head });
+        }
+        Ok(Some(BlockId::Number(block.into())))
+    }
+
+    /// Poll the balance with exponential backoff until it reaches `min_wei` (decimal wei),
+    /// returning the balance that satisfied it, or `BalanceTimeout` once `timeout` passes.
+    pub async fn wait_for_balance(&self, req: &BalanceRequest, min_wei: &str, timeout: Duration) -> Result<String, AdapterError> {
+        let min = parse_wei(min_wei)?;
+        let deadline = Instant::now() + timeout;
+        let mut delay = Duration::from_millis(BALANCE_POLL_INITIAL_MS);
+        loop {
+            let balance = self.get_balance(req).await?;
+            if parse_wei(&balance)? >= min {
+                return Ok(balance);
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(AdapterError::BalanceTimeout { min_wei: min_wei.to_string(), last_wei: balance });
+            }
+            tokio::time::sleep(delay.min(remaining)).await;
+            delay = (delay * 2).min(Duration::from_millis(BALANCE_POLL_MAX_MS));
+        }
+    }
+
     pub async fn get_code_len(&self, req: &CodeRequest) -> Result<(bool, u64), AdapterError> {
         let addr = EthAddress::from_str(req.addr().as_str()).map_err(|_| AdapterError::AddrParse(req.addr().as_str().into()))?;
-        let code: Bytes = self.provider.get_code(addr, None).await?;
+        let code: Bytes = self.read_with_retry(|| self.provider.get_code(addr, None)).await?;
         let len = code.0.len() as u64;
         Ok((len > 0, len))
     }
 
+    /// `balanceOf` for many holders of one token, in holder order. Uses Multicall3 (one
+    /// `eth_call`) when the chain has it and falls back to one call per holder; either
+    /// way a failing holder only fails its own entry.
+    pub async fn erc20_balances_of(&self, token: &Address, holders: &[Address]) -> Result<Vec<Result<String, AdapterError>>, AdapterError> {
+        let token_addr = EthAddress::from_str(token.as_str()).map_err(|_| AdapterError::AddrParse(token.as_str().into()))?;
+        if holders.is_empty() {
+            return Ok(Vec::new());
+        }
+        match self.multicall_balances(token_addr, token, holders).await {
+            Ok(results) => return Ok(results),
+            Err(e) => warn!("multicall balanceOf unavailable, querying {} holders one by one: {}", holders.len(), e),
+        }
+        let mut results = Vec::with_capacity(holders.len());
+        for holder in holders {
+            results.push(self.erc20_balance_of(&Erc20BalanceRequest::new(token.clone(), holder.clone())).await);
+        }
+        Ok(results)
+    }
+
+    async fn multicall_balances(&self, token_addr: EthAddress, token: &Address, holders: &[Address]) -> Result<Vec<Result<String, AdapterError>>, AdapterError> {
+        let multicall_addr = EthAddress::from_str(MULTICALL3).expect("valid Multicall3 address");
+        if self.provider.get_code(multicall_addr, None).await?.is_empty() {
+            return Err(AdapterError::Other(anyhow::anyhow!("no Multicall3 contract at {}", MULTICALL3)));
+        }
+        let abi = parse_abi_str("[function balanceOf(address) view returns (uint256)]").map_err(|e| AdapterError::Other(e.into()))?;
+        let contract = Contract::new(token_addr, abi, self.provider.clone().into());
+        let mut multicall = Multicall::new(self.provider.clone(), Some(multicall_addr)).await.map_err(|e| AdapterError::Other(e.into()))?;
+        // Unparseable holders are reported in place and never join the aggregate call
+        let parsed: Vec<Result<EthAddress, AdapterError>> = holders
+            .iter()
+            .map(|h| EthAddress::from_str(h.as_str()).map_err(|_| AdapterError::AddrParse(h.as_str().into())))
+            .collect();
+        for holder in parsed.iter().flatten() {
+            let call = contract.method::<_, U256>("balanceOf", *holder).map_err(|e| AdapterError::Other(e.into()))?;
+            multicall.add_call(call, true);
+        }
+        let mut returned = multicall.call_raw().await.map_err(|e| AdapterError::Other(e.into()))?.into_iter();
+        Ok(parsed
+            .into_iter()
+            .map(|holder| {
+                holder?;
+                match returned.next() {
+                    Some(Ok(Token::Uint(amount))) => Ok(amount.to_string()),
+                    // Empty return data: EOAs and non-token contracts
+                    Some(Err(data)) if data.is_empty() => Err(AdapterError::NotAnErc20 { token: token.as_str().to_string() }),
+                    Some(_) | None => Err(AdapterError::Other(anyhow::anyhow!("balanceOf failed for this holder"))),
+                }
+            })
+            .collect())
+    }
+
+    /// `Transfer` events of `token` sent or received by `holder` in `from_block..=to_block`,
+    /// oldest first. The range is capped at `MAX_LOG_BLOCK_RANGE` blocks.
+    pub async fn erc20_transfers(&self, token: &Address, holder: &Address, from_block: u64, to_block: u64) -> Result<Vec<TransferLog>, AdapterError> {
+        if from_block > to_block || to_block - from_block >= MAX_LOG_BLOCK_RANGE {
+            return Err(AdapterError::InvalidBlockRange { from_block, to_block, max: MAX_LOG_BLOCK_RANGE });
+        }
+        let token_addr = EthAddress::from_str(token.as_str()).map_err(|_| AdapterError::AddrParse(token.as_str().into()))?;
+        let holder_addr = EthAddress::from_str(holder.as_str()).map_err(|_| AdapterError::AddrParse(holder.as_str().into()))?;
+        let base = Filter::new().address(token_addr).event("Transfer(address,address,uint256)").from_block(from_block).to_block(to_block);
+        let holder_topic = H256::from(holder_addr);
+        // Topics are ANDed, so "from OR to holder" takes one query per slot
+        let (sent_filter, received_filter) = (base.clone().topic1(holder_topic), base.topic2(holder_topic));
+        let (sent, received) = tokio::join!(self.provider.get_logs(&sent_filter), self.provider.get_logs(&received_filter));
+        let mut logs: Vec<Log> = sent?.into_iter().chain(received?).collect();
+        logs.sort_by_key(|log| (log.block_number, log.log_index));
+        // Self-transfers match both queries
+        logs.dedup_by_key(|log| (log.transaction_hash, log.log_index));
+        logs.iter().map(decode_transfer).collect()
+    }
+
     pub async fn erc20_balance_of(&self, req: &Erc20BalanceRequest) -> Result<String, AdapterError> {
         let token = EthAddress::from_str(req.token().as_str()).map_err(|_| AdapterError::AddrParse(req.token().as_str().into()))?;
         let holder = EthAddress::from_str(req.holder().as_str()).map_err(|_| AdapterError::AddrParse(req.holder().as_str().into()))?;
         let abi = parse_abi_str("[function balanceOf(address) view returns (uint256)]").map_err(|e| AdapterError::Other(e.into()))?;
         let contract = Contract::new(token, abi, self.provider.clone().into());
         let method = contract.method::<_, U256>("balanceOf", holder).map_err(|e| AdapterError::Other(e.into()))?;
-        let amount: U256 = method.call().await.map_err(|e| AdapterError::Other(e.into()))?;
+        // EOAs and non-token contracts answer with empty or short return data
+        let amount: U256 = method.call().await.map_err(|e| match e {
+            ContractError::DecodingError(_) | ContractError::AbiError(_) | ContractError::DetokenizationError(_) => {
+                AdapterError::NotAnErc20 { token: req.token().as_str().to_string() }
+            }
+            other => AdapterError::Other(other.into()),
+        })?;
         Ok(amount.to_string())
     }
 
+    pub async fn erc20_total_supply(&self, token: &Address) -> Result<String, AdapterError> {
+        let token_addr = EthAddress::from_str(token.as_str()).map_err(|_| AdapterError::AddrParse(token.as_str().into()))?;
+        let abi = parse_abi_str("[function totalSupply() view returns (uint256)]").map_err(|e| AdapterError::Other(e.into()))?;
+        let contract = Contract::new(token_addr, abi, self.provider.clone().into());
+        let method = contract.method::<_, U256>("totalSupply", ()).map_err(|e| AdapterError::Other(e.into()))?;
+        let supply: U256 = method.call().await.map_err(|e| match e {
+            ContractError::DecodingError(_) | ContractError::AbiError(_) | ContractError::DetokenizationError(_) => {
+                AdapterError::NotAnErc20 { token: token.as_str().to_string() }
+            }
+            other => AdapterError::Other(other.into()),
+        })?;
+        Ok(supply.to_string())
+    }
+
+    /// How much of `owner`'s `token` balance `spender` may move, in base units
+    pub async fn erc20_allowance(&self, token: &Address, owner: &Address, spender: &Address) -> Result<String, AdapterError> {
+        let token_addr = EthAddress::from_str(token.as_str()).map_err(|_| AdapterError::AddrParse(token.as_str().into()))?;
+        let owner_addr = EthAddress::from_str(owner.as_str()).map_err(|_| AdapterError::AddrParse(owner.as_str().into()))?;
+        let spender_addr = EthAddress::from_str(spender.as_str()).map_err(|_| AdapterError::AddrParse(spender.as_str().into()))?;
+        let abi = parse_abi_str("[function allowance(address,address) view returns (uint256)]").map_err(|e| AdapterError::Other(e.into()))?;
+        let contract = Contract::new(token_addr, abi, self.provider.clone().into());
+        let method = contract.method::<_, U256>("allowance", (owner_addr, spender_addr)).map_err(|e| AdapterError::Other(e.into()))?;
+        let allowance: U256 = method.call().await.map_err(|e| match e {
+            ContractError::DecodingError(_) | ContractError::AbiError(_) | ContractError::DetokenizationError(_) => {
+                AdapterError::NotAnErc20 { token: token.as_str().to_string() }
+            }
+            other => AdapterError::Other(other.into()),
+        })?;
+        Ok(allowance.to_string())
+    }
+
+    /// `approve(spender, amount)` from `owner`'s local key; `amount` is in base units.
+    /// Simulates unless `simulate` is false, like `send_eth`.
+    pub async fn erc20_approve(&self, token: &Address, owner: &Address, spender: &Address, amount: &str, simulate: bool) -> Result<TxResult, AdapterError> {
+        let (token_addr, data) = self.approve_call(token, spender, amount)?;
+        self.send_contract_call(owner, token_addr, data, U256::zero(), simulate).await
+    }
+
+    /// Token address and `approve(spender, amount)` calldata; `amount` is in base units
+    fn approve_call(&self, token: &Address, spender: &Address, amount: &str) -> Result<(EthAddress, Bytes), AdapterError> {
+        let token_addr = EthAddress::from_str(token.as_str()).map_err(|_| AdapterError::AddrParse(token.as_str().into()))?;
+        let spender_addr = EthAddress::from_str(spender.as_str()).map_err(|_| AdapterError::AddrParse(spender.as_str().into()))?;
+        let abi = parse_abi_str("[function approve(address,uint256) returns (bool)]").map_err(|e| AdapterError::Other(e.into()))?;
+        let contract = Contract::new(token_addr, abi, self.provider.clone().into());
+        let data = contract
+            .method::<_, bool>("approve", (spender_addr, parse_wei(amount)?))
+            .map_err(|e| AdapterError::Other(e.into()))?
+            .calldata()
+            .expect("approve has calldata");
+        Ok((token_addr, data))
+    }
+
+    /// `transfer(to, amount)` from `req.from()`'s local key, with `amount` scaled by the
+    /// token's decimals. Simulates unless `req.simulate()` is false.
+    pub async fn erc20_transfer(&self, req: &Erc20TransferRequest) -> Result<TxResult, AdapterError> {
+        if self.read_only {
+            return Err(AdapterError::ReadOnly);
+        }
+        let (token_addr, data) = self.transfer_call(req.token(), req.to(), req.amount()).await?;
+        self.send_contract_call(req.from(), token_addr, data, U256::zero(), req.simulate()).await
+    }
+
+    /// Token address and `transfer(to, amount)` calldata, scaling whole-token `amount`
+    /// by the token's decimals
+    async fn transfer_call(&self, token: &Address, to: &Address, amount: &str) -> Result<(EthAddress, Bytes), AdapterError> {
+        let token_addr = EthAddress::from_str(token.as_str()).map_err(|_| AdapterError::AddrParse(token.as_str().into()))?;
+        let to_addr = EthAddress::from_str(to.as_str()).map_err(|_| AdapterError::AddrParse(to.as_str().into()))?;
+        let decimals = self.erc20_metadata(token).await?.decimals;
+        let base_units = parse_wei(&units::parse_units(amount, decimals as u32).map_err(|e| AdapterError::Other(e.into()))?)?;
+        if base_units.is_zero() {
+            return Err(AdapterError::InvalidAmount { amount: amount.to_string() });
+        }
+        let abi = parse_abi_str("[function transfer(address,uint256) returns (bool)]").map_err(|e| AdapterError::Other(e.into()))?;
+        let contract = Contract::new(token_addr, abi, self.provider.clone().into());
+        let data = contract
+            .method::<_, bool>("transfer", (to_addr, base_units))
+            .map_err(|e| AdapterError::Other(e.into()))?
+            .calldata()
+            .expect("transfer has calldata");
+        Ok((token_addr, data))
+    }
+
+    /// Wrap `amount_eth` ETH into WETH with `deposit()` from `from`'s local key.
+    /// Simulates unless `simulate` is false, like `send_eth`.
+    pub async fn wrap_eth(&self, from: &Address, amount_eth: &str, simulate: bool) -> Result<TxResult, AdapterError> {
+        let value = positive_eth(amount_eth)?;
+        let data = weth_calldata("deposit", &[])?;
+        self.send_contract_call(from, self.weth().await?, data, value, simulate).await
+    }
+
+    /// Unwrap `amount_eth` WETH back into ETH with `withdraw(uint256)` from `from`'s
+    /// local key; more than `from` holds reverts. Simulates unless `simulate` is false.
+    pub async fn unwrap_weth(&self, from: &Address, amount_eth: &str, simulate: bool) -> Result<TxResult, AdapterError> {
+        let amount = positive_eth(amount_eth)?;
+        let data = weth_calldata("withdraw", &[Token::Uint(amount)])?;
+        self.send_contract_call(from, self.weth().await?, data, U256::zero(), simulate).await
+    }
+
+    /// The mainnet WETH contract, once it is known to be deployed on this chain; sending
+    /// `deposit()` to an empty address would just burn the ETH
+    async fn weth(&self) -> Result<EthAddress, AdapterError> {
+        let weth = EthAddress::from_str(WETH_MAINNET).expect("WETH address constant is valid");
+        if self.provider.get_code(weth, None).await?.is_empty() {
+            return Err(AdapterError::WethUnavailable { chain_id: self.provider.get_chainid().await?.as_u64() });
+        }
+        Ok(weth)
+    }
+
+    /// Preview `steps` in order on a dev node such as an Anvil fork. Each step really
+    /// executes, from an impersonated sender (no key needed, but `with_impersonation(true)`
+    /// is), so later steps see the
+    /// effects of earlier ones; an `evm_snapshot` taken first is reverted at the end, so
+    /// the node is left as it was. A failing step is reported and the rest still run.
+    /// Broadcasts through this adapter wait for the preview to finish, and a preview waits
+    /// for broadcasts in flight to be confirmed.
+    pub async fn simulate_bundle(&self, steps: &[BundleStep]) -> Result<Vec<BundleStepResult>, AdapterError> {
+        if self.read_only {
+            return Err(AdapterError::ReadOnly);
+        }
+        if !self.allow_impersonation {
+            return Err(AdapterError::ImpersonationDisabled);
+        }
+        let _node = self.node_lock.write().await;
+        let snapshot = self.snapshot().await?;
+        let mut results = Vec::with_capacity(steps.len());
+        for step in steps {
+            let outcome = self.run_bundle_step(step).await;
+            results.push(match outcome {
+                Ok((gas_used, success)) => BundleStepResult { op: step.op().to_string(), success, gas_used, error: None },
+                Err(e) => BundleStepResult { op: step.op().to_string(), success: false, gas_used: None, error: Some(e.to_string()) },
+            });
+        }
+        if !self.revert(&snapshot).await? {
+            return Err(AdapterError::Other(anyhow::anyhow!("evm_revert rejected snapshot {}; the node keeps the bundle's changes", snapshot)));
+        }
+        Ok(results)
+    }
+
+    /// Save the dev node's state with `evm_snapshot`; the returned id is what `revert` takes
+    pub async fn snapshot(&self) -> Result<String, AdapterError> {
+        if self.read_only {
+            return Err(AdapterError::ReadOnly);
+        }
+        let id: U256 = self.provider.request("evm_snapshot", ()).await.map_err(|e| dev_method_error("evm_snapshot", e))?;
+        Ok(format!("{:#x}", id))
+    }
+
+    /// Roll the dev node back to snapshot `id` with `evm_revert`. `false` when the node
+    /// does not know the id; a snapshot can be reverted to only once.
+    pub async fn revert(&self, id: &str) -> Result<bool, AdapterError> {
+        if self.read_only {
+            return Err(AdapterError::ReadOnly);
+        }
+        let id = U256::from_str(id).map_err(|_| AdapterError::Other(anyhow::anyhow!("invalid snapshot id '{}': expected 0x-hex", id)))?;
+        self.provider.request("evm_revert", [id]).await.map_err(|e| dev_method_error("evm_revert", e))
+    }
+
+    /// Send one bundle step as its impersonated sender and wait for the receipt
+    async fn run_bundle_step(&self, step: &BundleStep) -> Result<(Option<u64>, bool), AdapterError> {
+        if !self.allow_impersonation {
+            return Err(AdapterError::ImpersonationDisabled);
+        }
+        let sender = step.sender();
+        let from = EthAddress::from_str(sender.as_str()).map_err(|_| AdapterError::AddrParse(sender.as_str().into()))?;
+        let (to, value, data) = match step {
+            BundleStep::Send { to, amount_eth, .. } => {
+                let to_addr = EthAddress::from_str(to.as_str()).map_err(|_| AdapterError::AddrParse(to.as_str().into()))?;
+                (to_addr, parse_wei(&units::eth_to_wei(amount_eth).map_err(|e| AdapterError::Other(e.into()))?)?, Bytes::default())
+            }
+            BundleStep::Approve { token, spender, amount, .. } => {
+                let (token_addr, data) = self.approve_call(token, spender, amount)?;
+                (token_addr, U256::zero(), data)
+            }
+            BundleStep::Transfer { token, to, amount, .. } => {
+                let (token_addr, data) = self.transfer_call(token, to, amount).await?;
+                (token_addr, U256::zero(), data)
+            }
+            BundleStep::Call { to, data, value_eth, .. } => {
+                let to_addr = EthAddress::from_str(to.as_str()).map_err(|_| AdapterError::AddrParse(to.as_str().into()))?;
+                let data = Bytes::from_str(data).map_err(|e| AdapterError::Other(anyhow::anyhow!("invalid calldata: {}", e)))?;
+                (to_addr, parse_wei(&units::eth_to_wei(value_eth).map_err(|e| AdapterError::Other(e.into()))?)?, data)
+            }
+        };
+        let tx_type = select_tx_type(None, self.supports_eip1559().await?);
+        let mut typed = transfer_tx(from, to, value, tx_type);
+        typed.set_data(data);
+
+        self.provider
+            .request::<_, ()>("anvil_impersonateAccount", [from])
+            .await
+            .map_err(|e| dev_method_error("anvil_impersonateAccount", e))?;
+        let outcome = async {
+            let pending = self.provider.send_transaction(typed, None).await.map_err(revert_error)?;
+            let receipt = self.await_confirmations(pending, 1).await?;
+            Ok(receipt.map_or((None, false), |rcpt| (rcpt.gas_used.map(|g| g.as_u64()), rcpt.status.is_some_and(|s| s.as_u64() == 1))))
+        }
+        .await;
+        // Keep accounts the caller impersonated with `impersonate`
+        if !self.is_impersonated(sender.as_str()) {
+            if let Err(e) = self.provider.request::<_, ()>("anvil_stopImpersonatingAccount", [from]).await {
+                warn!("failed to stop impersonating {:?}: {}", from, e);
+            }
+        }
+        outcome
+    }
+
+    /// Zero-value call of `to` with `data` from a local key: estimated within the gas cap,
+    /// simulated, and broadcast unless `simulate`. Shares the sender's send lock.
+    async fn send_contract_call(&self, from: &Address, to: EthAddress, data: Bytes, value: U256, simulate: bool) -> Result<TxResult, AdapterError> {
+        if self.read_only {
+            return Err(AdapterError::ReadOnly);
+        }
+        let from_addr = EthAddress::from_str(from.as_str()).map_err(|_| AdapterError::AddrParse(from.as_str().into()))?;
+        if let Some(expected) = self.expected_chain_id {
+            let chain_id = self.provider.get_chainid().await?.as_u64();
+            if chain_id != expected {
+                return Err(AdapterError::ChainIdMismatch { got: chain_id, expected });
+            }
+        }
+        let send_lock = self.send_lock(from.as_str());
+        let send_guard = if simulate { None } else { Some(send_lock.lock().await) };
+        let tx_type = select_tx_type(None, self.supports_eip1559().await?);
+        let mut typed = transfer_tx(from_addr, to, value, tx_type);
+        typed.set_data(data);
+        // Estimation fails opaquely when the value alone is more than the sender has
+        if !value.is_zero() && (!simulate || self.simulate_funds_check) {
+            ensure_funds(self.provider.get_balance(from_addr, None).await?, value)?;
+        }
+        let est = self.provider.estimate_gas(&typed, None).await.map_err(revert_error)?;
+        let gas = buffered_gas_within_cap(est, self.gas_buffer_bps, self.gas_cap)?;
+        typed.set_gas(gas);
+        let (fee_per_gas, _) = self.fill_fees(&mut typed).await?;
+        if !simulate || self.simulate_funds_check {
+            ensure_funds(self.provider.get_balance(from_addr, None).await?, value + gas * fee_per_gas)?;
+        }
+        self.provider.call(&typed, None).await.map_err(revert_error)?;
+        if simulate {
+            return Ok(TxResult::new(String::new(), Some(est.as_u64()), None));
+        }
+        let chain_id = self.provider.get_chainid().await?.as_u64();
+        let _node = self.node_lock.read().await;
+        let pending = self.broadcast(from.as_str(), from_addr, chain_id, typed).await?;
+        drop(send_guard);
+        let tx_hash = *pending;
+        match self.await_confirmations(pending, default_confirmations(chain_id)).await? {
+            Some(rcpt) => Ok(receipt_result(&rcpt)),
+            None => Ok(TxResult::new(format!("0x{:x}", tx_hash), Some(est.as_u64()), None)),
+        }
+    }
+
+    /// `eth_call` with raw calldata; returns the raw 0x-hex return data.
+    pub async fn eth_call(&self, req: &ContractCallRequest) -> Result<String, AdapterError> {
+        let to = EthAddress::from_str(req.to().as_str()).map_err(|_| AdapterError::AddrParse(req.to().as_str().into()))?;
+        let data = Bytes::from_str(req.data()).map_err(|e| AdapterError::Other(anyhow::anyhow!("invalid calldata: {}", e)))?;
+        let tx: TypedTransaction = TransactionRequest::new().to(to).data(data).into();
+        match self.provider.call(&tx, None).await {
+            Ok(out) => Ok(format!("{}", out)),
+            Err(e) => Err(match e.as_error_response().and_then(|rpc| rpc.as_revert_data()) {
+                Some(data) => {
+                    // Custom errors only decode with the contract's ABI, if one has been cached
+                    let abi = self.cached_abi(req.to().as_str()).and_then(|raw| serde_json::from_str(&raw).ok());
+                    AdapterError::Reverted { reason: decode_revert_reason(&data, abi.as_ref()) }
+                }
+                None => e.into(),
+            }),
+        }
+    }
+
+    /// Forward an arbitrary JSON-RPC call and return its raw `result`.
+    pub async fn raw_request(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value, AdapterError> {
+        Ok(self.provider.request(method, params).await?)
+    }
+
+    pub async fn erc20_metadata(&self, token: &Address) -> Result<Erc20Metadata, AdapterError> {
+        let key = normalize(token.as_str());
+        if let Some(cached) = self.erc20_metadata.lock().unwrap().get(&key) {
+            return Ok(cached.clone());
+        }
+        let token_addr = EthAddress::from_str(token.as_str()).map_err(|_| AdapterError::AddrParse(token.as_str().into()))?;
+        let abi = parse_abi_str("[function name() view returns (string)\nfunction symbol() view returns (string)\nfunction decimals() view returns (uint8)]")
+            .map_err(|e| AdapterError::Other(e.into()))?;
+        let contract = Contract::new(token_addr, abi, self.provider.clone().into());
+        let name_call = contract.method::<_, String>("name", ()).map_err(|e| AdapterError::Other(e.into()))?;
+        let symbol_call = contract.method::<_, String>("symbol", ()).map_err(|e| AdapterError::Other(e.into()))?;
+        let decimals_call = contract.method::<_, u8>("decimals", ()).map_err(|e| AdapterError::Other(e.into()))?;
+        let (name, symbol, decimals) = tokio::join!(name_call.call(), symbol_call.call(), decimals_call.call());
+        // `decimals()` is the one field a token must answer; EOAs and other contracts don't
+        let decimals = decimals.map_err(|e| match e {
+            ContractError::DecodingError(_) | ContractError::AbiError(_) | ContractError::DetokenizationError(_) => {
+                AdapterError::NotAnErc20 { token: token.as_str().to_string() }
+            }
+            other => AdapterError::Other(other.into()),
+        })?;
+        let metadata = Erc20Metadata { name: name.ok(), symbol: symbol.ok(), decimals };
+        self.erc20_metadata.lock().unwrap().insert(key, metadata.clone());
+        Ok(metadata)
+    }
+
+    /// Best-effort seconds until a tx paying the node's suggested EIP-1559 max fee is
+    /// included. `None` on pre-London chains or when the data can't be fetched.
+    async fn estimate_inclusion_seconds(&self) -> Option<u64> {
+        let result: Result<Option<u64>, AdapterError> = async {
+            let Some(latest) = self.provider.get_block(BlockNumber::Latest).await? else { return Ok(None) };
+            let (Some(number), Some(base_fee)) = (latest.number, latest.base_fee_per_gas) else { return Ok(None) };
+            let span = BLOCK_TIME_SAMPLE.min(number.as_u64());
+            if span == 0 {
+                return Ok(None);
+            }
+            let Some(earlier) = self.provider.get_block(number.as_u64() - span).await? else { return Ok(None) };
+            let block_time = latest.timestamp.saturating_sub(earlier.timestamp).as_u64() as f64 / span as f64;
+            let (max_fee, _priority) = self.provider.estimate_eip1559_fees(None).await?;
+            Ok(inclusion_seconds(base_fee, max_fee, block_time))
+        }
+        .await;
+        result.unwrap_or_else(|e| {
+            warn!("could not estimate inclusion time: {}", e);
+            None
+        })
+    }
+
+    /// Whether the latest block carries a base fee (London or later)
+    async fn supports_eip1559(&self) -> Result<bool, AdapterError> {
+        let latest = self.provider.get_block(BlockNumber::Latest).await?;
+        Ok(latest.is_some_and(|block| block.base_fee_per_gas.is_some()))
+    }
+
+    /// Base fee of the latest block plus a priority fee from `eth_maxPriorityFeePerGas`,
+    /// or the median fee-history percentile on nodes without that method.
+    pub async fn suggest_fees(&self) -> Result<FeeSuggestion, AdapterError> {
+        let (base_fee, priority_fee, max_fee) = self.eip1559_fees().await?;
+        Ok(FeeSuggestion { base_fee: base_fee.to_string(), priority_fee: priority_fee.to_string(), max_fee: max_fee.to_string() })
+    }
+
+    async fn eip1559_fees(&self) -> Result<(U256, U256, U256), AdapterError> {
+        let latest = self.provider.get_block(BlockNumber::Latest).await?;
+        let base_fee = latest.and_then(|block| block.base_fee_per_gas).ok_or(AdapterError::NoBaseFee)?;
+        let priority_fee = match self.provider.request::<_, U256>("eth_maxPriorityFeePerGas", ()).await {
+            Ok(fee) => fee,
+            Err(e) => {
+                warn!("eth_maxPriorityFeePerGas unavailable ({}); using fee history", e);
+                let history = self.provider.fee_history(FEE_HISTORY_BLOCKS, BlockNumber::Latest, &[FEE_HISTORY_PERCENTILE]).await?;
+                let mut rewards: Vec<U256> = history.reward.iter().filter_map(|r| r.first().copied()).collect();
+                rewards.sort();
+                rewards.get(rewards.len() / 2).copied().unwrap_or_default()
+            }
+        };
+        Ok((base_fee, priority_fee, max_fee(base_fee, priority_fee)))
+    }
+
+    /// Price `tx` at the node's current fees and return the most it can pay per gas and
+    /// what it is expected to pay at the current base fee
+    async fn fill_fees(&self, tx: &mut TypedTransaction) -> Result<(U256, U256), AdapterError> {
+        match tx {
+            TypedTransaction::Eip1559(inner) => {
+                let (base_fee, priority_fee, max_fee) = self.eip1559_fees().await?;
+                inner.max_fee_per_gas = Some(max_fee);
+                inner.max_priority_fee_per_gas = Some(priority_fee);
+                Ok((max_fee, base_fee + priority_fee))
+            }
+            _ => {
+                let gas_price = self.provider.get_gas_price().await?;
+                tx.set_gas_price(gas_price);
+                Ok((gas_price, gas_price))
+            }
+        }
+    }
+
+    /// Sender pays `value + gas_cost`, recipient gains `value`; a self-send only pays gas.
+    /// "Before" balances are read at `block` (latest when `None`).
+    async fn project_balance_changes(
+        &self,
+        from: EthAddress,
+        to: EthAddress,
+        value: U256,
+        gas_cost: U256,
+        from_balance: Option<U256>,
+        block: Option<BlockId>,
+    ) -> Result<Vec<BalanceChange>, AdapterError> {
+        let from_before = match from_balance {
+            Some(balance) => balance,
+            None => self.provider.get_balance(from, block).await?,
+        };
+        if from == to {
+            return Ok(vec![balance_change(from, from_before, gas_cost, false)]);
+        }
+        let to_before = self.provider.get_balance(to, block).await?;
+        Ok(vec![balance_change(from, from_before, value + gas_cost, false), balance_change(to, to_before, value, true)])
+    }
+
+    /// Block tag for the simulation calls: `fork_block` when set (it must not be ahead of
+    /// the head), otherwise the node's default of latest
+    async fn simulation_block(&self, fork_block: Option<u64>) -> Result<Option<BlockId>, AdapterError> {
+        let Some(fork_block) = fork_block else { return Ok(None) };
+        let head = self.provider.get_block_number().await?.as_u64();
+        if fork_block > head {
+            return Err(AdapterError::ForkBlockAhead { fork_block, head });
+        }
+        Ok(Some(BlockId::Number(fork_block.into())))
+    }
+
+    /// Raw `eth_estimateGas` for the transfer `req` describes, without the buffer or the
+    /// gas cap, for cost previews. Nothing is signed, so read-only adapters allow it.
+    pub async fn estimate_send_gas(&self, req: &SendRequest) -> Result<u64, AdapterError> {
+        let (from_addr, to_addr, value) = parse_transfer(req)?;
+        let block = self.simulation_block(req.fork_block()).await?;
+        let has_base_fee = req.tx_type().is_none() && self.supports_eip1559().await?;
+        let typed = transfer_tx(from_addr, to_addr, value, select_tx_type(req.tx_type(), has_base_fee));
+        Ok(self.provider.estimate_gas(&typed, block).await?.as_u64())
+    }
+
     pub async fn send_eth(&self, req: &SendRequest) -> Result<TxResult, AdapterError> {
+        if !req.simulate() {
+            reject_fork_block(req)?;
+        }
+        // Broadcasts from one account run one at a time from the balance check to the
+        // broadcast; simulations use no nonce and skip the lock
+        let send_lock = self.send_lock(req.from().as_str());
+        let send_guard = if req.simulate() { None } else { Some(send_lock.lock().await) };
+        let filled = self.fill_transfer(req, !req.simulate() || self.simulate_funds_check).await?;
+        if req.simulate() {
+            return self.simulated_transfer(&filled).await;
+        }
+        self.broadcast_transfer(req.from(), filled.from_addr, filled.typed, filled.est.as_u64(), req.confirmations(), send_guard).await
+    }
+
+    /// Simulate the transfer `req` describes (always checking funds) and return the result
+    /// with the filled transaction, for `send_prepared` to broadcast unchanged later.
+    pub async fn prepare_send(&self, req: &SendRequest) -> Result<(TxResult, PreparedTransfer), AdapterError> {
+        // The prepared transaction is broadcast later, so it must be built at the head
+        reject_fork_block(req)?;
+        let filled = self.fill_transfer(req, true).await?;
+        let result = self.simulated_transfer(&filled).await?;
+        let prepared = PreparedTransfer {
+            from: req.from().clone(),
+            tx: filled.typed,
+            estimated_gas: filled.est.as_u64(),
+            confirmations: req.confirmations(),
+        };
+        Ok((result, prepared))
+    }
+
+    /// Broadcast a `prepare_send` transaction as prepared. Funds are checked again, since
+    /// the balance may have moved since the preview.
+    pub async fn send_prepared(&self, prepared: &PreparedTransfer) -> Result<TxResult, AdapterError> {
+        if self.read_only {
+            return Err(AdapterError::ReadOnly);
+        }
+        let from_addr = EthAddress::from_str(prepared.from.as_str()).map_err(|_| AdapterError::AddrParse(prepared.from.as_str().into()))?;
+        let send_lock = self.send_lock(prepared.from.as_str());
+        let send_guard = send_lock.lock().await;
+        let tx = &prepared.tx;
+        let value = tx.value().copied().unwrap_or_default();
+        let fee_per_gas = match tx {
+            TypedTransaction::Eip1559(inner) => inner.max_fee_per_gas.unwrap_or_default(),
+            _ => tx.gas_price().unwrap_or_default(),
+        };
+        let gas = tx.gas().copied().unwrap_or_default();
+        ensure_funds(self.provider.get_balance(from_addr, None).await?, value + gas * fee_per_gas)?;
+        self.broadcast_transfer(&prepared.from, from_addr, tx.clone(), prepared.estimated_gas, prepared.confirmations, Some(send_guard)).await
+    }
+
+    /// Validate, estimate, price and simulate the transfer `req` describes. With
+    /// `funds_check`, underfunded sends fail here with `InsufficientFunds`.
+    async fn fill_transfer(&self, req: &SendRequest, funds_check: bool) -> Result<FilledTransfer, AdapterError> {
+        if self.read_only {
+            return Err(AdapterError::ReadOnly);
+        }
+        let (from_addr, to_addr, value) = parse_transfer(req)?;
+        if value.is_zero() {
+            return Err(AdapterError::InvalidAmount { amount: req.amount_eth().to_string() });
+        }
+        if from_addr == to_addr {
+            let address = ethers_core::utils::to_checksum(&from_addr, None);
+            if self.reject_self_transfers {
+                return Err(AdapterError::SelfTransfer { address });
+            }
+            warn!("self-transfer from {} to itself; only gas will be spent", address);
+        }
         if let Some(expected) = self.expected_chain_id {
             let chain_id = self.provider.get_chainid().await?.as_u64();
             if chain_id != expected {
                 return Err(AdapterError::ChainIdMismatch { got: chain_id, expected });
             }
         }
+        // Code lookup, estimation and the simulated call run "as of" fork_block
+        let block = self.simulation_block(req.fork_block()).await?;
+        // Nodes reject underfunded sends with opaque errors (often already at estimation),
+        // so compare the balance with the value up front and with value + gas once estimated
+        let balance = if funds_check { Some(self.provider.get_balance(from_addr, block).await?) } else { None };
+        if let Some(have) = balance {
+            ensure_funds(have, value)?;
+        }
+        // A forced type skips the base-fee lookup
+        let has_base_fee = req.tx_type().is_none() && self.supports_eip1559().await?;
+        let tx_type = select_tx_type(req.tx_type(), has_base_fee);
+        let mut typed = transfer_tx(from_addr, to_addr, value, tx_type);
+        // A contract without a payable receive/fallback reverts on plain transfers; gas
+        // estimation would fail with an opaque error, so probe with a call first.
+        let recipient_is_contract = !self.provider.get_code(to_addr, block).await?.is_empty();
+        if recipient_is_contract {
+            if let Err(e) = self.provider.call(&typed, block).await {
+                if is_revert(&e) {
+                    return Err(AdapterError::RecipientRejectsEth { to: req.to().as_str().to_string() });
+                }
+                return Err(e.into());
+            }
+        }
+        let est = self.provider.estimate_gas(&typed, block).await?;
+        let gas = buffered_gas_within_cap(est, self.gas_buffer_bps, self.gas_cap)?;
+        typed.set_gas(gas);
+        let (fee_per_gas, expected_fee_per_gas) = self.fill_fees(&mut typed).await?;
+        if let Some(have) = balance {
+            ensure_funds(have, value + gas * fee_per_gas)?;
+        }
+        let _sim = self.provider.call(&typed, block).await?;
+        Ok(FilledTransfer { from_addr, to_addr, value, typed, est, expected_fee_per_gas, balance, block })
+    }
+
+    /// The simulation result for a filled transfer: estimate, inclusion time, balance changes
+    async fn simulated_transfer(&self, filled: &FilledTransfer) -> Result<TxResult, AdapterError> {
+        let estimated_seconds = self.estimate_inclusion_seconds().await;
+        let gas_cost = filled.est * filled.expected_fee_per_gas;
+        let balance_changes = self.project_balance_changes(filled.from_addr, filled.to_addr, filled.value, gas_cost, filled.balance, filled.block).await?;
+        Ok(TxResult::new(String::new(), Some(filled.est.as_u64()), None)
+            .with_estimated_seconds(estimated_seconds)
+            .with_balance_changes(balance_changes))
+    }
+
+    /// Broadcast a filled transfer and wait for its receipt. `send_guard` is released as
+    /// soon as the node has the tx, since it then counts in the pending nonce.
+    async fn broadcast_transfer(
+        &self,
+        from: &Address,
+        from_addr: EthAddress,
+        typed: TypedTransaction,
+        estimated_gas: u64,
+        confirmations: Option<u64>,
+        send_guard: Option<tokio::sync::MutexGuard<'_, ()>>,
+    ) -> Result<TxResult, AdapterError> {
+        let chain_id = self.provider.get_chainid().await?.as_u64();
+        // Held until confirmed: a bundle preview reverting the node must not drop the tx
+        let _node = self.node_lock.read().await;
+        let pending = self.broadcast(from.as_str(), from_addr, chain_id, typed).await?;
+        drop(send_guard);
+        let tx_hash = *pending;
+        let confirmations = confirmations.map(|c| c as usize).unwrap_or_else(|| default_confirmations(chain_id));
+        let receipt = self.await_confirmations(pending, confirmations).await?;
+        match receipt {
+            Some(rcpt) => Ok(receipt_result(&rcpt)),
+            None => Ok(TxResult::new(format!("0x{:x}", tx_hash), Some(estimated_gas), None)),
+        }
+    }
+
+    /// Deploy `req.bytecode()` + `req.constructor_args()` from a local key. The creation is
+    /// estimated and simulated first; simulations stop there and report the address the
+    /// contract would get (from sender and pending nonce).
+    pub async fn deploy_contract(&self, req: &DeployRequest) -> Result<DeployResult, AdapterError> {
+        if self.read_only {
+            return Err(AdapterError::ReadOnly);
+        }
         let from_addr = EthAddress::from_str(req.from().as_str()).map_err(|_| AdapterError::AddrParse(req.from().as_str().into()))?;
-        let to_addr = EthAddress::from_str(req.to().as_str()).map_err(|_| AdapterError::AddrParse(req.to().as_str().into()))?;
-        let value = parse_ether(req.amount_eth()).map_err(|e| AdapterError::Other(e.into()))?;
-        let base = TransactionRequest::new().from(from_addr).to(to_addr).value(value);
-        let mut typed: TypedTransaction = base.into();
-        let est = self.provider.estimate_gas(&typed, None).await?;
-        if est.as_u64() > self.gas_cap {
-            return Err(AdapterError::GasCapExceeded { estimated: est.as_u64(), cap: self.gas_cap });
-        }
-        typed.set_gas(est);
-        let _sim = self.provider.call(&typed, None).await?;
+        let data = creation_data(req.bytecode(), req.constructor_args())?;
+        let value = parse_wei(&units::eth_to_wei(req.value_eth()).map_err(|e| AdapterError::Other(e.into()))?)?;
+        if let Some(expected) = self.expected_chain_id {
+            let chain_id = self.provider.get_chainid().await?.as_u64();
+            if chain_id != expected {
+                return Err(AdapterError::ChainIdMismatch { got: chain_id, expected });
+            }
+        }
+        // The address depends on the nonce, so deployments share the sender's send lock
+        let send_lock = self.send_lock(req.from().as_str());
+        let send_guard = if req.simulate() { None } else { Some(send_lock.lock().await) };
+        let nonce = self.provider.get_transaction_count(from_addr, Some(BlockNumber::Pending.into())).await?;
+        let address = ethers_core::utils::get_contract_address(from_addr, nonce);
+        let tx_type = select_tx_type(None, self.supports_eip1559().await?);
+        let mut typed = creation_tx(from_addr, data, value, tx_type);
+        typed.set_nonce(nonce);
+        let est = self.provider.estimate_gas(&typed, None).await.map_err(revert_error)?;
+        let gas = buffered_gas_within_cap(est, self.gas_buffer_bps, self.gas_cap)?;
+        typed.set_gas(gas);
+        let (fee_per_gas, _) = self.fill_fees(&mut typed).await?;
+        if !req.simulate() || self.simulate_funds_check {
+            ensure_funds(self.provider.get_balance(from_addr, None).await?, value + gas * fee_per_gas)?;
+        }
+        self.provider.call(&typed, None).await.map_err(revert_error)?;
         if req.simulate() {
-            return Ok(TxResult::new(String::new(), Some(est.as_u64()), None));
+            return Ok(DeployResult {
+                mode: SendMode::Simulated,
+                address: ethers_core::utils::to_checksum(&address, None),
+                tx_hash: None,
+                estimated_gas: Some(est.as_u64()),
+                gas_used: None,
+                success: true,
+            });
         }
-        let key = normalize(req.from().as_str());
-        let wallet = self.known_wallets.get(&key).cloned().ok_or_else(|| AdapterError::MissingLocalKey(req.from().as_str().to_string()))?;
         let chain_id = self.provider.get_chainid().await?.as_u64();
-        let wallet = wallet.with_chain_id(chain_id);
-        let client = SignerMiddleware::new(self.provider.clone(), wallet);
-        let pending = client
-            .send_transaction(typed, None)
-            .await
-            .map_err(|e| AdapterError::Other(e.into()))?;
+        let _node = self.node_lock.read().await;
+        let pending = self.broadcast(req.from().as_str(), from_addr, chain_id, typed).await?;
+        drop(send_guard);
+        let tx_hash = format!("0x{:x}", *pending);
+        let confirmations = req.confirmations().map(|c| c as usize).unwrap_or_else(|| default_confirmations(chain_id));
+        let receipt = self.await_confirmations(pending, confirmations).await?;
+        let (address, gas_used, success) = match receipt {
+            Some(rcpt) => (rcpt.contract_address.unwrap_or(address), rcpt.gas_used.map(|g| g.as_u64()), rcpt.status.is_some_and(|s| s.as_u64() == 1)),
+            None => (address, None, false),
+        };
+        Ok(DeployResult {
+            mode: SendMode::Broadcast,
+            address: ethers_core::utils::to_checksum(&address, None),
+            tx_hash: Some(tx_hash),
+            estimated_gas: None,
+            gas_used,
+            success,
+        })
+    }
+
+    /// Broadcast a transaction the caller already signed (0x-hex RLP) and wait for its
+    /// receipt at the chain's default confirmations. No local key is involved.
+    pub async fn send_raw(&self, raw_tx_hex: &str) -> Result<TxResult, AdapterError> {
+        if self.read_only {
+            return Err(AdapterError::ReadOnly);
+        }
+        let (raw, signer) = decode_raw_transaction(raw_tx_hex)?;
+        let chain_id = self.chain_id().await?;
+        tracing::info!("broadcasting pre-signed transaction from {:?}", signer);
+        let _node = self.node_lock.read().await;
+        let pending = self.provider.send_raw_transaction(raw).await?;
         let tx_hash = *pending;
-        let receipt = pending.await?;
-        if let Some(rcpt) = receipt {
-            let status = rcpt.status.map(|s| s.as_u64() == 1);
-            let gas_used = rcpt.gas_used.map(|g| g.as_u64());
-            Ok(TxResult::new(format!("0x{:x}", rcpt.transaction_hash), gas_used, status))
-        } else {
-            Ok(TxResult::new(format!("0x{:x}", tx_hash), Some(est.as_u64()), None))
+        match self.await_confirmations(pending, default_confirmations(chain_id)).await? {
+            Some(rcpt) => Ok(receipt_result(&rcpt)),
+            None => Ok(TxResult::new(format!("0x{:x}", tx_hash), None, None)),
         }
     }
 }
 
+/// Check that `raw_tx_hex` is a signed transaction (legacy, EIP-2930 or EIP-1559) and
+/// return its bytes and recovered signer.
+pub fn decode_raw_transaction(raw_tx_hex: &str) -> Result<(Bytes, EthAddress), AdapterError> {
+    let invalid = |reason: String| AdapterError::InvalidRawTx { reason };
+    let raw = Bytes::from_str(raw_tx_hex.trim()).map_err(|e| invalid(format!("not hex: {}", e)))?;
+    if raw.is_empty() {
+        return Err(invalid("empty".to_string()));
+    }
+    let (tx, signature) = TypedTransaction::decode_signed(&ethers_core::utils::rlp::Rlp::new(&raw))
+        .map_err(|e| invalid(format!("not a signed transaction: {}", e)))?;
+    let signer = signature.recover(tx.sighash()).map_err(|e| invalid(format!("bad signature: {}", e)))?;
+    Ok((raw, signer))
+}
+
+fn receipt_result(rcpt: &TransactionReceipt) -> TxResult {
+    let status = rcpt.status.map(|s| s.as_u64() == 1);
+    // Receipts come from the node; out-of-range values are dropped rather than trusted
+    let gas_used = rcpt.gas_used.and_then(|g| u64::try_from(g).ok());
+    let effective_gas_price = rcpt.effective_gas_price.and_then(|p| u64::try_from(p).ok());
+    TxResult::new(format!("0x{:x}", rcpt.transaction_hash), gas_used, status).with_effective_gas_price(effective_gas_price)
+}
+
 #[allow(dead_code)]
 pub fn placeholder_adapter() {}
 
 fn normalize(addr: &str) -> String { validation::normalize(addr) }
 
+/// Blocks until the base fee can fall to `max_fee` (it drops at most 12.5% per block),
+/// plus the inclusion block itself, times the average block time.
+fn inclusion_seconds(base_fee: U256, max_fee: U256, block_time_secs: f64) -> Option<u64> {
+    if max_fee.is_zero() || !block_time_secs.is_finite() || block_time_secs < 0.0 {
+        return None;
+    }
+    let blocks = if max_fee >= base_fee {
+        1.0
+    } else {
+        let ratio = max_fee.as_u128() as f64 / base_fee.as_u128() as f64;
+        1.0 + (ratio.ln() / 0.875f64.ln()).ceil()
+    };
+    Some((blocks * block_time_secs).round() as u64)
+}
+
+/// Failures where the node never answered: connection errors, timeouts, and non-JSON
+/// bodies such as a gateway's 502 page. A JSON-RPC error object is the node's answer.
+fn is_transient(e: &ProviderError) -> bool {
+    match e {
+        ProviderError::HTTPError(_) => true,
+        ProviderError::JsonRpcClientError(inner) => inner.as_error_response().is_none(),
+        _ => false,
+    }
+}
+
+/// `fork_block` pins simulations only; a broadcast built from an old block would
+/// carry a stale fee and funds check
+fn reject_fork_block(req: &SendRequest) -> Result<(), AdapterError> {
+    match req.fork_block() {
+        Some(fork_block) => Err(AdapterError::ForkBlockOnBroadcast { fork_block }),
+        None => Ok(()),
+    }
+}
+
+/// Sender, recipient and value in wei of a send request
+fn parse_transfer(req: &SendRequest) -> Result<(EthAddress, EthAddress, U256), AdapterError> {
+    let from_addr = EthAddress::from_str(req.from().as_str()).map_err(|_| AdapterError::AddrParse(req.from().as_str().into()))?;
+    let to_addr = EthAddress::from_str(req.to().as_str()).map_err(|_| AdapterError::AddrParse(req.to().as_str().into()))?;
+    let value = parse_wei(&units::eth_to_wei(req.amount_eth()).map_err(|e| AdapterError::Other(e.into()))?)?;
+    Ok((from_addr, to_addr, value))
+}
+
+fn balance_change(address: EthAddress, before: U256, amount: U256, credit: bool) -> BalanceChange {
+    let (after, sign) = if credit { (before.saturating_add(amount), '+') } else { (before.saturating_sub(amount), '-') };
+    BalanceChange {
+        address: ethers_core::utils::to_checksum(&address, None),
+        before: before.to_string(),
+        after: after.to_string(),
+        delta: format!("{}{}", sign, amount),
+    }
+}
+
+fn max_fee(base_fee: U256, priority_fee: U256) -> U256 {
+    base_fee * 2 + priority_fee
+}
+
+fn decode_transfer(log: &Log) -> Result<TransferLog, AdapterError> {
+    let [_, from, to] = log.topics[..] else {
+        return Err(AdapterError::Other(anyhow::anyhow!("Transfer log without indexed from/to topics")));
+    };
+    let checksummed = |topic: H256| ethers_core::utils::to_checksum(&EthAddress::from(topic), None);
+    Ok(TransferLog {
+        from: checksummed(from),
+        to: checksummed(to),
+        value: U256::from_big_endian(&log.data).to_string(),
+        tx_hash: log.transaction_hash.map(|h| format!("{:?}", h)).unwrap_or_default(),
+        block: log.block_number.map(|b| b.as_u64()).unwrap_or_default(),
+    })
+}
+
+/// A forced type always wins; otherwise EIP-1559 exactly when the chain has a base fee.
+fn select_tx_type(requested: Option<TxType>, has_base_fee: bool) -> TxType {
+    requested.unwrap_or(if has_base_fee { TxType::Eip1559 } else { TxType::Legacy })
+}
+
+/// Plain ETH transfer in the requested envelope; fees and gas are filled in later.
+fn transfer_tx(from: EthAddress, to: EthAddress, value: U256, tx_type: TxType) -> TypedTransaction {
+    match tx_type {
+        TxType::Legacy => TransactionRequest::new().from(from).to(to).value(value).into(),
+        TxType::Eip1559 => Eip1559TransactionRequest::new().from(from).to(to).value(value).into(),
+    }
+}
+
+fn creation_tx(from: EthAddress, data: Bytes, value: U256, tx_type: TxType) -> TypedTransaction {
+    match tx_type {
+        TxType::Legacy => TransactionRequest::new().from(from).data(data).value(value).into(),
+        TxType::Eip1559 => Eip1559TransactionRequest::new().from(from).data(data).value(value).into(),
+    }
+}
+
+/// Init code followed by the ABI-encoded constructor arguments
+pub fn creation_data(bytecode: &str, constructor_args: &str) -> Result<Bytes, AdapterError> {
+    let invalid = |what: &str, e: &dyn std::fmt::Display| AdapterError::InvalidBytecode { reason: format!("{} is not hex: {}", what, e) };
+    let code = Bytes::from_str(bytecode).map_err(|e| invalid("bytecode", &e))?;
+    if code.is_empty() {
+        return Err(AdapterError::InvalidBytecode { reason: "bytecode is empty".to_string() });
+    }
+    let args = if constructor_args.is_empty() { Bytes::default() } else { Bytes::from_str(constructor_args).map_err(|e| invalid("constructor_args", &e))? };
+    Ok([code.as_ref(), args.as_ref()].concat().into())
+}
+
+/// A reverting constructor or contract call surfaces as `Reverted` with the decoded reason
+fn revert_error(e: ProviderError) -> AdapterError {
+    match e.as_error_response().and_then(|rpc| rpc.as_revert_data()) {
+        Some(data) => AdapterError::Reverted { reason: decode_revert_reason(&data, None) },
+        None => e.into(),
+    }
+}
+
+/// Nodes without Anvil's dev methods answer "method not found" (or a provider's
+/// "unsupported method"); other errors pass through unchanged
+fn dev_method_error(method: &'static str, e: ProviderError) -> AdapterError {
+    match e.as_error_response() {
+        Some(rpc) if rpc.code == -32601 || ["not found", "does not exist", "not available", "unsupported", "not supported"].iter().any(|m| rpc.message.to_lowercase().contains(m)) => {
+            AdapterError::DevMethodUnsupported { method, reason: rpc.message.clone() }
+        }
+        _ => e.into(),
+    }
+}
+
+fn ensure_funds(have: U256, need: U256) -> Result<(), AdapterError> {
+    if have < need {
+        return Err(AdapterError::InsufficientFunds { have: have.to_string(), need: need.to_string() });
+    }
+    Ok(())
+}
+
+/// Confirmations to wait for on `chain_id` when the request leaves it unset.
+pub fn default_confirmations(chain_id: u64) -> usize {
+    DEFAULT_CONFIRMATIONS
+        .iter()
+        .find(|(id, _)| *id == chain_id)
+        .map(|(_, confirmations)| *confirmations)
+        .unwrap_or(FALLBACK_CONFIRMATIONS)
+}
+
+/// Pad `estimate` by `bps` basis points, then enforce the cap on the padded value,
+/// since that is the gas limit actually sent.
+fn buffered_gas_within_cap(estimate: U256, bps: u64, cap: u64) -> Result<U256, AdapterError> {
+    let gas = estimate * U256::from(10_000 + bps) / U256::from(10_000u64);
+    if gas > U256::from(cap) {
+        return Err(AdapterError::GasCapExceeded { estimated: gas.low_u64(), cap });
+    }
+    Ok(gas)
+}
+
+/// Broadcast `tx`, retrying exactly once with a refetched pending nonce when the node
+/// answers "nonce too low". Any other failure is returned as-is: an ambiguous error may
+/// mean the transaction was accepted, and resending it could double-spend.
+async fn send_with_nonce_retry<T, E, S, SF, N, NF>(mut tx: TypedTransaction, mut send: S, pending_nonce: N) -> Result<T, AdapterError>
+where
+    S: FnMut(TypedTransaction) -> SF,
+    SF: std::future::Future<Output = Result<T, E>>,
+    E: std::error::Error + Send + Sync + 'static,
+    N: FnOnce() -> NF,
+    NF: std::future::Future<Output = Result<U256, AdapterError>>,
+{
+    match send(tx.clone()).await {
+        Ok(sent) => Ok(sent),
+        Err(e) if is_nonce_too_low(&e) => {
+            let nonce = pending_nonce().await?;
+            warn!("broadcast rejected with nonce too low; retrying once with pending nonce {}", nonce);
+            tx.set_nonce(nonce);
+            send(tx).await.map_err(|e| AdapterError::Other(e.into()))
+        }
+        Err(e) => Err(AdapterError::Other(e.into())),
+    }
+}
+
+fn is_revert(err: &ProviderError) -> bool {
+    err.as_error_response().map(|rpc| rpc.code == 3 || rpc.message.to_lowercase().contains("revert")).unwrap_or(false)
+}
+
+fn is_nonce_too_low(err: &dyn std::error::Error) -> bool {
+    err.to_string().to_lowercase().contains("nonce too low")
+}
+
+/// A decimal ETH amount in wei; zero is an `InvalidAmount`
+fn positive_eth(amount_eth: &str) -> Result<U256, AdapterError> {
+    let wei = parse_wei(&units::eth_to_wei(amount_eth).map_err(|e| AdapterError::Other(e.into()))?)?;
+    if wei.is_zero() {
+        return Err(AdapterError::InvalidAmount { amount: amount_eth.to_string() });
+    }
+    Ok(wei)
+}
+
+/// Calldata for one of WETH's `deposit()` / `withdraw(uint256)`
+fn weth_calldata(function: &str, args: &[Token]) -> Result<Bytes, AdapterError> {
+    let abi = ethers_core::abi::parse_abi(&["function deposit() payable", "function withdraw(uint256)"]).map_err(|e| AdapterError::Other(e.into()))?;
+    Ok(abi.function(function)?.encode_input(args)?.into())
+}
+
+fn parse_wei(wei: &str) -> Result<U256, AdapterError> {
+    U256::from_dec_str(wei).map_err(|e| AdapterError::Other(anyhow::anyhow!("invalid wei amount {}: {}", wei, e)))
+}
+
+/// Pair each account with its key, skipping (and warning about) keys that fail to parse
+/// or derive a different address, so a bad entry can never sign for the wrong account.
+fn load_known_wallets(accounts: &[EthAddress], keys: &[&str]) -> HashMap<String, LocalWallet> {
+    if accounts.len() != keys.len() {
+        warn!("configured {} accounts but {} private keys; unmatched entries are ignored", accounts.len(), keys.len());
+    }
+    let mut known_wallets = HashMap::new();
+    for (index, (account, key)) in accounts.iter().zip(keys.iter()).enumerate() {
+        let wallet = match LocalWallet::from_str(key) {
+            Ok(wallet) => wallet,
+            Err(e) => {
+                warn!("skipping invalid private key at index {}: {}", index, e);
+                continue;
+            }
+        };
+        if wallet.address() != *account {
+            warn!("skipping private key at index {}: derives {:?}, expected {:?}", index, wallet.address(), account);
+            continue;
+        }
+        // Debug formatting yields the full hex address; Display abbreviates it (0xf39f…2266)
+        known_wallets.insert(normalize(&format!("{:?}", account)), wallet);
+    }
+    known_wallets
+}
+
 #[async_trait::async_trait]
 impl BlockchainProvider for FoundryAdapter {
     async fn get_native_balance(&self, req: BalanceRequest) -> anyhow::Result<BalanceResponse> {
-        let wei = self.get_balance(&req).await.map_err(|e| anyhow::Error::from(e))?;
+        let wei = self.get_balance(&req).await.map_err(anyhow::Error::from)?;
         Ok(BalanceResponse::new(wei))
     }
 
     async fn get_code(&self, req: CodeRequest) -> anyhow::Result<CodeResponse> {
-        let (deployed, bytecode_len) = self.get_code_len(&req).await.map_err(|e| anyhow::Error::from(e))?;
+        let (deployed, bytecode_len) = self.get_code_len(&req).await.map_err(anyhow::Error::from)?;
         Ok(CodeResponse::new(deployed, bytecode_len))
     }
 
     async fn get_fungible_balance(&self, req: Erc20BalanceRequest) -> anyhow::Result<Erc20BalanceResponse> {
-        let amount = self.erc20_balance_of(&req).await.map_err(|e| anyhow::Error::from(e))?;
+        let amount = self.erc20_balance_of(&req).await.map_err(anyhow::Error::from)?;
         Ok(Erc20BalanceResponse::new(amount))
     }
 
     async fn send_native(&self, req: SendRequest) -> anyhow::Result<TxResult> {
-        let tx = self.send_eth(&req).await.map_err(|e| anyhow::Error::from(e))?;
+        let tx = self.send_eth(&req).await.map_err(anyhow::Error::from)?;
         Ok(tx)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// JSON-RPC stub answering `eth_chainId` with `chain_id` and `eth_getCode` with `code`
+    fn mock_rpc(server: &httpmock::MockServer, chain_id: u64, code: &str) {
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_chainId"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": format!("0x{:x}", chain_id) }));
+        });
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_getCode"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 2, "result": code }));
+        });
+    }
+
+    fn mock_balance(server: &httpmock::MockServer, wei: u64) {
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_getBalance"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": format!("0x{:x}", wei) }));
+        });
+    }
+
+    #[tokio::test]
+    async fn ens_resolution_uses_the_configured_registry() {
+        let registry = "0x1111111111111111111111111111111111111111";
+        let server = httpmock::MockServer::start_async().await;
+        mock_rpc(&server, 31337, "0x6080");
+        // Registry reports no resolver for the name, so resolution ends as "unregistered"
+        let resolver_lookup = server.mock(|when, then| {
+            when.method(httpmock::Method::POST)
+                .json_body_partial(r#"{"method":"eth_call"}"#)
+                .body_contains(registry);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 3, "result": format!("0x{}", "0".repeat(64)) }));
+        });
+
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap()
+            .with_ens_registry(&Address::new(registry.to_string())).unwrap();
+        assert_eq!(adapter.resolve_ens("alice.test").await.unwrap(), None);
+        resolver_lookup.assert_hits(1);
+
+        let bad = FoundryAdapter::new(server.base_url()).await.unwrap().with_ens_registry(&Address::new("0xnope".to_string()));
+        assert!(bad.is_err());
+    }
+
+    #[tokio::test]
+    async fn send_above_balance_is_insufficient_funds() {
+        let server = httpmock::MockServer::start_async().await;
+        mock_rpc(&server, 31337, "0x");
+        mock_balance(&server, 1_000);
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap();
+        let req = SendRequest::builder()
+            .from(Address::new(ANVIL_ACCOUNT_0.to_string()))
+            .to(Address::new(ANVIL_ACCOUNT_1.to_string()))
+            .amount_eth("1")
+            .simulate(false)
+            .build()
+            .unwrap();
+        match adapter.send_eth(&req).await.unwrap_err() {
+            AdapterError::InsufficientFunds { have, need } => {
+                assert_eq!(have, "1000");
+                assert_eq!(need, "1000000000000000000");
+            }
+            other => panic!("expected InsufficientFunds, got {}", other),
+        }
+    }
+
+    /// JSON-RPC node on a std thread that answers the first `failures` requests with a
+    /// 502 gateway page and every later one with `result`; returns its URL and hit count
+    fn flaky_rpc(failures: usize, result: &'static str) -> (String, Arc<std::sync::atomic::AtomicUsize>) {
+        use std::io::{BufRead, BufReader, Read, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let hits = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = hits.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if let Some(len) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                        content_length = len.trim().parse().unwrap();
+                    }
+                    if line == "\r\n" || line.is_empty() {
+                        break;
+                    }
+                }
+                reader.read_exact(&mut vec![0; content_length]).unwrap();
+                let (status, body) = if counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst) < failures {
+                    ("502 Bad Gateway", "<html>502 Bad Gateway</html>".to_string())
+                } else {
+                    ("200 OK", serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": result }).to_string())
+                };
+                let response = format!("HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", status, body.len(), body);
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (url, hits)
+    }
+
+    #[tokio::test]
+    async fn transient_read_errors_are_retried_with_backoff() {
+        let (url, hits) = flaky_rpc(2, "0x6080");
+        let adapter = FoundryAdapter::new(&url).await.unwrap().with_retry(3, Duration::from_millis(1));
+        let req = CodeRequest::new(Address::new(ANVIL_ACCOUNT_0.to_string()));
+        assert_eq!(adapter.get_code_len(&req).await.unwrap(), (true, 2));
+        assert_eq!(hits.load(std::sync::atomic::Ordering::SeqCst), 3);
+
+        // The default policy fails on the first hiccup
+        let (url, hits) = flaky_rpc(1, "0x6080");
+        let adapter = FoundryAdapter::new(&url).await.unwrap();
+        assert!(adapter.get_code_len(&req).await.is_err());
+        assert_eq!(hits.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn slow_node_is_a_timeout_error() {
+        let server = httpmock::MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST);
+            then.status(200).delay(Duration::from_secs(2)).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x1" }));
+        });
+        let timeouts = HttpTimeouts { connect: Duration::from_secs(1), request: Duration::from_millis(200) };
+        let adapter = FoundryAdapter::new_with_timeouts(server.base_url(), timeouts).await.unwrap();
+        match adapter.chain_id().await {
+            Err(AdapterError::Timeout(timed_out)) => {
+                assert_eq!(timed_out.target, "JSON-RPC eth_chainId");
+                assert_eq!(timed_out.after, timeouts.request);
+            }
+            other => panic!("expected AdapterError::Timeout, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn node_errors_are_not_retried() {
+        let server = httpmock::MockServer::start_async().await;
+        let node_error = server.mock(|when, then| {
+            when.method(httpmock::Method::POST);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32000, "message": "header not found" } }));
+        });
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap().with_retry(3, Duration::from_millis(1));
+        assert!(adapter.chain_id().await.is_err());
+        node_error.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn send_raw_broadcasts_a_presigned_tx_and_returns_its_receipt() {
+        let wallet = LocalWallet::from_str(ANVIL_PRIVATE_KEYS[0]).unwrap().with_chain_id(31337u64);
+        let mut tx = transfer_tx(wallet.address(), ANVIL_ACCOUNT_1.parse().unwrap(), U256::from(1_000u64), TxType::Legacy);
+        tx.set_chain_id(31337).set_nonce(0).set_gas(21_000).set_gas_price(1_000_000_000u64);
+        let signature = wallet.sign_transaction_sync(&tx).unwrap();
+        let raw = tx.rlp_signed(&signature);
+        let hash = format!("{:?}", H256::from(ethers_core::utils::keccak256(&raw)));
+
+        let server = httpmock::MockServer::start_async().await;
+        mock_rpc(&server, 31337, "0x");
+        let broadcast = server.mock(|when, then| {
+            when.method(httpmock::Method::POST)
+                .json_body_partial(serde_json::json!({ "method": "eth_sendRawTransaction", "params": [format!("{}", raw)] }).to_string());
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": hash }));
+        });
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_getTransactionByHash"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": {
+                "hash": hash, "nonce": "0x0", "blockHash": format!("0x{}", "11".repeat(32)), "blockNumber": "0x1",
+                "transactionIndex": "0x0", "from": ANVIL_ACCOUNT_0, "to": ANVIL_ACCOUNT_1, "value": "0x3e8",
+                "gasPrice": "0x3b9aca00", "gas": "0x5208", "input": "0x", "v": "0xf4f5", "r": "0x1", "s": "0x1"
+            } }));
+        });
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_getTransactionReceipt"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": {
+                "transactionHash": hash, "transactionIndex": "0x0", "blockHash": format!("0x{}", "11".repeat(32)),
+                "blockNumber": "0x1", "from": ANVIL_ACCOUNT_0, "to": ANVIL_ACCOUNT_1, "cumulativeGasUsed": "0x5208",
+                "gasUsed": "0x5208", "contractAddress": null, "logs": [], "logsBloom": format!("0x{}", "00".repeat(256)),
+                "status": "0x1", "effectiveGasPrice": "0x3b9aca00", "type": "0x0"
+            } }));
+        });
+
+        // No signer is registered: the adapter only relays
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap().with_poll_interval(Duration::from_millis(10));
+        let result = adapter.send_raw(&raw.to_string()).await.unwrap();
+        broadcast.assert_hits(1);
+        assert_eq!(result.tx_hash(), hash);
+        assert_eq!(result.gas_used(), Some(21_000));
+        assert_eq!(result.status(), Some(true));
+        // 21000 gas at the receipt's 1 gwei
+        assert_eq!(result.effective_gas_price(), Some(1_000_000_000));
+        assert_eq!(result.fee_wei(), Some("21000000000000"));
+
+        assert_eq!(decode_raw_transaction(&raw.to_string()).unwrap().1, wallet.address());
+        for bad in ["", "0xzz", "0xdeadbeef"] {
+            let err = adapter.send_raw(bad).await.unwrap_err();
+            assert!(matches!(err, AdapterError::InvalidRawTx { .. }), "{:?}: {}", bad, err);
+        }
+    }
+
+    #[tokio::test]
+    async fn unconfirmed_broadcast_times_out_with_its_hash() {
+        let wallet = LocalWallet::from_str(ANVIL_PRIVATE_KEYS[0]).unwrap().with_chain_id(31337u64);
+        let mut tx = transfer_tx(wallet.address(), ANVIL_ACCOUNT_1.parse().unwrap(), U256::from(1_000u64), TxType::Legacy);
+        tx.set_chain_id(31337).set_nonce(0).set_gas(21_000).set_gas_price(1_000_000_000u64);
+        let raw = tx.rlp_signed(&wallet.sign_transaction_sync(&tx).unwrap());
+        let hash = format!("{:?}", H256::from(ethers_core::utils::keccak256(&raw)));
+
+        let server = httpmock::MockServer::start_async().await;
+        mock_rpc(&server, 31337, "0x");
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_sendRawTransaction"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": hash }));
+        });
+        // Seen in the mempool but never mined
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_getTransactionByHash"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": {
+                "hash": hash, "nonce": "0x0", "blockHash": null, "blockNumber": null, "transactionIndex": null,
+                "from": ANVIL_ACCOUNT_0, "to": ANVIL_ACCOUNT_1, "value": "0x3e8",
+                "gasPrice": "0x3b9aca00", "gas": "0x5208", "input": "0x", "v": "0xf4f5", "r": "0x1", "s": "0x1"
+            } }));
+        });
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_getTransactionReceipt"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": null }));
+        });
+
+        let adapter = FoundryAdapter::new(server.base_url())
+            .await
+            .unwrap()
+            .with_poll_interval(Duration::from_millis(10))
+            .with_confirmation_timeout(Duration::from_millis(200));
+        let err = adapter.send_raw(&raw.to_string()).await.unwrap_err();
+        match err {
+            AdapterError::ConfirmationTimeout { tx_hash, confirmations: 1, .. } => assert_eq!(tx_hash, hash),
+            other => panic!("expected a confirmation timeout, got {}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn prepared_transfer_broadcasts_with_the_previewed_gas_and_fees() {
+        let server = httpmock::MockServer::start_async().await;
+        mock_rpc(&server, 31337, "0x");
+        mock_balance(&server, 10_000_000_000_000_000_000);
+        let mut gas_price = server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_gasPrice"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x3b9aca00" }));
+        });
+        let estimate = server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_estimateGas"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x5208" }));
+        });
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_call"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x" }));
+        });
+        let adapter = FoundryAdapter::new(server.base_url())
+            .await
+            .unwrap()
+            .with_poll_interval(Duration::from_millis(10))
+            .with_confirmation_timeout(Duration::from_millis(200));
+        let req = SendRequest::builder()
+            .from(Address::new(ANVIL_ACCOUNT_0.to_string()))
+            .to(Address::new(ANVIL_ACCOUNT_1.to_string()))
+            .amount_eth("0.1")
+            .simulate(true)
+            .tx_type(Some(TxType::Legacy))
+            .build()
+            .unwrap();
+        let (preview, prepared) = adapter.prepare_send(&req).await.unwrap();
+        assert_eq!(preview.gas_used(), Some(21_000));
+
+        // Fees move between preview and commit; the broadcast must not follow them
+        gas_price.delete();
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_gasPrice"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x12a05f200" }));
+        });
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_getTransactionCount"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x0" }));
+        });
+        let wallet = LocalWallet::from_str(ANVIL_PRIVATE_KEYS[0]).unwrap().with_chain_id(31337u64);
+        let gas = buffered_gas_within_cap(U256::from(21_000), adapter.gas_buffer_bps, adapter.gas_cap).unwrap();
+        let mut expected = transfer_tx(wallet.address(), ANVIL_ACCOUNT_1.parse().unwrap(), U256::exp10(17), TxType::Legacy);
+        expected.set_chain_id(31337).set_nonce(0).set_gas(gas).set_gas_price(1_000_000_000u64);
+        let raw = expected.rlp_signed(&wallet.sign_transaction_sync(&expected).unwrap());
+        let hash = format!("{:?}", H256::from(ethers_core::utils::keccak256(&raw)));
+        let broadcast = server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(serde_json::json!({ "method": "eth_sendRawTransaction", "params": [raw.to_string()] }).to_string());
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": hash }));
+        });
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_getTransactionReceipt"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": null }));
+        });
+
+        let err = adapter.send_prepared(&prepared).await.unwrap_err();
+        assert!(matches!(err, AdapterError::ConfirmationTimeout { ref tx_hash, .. } if *tx_hash == hash), "{}", err);
+        broadcast.assert_hits(1);
+        estimate.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn send_locks_are_per_account_and_shared_by_clones() {
+        let adapter = FoundryAdapter::new("http://127.0.0.1:1").await.unwrap();
+        let clone = adapter.clone();
+        let alice = adapter.send_lock(ANVIL_ACCOUNT_0);
+        assert!(Arc::ptr_eq(&alice, &clone.send_lock(&ANVIL_ACCOUNT_0.to_uppercase().replace("0X", "0x"))));
+        assert!(!Arc::ptr_eq(&alice, &adapter.send_lock(ANVIL_ACCOUNT_1)));
+
+        // While Alice's lock is held, Bob can still take his
+        let _held = alice.lock().await;
+        assert!(alice.try_lock().is_err());
+        assert!(adapter.send_lock(ANVIL_ACCOUNT_1).try_lock().is_ok());
+    }
+
+    #[tokio::test]
+    async fn zero_value_and_self_transfers_are_rejected_before_network() {
+        // Nothing listens on this port; reaching the network would fail with a provider error
+        let adapter = FoundryAdapter::new("http://127.0.0.1:1").await.unwrap();
+        let send = |to: &str, amount: &str| {
+            SendRequest::builder()
+                .from(Address::new(ANVIL_ACCOUNT_0.to_string()))
+                .to(Address::new(to.to_string()))
+                .amount_eth(amount)
+                .simulate(true)
+                .build()
+                .unwrap()
+        };
+        for zero in ["0", "0.0"] {
+            let err = adapter.send_eth(&send(ANVIL_ACCOUNT_1, zero)).await.unwrap_err();
+            assert!(matches!(&err, AdapterError::InvalidAmount { amount } if amount == zero), "{}", err);
+        }
+
+        // Same account, differently cased
+        let err = adapter.send_eth(&send(&ANVIL_ACCOUNT_0.to_uppercase().replace("0X", "0x"), "1")).await.unwrap_err();
+        assert!(matches!(err, AdapterError::SelfTransfer { .. }), "{}", err);
+
+        // Opting out lets the send through to the node
+        let err = adapter.with_self_transfer_check(false).send_eth(&send(ANVIL_ACCOUNT_0, "1")).await.unwrap_err();
+        assert!(matches!(err, AdapterError::Provider(_)), "{}", err);
+    }
+
+    #[test]
+    fn forced_tx_type_overrides_base_fee_detection() {
+        // Legacy on a 1559-capable chain, and 1559 on a chain without a base fee
+        assert_eq!(select_tx_type(Some(TxType::Legacy), true), TxType::Legacy);
+        assert_eq!(select_tx_type(Some(TxType::Eip1559), false), TxType::Eip1559);
+        assert_eq!(select_tx_type(None, true), TxType::Eip1559);
+        assert_eq!(select_tx_type(None, false), TxType::Legacy);
+    }
+
+    #[test]
+    fn transfer_tx_uses_the_selected_envelope() {
+        let from = EthAddress::from_str(ANVIL_ACCOUNT_0).unwrap();
+        let to = EthAddress::from_str(ANVIL_ACCOUNT_1).unwrap();
+        let legacy = transfer_tx(from, to, U256::from(1u64), TxType::Legacy);
+        assert!(matches!(legacy, TypedTransaction::Legacy(_)));
+        let eip1559 = transfer_tx(from, to, U256::from(1u64), TxType::Eip1559);
+        assert!(matches!(eip1559, TypedTransaction::Eip1559(_)));
+        assert_eq!(eip1559.value(), Some(&U256::from(1u64)));
+    }
+
+    #[tokio::test]
+    async fn erc20_balances_fall_back_to_sequential_calls_without_multicall() {
+        let server = httpmock::MockServer::start_async().await;
+        mock_rpc(&server, 31337, "0x");
+        let balance_of = server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_call"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": format!("0x{:064x}", 42) }));
+        });
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap();
+        let holders = [ANVIL_ACCOUNT_0, "0xnot-an-address", ANVIL_ACCOUNT_1].map(|h| Address::new(h.to_string()));
+
+        let results = adapter.erc20_balances_of(&Address::new(USDC_MAINNET.to_string()), &holders).await.unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_deref().unwrap(), "42");
+        assert!(matches!(results[1], Err(AdapterError::AddrParse(_))));
+        assert_eq!(results[2].as_deref().unwrap(), "42");
+        balance_of.assert_hits(2);
+    }
+
+    #[tokio::test]
+    async fn eth_call_decodes_custom_error_with_cached_abi() {
+        let server = httpmock::MockServer::start_async().await;
+        // InsufficientBalance(uint256,uint256) with (5, 100)
+        let abi = r#"[{"type":"error","name":"InsufficientBalance","inputs":[{"name":"available","type":"uint256"},{"name":"required","type":"uint256"}]}]"#;
+        let parsed: ethers_core::abi::Abi = serde_json::from_str(abi).unwrap();
+        let data = parsed.error("InsufficientBalance").unwrap()
+            .encode(&[Token::Uint(U256::from(5u64)), Token::Uint(U256::from(100u64))]).unwrap();
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_call"}"#);
+            then.status(200).json_body(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1,
+                "error": { "code": 3, "message": "execution reverted", "data": format!("0x{}", data.iter().map(|b| format!("{:02x}", b)).collect::<String>()) }
+            }));
+        });
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap();
+        let req = ContractCallRequest::new(Address::new(WETH_MAINNET.to_string()), "0x12345678");
+
+        let without_abi = adapter.eth_call(&req).await.unwrap_err().to_string();
+        assert!(without_abi.contains("execution reverted: 0x"), "{}", without_abi);
+
+        adapter.cache_abi(WETH_MAINNET, abi.to_string(), true);
+        let err = adapter.eth_call(&req).await.unwrap_err();
+        assert_eq!(err.to_string(), "execution reverted: InsufficientBalance(available: 5, required: 100)");
+    }
+
+    #[tokio::test]
+    async fn erc20_transfers_decode_and_merge_sent_and_received() {
+        let server = httpmock::MockServer::start_async().await;
+        let topic = |addr: &str| format!("0x{:0>64}", addr.trim_start_matches("0x"));
+        let transfer = ethers_core::utils::keccak256("Transfer(address,address,uint256)");
+        let log = |from: &str, to: &str, block: u64, hash_byte: u8| serde_json::json!({
+            "address": USDC_MAINNET,
+            "topics": [format!("0x{}", transfer.iter().map(|b| format!("{:02x}", b)).collect::<String>()), topic(from), topic(to)],
+            "data": format!("0x{:064x}", 1_500_000u64),
+            "blockNumber": format!("0x{:x}", block),
+            "transactionHash": format!("0x{}", format!("{:02x}", hash_byte).repeat(32)),
+            "logIndex": "0x0"
+        });
+        // Every query returns both logs; the second copy of each must be dropped
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_getLogs"}"#);
+            then.status(200).json_body(serde_json::json!({
+                "jsonrpc": "2.0", "id": 1,
+                "result": [log(ANVIL_ACCOUNT_1, ANVIL_ACCOUNT_0, 12, 0xbb), log(ANVIL_ACCOUNT_0, ANVIL_ACCOUNT_1, 10, 0xaa)]
+            }));
+        });
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap();
+        let token = Address::new(USDC_MAINNET.to_string());
+        let alice = Address::new(ANVIL_ACCOUNT_0.to_string());
+
+        let transfers = adapter.erc20_transfers(&token, &alice, 1, 20).await.unwrap();
+        assert_eq!(transfers.len(), 2);
+        assert_eq!(transfers[0].block, 10);
+        assert_eq!(transfers[0].from, "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+        assert_eq!(transfers[0].value, "1500000");
+        assert_eq!(transfers[0].tx_hash, format!("0x{}", "aa".repeat(32)));
+        assert_eq!(transfers[1].to, "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+
+        let err = adapter.erc20_transfers(&token, &alice, 0, MAX_LOG_BLOCK_RANGE).await.unwrap_err();
+        assert!(matches!(err, AdapterError::InvalidBlockRange { .. }), "{}", err);
+        assert!(adapter.erc20_transfers(&token, &alice, 5, 4).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn suggest_fees_doubles_base_fee_and_adds_priority() {
+        let server = httpmock::MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_getBlockByNumber"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": { "number": "0x10", "baseFeePerGas": format!("0x{:x}", 30_000_000_000u64) } }));
+        });
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_maxPriorityFeePerGas"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 2, "result": format!("0x{:x}", 2_000_000_000u64) }));
+        });
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap();
+        let fees = adapter.suggest_fees().await.unwrap();
+        assert_eq!(fees, FeeSuggestion {
+            base_fee: "30000000000".to_string(),
+            priority_fee: "2000000000".to_string(),
+            max_fee: "62000000000".to_string(),
+        });
+    }
+
+    #[tokio::test]
+    async fn tiny_cache_evicts_oldest_abi() {
+        let config = CacheConfig::default().with_abi_size(1);
+        let adapter = FoundryAdapter::new("http://127.0.0.1:8545").await.unwrap().with_cache_config(config);
+        assert_eq!(adapter.cache_config().abi_size, 1);
+
+        adapter.cache_abi(USDC_MAINNET, "[\"usdc\"]".to_string(), true);
+        assert_eq!(adapter.cached_abi(&USDC_MAINNET.to_lowercase()).as_deref(), Some("[\"usdc\"]"));
+        adapter.cache_abi(WETH_MAINNET, "[\"weth\"]".to_string(), true);
+        assert!(adapter.cached_abi(USDC_MAINNET).is_none(), "oldest entry should be evicted");
+        assert_eq!(adapter.cached_abi(WETH_MAINNET).as_deref(), Some("[\"weth\"]"));
+    }
+
+    #[tokio::test]
+    async fn balance_of_with_empty_return_data_is_not_an_erc20() {
+        let server = httpmock::MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_call"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x" }));
+        });
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap();
+        let req = Erc20BalanceRequest::new(Address::new(ANVIL_ACCOUNT_1.to_string()), Address::new(ANVIL_ACCOUNT_0.to_string()));
+        let err = adapter.erc20_balance_of(&req).await.unwrap_err();
+        assert!(matches!(err, AdapterError::NotAnErc20 { ref token } if token == ANVIL_ACCOUNT_1), "got {:?}", err);
+    }
+
+    #[tokio::test]
+    async fn metadata_with_empty_return_data_is_not_an_erc20() {
+        let server = httpmock::MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_call"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x" }));
+        });
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap();
+        let err = adapter.erc20_metadata(&Address::new(ANVIL_ACCOUNT_1.to_string())).await.unwrap_err();
+        assert!(matches!(err, AdapterError::NotAnErc20 { ref token } if token == ANVIL_ACCOUNT_1), "got {:?}", err);
+    }
+
+    #[tokio::test]
+    async fn wait_for_balance_returns_once_threshold_is_met() {
+        let server = httpmock::MockServer::start_async().await;
+        mock_balance(&server, 5_000);
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap();
+        let req = BalanceRequest::new(AddressOrEns::Address(Address::new(ANVIL_ACCOUNT_1.to_string())));
+
+        let started = Instant::now();
+        let balance = adapter.wait_for_balance(&req, "5000", Duration::from_secs(5)).await.unwrap();
+        assert_eq!(balance, "5000");
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn wait_for_balance_times_out_below_threshold() {
+        let server = httpmock::MockServer::start_async().await;
+        mock_balance(&server, 10);
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap();
+        let req = BalanceRequest::new(AddressOrEns::Address(Address::new(ANVIL_ACCOUNT_1.to_string())));
+
+        let err = adapter.wait_for_balance(&req, "11", Duration::from_millis(300)).await.unwrap_err();
+        assert!(matches!(err, AdapterError::BalanceTimeout { ref last_wei, .. } if last_wei == "10"), "got {:?}", err);
+    }
+
+    #[test]
+    fn gas_buffer_pads_estimate_and_cap_applies_to_padded_value() {
+        assert_eq!(buffered_gas_within_cap(U256::from(21_000), 1000, 30_000_000).unwrap(), U256::from(23_100));
+        assert_eq!(buffered_gas_within_cap(U256::from(50_000), 2500, 30_000_000).unwrap(), U256::from(62_500));
+        assert_eq!(buffered_gas_within_cap(U256::from(21_000), 0, 21_000).unwrap(), U256::from(21_000));
+
+        // The raw estimate fits under the cap, but the buffered one does not
+        let err = buffered_gas_within_cap(U256::from(21_000), 1000, 22_000).unwrap_err();
+        assert!(matches!(err, AdapterError::GasCapExceeded { estimated: 23_100, cap: 22_000 }));
+    }
+
+    #[test]
+    fn inclusion_estimate_waits_for_base_fee_to_fall() {
+        let gwei = |n: u64| U256::from(n) * U256::exp10(9);
+        assert_eq!(inclusion_seconds(gwei(10), gwei(30), 12.0), Some(12));
+        // 10 → 8.75 → 7.66 gwei: two blocks of decay, then inclusion
+        assert_eq!(inclusion_seconds(gwei(10), gwei(8), 12.0), Some(36));
+        assert_eq!(inclusion_seconds(gwei(10), gwei(30), 0.0), Some(0));
+        assert_eq!(inclusion_seconds(gwei(10), U256::zero(), 12.0), None);
+    }
+
+    #[test]
+    fn default_confirmations_follow_chain_finality() {
+        assert_eq!(default_confirmations(1), 3);
+        assert_eq!(default_confirmations(31337), 1);
+        assert_eq!(default_confirmations(42161), 1);
+        assert_eq!(default_confirmations(999_999), FALLBACK_CONFIRMATIONS);
+    }
+
+    #[tokio::test]
+    async fn nonce_too_low_is_retried_once_with_pending_nonce() {
+        let attempts = Mutex::new(Vec::new());
+        let send = |tx: TypedTransaction| {
+            let mut seen = attempts.lock().unwrap();
+            seen.push(tx.nonce().copied());
+            let first = seen.len() == 1;
+            async move {
+                if first {
+                    Err(std::io::Error::other("(code: -32000, message: nonce too low, data: None)"))
+                } else {
+                    Ok("0xabc")
+                }
+            }
+        };
+        let tx: TypedTransaction = TransactionRequest::new().nonce(4).into();
+        let sent = send_with_nonce_retry(tx, send, || async { Ok(U256::from(7)) }).await.unwrap();
+        assert_eq!(sent, "0xabc");
+        assert_eq!(*attempts.lock().unwrap(), vec![Some(U256::from(4)), Some(U256::from(7))]);
+    }
+
+    #[tokio::test]
+    async fn other_broadcast_errors_are_not_retried() {
+        let attempts = Mutex::new(0);
+        let send = |_tx: TypedTransaction| {
+            *attempts.lock().unwrap() += 1;
+            async { Err::<(), _>(std::io::Error::other("connection reset")) }
+        };
+        let err = send_with_nonce_retry(TransactionRequest::new().into(), send, || async { Ok(U256::zero()) }).await.unwrap_err();
+        assert!(err.to_string().contains("connection reset"));
+        assert_eq!(*attempts.lock().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn ens_balance_on_base_reports_unsupported_chain() {
+        let server = httpmock::MockServer::start_async().await;
+        mock_rpc(&server, 8453, "0x");
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap();
+
+        let req = BalanceRequest::new(AddressOrEns::from_ens("vitalik.eth".to_string()));
+        let err = adapter.get_balance(&req).await.unwrap_err();
+        assert!(matches!(err, AdapterError::EnsUnsupported { chain_id: 8453 }), "got {:?}", err);
+        assert!(err.to_string().contains("mainnet"));
+        assert!(matches!(adapter.resolve_ens("vitalik.eth").await, Err(AdapterError::EnsUnsupported { chain_id: 8453 })));
+    }
+
+    #[tokio::test]
+    async fn raw_addresses_skip_the_ens_check() {
+        let server = httpmock::MockServer::start_async().await;
+        mock_rpc(&server, 8453, "0x");
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap();
+        let addr = AddressOrEns::from_address("0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045".to_string());
+        assert!(adapter.resolve_address_or_ens(&addr).await.is_ok());
+    }
+
+    #[test]
+    fn known_wallets_match_anvil_accounts() {
+        let accounts = get_anvil_accounts();
+        let wallets = load_known_wallets(&accounts, &ANVIL_PRIVATE_KEYS);
+        assert_eq!(wallets.len(), accounts.len());
+        for account in &accounts {
+            let wallet = wallets.get(&normalize(&format!("{:?}", account))).expect("wallet registered for account");
+            assert_eq!(wallet.address(), *account);
+        }
+    }
+
+    #[test]
+    fn load_known_wallets_skips_invalid_and_mismatched_keys() {
+        let accounts = get_anvil_accounts();
+        // Index 0: unparsable key; index 1: valid key for a different account
+        let keys = ["0xnot-a-key", ANVIL_PRIVATE_KEYS[0]];
+        let wallets = load_known_wallets(&accounts[..2], &keys);
+        assert!(wallets.is_empty());
+    }
+
+    #[tokio::test]
+    async fn env_key_signs_while_anvil_keys_are_absent_off_local_chains() {
+        let key = format!("0x{}", "11".repeat(32));
+        let owner = LocalWallet::from_str(&key).unwrap().address();
+        let adapter = FoundryAdapter::new("http://127.0.0.1:1")
+            .await
+            .unwrap()
+            .with_signers_from_vars(|name| (name == "PRIVATE_KEYS").then(|| format!(" {} ,", key)))
+            .unwrap();
+
+        let wallet = adapter.wallet_for(&format!("{:?}", owner), 1).unwrap();
+        let mut tx = transfer_tx(owner, ANVIL_ACCOUNT_1.parse().unwrap(), U256::from(1u64), TxType::Legacy);
+        tx.set_chain_id(1);
+        let signature = wallet.sign_transaction(&tx).await.unwrap();
+        assert_eq!(signature.recover(tx.sighash()).unwrap(), owner);
+        assert_eq!(wallet.chain_id(), 1);
+
+        // Anvil keys are public; they only sign on local chains
+        assert!(matches!(adapter.wallet_for(ANVIL_ACCOUNT_0, 1), Err(AdapterError::MissingLocalKey(_))));
+        assert!(adapter.wallet_for(ANVIL_ACCOUNT_0, 31337).is_ok());
+
+        let err = adapter.with_signers_from_vars(|_| Some("0xnot-a-key".to_string())).unwrap_err();
+        assert!(matches!(err, AdapterError::InvalidPrivateKey { index: 0 }), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn http_url_builds_an_http_transport_with_the_usual_config() {
+        let server = httpmock::MockServer::start_async().await;
+        mock_rpc(&server, 31337, "0x");
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap().with_expected_chain_id(31337).with_gas_cap(50_000);
+        assert!(!adapter.is_ws());
+        assert_eq!(adapter.chain_id().await.unwrap(), 31337);
+        assert_eq!(adapter.gas_cap, 50_000);
+
+        assert!(matches!(adapter.subscribe_blocks().await.err(), Some(AdapterError::SubscriptionsNeedWs)));
+        assert!(FoundryAdapter::new("not a url").await.is_err());
+        // Nothing listens on this port, so the WebSocket handshake fails up front
+        assert!(FoundryAdapter::new_ws("ws://127.0.0.1:1").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn estimate_send_gas_ignores_the_gas_cap() {
+        let server = httpmock::MockServer::start_async().await;
+        let estimate = server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_estimateGas"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": format!("0x{:x}", 200_000) }));
+        });
+        // Far below the estimate; sends would fail with GasCapExceeded
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap().with_gas_cap(50_000).with_read_only(true);
+        let req = SendRequest::builder()
+            .from(Address::new(ANVIL_ACCOUNT_0.to_string()))
+            .to(Address::new(ANVIL_ACCOUNT_1.to_string()))
+            .amount_eth("0.1")
+            .simulate(true)
+            .tx_type(Some(TxType::Legacy))
+            .build()
+            .unwrap();
+        assert_eq!(adapter.estimate_send_gas(&req).await.unwrap(), 200_000);
+        estimate.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn approve_encodes_the_call_and_respects_the_gas_cap() {
+        let token = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+        let server = httpmock::MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_getBlockByNumber"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": null }));
+        });
+        // approve(Bob, 1000): selector, padded spender, padded amount
+        let calldata = format!("0x095ea7b3{:0>64}{:064x}", &ANVIL_ACCOUNT_1[2..], 1000);
+        let estimate = server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_estimateGas"}"#).body_contains(token).body_contains(&calldata);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": format!("0x{:x}", 200_000) }));
+        });
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap().with_gas_cap(50_000);
+        let (owner, spender) = (Address::new(ANVIL_ACCOUNT_0.to_string()), Address::new(ANVIL_ACCOUNT_1.to_string()));
+        let err = adapter.erc20_approve(&Address::new(token.to_string()), &owner, &spender, "1000", true).await.unwrap_err();
+        // The cap applies to the estimate plus the 10% buffer
+        assert!(matches!(err, AdapterError::GasCapExceeded { estimated: 220_000, cap: 50_000 }), "{}", err);
+        estimate.assert_hits(1);
+
+        let read_only = adapter.with_read_only(true);
+        let err = read_only.erc20_approve(&Address::new(token.to_string()), &owner, &spender, "1000", true).await.unwrap_err();
+        assert!(matches!(err, AdapterError::ReadOnly));
+    }
+
+    #[tokio::test]
+    async fn wrapping_needs_weth_on_the_chain() {
+        assert_eq!(weth_calldata("deposit", &[]).unwrap().to_string(), "0xd0e30db0");
+        let one_eth = U256::exp10(18);
+        assert_eq!(weth_calldata("withdraw", &[Token::Uint(one_eth)]).unwrap().to_string(), format!("0x2e1a7d4d{:064x}", one_eth));
+
+        let server = httpmock::MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_getCode"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x" }));
+        });
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_chainId"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x2105" }));
+        });
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap();
+        let alice = Address::new(ANVIL_ACCOUNT_0.to_string());
+        let err = adapter.wrap_eth(&alice, "1", true).await.unwrap_err();
+        assert!(matches!(err, AdapterError::WethUnavailable { chain_id: 8453 }), "{}", err);
+        let err = adapter.unwrap_weth(&alice, "0", true).await.unwrap_err();
+        assert!(matches!(err, AdapterError::InvalidAmount { .. }), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn token_transfer_scales_the_amount_by_decimals() {
+        let token = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+        let server = httpmock::MockServer::start_async().await;
+        // Every metadata call answers 6; only decimals() needs to decode
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_call"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": format!("0x{:064x}", 6) }));
+        });
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_getBlockByNumber"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": null }));
+        });
+        // transfer(Bob, 1.5 * 10^6)
+        let calldata = format!("0xa9059cbb{:0>64}{:064x}", &ANVIL_ACCOUNT_1[2..], 1_500_000);
+        let estimate = server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_estimateGas"}"#).body_contains(&calldata);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": format!("0x{:x}", 200_000) }));
+        });
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap().with_gas_cap(50_000);
+        let transfer = |amount: &str| {
+            Erc20TransferRequest::builder()
+                .token(Address::new(token.to_string()))
+                .from(Address::new(ANVIL_ACCOUNT_0.to_string()))
+                .to(Address::new(ANVIL_ACCOUNT_1.to_string()))
+                .amount(amount)
+                .build()
+                .unwrap()
+        };
+        // Stopping at the gas cap keeps the test to estimation
+        let err = adapter.erc20_transfer(&transfer("1.5")).await.unwrap_err();
+        assert!(matches!(err, AdapterError::GasCapExceeded { .. }), "{}", err);
+        estimate.assert_hits(1);
+
+        let err = adapter.erc20_transfer(&transfer("0")).await.unwrap_err();
+        assert!(matches!(err, AdapterError::InvalidAmount { .. }), "{}", err);
+        assert!(adapter.erc20_transfer(&transfer("0.0000001")).await.is_err(), "more decimals than the token has");
+    }
+
+    #[tokio::test]
+    async fn simulation_at_fork_block_pins_estimate_and_call() {
+        let server = httpmock::MockServer::start_async().await;
+        mock_rpc(&server, 31337, "0x");
+        // Only balances at the fork block are served; a latest read would fail the send
+        let balances = server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_getBalance"}"#).body_contains(r#""0x64""#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x8ac7230489e80000" }));
+        });
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_blockNumber"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x100" }));
+        });
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_gasPrice"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x3b9aca00" }));
+        });
+        let estimate = server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_estimateGas","params":[{},"0x64"]}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x5208" }));
+        });
+        let call = server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_call","params":[{},"0x64"]}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x" }));
+        });
+
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap();
+        let send = |fork_block| {
+            SendRequest::builder()
+                .from(Address::new(ANVIL_ACCOUNT_0.to_string()))
+                .to(Address::new(ANVIL_ACCOUNT_1.to_string()))
+                .amount_eth("0.1")
+                .simulate(true)
+                .tx_type(Some(TxType::Legacy))
+                .fork_block(Some(fork_block))
+                .build()
+                .unwrap()
+        };
+        let result = adapter.send_eth(&send(100)).await.unwrap();
+        assert_eq!(result.gas_used(), Some(21_000));
+        estimate.assert_hits(1);
+        call.assert_hits(1);
+        balances.assert_hits(2);
+        assert_eq!(result.balance_changes()[0].before, "10000000000000000000");
+
+        let err = adapter.send_eth(&send(0x101)).await.unwrap_err();
+        assert!(matches!(err, AdapterError::ForkBlockAhead { fork_block: 0x101, head: 0x100 }), "{}", err);
+    }
+
+    #[test]
+    fn receipt_with_gas_price_beyond_u64_has_no_fee() {
+        let rcpt = TransactionReceipt {
+            gas_used: Some(U256::from(21_000)),
+            status: Some(1u64.into()),
+            effective_gas_price: Some(U256::from(u64::MAX) + 1),
+            ..Default::default()
+        };
+        let result = receipt_result(&rcpt);
+        assert_eq!(result.gas_used(), Some(21_000));
+        assert_eq!(result.status(), Some(true));
+        assert_eq!(result.effective_gas_price(), None);
+        assert_eq!(result.fee_wei(), None);
+    }
+
+    #[tokio::test]
+    async fn broadcast_with_fork_block_is_refused() {
+        let server = httpmock::MockServer::start_async().await;
+        mock_rpc(&server, 31337, "0x");
+        let broadcast = server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_sendRawTransaction"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": format!("0x{}", "ab".repeat(32)) }));
+        });
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap();
+        let req = SendRequest::builder()
+            .from(Address::new(ANVIL_ACCOUNT_0.to_string()))
+            .to(Address::new(ANVIL_ACCOUNT_1.to_string()))
+            .amount_eth("0.1")
+            .simulate(false)
+            .fork_block(Some(100))
+            .build()
+            .unwrap();
+
+        let err = adapter.send_eth(&req).await.unwrap_err();
+        assert!(matches!(err, AdapterError::ForkBlockOnBroadcast { fork_block: 100 }), "{}", err);
+        let err = adapter.prepare_send(&req).await.unwrap_err();
+        assert!(matches!(err, AdapterError::ForkBlockOnBroadcast { fork_block: 100 }), "{}", err);
+        broadcast.assert_hits(0);
+    }
+
+    #[tokio::test]
+    async fn deploy_broadcast_waits_for_the_requested_confirmations() {
+        let server = httpmock::MockServer::start_async().await;
+        mock_rpc(&server, 31337, "0x");
+        mock_balance(&server, 10_000_000_000_000_000_000);
+        for (method, result) in [
+            ("eth_getTransactionCount", serde_json::json!("0x0")),
+            ("eth_getBlockByNumber", serde_json::Value::Null),
+            ("eth_estimateGas", serde_json::json!("0x186a0")),
+            ("eth_gasPrice", serde_json::json!("0x3b9aca00")),
+            ("eth_call", serde_json::json!("0x")),
+            ("eth_getTransactionReceipt", serde_json::Value::Null),
+        ] {
+            server.mock(|when, then| {
+                when.method(httpmock::Method::POST).json_body_partial(serde_json::json!({ "method": method }).to_string());
+                then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": result }));
+            });
+        }
+        let hash = format!("0x{}", "ab".repeat(32));
+        let broadcast = server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_sendRawTransaction"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": hash }));
+        });
+        let adapter = FoundryAdapter::new(server.base_url())
+            .await
+            .unwrap()
+            .with_poll_interval(Duration::from_millis(10))
+            .with_confirmation_timeout(Duration::from_millis(200));
+        let req = DeployRequest::builder()
+            .from(Address::new(ANVIL_ACCOUNT_0.to_string()))
+            .bytecode("0x6001")
+            .simulate(false)
+            .confirmations(Some(2))
+            .build()
+            .unwrap();
+        let err = adapter.deploy_contract(&req).await.unwrap_err();
+        assert!(matches!(err, AdapterError::ConfirmationTimeout { ref tx_hash, confirmations: 2, .. } if *tx_hash == hash), "{}", err);
+        broadcast.assert_hits(1);
+    }
+
+    #[test]
+    fn creation_data_appends_constructor_args_to_init_code() {
+        assert_eq!(creation_data("0x6001", "").unwrap().to_vec(), vec![0x60, 0x01]);
+        assert_eq!(creation_data("0x6001", "0x00ff").unwrap().to_vec(), vec![0x60, 0x01, 0x00, 0xff]);
+        for (code, args) in [("0x", ""), ("0xzz", ""), ("0x6001", "0xzz")] {
+            let err = creation_data(code, args).unwrap_err();
+            assert!(matches!(err, AdapterError::InvalidBytecode { .. }), "{}", err);
+        }
+    }
+
+    #[tokio::test]
+    async fn bundle_on_a_node_without_snapshots_sends_nothing() {
+        let server = httpmock::MockServer::start_async().await;
+        let snapshot = server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"evm_snapshot"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32601, "message": "the method evm_snapshot does not exist" } }));
+        });
+        let send = server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_sendTransaction"}"#);
+            then.status(500);
+        });
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap();
+        let steps = [BundleStep::Send {
+            from: Address::new(ANVIL_ACCOUNT_0.to_string()),
+            to: Address::new(ANVIL_ACCOUNT_1.to_string()),
+            amount_eth: "1".to_string(),
+        }];
+        let err = adapter.simulate_bundle(&steps).await.unwrap_err();
+        assert!(matches!(err, AdapterError::ImpersonationDisabled), "{}", err);
+        snapshot.assert_hits(0);
+
+        let adapter = adapter.with_impersonation(true);
+        let err = adapter.simulate_bundle(&steps).await.unwrap_err();
+        assert!(matches!(err, AdapterError::DevMethodUnsupported { method: "evm_snapshot", .. }), "{}", err);
+        snapshot.assert_hits(1);
+        send.assert_hits(0);
+    }
+
+    #[tokio::test]
+    async fn broadcast_during_a_bundle_goes_out_after_the_revert() {
+        let wallet = LocalWallet::from_str(ANVIL_PRIVATE_KEYS[0]).unwrap().with_chain_id(31337u64);
+        let mut tx = transfer_tx(wallet.address(), ANVIL_ACCOUNT_1.parse().unwrap(), U256::from(1_000u64), TxType::Legacy);
+        tx.set_chain_id(31337).set_nonce(0).set_gas(21_000).set_gas_price(1_000_000_000u64);
+        let raw = tx.rlp_signed(&wallet.sign_transaction_sync(&tx).unwrap());
+        let hash = format!("{:?}", H256::from(ethers_core::utils::keccak256(&raw)));
+
+        let server = httpmock::MockServer::start_async().await;
+        mock_rpc(&server, 31337, "0x");
+        for (method, result) in [
+            ("evm_snapshot", serde_json::json!("0x1")),
+            ("evm_revert", serde_json::json!(true)),
+            ("eth_getBlockByNumber", serde_json::Value::Null),
+            ("anvil_impersonateAccount", serde_json::Value::Null),
+            ("anvil_stopImpersonatingAccount", serde_json::Value::Null),
+            ("eth_estimateGas", serde_json::json!("0x5208")),
+            ("eth_gasPrice", serde_json::json!("0x3b9aca00")),
+            ("eth_getTransactionCount", serde_json::json!("0x0")),
+            ("eth_getTransactionByHash", serde_json::json!({
+                "hash": hash, "nonce": "0x0", "blockHash": format!("0x{}", "11".repeat(32)), "blockNumber": "0x1",
+                "transactionIndex": "0x0", "from": ANVIL_ACCOUNT_0, "to": ANVIL_ACCOUNT_1, "value": "0x3e8",
+                "gasPrice": "0x3b9aca00", "gas": "0x5208", "input": "0x", "v": "0xf4f5", "r": "0x1", "s": "0x1"
+            })),
+            ("eth_getTransactionReceipt", serde_json::json!({
+                "transactionHash": hash, "transactionIndex": "0x0", "blockHash": format!("0x{}", "11".repeat(32)),
+                "blockNumber": "0x1", "from": ANVIL_ACCOUNT_0, "to": ANVIL_ACCOUNT_1, "cumulativeGasUsed": "0x5208",
+                "gasUsed": "0x5208", "contractAddress": null, "logs": [], "logsBloom": format!("0x{}", "00".repeat(256)),
+                "status": "0x1", "effectiveGasPrice": "0x3b9aca00", "type": "0x0"
+            })),
+        ] {
+            server.mock(|when, then| {
+                when.method(httpmock::Method::POST).json_body_partial(serde_json::json!({ "method": method }).to_string());
+                then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": result }));
+            });
+        }
+        // The bundle's step takes a while to mine, keeping the preview open
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_sendTransaction"}"#);
+            then.status(200).delay(Duration::from_millis(400)).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": hash }));
+        });
+        let broadcast = server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_sendRawTransaction"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": hash }));
+        });
+
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap().with_poll_interval(Duration::from_millis(10)).with_impersonation(true);
+        let steps = [BundleStep::Send {
+            from: Address::new(ANVIL_ACCOUNT_0.to_string()),
+            to: Address::new(ANVIL_ACCOUNT_1.to_string()),
+            amount_eth: "1".to_string(),
+        }];
+        let (bundle, sent, ()) = tokio::join!(
+            adapter.simulate_bundle(&steps),
+            async {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                adapter.send_raw(&raw.to_string()).await
+            },
+            async {
+                // Still inside the preview: a broadcast now would be wiped by its evm_revert
+                tokio::time::sleep(Duration::from_millis(250)).await;
+                broadcast.assert_hits(0);
+            },
+        );
+        assert!(bundle.unwrap()[0].success);
+        assert_eq!(sent.unwrap().tx_hash(), hash);
+        broadcast.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn snapshot_on_a_regular_node_is_a_clear_error() {
+        let server = httpmock::MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"evm_snapshot"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32601, "message": "the method evm_snapshot does not exist/is not available" } }));
+        });
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"evm_revert"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": false }));
+        });
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap();
+        let err = adapter.snapshot().await.unwrap_err();
+        assert!(err.to_string().starts_with("evm_snapshot needs a dev node"), "{}", err);
+        assert!(!adapter.revert("0x5").await.unwrap());
+        assert!(adapter.revert("five").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn balance_at_a_future_block_is_rejected() {
+        let server = httpmock::MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_blockNumber"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x10" }));
+        });
+        let at_block = server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_getBalance","params":["0x70997970c51812dc3a010c7d01b50e0d17dc79c8","0x10"]}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x2a" }));
+        });
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap();
+        let who = AddressOrEns::Address(Address::new(ANVIL_ACCOUNT_1.to_string()));
+        assert_eq!(adapter.get_balance(&BalanceRequest::new(who.clone()).with_block(Some(16))).await.unwrap(), "42");
+        at_block.assert();
+
+        let err = adapter.get_balance(&BalanceRequest::new(who).with_block(Some(17))).await.unwrap_err();
+        assert!(matches!(err, AdapterError::BlockInFuture { block: 17, head: 16 }), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn impersonation_needs_the_builder_flag() {
+        let whale = Address::new("0x28c6c06298d514db089934071355e5743bf21d60".to_string());
+        // Nothing listens on this port; without the flag no anvil_* call is attempted
+        let adapter = FoundryAdapter::new("http://127.0.0.1:1").await.unwrap();
+        assert!(matches!(adapter.impersonate(&whale).await, Err(AdapterError::ImpersonationDisabled)));
+
+        let server = httpmock::MockServer::start_async().await;
+        let impersonate = server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"anvil_impersonateAccount"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": null }));
+        });
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap().with_impersonation(true);
+        adapter.impersonate(&whale).await.unwrap();
+        impersonate.assert();
+        assert!(adapter.clone().is_impersonated("0x28C6c06298d514Db089934071355E5743bf21d60"));
+    }
+
+    #[tokio::test]
+    async fn read_only_adapter_rejects_send_before_network() {
+        // Nothing listens on this port; a network call would fail with a provider error instead
+        let adapter = FoundryAdapter::new("http://127.0.0.1:1").await.unwrap().with_read_only(true);
+        assert!(adapter.is_read_only());
+        assert!(adapter.known_wallets.is_empty());
+        assert!(adapter.anvil_wallets.is_empty());
+
+        let req = SendRequest::builder()
+            .from(Address::new(ANVIL_ACCOUNT_0.to_string()))
+            .to(Address::new(ANVIL_ACCOUNT_1.to_string()))
+            .amount_eth("0.1")
+            .simulate(false)
+            .build()
+            .unwrap();
+        let err = adapter.send_eth(&req).await.unwrap_err();
+        assert!(matches!(err, AdapterError::ReadOnly));
+    }
+}
@@ -0,0 +1,100 @@
+use ethers_core::abi::{decode, Abi, ParamType, Token};
+
+/// `Error(string)`, the selector `require(cond, "msg")` and `revert("msg")` use
+const ERROR_STRING_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+/// `Panic(uint256)`, raised by failed asserts, overflows, division by zero, ...
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// Readable revert reason for `data`: `Error(string)` and `Panic(uint256)` always,
+/// custom errors (`error Foo(uint256)`) when `abi` declares one with a matching
+/// selector. Anything else comes back as the raw hex.
+pub fn decode_revert_reason(data: &[u8], abi: Option<&Abi>) -> String {
+    let raw = || format!("0x{}", hex(data));
+    let Some((selector, args)) = data.split_first_chunk::<4>() else {
+        return if data.is_empty() { "reverted without data".to_string() } else { raw() };
+    };
+    if *selector == ERROR_STRING_SELECTOR {
+        if let Ok(tokens) = decode(&[ParamType::String], args) {
+            return tokens.into_iter().next().map(|t| format_token(&t)).unwrap_or_else(raw);
+        }
+    }
+    if *selector == PANIC_SELECTOR {
+        if let Ok(tokens) = decode(&[ParamType::Uint(256)], args) {
+            return format!("Panic({})", tokens.iter().map(format_token).collect::<Vec<_>>().join(", "));
+        }
+    }
+    let custom = abi.into_iter().flat_map(|abi| abi.errors()).find(|error| error.signature()[..4] == selector[..]);
+    if let Some(error) = custom {
+        if let Ok(tokens) = error.decode(args) {
+            let params: Vec<String> = error
+                .inputs
+                .iter()
+                .zip(&tokens)
+                .map(|(param, token)| {
+                    if param.name.is_empty() { format_token(token) } else { format!("{}: {}", param.name, format_token(token)) }
+                })
+                .collect();
+            return format!("{}({})", error.name, params.join(", "));
+        }
+    }
+    raw()
+}
+
+/// Decimal integers and 0x-prefixed addresses/bytes, unlike ethabi's hex `Display`
+fn format_token(token: &Token) -> String {
+    match token {
+        Token::Uint(value) | Token::Int(value) => value.to_string(),
+        Token::Address(addr) => ethers_core::utils::to_checksum(addr, None),
+        Token::Bytes(bytes) | Token::FixedBytes(bytes) => format!("0x{}", hex(bytes)),
+        Token::Array(items) | Token::FixedArray(items) => format!("[{}]", items.iter().map(format_token).collect::<Vec<_>>().join(", ")),
+        Token::Tuple(items) => format!("({})", items.iter().map(format_token).collect::<Vec<_>>().join(", ")),
+        other => other.to_string(),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::abi::encode;
+    use ethers_core::types::U256;
+
+    const VAULT_ABI: &str = r#"[
+        {"type":"error","name":"InsufficientBalance","inputs":[
+            {"name":"available","type":"uint256"},{"name":"required","type":"uint256"}]},
+        {"type":"error","name":"Unauthorized","inputs":[{"name":"","type":"address"}]}
+    ]"#;
+
+    #[test]
+    fn decodes_custom_error_from_abi() {
+        let abi: Abi = serde_json::from_str(VAULT_ABI).unwrap();
+        let error = abi.error("InsufficientBalance").unwrap();
+        let data = error.encode(&[Token::Uint(U256::from(5u64)), Token::Uint(U256::from(100u64))]).unwrap();
+        assert_eq!(decode_revert_reason(&data, Some(&abi)), "InsufficientBalance(available: 5, required: 100)");
+
+        let unauthorized = abi.error("Unauthorized").unwrap();
+        let who = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266".parse().unwrap();
+        let data = unauthorized.encode(&[Token::Address(who)]).unwrap();
+        assert_eq!(decode_revert_reason(&data, Some(&abi)), "Unauthorized(0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266)");
+
+        // Without the ABI the selector is unknown, so the raw data is returned
+        assert!(decode_revert_reason(&data, None).starts_with("0x"));
+    }
+
+    #[test]
+    fn decodes_standard_reasons_without_abi() {
+        let mut data = ERROR_STRING_SELECTOR.to_vec();
+        data.extend(encode(&[Token::String("not owner".to_string())]));
+        assert_eq!(decode_revert_reason(&data, None), "not owner");
+
+        let mut panic = PANIC_SELECTOR.to_vec();
+        panic.extend(encode(&[Token::Uint(U256::from(0x11u64))]));
+        assert_eq!(decode_revert_reason(&panic, None), "Panic(17)");
+
+        assert_eq!(decode_revert_reason(&[0xde, 0xad], None), "0xdead");
+        assert_eq!(decode_revert_reason(&[], None), "reverted without data");
+    }
+}
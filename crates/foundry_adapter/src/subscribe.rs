@@ -0,0 +1,51 @@
+use ethers_providers::{Middleware, Provider, ProviderError, StreamExt, Ws};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tracing::warn;
+
+use crate::constants::{BLOCK_STREAM_BACKOFF_MS, BLOCK_STREAM_MAX_BACKOFF_MS};
+
+type Ready = Option<oneshot::Sender<Result<(), ProviderError>>>;
+
+/// Forward new block numbers from `ws_url` into `tx` until the receiver is dropped.
+/// The first subscription's outcome goes to `ready`, and failing there ends the task so
+/// callers see bad URLs. Later drops are retried on a fresh connection, waiting twice as
+/// long after each consecutive failure (capped).
+pub(crate) async fn forward_blocks(ws_url: String, tx: mpsc::Sender<u64>, ready: oneshot::Sender<Result<(), ProviderError>>) {
+    let mut ready = Some(ready);
+    let mut backoff = Duration::from_millis(BLOCK_STREAM_BACKOFF_MS);
+    loop {
+        match forward_once(&ws_url, &tx, &mut ready).await {
+            Ok(()) => backoff = Duration::from_millis(BLOCK_STREAM_BACKOFF_MS),
+            Err(e) => {
+                if let Some(ready) = ready.take() {
+                    let _ = ready.send(Err(e));
+                    return;
+                }
+                warn!("resubscribing to blocks on {} failed: {}", ws_url, e);
+            }
+        }
+        if tx.is_closed() {
+            return;
+        }
+        warn!("block subscription on {} dropped; resubscribing in {:?}", ws_url, backoff);
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(Duration::from_millis(BLOCK_STREAM_MAX_BACKOFF_MS));
+    }
+}
+
+/// One connection: subscribe, signal `ready`, then forward until the socket drops or `tx` closes
+async fn forward_once(ws_url: &str, tx: &mpsc::Sender<u64>, ready: &mut Ready) -> Result<(), ProviderError> {
+    let provider = Provider::<Ws>::connect(ws_url).await?;
+    let mut blocks = provider.subscribe_blocks().await?;
+    if let Some(ready) = ready.take() {
+        let _ = ready.send(Ok(()));
+    }
+    while let Some(block) = blocks.next().await {
+        let Some(number) = block.number else { continue };
+        if tx.send(number.as_u64()).await.is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,27 @@
+//! In-process Anvil for integration tests (`test-anvil` feature).
+//!
+//! Set `ANVIL_FORK_URL` to the RPC to fork (and optionally `ANVIL_FORK_BLOCK` to pin
+//! a block). The `anvil` binary must be on `PATH`.
+
+use ethers_core::utils::{Anvil, AnvilInstance};
+
+/// Forking can take a while on a cold RPC, so allow more than Anvil's 2s default
+const SPAWN_TIMEOUT_MS: u64 = 60_000;
+
+/// Spawn Anvil forking `ANVIL_FORK_URL`, or `None` when no fork URL is configured.
+/// The node shuts down when the returned handle is dropped; pass `endpoint()` to
+/// `FoundryAdapter::new`.
+pub fn fork_from_env() -> Option<AnvilInstance> {
+    let url = std::env::var("ANVIL_FORK_URL").ok().filter(|url| !url.trim().is_empty())?;
+    let block = std::env::var("ANVIL_FORK_BLOCK").ok().and_then(|b| b.parse::<u64>().ok());
+    Some(spawn_fork(&url, block))
+}
+
+/// Spawn Anvil forking `url`, pinned to `block` when given.
+pub fn spawn_fork(url: &str, block: Option<u64>) -> AnvilInstance {
+    let mut anvil = Anvil::new().fork(url).timeout(SPAWN_TIMEOUT_MS);
+    if let Some(block) = block {
+        anvil = anvil.fork_block_number(block);
+    }
+    anvil.spawn()
+}
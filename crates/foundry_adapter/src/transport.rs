@@ -0,0 +1,126 @@
+use async_trait::async_trait;
+use domain::http::{HttpTimeouts, TimedOut};
+use ethers_providers::{Http, HttpClientError, JsonRpcClient, JsonRpcError, ProviderError, RpcError, Ws};
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt::Debug;
+use std::time::Instant;
+use tracing::Instrument;
+
+/// JSON-RPC transport behind the adapter's provider, so every adapter method works
+/// the same over plain HTTP or a WebSocket. Both carry the timeouts a call is held to.
+#[derive(Clone, Debug)]
+pub enum Transport {
+    Http(Http, HttpTimeouts),
+    Ws(Ws, HttpTimeouts),
+}
+
+impl Transport {
+    /// HTTP transport whose client gives up on slow connects and requests
+    pub fn http(url: &str, timeouts: HttpTimeouts) -> Result<Self, anyhow::Error> {
+        let client = reqwest::Client::builder().connect_timeout(timeouts.connect).timeout(timeouts.request).build()?;
+        Ok(Self::Http(Http::new_with_client(reqwest::Url::parse(url)?, client), timeouts))
+    }
+
+    pub fn is_ws(&self) -> bool {
+        matches!(self, Self::Ws(..))
+    }
+}
+
+/// A call that hit its timeout; `AdapterError` turns it into `AdapterError::Timeout`
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub(crate) struct RpcTimedOut(pub(crate) TimedOut);
+
+impl RpcError for RpcTimedOut {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        None
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        None
+    }
+}
+
+fn timed_out(timeouts: &HttpTimeouts, method: &str, connecting: bool) -> ProviderError {
+    ProviderError::JsonRpcClientError(Box::new(RpcTimedOut(timeouts.timed_out(format!("JSON-RPC {}", method), connecting))))
+}
+
+#[async_trait]
+impl JsonRpcClient for Transport {
+    type Error = ProviderError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, ProviderError>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        // Every provider call passes through here, so this is the one place to time them;
+        // enable with RUST_LOG=foundry_adapter::transport=debug
+        let span = tracing::debug_span!("rpc", method);
+        async {
+            let started = Instant::now();
+            let result = match self {
+                Self::Http(http, timeouts) => http.request(method, params).await.map_err(|e| match e {
+                    HttpClientError::ReqwestError(e) if e.is_timeout() => timed_out(timeouts, method, e.is_connect()),
+                    e => e.into(),
+                }),
+                // The socket is already open, so only the request timeout applies
+                Self::Ws(ws, timeouts) => match tokio::time::timeout(timeouts.request, ws.request(method, params)).await {
+                    Ok(result) => result.map_err(Into::into),
+                    Err(_) => Err(timed_out(timeouts, method, false)),
+                },
+            };
+            tracing::debug!(elapsed_ms = started.elapsed().as_millis() as u64, ok = result.is_ok(), "rpc call finished");
+            result
+        }
+        .instrument(span)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FoundryAdapter;
+    use domain::{Address, AddressOrEns, BalanceRequest};
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn balance_call_is_logged_with_method_and_timing() {
+        let server = httpmock::MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_getBalance"}"#);
+            then.status(200).json_body(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": "0x2a" }));
+        });
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let adapter = FoundryAdapter::new(server.base_url()).await.unwrap();
+        let who = AddressOrEns::Address(Address::new("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".to_string()));
+        assert_eq!(adapter.get_balance(&BalanceRequest::new(who)).await.unwrap(), "42");
+
+        let logs = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        let line = logs.lines().find(|l| l.contains("rpc call finished")).unwrap_or_else(|| panic!("no rpc log in:\n{}", logs));
+        assert!(line.contains(r#"rpc{method="eth_getBalance"}"#), "{}", line);
+        assert!(line.contains("elapsed_ms=") && line.contains("ok=true"), "{}", line);
+    }
+}
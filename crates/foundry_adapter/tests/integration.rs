@@ -1,29 +1,22 @@
 use foundry_adapter::FoundryAdapter;
 use domain::{Address, AddressOrEns, BalanceRequest, CodeRequest, Erc20BalanceRequest};
 
+#[cfg(feature = "test-anvil")]
 #[tokio::test]
 async fn test_router02_deployment_check() {
-    // This test requires a running Anvil fork with mainnet data
-    let adapter = FoundryAdapter::new("http://127.0.0.1:8545").await;
-    
-    if let Ok(adapter) = adapter {
-        // Check if Uniswap V2 Router02 is deployed
-        let router_addr = "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D";
-        let req = CodeRequest::new(Address::new(router_addr.to_string()));
-        
-        let result = adapter.get_code_len(&req).await;
-        if let Ok((deployed, bytecode_len)) = result {
-            // Router02 should be deployed on mainnet fork
-            assert!(deployed, "Router02 should be deployed on mainnet fork");
-            assert!(bytecode_len > 0, "Router02 should have bytecode");
-            println!("Router02 deployed: {}, bytecode length: {}", deployed, bytecode_len);
-        } else {
-            // Skip test if no Anvil fork is running
-            println!("Skipping Router02 test - no Anvil fork detected");
-        }
-    } else {
-        println!("Skipping Router02 test - could not connect to Anvil");
-    }
+    // Spawns its own forked Anvil: cargo test -p foundry_adapter --features test-anvil
+    let Some(anvil) = foundry_adapter::test_anvil::fork_from_env() else {
+        println!("Skipping Router02 test - ANVIL_FORK_URL not set");
+        return;
+    };
+    let adapter = FoundryAdapter::new(&anvil.endpoint()).await.expect("adapter connects to spawned Anvil");
+
+    // Uniswap V2 Router02 is deployed on mainnet, so the fork must see its bytecode
+    let router_addr = "0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D";
+    let req = CodeRequest::new(Address::new(router_addr.to_string()));
+    let (deployed, bytecode_len) = adapter.get_code_len(&req).await.expect("eth_getCode on fork");
+    assert!(deployed, "Router02 should be deployed on mainnet fork");
+    assert!(bytecode_len > 0, "Router02 should have bytecode");
 }
 
 #[tokio::test]
@@ -80,6 +73,30 @@ async fn test_ens_resolution() {
     }
 }
 
+#[tokio::test]
+async fn test_balance_at_early_block_differs_from_latest() {
+    // This test requires a running Anvil fork with mainnet data
+    let adapter = FoundryAdapter::new("http://127.0.0.1:8545").await;
+
+    if let Ok(adapter) = adapter {
+        // WETH was deployed at block 4,719,568 and has held ETH ever since
+        let weth = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
+        let result = adapter.get_code_len(&CodeRequest::new(Address::new(weth.to_string()))).await;
+        if let Ok((true, _)) = result {
+            let req = BalanceRequest::new(AddressOrEns::from_address(weth.to_string()));
+            let latest = adapter.get_balance(&req).await.expect("latest balance");
+            let early = adapter.get_balance(&req.clone().with_block(Some(4_000_000))).await.expect("balance at block 4,000,000");
+            assert_eq!(early, "0");
+            assert_ne!(latest, "0");
+            assert!(adapter.get_balance(&req.with_block(Some(u64::MAX))).await.is_err(), "future blocks are rejected");
+        } else {
+            println!("Skipping balance-at-block test - no Anvil fork detected");
+        }
+    } else {
+        println!("Skipping balance-at-block test - could not connect to Anvil");
+    }
+}
+
 #[tokio::test]
 async fn test_eth_balance_check() {
     // This test requires a running Anvil fork with mainnet data
@@ -119,7 +136,7 @@ async fn test_chain_id_validation() {
         let req = BalanceRequest::new(alice);
         
         let result = adapter.get_balance(&req).await;
-        if let Ok(_) = result {
+        if result.is_ok() {
             println!("Chain ID validation passed on mainnet fork");
         } else {
             println!("Skipping chain ID validation test - no Anvil fork detected");
@@ -128,3 +145,605 @@ async fn test_chain_id_validation() {
         println!("Skipping chain ID validation test - could not connect to Anvil");
     }
 }
+
+#[tokio::test]
+async fn test_read_only_balance_check() {
+    // This test requires a running Anvil fork with mainnet data
+    let adapter = FoundryAdapter::new("http://127.0.0.1:8545").await;
+    
+    if let Ok(adapter) = adapter {
+        // Reads must keep working when signing is disabled
+        let adapter = adapter.with_read_only(true);
+        let alice = AddressOrEns::from_address("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".to_string());
+        let req = BalanceRequest::new(alice);
+        
+        let result = adapter.get_balance(&req).await;
+        if let Ok(balance) = result {
+            assert!(balance.parse::<u128>().is_ok(), "ETH balance should be a valid number");
+            println!("Read-only Alice ETH balance: {} wei", balance);
+        } else {
+            println!("Skipping read-only balance test - no Anvil fork detected");
+        }
+    } else {
+        println!("Skipping read-only balance test - could not connect to Anvil");
+    }
+}
+
+#[tokio::test]
+async fn test_usdc_metadata_and_formatting() {
+    // This test requires a running Anvil fork with mainnet data
+    let adapter = FoundryAdapter::new("http://127.0.0.1:8545").await;
+
+    if let Ok(adapter) = adapter {
+        let usdc_token = Address::new("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".to_string());
+        let holder = Address::new("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".to_string());
+
+        let req = Erc20BalanceRequest::new(usdc_token.clone(), holder);
+        let (balance, metadata) = tokio::join!(adapter.erc20_balance_of(&req), adapter.erc20_metadata(&usdc_token));
+        if let (Ok(balance), Ok(metadata)) = (balance, metadata) {
+            assert_eq!(metadata.symbol.as_deref(), Some("USDC"));
+            assert_eq!(metadata.name.as_deref(), Some("USD Coin"));
+            assert_eq!(metadata.decimals, 6);
+            let formatted = domain::units::format_units(&balance, metadata.decimals as u32).unwrap();
+            assert_eq!(domain::units::parse_units(&formatted, 6).unwrap(), balance);
+
+            // Second lookup is served from the per-token cache
+            assert_eq!(adapter.erc20_metadata(&usdc_token).await.unwrap(), metadata);
+            println!("Alice USDC balance: {} ({:?})", formatted, metadata.symbol);
+        } else {
+            println!("Skipping USDC metadata test - no Anvil fork detected");
+        }
+    } else {
+        println!("Skipping USDC metadata test - could not connect to Anvil");
+    }
+}
+
+#[tokio::test]
+async fn test_resolve_ens_known_and_unregistered_names() {
+    // This test requires a running Anvil fork with mainnet data
+    let adapter = FoundryAdapter::new("http://127.0.0.1:8545").await;
+
+    if let Ok(adapter) = adapter {
+        match adapter.resolve_ens("vitalik.eth").await {
+            Ok(resolved) => {
+                let resolved = resolved.expect("vitalik.eth should resolve on a mainnet fork");
+                assert_eq!(resolved.as_str(), "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045");
+
+                // Unregistered names are a clean `None`, not an error
+                let missing = adapter.resolve_ens("surely-not-registered-chainagent-7f3a9c.eth").await;
+                assert!(matches!(missing, Ok(None)), "unregistered name should resolve to None, got {:?}", missing);
+            }
+            Err(_) => println!("Skipping ENS resolve test - no Anvil fork detected"),
+        }
+    } else {
+        println!("Skipping ENS resolve test - could not connect to Anvil");
+    }
+}
+
+#[tokio::test]
+async fn test_eth_call_returns_raw_data() {
+    // This test requires a running Anvil fork with mainnet data
+    let adapter = FoundryAdapter::new("http://127.0.0.1:8545").await;
+
+    if let Ok(adapter) = adapter {
+        // USDC decimals() selector
+        let req = domain::ContractCallRequest::new(
+            Address::new("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".to_string()),
+            "0x313ce567",
+        );
+        if let Ok(data) = adapter.eth_call(&req).await {
+            assert_eq!(data, format!("0x{:064x}", 6));
+        } else {
+            println!("Skipping eth_call test - no Anvil fork detected");
+        }
+    } else {
+        println!("Skipping eth_call test - could not connect to Anvil");
+    }
+}
+
+#[tokio::test]
+async fn test_send_to_non_payable_contract_is_rejected() {
+    // This test requires a running Anvil fork with mainnet data
+    let adapter = FoundryAdapter::new("http://127.0.0.1:8545").await;
+
+    if let Ok(adapter) = adapter {
+        // Router02's receive() only accepts ETH from WETH, so a plain transfer reverts
+        let req = domain::SendRequest::builder()
+            .from(Address::new("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".to_string()))
+            .to(Address::new("0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D".to_string()))
+            .amount_eth("0.01")
+            .simulate(true)
+            .build()
+            .unwrap();
+        match adapter.get_code_len(&CodeRequest::new(req.to().clone())).await {
+            Ok((true, _)) => {
+                let err = adapter.send_eth(&req).await.unwrap_err();
+                assert!(err.to_string().contains("rejects ETH transfers"), "unexpected error: {}", err);
+            }
+            _ => println!("Skipping non-payable recipient test - no Anvil fork detected"),
+        }
+    } else {
+        println!("Skipping non-payable recipient test - could not connect to Anvil");
+    }
+}
+
+#[tokio::test]
+async fn test_wait_for_balance_after_funding() {
+    // This test requires a running Anvil node (fork or plain)
+    let adapter = FoundryAdapter::new("http://127.0.0.1:8545").await;
+
+    if let Ok(adapter) = adapter {
+        let eve = Address::new("0x15d34aaf54267db7d7c367839aaf71a00a2c6a65".to_string());
+        let balance_req = BalanceRequest::new(AddressOrEns::Address(eve.clone()));
+        let Ok(before) = adapter.get_balance(&balance_req).await else {
+            println!("Skipping wait-for-balance test - no Anvil node detected");
+            return;
+        };
+        let send = domain::SendRequest::builder()
+            .from(Address::new("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".to_string()))
+            .to(eve)
+            .amount_eth("1")
+            .simulate(false)
+            .build()
+            .unwrap();
+        adapter.send_eth(&send).await.expect("funding transfer");
+
+        let target = (before.parse::<u128>().unwrap() + 1_000_000_000_000_000_000).to_string();
+        let started = std::time::Instant::now();
+        let after = adapter.wait_for_balance(&balance_req, &target, std::time::Duration::from_secs(10)).await.unwrap();
+        assert_eq!(after, target);
+        assert!(started.elapsed() < std::time::Duration::from_secs(2), "wait should return promptly once funded");
+    } else {
+        println!("Skipping wait-for-balance test - could not connect to Anvil");
+    }
+}
+
+#[tokio::test]
+async fn test_erc20_balance_of_eoa_is_not_an_erc20() {
+    // This test requires a running Anvil node (fork or plain)
+    let adapter = FoundryAdapter::new("http://127.0.0.1:8545").await;
+
+    if let Ok(adapter) = adapter {
+        // Bob is an EOA, so balanceOf returns no data
+        let bob = "0x70997970c51812dc3a010c7d01b50e0d17dc79c8";
+        let alice = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+        if adapter.get_code_len(&CodeRequest::new(Address::new(bob.to_string()))).await.is_err() {
+            println!("Skipping not-an-ERC-20 test - no Anvil node detected");
+            return;
+        }
+        let req = Erc20BalanceRequest::new(Address::new(bob.to_string()), Address::new(alice.to_string()));
+        let err = adapter.erc20_balance_of(&req).await.unwrap_err();
+        assert!(err.to_string().contains("is not an ERC-20 token"), "unexpected error: {}", err);
+    } else {
+        println!("Skipping not-an-ERC-20 test - could not connect to Anvil");
+    }
+}
+
+#[tokio::test]
+async fn test_simulated_send_estimates_inclusion_time() {
+    // This test requires a running Anvil fork with mainnet data
+    let adapter = FoundryAdapter::new("http://127.0.0.1:8545").await;
+
+    if let Ok(adapter) = adapter {
+        let req = domain::SendRequest::builder()
+            .from(Address::new("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".to_string()))
+            .to(Address::new("0x70997970c51812dc3a010c7d01b50e0d17dc79c8".to_string()))
+            .amount_eth("0.01")
+            .simulate(true)
+            .build()
+            .unwrap();
+        match adapter.send_eth(&req).await {
+            Ok(tx) => {
+                let seconds = tx.estimated_seconds();
+                assert!(seconds.is_some(), "simulation on a London fork should estimate inclusion time");
+                println!("Estimated inclusion in {:?}s", seconds);
+            }
+            Err(_) => println!("Skipping inclusion estimate test - no Anvil fork detected"),
+        }
+    } else {
+        println!("Skipping inclusion estimate test - could not connect to Anvil");
+    }
+}
+
+
+#[tokio::test]
+async fn test_draining_send_is_insufficient_funds() {
+    // This test requires a running Anvil node (fork or plain)
+    let adapter = FoundryAdapter::new("http://127.0.0.1:8545").await;
+
+    if let Ok(adapter) = adapter {
+        // Sending David's whole balance leaves nothing for gas
+        let david = Address::new("0x90f79bf6eb2c4f870365e785982e1f101e93b906".to_string());
+        let Ok(balance) = adapter.get_balance(&BalanceRequest::new(AddressOrEns::Address(david.clone()))).await else {
+            println!("Skipping insufficient funds test - no Anvil node detected");
+            return;
+        };
+        let req = domain::SendRequest::builder()
+            .from(david)
+            .to(Address::new("0x70997970c51812dc3a010c7d01b50e0d17dc79c8".to_string()))
+            .amount_eth(domain::units::wei_to_eth(&balance).unwrap())
+            .simulate(false)
+            .build()
+            .unwrap();
+        let err = adapter.send_eth(&req).await.unwrap_err();
+        assert!(err.to_string().contains("insufficient funds"), "unexpected error: {}", err);
+        assert!(err.to_string().contains(&format!("have {} wei", balance)), "unexpected error: {}", err);
+    } else {
+        println!("Skipping insufficient funds test - could not connect to Anvil");
+    }
+}
+
+#[tokio::test]
+async fn test_usdc_balances_for_many_holders() {
+    // This test requires a running Anvil fork with mainnet data
+    let adapter = FoundryAdapter::new("http://127.0.0.1:8545").await;
+
+    if let Ok(adapter) = adapter {
+        let usdc = Address::new("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".to_string());
+        let holders: Vec<Address> = [
+            "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266",
+            "0x70997970c51812dc3a010c7d01b50e0d17dc79c8",
+            "0x0000000000000000000000000000000000000000",
+            "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48",
+        ]
+        .into_iter()
+        .map(|h| Address::new(h.to_string()))
+        .collect();
+        let Ok(code) = adapter.get_code_len(&CodeRequest::new(usdc.clone())).await else {
+            println!("Skipping USDC multi-holder test - no Anvil node detected");
+            return;
+        };
+        if !code.0 {
+            println!("Skipping USDC multi-holder test - no Anvil fork detected");
+            return;
+        }
+        let results = adapter.erc20_balances_of(&usdc, &holders).await.expect("token address is valid");
+        assert_eq!(results.len(), holders.len());
+        for (holder, result) in holders.iter().zip(&results) {
+            let amount = result.as_ref().unwrap_or_else(|e| panic!("balance for {}: {}", holder.as_str(), e));
+            assert!(amount.parse::<u128>().is_ok(), "balance should be numeric");
+        }
+    } else {
+        println!("Skipping USDC multi-holder test - could not connect to Anvil");
+    }
+}
+
+#[tokio::test]
+async fn test_usdc_transfer_history_includes_known_transfer() {
+    // This test requires a running Anvil fork with mainnet data
+    let adapter = FoundryAdapter::new("http://127.0.0.1:8545").await;
+
+    if let Ok(adapter) = adapter {
+        let usdc = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+        let Ok(head) = adapter.raw_request("eth_blockNumber", serde_json::json!([])).await else {
+            println!("Skipping USDC transfer history test - no Anvil node detected");
+            return;
+        };
+        let head = u64::from_str_radix(head.as_str().unwrap().trim_start_matches("0x"), 16).unwrap();
+        // Pick any USDC transfer from the last few mainnet blocks as the known one
+        let transfer_topic = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+        let recent = adapter
+            .raw_request("eth_getLogs", serde_json::json!([{
+                "address": usdc,
+                "topics": [transfer_topic],
+                "fromBlock": format!("0x{:x}", head.saturating_sub(20)),
+                "toBlock": format!("0x{:x}", head)
+            }]))
+            .await;
+        let Some(known) = recent.ok().and_then(|logs| logs.as_array().and_then(|l| l.first().cloned())) else {
+            println!("Skipping USDC transfer history test - no Anvil fork detected");
+            return;
+        };
+        let sender = format!("0x{}", &known["topics"][1].as_str().unwrap()[26..]);
+        let block = u64::from_str_radix(known["blockNumber"].as_str().unwrap().trim_start_matches("0x"), 16).unwrap();
+
+        let transfers = adapter
+            .erc20_transfers(&Address::new(usdc.to_string()), &Address::new(sender.clone()), block, block)
+            .await
+            .expect("eth_getLogs on fork");
+        let tx_hash = known["transactionHash"].as_str().unwrap();
+        let found = transfers.iter().find(|t| t.tx_hash == tx_hash).expect("known transfer is returned");
+        assert_eq!(found.from.to_lowercase(), sender);
+        assert_eq!(found.block, block);
+    } else {
+        println!("Skipping USDC transfer history test - could not connect to Anvil");
+    }
+}
+
+#[tokio::test]
+async fn test_simulated_send_projects_balance_changes() {
+    // This test requires a running Anvil node (fork or plain)
+    let adapter = FoundryAdapter::new("http://127.0.0.1:8545").await;
+
+    if let Ok(adapter) = adapter {
+        let alice = Address::new("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".to_string());
+        let bob = Address::new("0x70997970c51812dc3a010c7d01b50e0d17dc79c8".to_string());
+        let Ok(alice_before) = adapter.get_balance(&BalanceRequest::new(AddressOrEns::Address(alice.clone()))).await else {
+            println!("Skipping dry-run diff test - no Anvil node detected");
+            return;
+        };
+        let req = domain::SendRequest::builder().from(alice).to(bob).amount_eth("1").simulate(true).build().unwrap();
+        let result = adapter.send_eth(&req).await.unwrap();
+        let [sender, recipient] = result.balance_changes() else {
+            panic!("expected sender and recipient changes, got {:?}", result.balance_changes());
+        };
+        let value: u128 = 1_000_000_000_000_000_000;
+        let sender_delta: u128 = sender.delta.strip_prefix('-').expect("sender is debited").parse().unwrap();
+        let gas_cost = sender_delta - value;
+        assert!(gas_cost > 0 && gas_cost < value / 100, "gas cost should be small but non-zero: {}", gas_cost);
+        assert_eq!(sender.before, alice_before);
+        assert_eq!(recipient.delta, format!("+{}", value));
+        assert_eq!(recipient.after.parse::<u128>().unwrap() - recipient.before.parse::<u128>().unwrap(), value);
+    } else {
+        println!("Skipping dry-run diff test - could not connect to Anvil");
+    }
+}
+
+#[tokio::test]
+async fn test_ws_transport_matches_http() {
+    // This test requires a running Anvil node (fork or plain); Anvil serves WebSockets on the HTTP port
+    let Ok(ws) = FoundryAdapter::new_ws("ws://127.0.0.1:8545").await else {
+        println!("Skipping WebSocket transport test - could not connect to Anvil");
+        return;
+    };
+    let http = FoundryAdapter::new("http://127.0.0.1:8545").await.unwrap();
+    assert!(ws.is_ws());
+    assert!(!http.is_ws());
+
+    let chain_id = http.chain_id().await.unwrap();
+    let ws = ws.with_expected_chain_id(chain_id);
+    assert_eq!(ws.chain_id().await.unwrap(), chain_id);
+    let alice = BalanceRequest::new(AddressOrEns::Address(Address::new("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".to_string())));
+    assert_eq!(ws.get_balance(&alice).await.unwrap(), http.get_balance(&alice).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_block_subscription_receives_mined_block() {
+    // This test requires a running Anvil node (fork or plain)
+    let Ok(adapter) = FoundryAdapter::new_ws("ws://127.0.0.1:8545").await else {
+        println!("Skipping block subscription test - could not connect to Anvil");
+        return;
+    };
+    use ethers_providers::{Middleware, StreamExt};
+    let blocks = adapter.subscribe_blocks().await.unwrap();
+    let mut blocks = Box::pin(blocks);
+    let rpc = ethers_providers::Provider::<ethers_providers::Http>::try_from("http://127.0.0.1:8545").unwrap();
+    let head = rpc.get_block_number().await.unwrap().as_u64();
+    let _: serde_json::Value = rpc.request("evm_mine", ()).await.unwrap();
+
+    let number = tokio::time::timeout(std::time::Duration::from_secs(10), blocks.next()).await.expect("a block within 10s");
+    assert!(number.unwrap() > head);
+}
+
+#[tokio::test]
+async fn test_concurrent_sends_from_one_account_get_distinct_nonces() {
+    // This test requires a running Anvil node (fork or plain)
+    let adapter = FoundryAdapter::new("http://127.0.0.1:8545").await;
+
+    if let Ok(adapter) = adapter {
+        let charlie = Address::new("0x3c44cdddb6a900fa2b585dd299e03d12fa4293bc".to_string());
+        if adapter.get_balance(&BalanceRequest::new(AddressOrEns::Address(charlie.clone()))).await.is_err() {
+            println!("Skipping concurrent sends test - no Anvil node detected");
+            return;
+        }
+        let send = |to: &str| {
+            domain::SendRequest::builder()
+                .from(charlie.clone())
+                .to(Address::new(to.to_string()))
+                .amount_eth("0.01")
+                .simulate(false)
+                .build()
+                .unwrap()
+        };
+        let (first, second) = (send("0x70997970c51812dc3a010c7d01b50e0d17dc79c8"), send("0x15d34aaf54267db7d7c367839aaf71a00a2c6a65"));
+        let other = adapter.clone();
+        let (a, b) = tokio::join!(adapter.send_eth(&first), other.send_eth(&second));
+        let (a, b) = (a.expect("first send"), b.expect("second send"));
+        assert_eq!(a.status(), Some(true));
+        assert_eq!(b.status(), Some(true));
+
+        use ethers_providers::Middleware;
+        let rpc = ethers_providers::Provider::<ethers_providers::Http>::try_from("http://127.0.0.1:8545").unwrap();
+        let nonce = |hash: &str| {
+            let rpc = rpc.clone();
+            let hash: ethers_core::types::H256 = hash.parse().unwrap();
+            async move { rpc.get_transaction(hash).await.unwrap().expect("mined tx").nonce }
+        };
+        assert_ne!(nonce(a.tx_hash()).await, nonce(b.tx_hash()).await);
+    } else {
+        println!("Skipping concurrent sends test - could not connect to Anvil");
+    }
+}
+
+#[tokio::test]
+async fn test_deployed_contract_has_code_at_returned_address() {
+    // This test requires a running Anvil node (fork or plain)
+    let adapter = FoundryAdapter::new("http://127.0.0.1:8545").await;
+
+    if let Ok(adapter) = adapter {
+        let eve = Address::new("0x15d34aaf54267db7d7c367839aaf71a00a2c6a65".to_string());
+        if adapter.get_balance(&BalanceRequest::new(AddressOrEns::Address(eve.clone()))).await.is_err() {
+            println!("Skipping deploy test - no Anvil node detected");
+            return;
+        }
+        // Init code returning a 10-byte runtime that stores 42 and returns it
+        let deploy = |simulate: bool| {
+            domain::DeployRequest::builder()
+                .from(eve.clone())
+                .bytecode("0x600a600c600039600a6000f3602a60005260206000f3")
+                .simulate(simulate)
+                .build()
+                .unwrap()
+        };
+        let simulated = adapter.deploy_contract(&deploy(true)).await.expect("simulated deploy");
+        assert!(simulated.tx_hash.is_none());
+        let deployed = adapter.deploy_contract(&deploy(false)).await.expect("deploy");
+        assert!(deployed.success);
+        assert_eq!(deployed.address, simulated.address);
+
+        let req = CodeRequest::new(Address::new(deployed.address.clone()));
+        assert_eq!(adapter.get_code_len(&req).await.expect("eth_getCode"), (true, 10));
+    } else {
+        println!("Skipping deploy test - could not connect to Anvil");
+    }
+}
+
+#[tokio::test]
+async fn test_send_waits_for_requested_confirmations() {
+    // This test requires a running Anvil node (fork or plain) with automine
+    let adapter = FoundryAdapter::new("http://127.0.0.1:8545").await;
+
+    if let Ok(adapter) = adapter {
+        let adapter = adapter.with_poll_interval(std::time::Duration::from_millis(100));
+        let bob = Address::new("0x70997970c51812dc3a010c7d01b50e0d17dc79c8".to_string());
+        if adapter.get_balance(&BalanceRequest::new(AddressOrEns::Address(bob.clone()))).await.is_err() {
+            println!("Skipping confirmations test - no Anvil node detected");
+            return;
+        }
+        let req = domain::SendRequest::builder()
+            .from(bob)
+            .to(Address::new("0x90f79bf6eb2c4f870365e785982e1f101e93b906".to_string()))
+            .amount_eth("0.01")
+            .simulate(false)
+            .confirmations(Some(2))
+            .build()
+            .unwrap();
+        // Automine puts the tx in its own block; the second confirmation needs another one
+        let send = adapter.send_eth(&req);
+        tokio::pin!(send);
+        let result = loop {
+            tokio::select! {
+                result = &mut send => break result.expect("send"),
+                _ = tokio::time::sleep(std::time::Duration::from_millis(300)) => {
+                    adapter.raw_request("evm_mine", serde_json::json!([])).await.expect("evm_mine");
+                }
+            }
+        };
+        assert_eq!(result.status(), Some(true));
+
+        use ethers_providers::Middleware;
+        let rpc = ethers_providers::Provider::<ethers_providers::Http>::try_from("http://127.0.0.1:8545").unwrap();
+        let hash: ethers_core::types::H256 = result.tx_hash().parse().unwrap();
+        let mined_in = rpc.get_transaction(hash).await.unwrap().expect("mined tx").block_number.unwrap();
+        assert!(rpc.get_block_number().await.unwrap() > mined_in, "returned before a second block");
+    } else {
+        println!("Skipping confirmations test - could not connect to Anvil");
+    }
+}
+
+#[tokio::test]
+async fn test_usdc_approve_raises_allowance() {
+    // This test requires a running Anvil fork with mainnet data
+    let adapter = FoundryAdapter::new("http://127.0.0.1:8545").await;
+
+    if let Ok(adapter) = adapter {
+        let usdc = Address::new("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".to_string());
+        let Ok(code) = adapter.get_code_len(&CodeRequest::new(usdc.clone())).await else {
+            println!("Skipping USDC approve test - no Anvil node detected");
+            return;
+        };
+        if !code.0 {
+            println!("Skipping USDC approve test - no Anvil fork detected");
+            return;
+        }
+        let owner = Address::new("0x90f79bf6eb2c4f870365e785982e1f101e93b906".to_string());
+        let spender = Address::new("0x15d34aaf54267db7d7c367839aaf71a00a2c6a65".to_string());
+        let before: u128 = adapter.erc20_allowance(&usdc, &owner, &spender).await.expect("allowance").parse().unwrap();
+        let raised = (before + 1_000_000).to_string();
+
+        // A simulated approve leaves the allowance alone
+        adapter.erc20_approve(&usdc, &owner, &spender, &raised, true).await.expect("simulated approve");
+        assert_eq!(adapter.erc20_allowance(&usdc, &owner, &spender).await.unwrap(), before.to_string());
+
+        let result = adapter.erc20_approve(&usdc, &owner, &spender, &raised, false).await.expect("approve");
+        assert_eq!(result.status(), Some(true));
+        assert_eq!(adapter.erc20_allowance(&usdc, &owner, &spender).await.unwrap(), raised);
+    } else {
+        println!("Skipping USDC approve test - could not connect to Anvil");
+    }
+}
+
+#[tokio::test]
+async fn test_usdc_transfer_between_funded_accounts() {
+    // This test requires a running Anvil fork with mainnet data
+    let adapter = FoundryAdapter::new("http://127.0.0.1:8545").await;
+
+    if let Ok(adapter) = adapter {
+        let usdc = Address::new("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".to_string());
+        let Ok(code) = adapter.get_code_len(&CodeRequest::new(usdc.clone())).await else {
+            println!("Skipping USDC transfer test - no Anvil node detected");
+            return;
+        };
+        if !code.0 {
+            println!("Skipping USDC transfer test - no Anvil fork detected");
+            return;
+        }
+        let (alice, bob) = ("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266", "0x70997970c51812dc3a010c7d01b50e0d17dc79c8");
+        // Fund Alice with 1000 USDC by writing her entry in the balances mapping (slot 9)
+        let slot = ethers_core::utils::keccak256(ethers_core::abi::encode(&[
+            ethers_core::abi::Token::Address(alice.parse().unwrap()),
+            ethers_core::abi::Token::Uint(9.into()),
+        ]));
+        let funded = format!("0x{:064x}", 1_000_000_000u64);
+        adapter
+            .raw_request("anvil_setStorageAt", serde_json::json!([usdc.as_str(), format!("{:?}", ethers_core::types::H256::from(slot)), funded]))
+            .await
+            .expect("anvil_setStorageAt");
+
+        let balance = |holder: &str| {
+            let req = Erc20BalanceRequest::new(usdc.clone(), Address::new(holder.to_string()));
+            let adapter = adapter.clone();
+            async move { adapter.erc20_balance_of(&req).await.unwrap().parse::<u128>().unwrap() }
+        };
+        let bob_before = balance(bob).await;
+        let req = domain::Erc20TransferRequest::builder()
+            .token(usdc.clone())
+            .from(Address::new(alice.to_string()))
+            .to(Address::new(bob.to_string()))
+            .amount("2.5")
+            .simulate(false)
+            .build()
+            .unwrap();
+        let result = adapter.erc20_transfer(&req).await.expect("transfer");
+        assert_eq!(result.status(), Some(true));
+        assert_eq!(balance(alice).await, 1_000_000_000 - 2_500_000);
+        assert_eq!(balance(bob).await, bob_before + 2_500_000);
+    } else {
+        println!("Skipping USDC transfer test - could not connect to Anvil");
+    }
+}
+
+#[tokio::test]
+async fn test_wrap_and_unwrap_eth_on_fork() {
+    // This test requires a running Anvil fork with mainnet data
+    let adapter = FoundryAdapter::new("http://127.0.0.1:8545").await;
+
+    if let Ok(adapter) = adapter {
+        let weth = Address::new("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2".to_string());
+        match adapter.get_code_len(&CodeRequest::new(weth.clone())).await {
+            Ok((true, _)) => {}
+            _ => {
+                println!("Skipping WETH wrap test - no Anvil fork detected");
+                return;
+            }
+        }
+        let alice = Address::new("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".to_string());
+        let eth = || async { adapter.get_balance(&BalanceRequest::new(AddressOrEns::from_address(alice.as_str().to_string()))).await.unwrap().parse::<u128>().unwrap() };
+        let weth_balance = || async { adapter.erc20_balance_of(&Erc20BalanceRequest::new(weth.clone(), alice.clone())).await.unwrap().parse::<u128>().unwrap() };
+        let fee = |result: &domain::TxResult| result.gas_used().unwrap() as u128 * result.effective_gas_price().unwrap() as u128;
+        let one_eth = 1_000_000_000_000_000_000u128;
+
+        let (eth_before, weth_before) = (eth().await, weth_balance().await);
+        let wrapped = adapter.wrap_eth(&alice, "1", false).await.expect("wrap");
+        assert_eq!(wrapped.status(), Some(true));
+        assert_eq!(weth_balance().await, weth_before + one_eth);
+        assert_eq!(eth().await, eth_before - one_eth - fee(&wrapped));
+
+        let eth_before = eth().await;
+        let unwrapped = adapter.unwrap_weth(&alice, "0.4", false).await.expect("unwrap");
+        assert_eq!(weth_balance().await, weth_before + one_eth * 6 / 10);
+        assert_eq!(eth().await, eth_before + one_eth * 4 / 10 - fee(&unwrapped));
+    } else {
+        println!("Skipping WETH wrap test - could not connect to Anvil");
+    }
+}
@@ -0,0 +1,141 @@
+//! Tests that snapshot and revert the whole Anvil node. They live in their own binary
+//! (test binaries run one after another) and hold `NODE_STATE`, so a revert never rolls
+//! back another test's transactions.
+use foundry_adapter::FoundryAdapter;
+use domain::{Address, BalanceRequest, AddressOrEns, CodeRequest, Erc20BalanceRequest, SendRequest};
+
+static NODE_STATE: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+#[tokio::test]
+async fn test_revert_restores_balance_after_send() {
+    // This test requires a running Anvil node
+    let adapter = FoundryAdapter::new("http://127.0.0.1:8545").await;
+
+    if let Ok(adapter) = adapter {
+        let _node = NODE_STATE.lock().await;
+        let Ok(snapshot) = adapter.snapshot().await else {
+            println!("Skipping snapshot test - no Anvil node detected");
+            return;
+        };
+        let (grace, henry) = ("0x976ea74026e726554db657fa54763abd0c3a0aa9", "0x14dc79964da2c08b23698b3d3cc7ca32193d9955");
+        let balance = |who: &str| {
+            let req = BalanceRequest::new(AddressOrEns::Address(Address::new(who.to_string())));
+            let adapter = adapter.clone();
+            async move { adapter.get_balance(&req).await.unwrap() }
+        };
+        let before = balance(grace).await;
+        let req = SendRequest::builder()
+            .from(Address::new(grace.to_string()))
+            .to(Address::new(henry.to_string()))
+            .amount_eth("1")
+            .simulate(false)
+            .build()
+            .unwrap();
+        adapter.send_eth(&req).await.expect("send");
+        assert_ne!(balance(grace).await, before);
+
+        assert!(adapter.revert(&snapshot).await.unwrap());
+        assert_eq!(balance(grace).await, before);
+        // Each snapshot can be reverted to only once
+        assert!(!adapter.revert(&snapshot).await.unwrap());
+    } else {
+        println!("Skipping snapshot test - could not connect to Anvil");
+    }
+}
+
+#[tokio::test]
+async fn test_bundle_preview_runs_approve_then_transfer_and_rolls_back() {
+    // This test requires a running Anvil fork with mainnet data
+    let adapter = FoundryAdapter::new("http://127.0.0.1:8545").await;
+
+    if let Ok(adapter) = adapter {
+        let adapter = adapter.with_impersonation(true);
+        let _node = NODE_STATE.lock().await;
+        let usdc = Address::new("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".to_string());
+        let Ok(code) = adapter.get_code_len(&CodeRequest::new(usdc.clone())).await else {
+            println!("Skipping bundle preview test - no Anvil node detected");
+            return;
+        };
+        if !code.0 {
+            println!("Skipping bundle preview test - no Anvil fork detected");
+            return;
+        }
+        let (frank, bob) = (Address::new("0x9965507d1a55bcc2695c58ba16fb37d819b0a4dc".to_string()), Address::new("0x70997970c51812dc3a010c7d01b50e0d17dc79c8".to_string()));
+        // Fund Frank with 100 USDC by writing his entry in the balances mapping (slot 9)
+        let slot = ethers_core::utils::keccak256(ethers_core::abi::encode(&[
+            ethers_core::abi::Token::Address(frank.as_str().parse().unwrap()),
+            ethers_core::abi::Token::Uint(9.into()),
+        ]));
+        adapter
+            .raw_request("anvil_setStorageAt", serde_json::json!([usdc.as_str(), format!("{:?}", ethers_core::types::H256::from(slot)), format!("0x{:064x}", 100_000_000u64)]))
+            .await
+            .expect("anvil_setStorageAt");
+
+        let balance_req = Erc20BalanceRequest::new(usdc.clone(), frank.clone());
+        let balance_before = adapter.erc20_balance_of(&balance_req).await.unwrap();
+        let allowance_before = adapter.erc20_allowance(&usdc, &frank, &bob).await.unwrap();
+        let steps = [
+            domain::BundleStep::Approve { token: usdc.clone(), owner: frank.clone(), spender: bob.clone(), amount: "5000000".to_string() },
+            domain::BundleStep::Transfer { token: usdc.clone(), from: frank.clone(), to: bob.clone(), amount: "1".to_string() },
+        ];
+        let results = adapter.simulate_bundle(&steps).await.expect("bundle preview");
+        assert_eq!(results.len(), 2);
+        for (result, op) in results.iter().zip(["approve", "transfer"]) {
+            assert_eq!(result.op, op);
+            assert!(result.success, "{:?}", result);
+            assert!(result.gas_used.is_some_and(|gas| gas > 21_000), "{:?}", result);
+        }
+
+        // The preview leaves no trace on the fork
+        assert_eq!(adapter.erc20_balance_of(&balance_req).await.unwrap(), balance_before);
+        assert_eq!(adapter.erc20_allowance(&usdc, &frank, &bob).await.unwrap(), allowance_before);
+    } else {
+        println!("Skipping bundle preview test - could not connect to Anvil");
+    }
+}
+
+#[tokio::test]
+async fn test_send_from_impersonated_whale() {
+    // This test requires a running Anvil fork with mainnet data
+    let adapter = FoundryAdapter::new("http://127.0.0.1:8545").await;
+
+    if let Ok(adapter) = adapter {
+        let adapter = adapter.with_impersonation(true);
+        let _node = NODE_STATE.lock().await;
+        // Binance's hot wallet: plenty of ETH on mainnet, and no key for it here
+        let whale = Address::new("0x28c6c06298d514db089934071355e5743bf21d60".to_string());
+        let bob = Address::new("0x70997970c51812dc3a010c7d01b50e0d17dc79c8".to_string());
+        let balance = |who: &Address| {
+            let req = BalanceRequest::new(AddressOrEns::Address(who.clone()));
+            let adapter = adapter.clone();
+            async move { adapter.get_balance(&req).await.unwrap().parse::<u128>().unwrap() }
+        };
+        match adapter.get_balance(&BalanceRequest::new(AddressOrEns::Address(whale.clone()))).await {
+            Ok(wei) if wei != "0" => {}
+            Ok(_) => {
+                println!("Skipping impersonation test - no Anvil fork detected");
+                return;
+            }
+            Err(_) => {
+                println!("Skipping impersonation test - no Anvil node detected");
+                return;
+            }
+        }
+        let snapshot = adapter.snapshot().await.expect("evm_snapshot");
+        let req = SendRequest::builder().from(whale.clone()).to(bob.clone()).amount_eth("1").simulate(false).build().unwrap();
+        // Without impersonation there is no key to sign with
+        assert!(adapter.send_eth(&req).await.is_err());
+
+        let bob_before = balance(&bob).await;
+        adapter.impersonate(&whale).await.expect("anvil_impersonateAccount");
+        let result = adapter.send_eth(&req).await.expect("send from impersonated whale");
+        assert!(result.gas_used().is_some());
+        assert_eq!(balance(&bob).await, bob_before + 1_000_000_000_000_000_000);
+
+        adapter.stop_impersonate(&whale).await.expect("anvil_stopImpersonatingAccount");
+        assert!(adapter.send_eth(&req).await.is_err());
+        assert!(adapter.revert(&snapshot).await.unwrap());
+    } else {
+        println!("Skipping impersonation test - could not connect to Anvil");
+    }
+}
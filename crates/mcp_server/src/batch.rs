@@ -0,0 +1,129 @@
+use domain::Toolbox;
+use serde_json::{json, Value};
+
+use crate::dto::{BalanceIn, CodeIn, Erc20BalanceIn, SendIn};
+
+/// Upper bound on operations per `/batch` call
+pub const MAX_BATCH_OPS: usize = 32;
+
+/// Run each operation in order. Failures are reported per item and never abort the
+/// rest of the batch: `{ "op", "ok": true, "result" }` or `{ "op", "ok": false, "error" }`.
+pub async fn run_batch<T: Toolbox + ?Sized>(toolbox: &T, ops: Vec<Value>) -> Vec<Value> {
+    let mut results = Vec::with_capacity(ops.len());
+    for item in ops {
+        let op = item.get("op").and_then(|v| v.as_str()).unwrap_or("").to_string();
+        let outcome = match dispatch(toolbox, &op, item).await {
+            Ok(result) => json!({ "op": op, "ok": true, "result": result }),
+            Err(error) => json!({ "op": op, "ok": false, "error": error }),
+        };
+        results.push(outcome);
+    }
+    results
+}
+
+async fn dispatch<T: Toolbox + ?Sized>(toolbox: &T, op: &str, payload: Value) -> Result<Value, String> {
+    match op {
+        "balance" => {
+            let input: BalanceIn = parse(payload)?;
+            primary_only(input.chain.as_deref())?;
+            let req = input.try_into().map_err(|e: anyhow::Error| e.to_string())?;
+            let response = toolbox.balance(req).await.map_err(|e| e.to_string())?;
+            Ok(json!({ "balance": response.wei() }))
+        }
+        "code" => {
+            let input: CodeIn = parse(payload)?;
+            primary_only(input.chain.as_deref())?;
+            let req = input.try_into().map_err(|e: anyhow::Error| e.to_string())?;
+            let response = toolbox.code(req).await.map_err(|e| e.to_string())?;
+            Ok(json!({ "deployed": response.deployed(), "bytecode_len": response.bytecode_len() }))
+        }
+        "erc20_balance_of" => {
+            let input: Erc20BalanceIn = parse(payload)?;
+            primary_only(input.chain.as_deref())?;
+            let req = input.try_into().map_err(|e: anyhow::Error| e.to_string())?;
+            let response = toolbox.erc20_balance_of(req).await.map_err(|e| e.to_string())?;
+            Ok(json!({ "amount": response.amount() }))
+        }
+        "send" => {
+            let input: SendIn = parse(payload)?;
+            primary_only(input.chain.as_deref())?;
+            let req: domain::SendRequest = input.try_into().map_err(|e: anyhow::Error| e.to_string())?;
+            let simulate = req.simulate();
+            let result = toolbox.send(req).await.map_err(|e| e.to_string())?;
+            Ok(json!(domain::SimResult::from_tx(&result, simulate)))
+        }
+        "" => Err("missing 'op'".to_string()),
+        other => Err(format!("unknown op: {}", other)),
+    }
+}
+
+/// Batches run on the primary chain; a per-op `chain` is refused rather than ignored
+fn primary_only(chain: Option<&str>) -> Result<(), String> {
+    match chain {
+        Some(chain) => Err(format!("'chain' is not supported in /batch (got '{}')", chain)),
+        None => Ok(()),
+    }
+}
+
+fn parse<T: serde::de::DeserializeOwned>(payload: Value) -> Result<T, String> {
+    serde_json::from_value(payload).map_err(|e| format!("invalid input: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use domain::*;
+
+    const DEAD: &str = "0x000000000000000000000000000000000000dEaD";
+
+    struct MockToolbox;
+
+    #[async_trait]
+    impl Toolbox for MockToolbox {
+        async fn balance(&self, _req: BalanceRequest) -> anyhow::Result<BalanceResponse> {
+            Ok(BalanceResponse::new("1000".to_string()))
+        }
+        async fn code(&self, req: CodeRequest) -> anyhow::Result<CodeResponse> {
+            if req.addr().as_str() == DEAD {
+                anyhow::bail!("rpc unavailable");
+            }
+            Ok(CodeResponse::new(true, 42))
+        }
+        async fn erc20_balance_of(&self, _req: Erc20BalanceRequest) -> anyhow::Result<Erc20BalanceResponse> {
+            Ok(Erc20BalanceResponse::new("7".to_string()))
+        }
+        async fn send(&self, _req: SendRequest) -> anyhow::Result<TxResult> {
+            Ok(TxResult::new(String::new(), Some(21_000), None))
+        }
+    }
+
+    #[tokio::test]
+    async fn batch_runs_mixed_ops_in_order() {
+        let ops = vec![
+            json!({ "op": "balance", "who": "vitalik.eth" }),
+            json!({ "op": "code", "addr": "0x0000000000000000000000000000000000000000" }),
+        ];
+        let results = run_batch(&MockToolbox, ops).await;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0], json!({ "op": "balance", "ok": true, "result": { "balance": "1000" } }));
+        assert_eq!(results[1], json!({ "op": "code", "ok": true, "result": { "deployed": true, "bytecode_len": 42 } }));
+    }
+
+    #[tokio::test]
+    async fn batch_isolates_per_item_errors() {
+        let ops = vec![
+            json!({ "op": "mint", "amount": "1" }),
+            json!({ "op": "code", "addr": DEAD }),
+            json!({ "op": "code" }),
+            json!({ "op": "balance", "who": "0x0000000000000000000000000000000000000000" }),
+            json!({ "op": "balance", "who": "vitalik.eth", "chain": "base" }),
+        ];
+        let results = run_batch(&MockToolbox, ops).await;
+        assert_eq!(results[0]["error"], "unknown op: mint");
+        assert_eq!(results[1]["error"], "rpc unavailable");
+        assert!(results[2]["error"].as_str().unwrap().starts_with("invalid input"));
+        assert_eq!(results[3]["ok"], true);
+        assert_eq!(results[4]["error"], "'chain' is not supported in /batch (got 'base')");
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use foundry_adapter::FoundryAdapter;
+
+use crate::token_list::canonical_chain;
+
+/// Name of the `RPC_URL` chain unless `CHAIN_NAME` says otherwise
+pub const DEFAULT_PRIMARY_CHAIN: &str = "ethereum";
+
+/// An extra network from `CHAINS`, e.g. `CHAINS=base` with `BASE_RPC_URL` and `BASE_CHAIN_ID`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChainConfig {
+    pub name: String,
+    pub rpc_url: String,
+    pub chain_id: u64,
+}
+
+impl ChainConfig {
+    pub fn from_env() -> anyhow::Result<Vec<Self>> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    pub fn from_vars(var: impl Fn(&str) -> Option<String>) -> anyhow::Result<Vec<Self>> {
+        let Some(chains) = var("CHAINS") else { return Ok(Vec::new()) };
+        chains
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                let prefix = name.to_uppercase().replace('-', "_");
+                let rpc_url = var(&format!("{}_RPC_URL", prefix)).ok_or_else(|| anyhow::anyhow!("chain '{}' needs {}_RPC_URL", name, prefix))?;
+                let chain_id = var(&format!("{}_CHAIN_ID", prefix))
+                    .and_then(|id| id.trim().parse().ok())
+                    .ok_or_else(|| anyhow::anyhow!("chain '{}' needs a numeric {}_CHAIN_ID", name, prefix))?;
+                Ok(Self { name: canonical_chain(name), rpc_url, chain_id })
+            })
+            .collect()
+    }
+}
+
+/// Adapters by chain name. Requests without a `chain` go to the primary chain; names
+/// are canonicalized like `/token_lookup` ones, so "op" and "optimism" match.
+#[derive(Clone)]
+pub struct ChainRegistry {
+    primary: String,
+    adapters: HashMap<String, FoundryAdapter>,
+}
+
+impl ChainRegistry {
+    pub fn new(primary: &str, adapter: FoundryAdapter) -> Self {
+        let primary = canonical_chain(primary);
+        Self { adapters: HashMap::from([(primary.clone(), adapter)]), primary }
+    }
+
+    pub fn with_chain(mut self, name: &str, adapter: FoundryAdapter) -> Self {
+        self.adapters.insert(canonical_chain(name), adapter);
+        self
+    }
+
+    pub fn primary(&self) -> &FoundryAdapter {
+        &self.adapters[&self.primary]
+    }
+
+    pub fn primary_name(&self) -> &str {
+        &self.primary
+    }
+
+    pub fn is_primary(&self, chain: Option<&str>) -> bool {
+        chain.is_none_or(|name| canonical_chain(name) == self.primary)
+    }
+
+    pub fn get(&self, chain: Option<&str>) -> anyhow::Result<&FoundryAdapter> {
+        let Some(name) = chain else { return Ok(self.primary()) };
+        self.adapters.get(&canonical_chain(name)).ok_or_else(|| {
+            let mut known: Vec<&str> = self.adapters.keys().map(String::as_str).collect();
+            known.sort_unstable();
+            anyhow::anyhow!("unknown chain '{}'; configured: {}", name, known.join(", "))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chains_come_from_per_name_variables() {
+        let vars = HashMap::from([
+            ("CHAINS", "base, op"),
+            ("BASE_RPC_URL", "https://base.example"),
+            ("BASE_CHAIN_ID", "8453"),
+            ("OP_RPC_URL", "https://op.example"),
+            ("OP_CHAIN_ID", "10"),
+        ]);
+        let chains = ChainConfig::from_vars(|name| vars.get(name).map(|v| v.to_string())).unwrap();
+        assert_eq!(chains, vec![
+            ChainConfig { name: "base".into(), rpc_url: "https://base.example".into(), chain_id: 8453 },
+            ChainConfig { name: "optimism".into(), rpc_url: "https://op.example".into(), chain_id: 10 },
+        ]);
+
+        assert!(ChainConfig::from_vars(|_| None).unwrap().is_empty());
+        let missing_id = HashMap::from([("CHAINS", "base"), ("BASE_RPC_URL", "https://base.example")]);
+        let err = ChainConfig::from_vars(|name| missing_id.get(name).map(|v| v.to_string())).unwrap_err();
+        assert!(err.to_string().contains("BASE_CHAIN_ID"), "{}", err);
+    }
+}
@@ -0,0 +1,135 @@
+use anyhow::Result;
+use baml_client::baml::BamlFunction;
+use baml_client::parser::{self, NlParser};
+use baml_client::provider::{ChatMessage, ChatProvider};
+use baml_client::provider_select::ProviderKind;
+use baml_client::transport::RecordReplayTransport;
+use domain::{Erc20TransferRequest, SendRequest, Toolbox};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::info;
+
+use crate::toolbox::ServerToolbox;
+
+#[derive(Debug, Deserialize)]
+pub struct ChatIn {
+    #[serde(default)]
+    pub messages: Vec<ChatMessage>,
+    pub query: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChatOut {
+    pub function: &'static str,
+    pub result: Value,
+    /// The request's messages plus this turn, ready to send back next time
+    pub messages: Vec<ChatMessage>,
+}
+
+/// Server-side agent loop for stateless clients: parse the query with the configured
+/// LLM, run the chosen tool against this server's toolbox, and return the new history.
+/// Nobody confirms what the LLM picks (and the client supplies the history it reads), so
+/// sends and token transfers only ever simulate; `/prepare_send` + `/commit_send` broadcast.
+pub struct ChatAgent {
+    parser: NlParser<Box<dyn ChatProvider>>,
+}
+
+impl ChatAgent {
+    pub fn new(parser: NlParser<Box<dyn ChatProvider>>) -> Self {
+        Self { parser }
+    }
+
+    /// `None` unless `ENABLE_CHAT=1`. The provider comes from `PROVIDER` (and `CHAT_MODEL`),
+    /// with the same key checks as the CLI, so a missing API key fails at startup.
+    pub fn from_env() -> Result<Option<Self>> {
+        let enabled = std::env::var("ENABLE_CHAT").ok().map(|v| v == "1").unwrap_or(false);
+        if !enabled {
+            return Ok(None);
+        }
+        let model = std::env::var("CHAT_MODEL").ok().filter(|m| !m.trim().is_empty());
+        let kind = ProviderKind::resolve(false, None, std::env::var("PROVIDER").ok().as_deref(), model.as_deref())?;
+        let provider = kind.build(|var| std::env::var(var).ok(), RecordReplayTransport::from_env()?)?;
+        info!("/chat enabled with LLM provider {}", provider.name());
+        let baml_enabled = std::env::var("ENABLE_BAML").ok().map(|v| v == "1").unwrap_or(false);
+        let parser = NlParser::new_with_baml(provider, baml_enabled)
+            .with_system_prompt(parser::resolve_system_prompt(None)?)
+            .with_model(model.unwrap_or_else(|| kind.default_model().to_string()));
+        Ok(Some(Self::new(parser)))
+    }
+
+    pub async fn run(&self, toolbox: &ServerToolbox, chat_in: ChatIn) -> Result<ChatOut> {
+        let function = self.parser.parse_query_with_history(&chat_in.query, &chat_in.messages).await?;
+        let result = match &function {
+            BamlFunction::Sequence(steps) => {
+                let mut done = Vec::with_capacity(steps.len());
+                for (i, step) in steps.iter().enumerate() {
+                    let result = run_step(toolbox, step)
+                        .await
+                        .map_err(|e| anyhow::anyhow!("step {} of {} ({}) failed: {}", i + 1, steps.len(), step.name(), e))?;
+                    done.push(json!({ "function": step.name(), "result": result }));
+                }
+                json!({ "steps": done })
+            }
+            single => run_step(toolbox, single).await?,
+        };
+
+        let reply = match &function {
+            BamlFunction::Chat(text) => text.clone(),
+            _ => result.to_string(),
+        };
+        let mut messages = chat_in.messages;
+        messages.push(ChatMessage { role: "user".to_string(), content: chat_in.query });
+        messages.push(ChatMessage { role: "assistant".to_string(), content: reply });
+        Ok(ChatOut { function: function.name(), result, messages })
+    }
+}
+
+async fn run_step(toolbox: &ServerToolbox, function: &BamlFunction) -> Result<Value> {
+    Ok(match function {
+        BamlFunction::Chat(text) => json!({ "message": text }),
+        BamlFunction::Balance(req) => json!({ "balance": toolbox.balance(req.clone()).await?.wei() }),
+        BamlFunction::Code(req) => {
+            let code = toolbox.code(req.clone()).await?;
+            json!({ "deployed": code.deployed(), "bytecode_len": code.bytecode_len() })
+        }
+        BamlFunction::Erc20Balance(req) => json!({ "amount": toolbox.erc20_balance_of(req.clone()).await?.amount() }),
+        BamlFunction::Send(req) => {
+            let req = simulated_send(req)?;
+            let tx = toolbox.send(req).await?;
+            json!(domain::SimResult::from_tx(&tx, true))
+        }
+        BamlFunction::Erc20Transfer(req) => {
+            let req = simulated_transfer(req)?;
+            let tx = toolbox.erc20_transfer(req).await?;
+            json!(domain::SimResult::from_tx(&tx, true))
+        }
+        // The server loads no tools config, so the parser never produces these
+        BamlFunction::Custom(call) => anyhow::bail!("declarative tool {} is not available in /chat", call.tool),
+        BamlFunction::Sequence(_) => anyhow::bail!("nested sequences are not supported"),
+    })
+}
+
+/// `req` with `simulate` forced on, whatever the LLM asked for
+fn simulated_send(req: &SendRequest) -> Result<SendRequest> {
+    SendRequest::builder()
+        .from(req.from().clone())
+        .to(req.to().clone())
+        .amount_eth(req.amount_eth())
+        .simulate(true)
+        .fork_block(req.fork_block())
+        .confirmations(req.confirmations())
+        .tx_type(req.tx_type())
+        .build()
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+fn simulated_transfer(req: &Erc20TransferRequest) -> Result<Erc20TransferRequest> {
+    Erc20TransferRequest::builder()
+        .token(req.token().clone())
+        .from(req.from().clone())
+        .to(req.to().clone())
+        .amount(req.amount())
+        .simulate(true)
+        .build()
+        .map_err(|e| anyhow::anyhow!(e))
+}
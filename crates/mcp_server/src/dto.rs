@@ -1,37 +1,68 @@
 use domain::{
-    Address, AddressOrEns, BalanceRequest, CodeRequest, Erc20BalanceRequest, SendRequest, SendRequestBuilder,
+    looks_like_address, looks_like_ens, Address, AddressOrEns, BalanceRequest, BundleStep, CodeRequest, DeployRequest, Erc20BalanceRequest, Erc20TransferRequest, SendRequest, SendRequestBuilder,
+    TxType,
 };
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BalanceIn {
     pub who: String,
+    /// Historical block number; latest when omitted
+    #[serde(default)]
+    pub block: Option<u64>,
+    /// Configured chain name; omitted for the primary chain
+    #[serde(default)]
+    pub chain: Option<String>,
 }
 
 impl TryFrom<BalanceIn> for BalanceRequest {
     type Error = anyhow::Error;
     fn try_from(value: BalanceIn) -> Result<Self, Self::Error> {
-        let who = if value.who.ends_with(".eth") {
-            AddressOrEns::from_ens(value.who)
+        let who = value.who.trim();
+        if who.is_empty() {
+            anyhow::bail!("'who' must not be empty");
+        }
+        let who = if looks_like_ens(who) {
+            AddressOrEns::from_ens(who.to_string())
+        } else if looks_like_address(who) {
+            AddressOrEns::from_address(who.to_string())
         } else {
-            AddressOrEns::from_address(value.who)
+            anyhow::bail!("'who' must be a 0x-prefixed 20-byte address or an ENS name, got '{}'", who);
         };
-        Ok(BalanceRequest::new(who))
+        Ok(BalanceRequest::new(who).with_block(value.block))
     }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct CodeIn { pub addr: String }
+pub struct CodeIn {
+    pub addr: String,
+    /// Configured chain name; omitted for the primary chain
+    #[serde(default)]
+    pub chain: Option<String>,
+}
 
 impl TryFrom<CodeIn> for CodeRequest {
     type Error = anyhow::Error;
     fn try_from(value: CodeIn) -> Result<Self, Self::Error> {
-        Ok(CodeRequest::new(Address::new(value.addr)))
+        let addr = value.addr.trim();
+        if addr.is_empty() {
+            anyhow::bail!("'addr' must not be empty");
+        }
+        if !looks_like_address(addr) {
+            anyhow::bail!("'addr' must be a 0x-prefixed 20-byte address, got '{}'", addr);
+        }
+        Ok(CodeRequest::new(Address::new(addr.to_string())))
     }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct Erc20BalanceIn { pub token: String, pub holder: String }
+pub struct Erc20BalanceIn {
+    pub token: String,
+    pub holder: String,
+    /// Configured chain name; omitted for the primary chain
+    #[serde(default)]
+    pub chain: Option<String>,
+}
 
 impl TryFrom<Erc20BalanceIn> for Erc20BalanceRequest {
     type Error = anyhow::Error;
@@ -40,6 +71,106 @@ impl TryFrom<Erc20BalanceIn> for Erc20BalanceRequest {
     }
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Erc20AllowanceIn {
+    pub token: String,
+    pub owner: String,
+    pub spender: String,
+    /// Configured chain name; omitted for the primary chain
+    #[serde(default)]
+    pub chain: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Erc20ApproveIn {
+    pub token: String,
+    pub owner: String,
+    pub spender: String,
+    /// Base units (no decimals applied)
+    pub amount: String,
+    pub simulate: Option<bool>,
+    /// Configured chain name; omitted for the primary chain
+    #[serde(default)]
+    pub chain: Option<String>,
+}
+
+impl Erc20ApproveIn {
+    /// Addresses must be 0x-hex and the amount a base-unit integer
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for (field, value) in [("token", &self.token), ("owner", &self.owner), ("spender", &self.spender)] {
+            if !looks_like_address(value) {
+                anyhow::bail!("'{}' must be a 0x-prefixed 20-byte address, got '{}'", field, value);
+            }
+        }
+        if self.amount.is_empty() || !self.amount.bytes().all(|b| b.is_ascii_digit()) {
+            anyhow::bail!("'amount' must be an integer in base units, got '{}'", self.amount);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Erc20TransferIn {
+    pub token: String,
+    pub from: String,
+    pub to: String,
+    /// Whole tokens, e.g. "1.5"; scaled by the token's decimals
+    pub amount: String,
+    pub simulate: Option<bool>,
+    /// Configured chain name; omitted for the primary chain
+    #[serde(default)]
+    pub chain: Option<String>,
+}
+
+/// Body of `/wrap` (ETH to WETH) and `/unwrap` (WETH to ETH)
+#[cfg(feature = "bonus_uniswap_v2")]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WrapIn {
+    pub from: String,
+    /// Decimal ETH, e.g. "0.5"; WETH has the same 18 decimals
+    pub amount_eth: String,
+    pub simulate: Option<bool>,
+    /// Configured chain name; omitted for the primary chain
+    #[serde(default)]
+    pub chain: Option<String>,
+}
+
+#[cfg(feature = "bonus_uniswap_v2")]
+impl WrapIn {
+    /// `from` must be 0x-hex and the amount a decimal ETH value
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if !looks_like_address(self.from.trim()) {
+            anyhow::bail!("'from' must be a 0x-prefixed 20-byte address, got '{}'", self.from);
+        }
+        domain::units::eth_to_wei(&self.amount_eth).map_err(|e| anyhow::anyhow!("'amount_eth' is not a decimal ETH amount: {}", e))?;
+        Ok(())
+    }
+}
+
+impl TryFrom<Erc20TransferIn> for Erc20TransferRequest {
+    type Error = anyhow::Error;
+    fn try_from(value: Erc20TransferIn) -> Result<Self, Self::Error> {
+        for (field, addr) in [("token", &value.token), ("from", &value.from), ("to", &value.to)] {
+            if !looks_like_address(addr.trim()) {
+                anyhow::bail!("'{}' must be a 0x-prefixed 20-byte address, got '{}'", field, addr);
+            }
+        }
+        let mut b = Erc20TransferRequest::builder()
+            .token(Address::new(value.token.trim().to_string()))
+            .from(Address::new(value.from.trim().to_string()))
+            .to(Address::new(value.to.trim().to_string()))
+            .amount(value.amount);
+        if let Some(sim) = value.simulate { b = b.simulate(sim); }
+        b.build().map_err(|e| anyhow::anyhow!(e))
+    }
+}
+
+/// Upper bound on holders per `/erc20_balances` call
+pub const MAX_ERC20_HOLDERS: usize = 500;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Erc20BalancesIn { pub token: String, pub holders: Vec<String> }
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SendIn {
     pub from: String,
@@ -47,6 +178,14 @@ pub struct SendIn {
     pub amount_eth: String,
     pub simulate: Option<bool>,
     pub fork_block: Option<u64>,
+    #[serde(default)]
+    pub confirmations: Option<u64>,
+    /// "legacy" or "eip1559"; omitted to auto-detect
+    #[serde(default)]
+    pub tx_type: Option<TxType>,
+    /// Configured chain name; omitted for the primary chain
+    #[serde(default)]
+    pub chain: Option<String>,
 }
 
 impl TryFrom<SendIn> for SendRequest {
@@ -57,7 +196,111 @@ impl TryFrom<SendIn> for SendRequest {
             .to(Address::new(value.to))
             .amount_eth(value.amount_eth);
         if let Some(sim) = value.simulate { b = b.simulate(sim); }
-        Ok(b.fork_block(value.fork_block).build().map_err(|e| anyhow::anyhow!(e))?)
+        b.fork_block(value.fork_block).confirmations(value.confirmations).tx_type(value.tx_type).build().map_err(|e| anyhow::anyhow!(e))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeployIn {
+    pub from: String,
+    /// 0x-prefixed init code
+    pub bytecode: String,
+    /// 0x-prefixed ABI-encoded constructor arguments, appended to the init code
+    #[serde(default)]
+    pub constructor_args: Option<String>,
+    #[serde(default)]
+    pub value_eth: Option<String>,
+    pub simulate: Option<bool>,
+    #[serde(default)]
+    pub confirmations: Option<u64>,
+}
+
+impl TryFrom<DeployIn> for DeployRequest {
+    type Error = anyhow::Error;
+    fn try_from(value: DeployIn) -> Result<Self, Self::Error> {
+        if !looks_like_address(value.from.trim()) {
+            anyhow::bail!("'from' must be a 0x-prefixed 20-byte address, got '{}'", value.from);
+        }
+        let mut b = DeployRequest::builder().from(Address::new(value.from.trim().to_string())).bytecode(value.bytecode);
+        if let Some(args) = value.constructor_args { b = b.constructor_args(args); }
+        if let Some(v) = value.value_eth { b = b.value_eth(v); }
+        if let Some(sim) = value.simulate { b = b.simulate(sim); }
+        b.confirmations(value.confirmations).build().map_err(|e| anyhow::anyhow!(e))
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct SnapshotIn {
+    /// Configured chain name; omitted for the primary chain
+    #[serde(default)]
+    pub chain: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct RevertIn {
+    /// Id returned by `/snapshot`
+    pub id: String,
+    /// Configured chain name; omitted for the primary chain
+    #[serde(default)]
+    pub chain: Option<String>,
+}
+
+/// Request body cap unless `MAX_BODY_BYTES` says otherwise; larger bodies get 413
+pub const DEFAULT_MAX_BODY_BYTES: usize = 256 * 1024;
+
+/// Upper bound on steps per `/simulate` bundle
+pub const MAX_BUNDLE_STEPS: usize = 16;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct SimulateIn {
+    pub steps: Vec<BundleStepIn>,
+    /// Configured chain name; omitted for the primary chain
+    #[serde(default)]
+    pub chain: Option<String>,
+}
+
+/// One bundle step, tagged by `op`; fields match the single-transaction endpoints
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BundleStepIn {
+    Send { from: String, to: String, amount_eth: String },
+    /// `amount` in base units, like `/erc20_approve`
+    Approve { token: String, owner: String, spender: String, amount: String },
+    /// `amount` in whole tokens, like `/erc20_transfer`
+    Transfer { token: String, from: String, to: String, amount: String },
+    /// Raw calldata, e.g. a router swap
+    Call { from: String, to: String, data: String, #[serde(default)] value_eth: Option<String> },
+}
+
+impl TryFrom<BundleStepIn> for BundleStep {
+    type Error = anyhow::Error;
+    fn try_from(value: BundleStepIn) -> Result<Self, Self::Error> {
+        let address = |field: &str, value: String| {
+            let value = value.trim().to_string();
+            if !looks_like_address(&value) {
+                anyhow::bail!("'{}' must be a 0x-prefixed 20-byte address, got '{}'", field, value);
+            }
+            Ok(Address::new(value))
+        };
+        Ok(match value {
+            BundleStepIn::Send { from, to, amount_eth } => BundleStep::Send { from: address("from", from)?, to: address("to", to)?, amount_eth },
+            BundleStepIn::Approve { token, owner, spender, amount } => {
+                if amount.is_empty() || !amount.bytes().all(|b| b.is_ascii_digit()) {
+                    anyhow::bail!("'amount' must be an integer in base units, got '{}'", amount);
+                }
+                BundleStep::Approve { token: address("token", token)?, owner: address("owner", owner)?, spender: address("spender", spender)?, amount }
+            }
+            BundleStepIn::Transfer { token, from, to, amount } => {
+                BundleStep::Transfer { token: address("token", token)?, from: address("from", from)?, to: address("to", to)?, amount }
+            }
+            BundleStepIn::Call { from, to, data, value_eth } => {
+                let hex = data.strip_prefix("0x").unwrap_or(&data);
+                if hex.len() % 2 != 0 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                    anyhow::bail!("'data' must be 0x-prefixed hex calldata");
+                }
+                BundleStep::Call { from: address("from", from)?, to: address("to", to)?, data, value_eth: value_eth.unwrap_or_else(|| "0".to_string()) }
+            }
+        })
     }
 }
 
@@ -82,30 +325,79 @@ mod tests {
 
     #[test]
     fn balance_in_to_request_handles_ens() {
-        let b = BalanceIn { who: "vitalik.eth".into() };
+        let b = BalanceIn { who: "vitalik.eth".into(), block: Some(1_000_000), chain: None };
         let br: BalanceRequest = b.try_into().unwrap();
         matches!(br.who(), AddressOrEns::Ens(_));
+        assert_eq!(br.block(), Some(1_000_000));
+    }
+
+    #[test]
+    fn balance_in_routes_non_eth_names_to_ens() {
+        for who in ["foo.xyz", "vitalik.eth", "alice.cb.id"] {
+            let br = BalanceRequest::try_from(BalanceIn { who: who.into(), block: None, chain: None }).unwrap();
+            assert_eq!(br.who(), &AddressOrEns::from_ens(who.into()));
+        }
+    }
+
+    #[test]
+    fn balance_in_rejects_empty_and_whitespace_who() {
+        for who in ["", "   ", "\t\n"] {
+            let err = BalanceRequest::try_from(BalanceIn { who: who.into(), block: None, chain: None }).unwrap_err();
+            assert_eq!(err.to_string(), "'who' must not be empty");
+        }
+    }
+
+    #[test]
+    fn balance_in_rejects_non_address_who() {
+        let err = BalanceRequest::try_from(BalanceIn { who: "0x1234".into(), block: None, chain: None }).unwrap_err();
+        assert!(err.to_string().contains("0x-prefixed 20-byte address"));
+        let br = BalanceRequest::try_from(BalanceIn { who: " 0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266 ".into(), block: None, chain: None }).unwrap();
+        assert_eq!(br.who(), &AddressOrEns::from_address("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".into()));
+    }
+
+    #[test]
+    fn code_in_rejects_empty_and_malformed_addr() {
+        assert!(CodeRequest::try_from(CodeIn { addr: "".into(), chain: None }).is_err());
+        assert!(CodeRequest::try_from(CodeIn { addr: "  ".into(), chain: None }).is_err());
+        assert!(CodeRequest::try_from(CodeIn { addr: "vitalik.eth".into(), chain: None }).is_err());
     }
 
     #[test]
     fn code_in_roundtrip() {
-        let c = CodeIn { addr: "0x0000000000000000000000000000000000000000".into() };
+        let c = CodeIn { addr: "0x0000000000000000000000000000000000000000".into(), chain: None };
         let cr: CodeRequest = c.try_into().unwrap();
         assert_eq!(cr.addr().as_str(), "0x0000000000000000000000000000000000000000");
     }
 
     #[test]
     fn erc20_balance_in_roundtrip() {
-        let e = Erc20BalanceIn { token: "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".into(), holder: "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".into() };
+        let e = Erc20BalanceIn { token: "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".into(), holder: "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".into(), chain: None };
         let er: Erc20BalanceRequest = e.try_into().unwrap();
         assert!(er.token().as_str().starts_with("0x"));
     }
 
     #[test]
     fn send_in_defaults_simulate_true_when_missing() {
-        let s = SendIn { from: "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".into(), to: "0x70997970c51812dc3a010c7d01b50e0d17dc79c8".into(), amount_eth: "1.0".into(), simulate: None, fork_block: None };
+        let s = SendIn { from: "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".into(), to: "0x70997970c51812dc3a010c7d01b50e0d17dc79c8".into(), amount_eth: "1.0".into(), simulate: None, fork_block: None, confirmations: None, tx_type: None, chain: None };
         let sr: SendRequest = s.try_into().unwrap();
         assert!(sr.simulate());
     }
+
+    #[test]
+    fn send_in_carries_forced_tx_type() {
+        let s: SendIn = serde_json::from_value(serde_json::json!({
+            "from": "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266",
+            "to": "0x70997970c51812dc3a010c7d01b50e0d17dc79c8",
+            "amount_eth": "1",
+            "tx_type": "legacy"
+        }))
+        .unwrap();
+        let sr: SendRequest = s.try_into().unwrap();
+        assert_eq!(sr.tx_type(), Some(TxType::Legacy));
+        assert!(serde_json::from_value::<SendIn>(serde_json::json!({
+            "from": "0x0", "to": "0x0", "amount_eth": "1", "tx_type": "type2"
+        }))
+        .is_err());
+    }
 }
 
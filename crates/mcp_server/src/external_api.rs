@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
 use anyhow::Result;
+use domain::http::HttpTimeouts;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -16,6 +17,7 @@ pub struct TokenInfo {
 pub struct TokenLookupClient {
     base_url: String,
     http: reqwest::Client,
+    timeouts: HttpTimeouts,
     cache_ttl: Duration,
     cache: HashMap<String, (TokenInfo, Instant)>,
 }
@@ -24,12 +26,20 @@ impl TokenLookupClient {
     pub fn new(base_url: impl Into<String>, cache_ttl_seconds: u64) -> Self {
         Self {
             base_url: base_url.into(),
-            http: reqwest::Client::new(),
+            http: http_client(HttpTimeouts::default()),
+            timeouts: HttpTimeouts::default(),
             cache_ttl: Duration::from_secs(cache_ttl_seconds),
             cache: HashMap::new(),
         }
     }
 
+    /// A lookup that hits a timeout fails with `domain::http::TimedOut` instead of retrying
+    pub fn with_timeouts(mut self, timeouts: HttpTimeouts) -> Self {
+        self.http = http_client(timeouts);
+        self.timeouts = timeouts;
+        self
+    }
+
     fn cache_key(symbol: &str, chain: &str) -> String {
         format!("{}::{}", chain.to_lowercase(), symbol.to_uppercase())
     }
@@ -47,7 +57,7 @@ impl TokenLookupClient {
         let url = format!("{}/tokens", self.base_url.trim_end_matches('/'));
         let req = self
             .http
-            .get(url)
+            .get(&url)
             .query(&[("symbol", symbol), ("chain", chain)]);
 
         let mut attempt = 0;
@@ -75,6 +85,7 @@ impl TokenLookupClient {
                         break None;
                     }
                 }
+                Err(e) if e.is_timeout() => return Err(self.timeouts.timed_out(url, e.is_connect()).into()),
                 Err(_) if attempt < 2 => {
                     tokio::time::sleep(Duration::from_millis(100 * attempt)).await;
                     continue;
@@ -91,6 +102,14 @@ impl TokenLookupClient {
     }
 }
 
+fn http_client(timeouts: HttpTimeouts) -> reqwest::Client {
+    reqwest::Client::builder()
+        .connect_timeout(timeouts.connect)
+        .timeout(timeouts.request)
+        .build()
+        .expect("reqwest client with timeouts")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -139,4 +158,20 @@ mod tests {
         let res = client.lookup_by_symbol("FOO", "ethereum").await.unwrap();
         assert!(res.is_none());
     }
+
+    #[tokio::test]
+    async fn slow_api_fails_with_a_timeout_error() {
+        let server = MockServer::start();
+        let slow = server.mock(|when, then| {
+            when.method(GET).path("/tokens");
+            then.status(200).delay(Duration::from_secs(2)).json_body(serde_json::json!({}));
+        });
+        let timeouts = HttpTimeouts { connect: Duration::from_secs(1), request: Duration::from_millis(200) };
+        let mut client = TokenLookupClient::new(server.base_url(), 60).with_timeouts(timeouts);
+        let err = client.lookup_by_symbol("USDC", "ethereum").await.unwrap_err();
+        let timed_out = err.downcast_ref::<domain::http::TimedOut>().unwrap_or_else(|| panic!("expected a timeout, got {:#}", err));
+        assert_eq!(timed_out.after, timeouts.request);
+        // Timeouts are not retried
+        slow.assert_hits(1);
+    }
 }
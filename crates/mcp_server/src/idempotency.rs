@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+/// How long a `/send` result is replayed for its `Idempotency-Key`; `IDEMPOTENCY_TTL` overrides it
+pub const DEFAULT_IDEMPOTENCY_TTL_SECS: u64 = 3600;
+
+struct Entry {
+    request: String,
+    /// `None` while the send is running; a second one must not broadcast alongside it
+    result: Option<Value>,
+    /// When the key was claimed, then when its send completed
+    at: Instant,
+}
+
+/// What a `/send` with an `Idempotency-Key` should do
+#[derive(Debug, PartialEq)]
+pub enum Claim {
+    /// First use of the key: broadcast, then `complete` or `release` it
+    Proceed,
+    /// The key already sent this request: reply with its result instead
+    Replay(Value),
+    /// The same key is still broadcasting
+    InFlight,
+    /// The key was used for a different request
+    Mismatch,
+}
+
+/// Completed `/send` results keyed by sender and `Idempotency-Key`, so a client that
+/// retries after a timeout gets the first broadcast back instead of sending twice.
+/// Keys are scoped per sender, so two wallets cannot collide on the same key.
+pub struct IdempotentSends {
+    inner: Mutex<HashMap<(String, String), Entry>>,
+    ttl: Duration,
+}
+
+impl Default for IdempotentSends {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(DEFAULT_IDEMPOTENCY_TTL_SECS))
+    }
+}
+
+impl IdempotentSends {
+    pub fn new(ttl: Duration) -> Self {
+        Self { inner: Mutex::new(HashMap::new()), ttl }
+    }
+
+    /// Reserve `key` for `from`. `request` describes the send (anything that must match
+    /// for a retry to count as the same send).
+    pub fn claim(&self, from: &str, key: &str, request: &str) -> Claim {
+        let mut map = self.inner.lock().unwrap();
+        map.retain(|_, entry| entry.at.elapsed() < self.ttl);
+        let slot = (from.to_lowercase(), key.to_string());
+        match map.get(&slot) {
+            Some(entry) if entry.request != request => Claim::Mismatch,
+            Some(Entry { result: Some(result), .. }) => Claim::Replay(result.clone()),
+            Some(_) => Claim::InFlight,
+            None => {
+                map.insert(slot, Entry { request: request.to_string(), result: None, at: Instant::now() });
+                Claim::Proceed
+            }
+        }
+    }
+
+    /// Record the result of a claimed send for replay.
+    pub fn complete(&self, from: &str, key: &str, result: &Value) {
+        let mut map = self.inner.lock().unwrap();
+        if let Some(entry) = map.get_mut(&(from.to_lowercase(), key.to_string())) {
+            entry.result = Some(result.clone());
+            entry.at = Instant::now();
+        }
+    }
+
+    /// Free a claimed key whose send failed, so the client can retry it.
+    pub fn release(&self, from: &str, key: &str) {
+        let mut map = self.inner.lock().unwrap();
+        let slot = (from.to_lowercase(), key.to_string());
+        if map.get(&slot).is_some_and(|entry| entry.result.is_none()) {
+            map.remove(&slot);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const ALICE: &str = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+    const BOB: &str = "0x70997970c51812dc3a010c7d01b50e0d17dc79c8";
+
+    #[test]
+    fn a_key_sends_once_and_then_replays() {
+        let store = IdempotentSends::default();
+        assert_eq!(store.claim(ALICE, "k1", "0.5 to bob"), Claim::Proceed);
+        assert_eq!(store.claim(ALICE, "k1", "0.5 to bob"), Claim::InFlight);
+        store.complete(ALICE, "k1", &json!({ "tx_hash": "0xfeed" }));
+
+        assert_eq!(store.claim(&ALICE.to_uppercase().replace("0X", "0x"), "k1", "0.5 to bob"), Claim::Replay(json!({ "tx_hash": "0xfeed" })));
+        assert_eq!(store.claim(ALICE, "k1", "9 to bob"), Claim::Mismatch);
+        assert_eq!(store.claim(BOB, "k1", "0.5 to alice"), Claim::Proceed, "keys are scoped per sender");
+    }
+
+    #[test]
+    fn failed_sends_free_the_key_and_results_expire() {
+        let store = IdempotentSends::default();
+        assert_eq!(store.claim(ALICE, "k1", "send"), Claim::Proceed);
+        store.release(ALICE, "k1");
+        assert_eq!(store.claim(ALICE, "k1", "send"), Claim::Proceed);
+
+        let expiring = IdempotentSends::new(Duration::ZERO);
+        assert_eq!(expiring.claim(ALICE, "k1", "send"), Claim::Proceed);
+        expiring.complete(ALICE, "k1", &json!({ "tx_hash": "0xfeed" }));
+        assert_eq!(expiring.claim(ALICE, "k1", "send"), Claim::Proceed);
+    }
+}
@@ -1,32 +1,46 @@
+mod batch;
+mod chains;
+mod chat;
 mod dto;
 mod facade;
 mod toolbox;
 #[cfg(feature = "bonus_uniswap_v2")]
 mod uniswap_v2;
 mod external_api;
+mod idempotency;
+mod prepared;
+mod rpc_passthrough;
 mod sessions;
+mod token_list;
 
 use axum::{
-    extract::{Json, Query, State},
-    http::StatusCode,
+    extract::{DefaultBodyLimit, Json, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::sse::{Event, KeepAlive, Sse},
     response::Json as ResponseJson,
     routing::post,
     Router,
 };
-use dto::{BalanceIn, CodeIn, Erc20BalanceIn, SendIn, TokenLookupIn, TokenLookupOut};
-use foundry_adapter::FoundryAdapter;
+use dto::{BalanceIn, CodeIn, DeployIn, Erc20AllowanceIn, Erc20ApproveIn, Erc20BalanceIn, Erc20TransferIn, Erc20BalancesIn, RevertIn, SendIn, SimulateIn, SnapshotIn, TokenLookupIn, TokenLookupOut, MAX_BUNDLE_STEPS, MAX_ERC20_HOLDERS};
+use foundry_adapter::{AdapterError, CacheConfig, FoundryAdapter};
+use futures_util::{Stream, StreamExt};
 use serde_json::{json, Value};
 use std::sync::Arc;
-use toolbox::ServerToolbox;
+use toolbox::{ChainToolbox, ServerToolbox};
+use domain::http::HttpTimeouts;
 use domain::Toolbox;
-use tracing::{info, error};
+use tracing::{info, error, warn};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     println!("DEBUG: Server starting...");
-    // Load .env first so RPC_URL and others are available without exporting in shell
+    // Load ENV_FILE profile over .env so RPC_URL and others are available without exporting
+    // in shell; dotenvy never overwrites, so the profile is loaded first to take precedence
+    if let Ok(profile) = std::env::var("ENV_FILE") {
+        dotenvy::from_path(&profile).map_err(|e| anyhow::anyhow!("failed to load ENV_FILE {}: {}", profile, e))?;
+    }
     let _ = dotenvy::dotenv();
-    tracing_subscriber::fmt::init();
+    baml_client::logging::init(baml_client::logging::LogFormat::from_env(), false);
     println!("DEBUG: Tracing initialized");
     
     let bonus_enabled = std::env::var("BONUS").ok().map(|v| v == "1").unwrap_or(false);
@@ -39,43 +53,185 @@ async fn main() -> anyhow::Result<()> {
     // Initialize adapter with RPC URL from env or default to Anvil
     let rpc_url = std::env::var("RPC_URL").unwrap_or_else(|_| "http://127.0.0.1:8545".to_string());
     info!("Connecting to RPC at: {}", rpc_url);
-    let adapter = FoundryAdapter::new(&rpc_url).await?;
+    let read_only = std::env::var("READ_ONLY").ok().map(|v| v == "1").unwrap_or(false);
+    let mut adapter = connect_adapter(&rpc_url, read_only).await?;
+    if let Ok(registry) = std::env::var("ENS_REGISTRY") {
+        info!("ENS registry override: {}", registry);
+        adapter = adapter.with_ens_registry(&domain::Address::new(registry))?;
+    }
+    if read_only {
+        info!("mcp_server: READ_ONLY enabled; sends will be rejected");
+    }
     info!("FoundryAdapter initialized successfully");
-    let toolbox = Arc::new(ServerToolbox::new(adapter));
-    let session_store = Arc::new(sessions::SessionStore::new(3600, 50, 1000));
+    let api_key = std::env::var("ETHERSCAN_API_KEY").ok();
+    let explorer = if let Ok(url) = std::env::var("BLOCKSCOUT_URL") {
+        info!("Explorer API (Blockscout): {}", url);
+        Some(foundry_adapter::ExplorerClient::blockscout(url))
+    } else {
+        // ABI discovery targets the explorer for whichever chain the RPC reports
+        match adapter.chain_id().await {
+            Ok(chain_id) => {
+                let client = foundry_adapter::ExplorerClient::for_chain(api_key.clone(), chain_id);
+                match &client {
+                    Some(client) => info!("Explorer API for chain {}: {} ({:?})", chain_id, client.base_url(), client.dialect()),
+                    None if api_key.is_some() => warn!("No known explorer API for chain {}; ABI lookups disabled", chain_id),
+                    None => {}
+                }
+                client
+            }
+            Err(e) => {
+                warn!("Could not detect chain id for explorer selection: {}", e);
+                None
+            }
+        }
+    }
+    .map(|client| client.with_timeouts(HttpTimeouts::from_env()));
+    let rpc_passthrough = rpc_passthrough::RpcPassthrough::from_env();
+    if rpc_passthrough.enabled {
+        warn!("mcp_server: ENABLE_RPC_PASSTHROUGH on; POST /rpc forwards allowlisted methods (send allowed: {})", rpc_passthrough.allow_send);
+    }
+    let chat_agent = chat::ChatAgent::from_env()?;
+    let prepared_ttl = std::env::var("PREPARED_SEND_TTL").ok().and_then(|v| v.parse().ok()).unwrap_or(prepared::DEFAULT_PREPARED_SEND_TTL_SECS);
+    let idempotency_ttl = std::env::var("IDEMPOTENCY_TTL").ok().and_then(|v| v.parse().ok()).unwrap_or(idempotency::DEFAULT_IDEMPOTENCY_TTL_SECS);
+    let max_body_bytes = std::env::var("MAX_BODY_BYTES").ok().and_then(|v| v.parse().ok()).unwrap_or(dto::DEFAULT_MAX_BODY_BYTES);
+    let primary_chain = std::env::var("CHAIN_NAME").unwrap_or_else(|_| chains::DEFAULT_PRIMARY_CHAIN.to_string());
+    let mut chain_registry = chains::ChainRegistry::new(&primary_chain, adapter);
+    for chain in chains::ChainConfig::from_env()? {
+        info!("Chain {} (id {}) at {}", chain.name, chain.chain_id, chain.rpc_url);
+        let adapter = connect_adapter(&chain.rpc_url, read_only).await?.with_expected_chain_id(chain.chain_id);
+        chain_registry = chain_registry.with_chain(&chain.name, adapter);
+    }
+    info!("Primary chain: {}", chain_registry.primary_name());
+    let toolbox = Arc::new(
+        ServerToolbox::with_chains(chain_registry)
+            .with_rpc_passthrough(rpc_passthrough)
+            .with_chat_agent(chat_agent)
+            .with_explorer(explorer)
+            .with_prepared_send_ttl(std::time::Duration::from_secs(prepared_ttl))
+            .with_idempotency_ttl(std::time::Duration::from_secs(idempotency_ttl))
+            .with_max_body_bytes(max_body_bytes),
+    );
+    let session_store = Arc::new(sessions::SessionStore::from_env());
     info!("ServerToolbox created");
     
-    let app = Router::new()
+    let app = router(toolbox, session_store);
+
+    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await?;
+    info!("Server listening on http://0.0.0.0:3000");
+    
+    axum::serve(listener, app).await?;
+    
+    Ok(())
+}
+
+/// Adapter for one RPC URL with the server-wide settings applied
+async fn connect_adapter(rpc_url: &str, read_only: bool) -> anyhow::Result<FoundryAdapter> {
+    // ws:// and wss:// URLs connect over a WebSocket, which block subscriptions need
+    let timeouts = HttpTimeouts::from_env();
+    let adapter = if rpc_url.starts_with("ws://") || rpc_url.starts_with("wss://") {
+        FoundryAdapter::new_ws_with_timeouts(rpc_url, timeouts).await?
+    } else {
+        FoundryAdapter::new_with_timeouts(rpc_url, timeouts).await?
+    };
+    let mut adapter = adapter.with_read_only(read_only).with_cache_config(CacheConfig::from_env()).with_env_signers()?;
+    if let Some(secs) = std::env::var("CONFIRMATION_TIMEOUT_SECS").ok().and_then(|v| v.parse().ok()) {
+        adapter = adapter.with_confirmation_timeout(std::time::Duration::from_secs(secs));
+    }
+    // Bundle previews impersonate their senders, so they need this (dev nodes only)
+    if std::env::var("ALLOW_IMPERSONATION").is_ok_and(|v| v == "1") {
+        adapter = adapter.with_impersonation(true);
+    }
+    Ok(adapter)
+}
+
+/// Bodies are typed per route, so a body of the wrong shape gets 422 (400 for invalid
+/// JSON) before its handler runs, and one over the toolbox's size limit gets 413
+fn router(toolbox: Arc<ServerToolbox>, session_store: Arc<sessions::SessionStore>) -> Router {
+    let body_limit = DefaultBodyLimit::max(toolbox.max_body_bytes());
+    let router = Router::new();
+    #[cfg(feature = "bonus_uniswap_v2")]
+    let router = router.route("/wrap", post(handle_wrap)).route("/unwrap", post(handle_unwrap));
+    router
+        .route("/health", axum::routing::get(handle_health))
+        .route("/version", axum::routing::get(handle_version))
+        .route("/fees", axum::routing::get(handle_fees))
+        .route("/blocks/stream", axum::routing::get(handle_blocks_stream))
         .route("/balance", post(handle_balance))
         .route("/code", post(handle_code))
         .route("/erc20_balance_of", post(handle_erc20_balance))
+        .route("/erc20_balances", post(handle_erc20_balances))
+        .route("/erc20_allowance", post(handle_erc20_allowance))
+        .route("/erc20_approve", post(handle_erc20_approve))
+        .route("/erc20_transfer", post(handle_erc20_transfer))
+        .route("/erc20_info", axum::routing::get(handle_erc20_info))
+        .route("/contract_info", axum::routing::get(handle_contract_info))
         .route("/send", post(handle_send))
+        .route("/estimate_gas", post(handle_estimate_gas))
+        .route("/send_raw", post(handle_send_raw))
+        .route("/deploy", post(handle_deploy))
+        .route("/prepare_send", post(handle_prepare_send))
+        .route("/commit_send", post(handle_commit_send))
+        .route("/batch", post(handle_batch))
+        .route("/simulate", post(handle_simulate))
+        .route("/snapshot", post(handle_snapshot))
+        .route("/revert", post(handle_revert))
         .route("/token_lookup", post(handle_token_lookup))
+        .route("/rpc", post(handle_rpc))
+        .route("/chat", post(handle_chat))
+        .route("/ens_resolve", axum::routing::get(handle_ens_resolve))
         .route("/session/get", axum::routing::get(handle_session_get))
         .route("/session/append", post(handle_session_append))
+        .route("/session/partial", axum::routing::get(handle_session_partial_get).post(handle_session_partial_set))
         .route("/session/partial_intent/get", axum::routing::get(handle_session_partial_get))
         .route("/session/partial_intent/set", post(handle_session_partial_set))
-        .with_state((toolbox, session_store));
-
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await?;
-    info!("Server listening on http://0.0.0.0:3000");
-    
-    axum::serve(listener, app).await?;
-    
-    Ok(())
+        .layer(body_limit)
+        .with_state((toolbox, session_store))
 }
 
 // HTTP Handlers
+async fn handle_health() -> ResponseJson<Value> {
+    ResponseJson(json!({ "status": "ok" }))
+}
+
+/// Build info so operators can tell which deployment they are talking to
+async fn handle_version() -> ResponseJson<Value> {
+    ResponseJson(json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "git_sha": env!("GIT_SHA"),
+        "features": enabled_features(),
+    }))
+}
+
+fn enabled_features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+    if cfg!(feature = "bonus_uniswap_v2") {
+        features.push("bonus_uniswap_v2");
+    }
+    features
+}
+
+/// The tools for a request's `chain`; an unconfigured name is the client's mistake
+fn chain_tools<'a>(toolbox: &'a ServerToolbox, chain: Option<&str>) -> Result<ChainToolbox<'a>, StatusCode> {
+    toolbox.on_chain(chain).map_err(|e| {
+        warn!("{}", e);
+        StatusCode::BAD_REQUEST
+    })
+}
+
 async fn handle_balance(
     State((toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
-    Json(payload): Json<Value>,
+    Json(balance_in): Json<BalanceIn>,
 ) -> Result<ResponseJson<Value>, StatusCode> {
-    let balance_in: BalanceIn = serde_json::from_value(payload).map_err(|_| StatusCode::BAD_REQUEST)?;
+    let tools = chain_tools(&toolbox, balance_in.chain.as_deref())?;
     
     match balance_in.try_into() {
         Ok(req) => {
-            match toolbox.balance(req).await {
+            match tools.balance(req).await {
                 Ok(response) => Ok(ResponseJson(json!({ "balance": response.wei() }))),
+                Err(e) if matches!(e.downcast_ref::<AdapterError>(), Some(AdapterError::BlockInFuture { .. })) => {
+                    warn!("Invalid balance request: {}", e);
+                    Err(StatusCode::BAD_REQUEST)
+                }
                 Err(e) => {
                     error!("Balance error: {}", e);
                     Err(StatusCode::INTERNAL_SERVER_ERROR)
@@ -91,13 +247,13 @@ async fn handle_balance(
 
 async fn handle_code(
     State((toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
-    Json(payload): Json<Value>,
+    Json(code_in): Json<CodeIn>,
 ) -> Result<ResponseJson<Value>, StatusCode> {
-    let code_in: CodeIn = serde_json::from_value(payload).map_err(|_| StatusCode::BAD_REQUEST)?;
+    let tools = chain_tools(&toolbox, code_in.chain.as_deref())?;
     
     match code_in.try_into() {
         Ok(req) => {
-            match toolbox.code(req).await {
+            match tools.code(req).await {
                 Ok(response) => Ok(ResponseJson(json!({ 
                     "deployed": response.deployed(), 
                     "bytecode_len": response.bytecode_len() 
@@ -117,14 +273,29 @@ async fn handle_code(
 
 async fn handle_erc20_balance(
     State((toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
-    Json(payload): Json<Value>,
+    Json(erc20_in): Json<Erc20BalanceIn>,
 ) -> Result<ResponseJson<Value>, StatusCode> {
-    let erc20_in: Erc20BalanceIn = serde_json::from_value(payload).map_err(|_| StatusCode::BAD_REQUEST)?;
+    let tools = chain_tools(&toolbox, erc20_in.chain.as_deref())?;
     
     match erc20_in.try_into() {
         Ok(req) => {
-            match toolbox.erc20_balance_of(req).await {
-                Ok(response) => Ok(ResponseJson(json!({ "amount": response.amount() }))),
+            let req: domain::Erc20BalanceRequest = req;
+            let token = req.token().clone();
+            let (balance, metadata) = tokio::join!(tools.erc20_balance_of(req), tools.erc20_metadata(&token));
+            match balance {
+                Ok(response) => {
+                    let mut body = json!({ "amount": response.amount() });
+                    // Non-standard tokens may lack symbol()/decimals(); the raw amount is still useful
+                    match metadata {
+                        Ok(meta) => {
+                            body["decimals"] = json!(meta.decimals);
+                            body["symbol"] = json!(meta.symbol);
+                            body["formatted"] = json!(domain::units::format_units(response.amount(), meta.decimals as u32).ok());
+                        }
+                        Err(e) => warn!("ERC20 metadata unavailable for {}: {}", token.as_str(), e),
+                    }
+                    Ok(ResponseJson(body))
+                }
                 Err(e) => {
                     error!("ERC20 balance error: {}", e);
                     Err(StatusCode::INTERNAL_SERVER_ERROR)
@@ -138,22 +309,321 @@ async fn handle_erc20_balance(
     }
 }
 
+/// Server-sent `block` events, one per new block, with data `{"number": n}`.
+/// Needs a WebSocket RPC; over HTTP this is 501.
+async fn handle_blocks_stream(
+    State((toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
+) -> Result<Sse<impl Stream<Item = Result<Event, std::convert::Infallible>>>, StatusCode> {
+    if !toolbox.supports_subscriptions() {
+        return Err(StatusCode::NOT_IMPLEMENTED);
+    }
+    match toolbox.subscribe_blocks().await {
+        Ok(blocks) => {
+            let events = blocks.map(|number| Ok(Event::default().event("block").data(json!({ "number": number }).to_string())));
+            Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+        }
+        Err(e) => {
+            error!("Block subscription error: {}", e);
+            Err(StatusCode::BAD_GATEWAY)
+        }
+    }
+}
+
+async fn handle_fees(
+    State((toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
+) -> Result<ResponseJson<Value>, StatusCode> {
+    match toolbox.suggest_fees().await {
+        Ok(fees) => Ok(ResponseJson(json!(fees))),
+        Err(e) => {
+            error!("Fee suggestion error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn handle_erc20_allowance(
+    State((toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
+    Json(payload): Json<Erc20AllowanceIn>,
+) -> Result<ResponseJson<Value>, StatusCode> {
+    let tools = chain_tools(&toolbox, payload.chain.as_deref())?;
+    if ![&payload.token, &payload.owner, &payload.spender].iter().all(|a| domain::looks_like_address(a)) {
+        warn!("Invalid erc20_allowance addresses: {} {} {}", payload.token, payload.owner, payload.spender);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let (token, owner, spender) = (domain::Address::new(payload.token), domain::Address::new(payload.owner), domain::Address::new(payload.spender));
+    match tools.erc20_allowance(&token, &owner, &spender).await {
+        Ok(allowance) => Ok(ResponseJson(json!({ "allowance": allowance }))),
+        Err(e) => {
+            error!("ERC20 allowance error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// `approve` from a local key; simulates unless `simulate` is false and replies like `/send`
+async fn handle_erc20_approve(
+    State((toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
+    Json(payload): Json<Erc20ApproveIn>,
+) -> Result<ResponseJson<Value>, StatusCode> {
+    let tools = chain_tools(&toolbox, payload.chain.as_deref())?;
+    if let Err(e) = payload.validate() {
+        warn!("Invalid erc20_approve request: {}", e);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let simulate = payload.simulate.unwrap_or(true);
+    let (token, owner, spender) = (domain::Address::new(payload.token), domain::Address::new(payload.owner), domain::Address::new(payload.spender));
+    match tools.erc20_approve(&token, &owner, &spender, &payload.amount, simulate).await {
+        Ok(result) => Ok(ResponseJson(json!(domain::SimResult::from_tx(&result, simulate)))),
+        Err(e) => {
+            error!("ERC20 approve error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Wrap ETH into mainnet WETH from a local key; simulates unless `simulate` is false.
+/// Replies like `/send`
+#[cfg(feature = "bonus_uniswap_v2")]
+async fn handle_wrap(
+    State((toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
+    Json(payload): Json<dto::WrapIn>,
+) -> Result<ResponseJson<Value>, StatusCode> {
+    handle_weth(&toolbox, payload, true).await
+}
+
+/// Unwrap mainnet WETH back into ETH; same body and reply as `/wrap`
+#[cfg(feature = "bonus_uniswap_v2")]
+async fn handle_unwrap(
+    State((toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
+    Json(payload): Json<dto::WrapIn>,
+) -> Result<ResponseJson<Value>, StatusCode> {
+    handle_weth(&toolbox, payload, false).await
+}
+
+/// 400 for a bad body or zero amount, 403 on a read-only server, 501 when the chain has no WETH
+#[cfg(feature = "bonus_uniswap_v2")]
+async fn handle_weth(toolbox: &ServerToolbox, payload: dto::WrapIn, wrap: bool) -> Result<ResponseJson<Value>, StatusCode> {
+    let what = if wrap { "wrap" } else { "unwrap" };
+    let tools = chain_tools(toolbox, payload.chain.as_deref())?;
+    if let Err(e) = payload.validate() {
+        warn!("Invalid {} request: {}", what, e);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let simulate = payload.simulate.unwrap_or(true);
+    let from = domain::Address::new(payload.from.trim().to_string());
+    let result = if wrap {
+        tools.wrap_eth(&from, &payload.amount_eth, simulate).await
+    } else {
+        tools.unwrap_weth(&from, &payload.amount_eth, simulate).await
+    };
+    match result {
+        Ok(result) => Ok(ResponseJson(json!(domain::SimResult::from_tx(&result, simulate)))),
+        Err(e) => Err(match e.downcast_ref::<AdapterError>() {
+            Some(AdapterError::InvalidAmount { .. }) => StatusCode::BAD_REQUEST,
+            Some(AdapterError::ReadOnly) => StatusCode::FORBIDDEN,
+            Some(AdapterError::WethUnavailable { .. }) => {
+                warn!("{} refused: {}", what, e);
+                StatusCode::NOT_IMPLEMENTED
+            }
+            _ => {
+                error!("{} error: {}", what, e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }),
+    }
+}
+
+/// Token `transfer` from a local key; `amount` is in whole tokens. Replies like `/send`
+async fn handle_erc20_transfer(
+    State((toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
+    Json(payload): Json<Erc20TransferIn>,
+) -> Result<ResponseJson<Value>, StatusCode> {
+    let tools = chain_tools(&toolbox, payload.chain.as_deref())?;
+    let req: domain::Erc20TransferRequest = payload.try_into().map_err(|e: anyhow::Error| {
+        warn!("Invalid erc20_transfer request: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+    match tools.erc20_transfer(req.clone()).await {
+        Ok(result) => Ok(ResponseJson(json!(domain::SimResult::from_tx(&result, req.simulate())))),
+        Err(e) => {
+            error!("ERC20 transfer error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn handle_erc20_balances(
+    State((toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
+    Json(input): Json<Erc20BalancesIn>,
+) -> Result<ResponseJson<Value>, StatusCode> {
+    if input.holders.len() > MAX_ERC20_HOLDERS {
+        error!("Too many holders: {} (max {})", input.holders.len(), MAX_ERC20_HOLDERS);
+        return Err(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+    if !domain::looks_like_address(&input.token) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let token = domain::Address::new(input.token);
+    let holders: Vec<domain::Address> = input.holders.into_iter().map(domain::Address::new).collect();
+    match toolbox.erc20_balances_of(&token, &holders).await {
+        Ok(results) => {
+            let balances: Vec<Value> = holders
+                .iter()
+                .zip(results)
+                .map(|(holder, result)| match result {
+                    Ok(amount) => json!({ "holder": holder.as_str(), "ok": true, "amount": amount }),
+                    Err(e) => json!({ "holder": holder.as_str(), "ok": false, "error": e.to_string() }),
+                })
+                .collect();
+            Ok(ResponseJson(json!({ "token": token.as_str(), "balances": balances })))
+        }
+        Err(e) => {
+            error!("ERC20 balances error for {}: {}", token.as_str(), e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct Erc20InfoQuery {
+    token: String,
+    holder: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct ContractInfoQuery {
+    address: String,
+}
+
+/// Explorer name, verification status and ABI for a contract on the primary chain.
+/// 404 when no explorer is configured (`ETHERSCAN_API_KEY` unset or an unknown chain)
+async fn handle_contract_info(
+    State((toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
+    Query(q): Query<ContractInfoQuery>,
+) -> Result<ResponseJson<Value>, StatusCode> {
+    if !domain::looks_like_address(&q.address) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    match toolbox.contract_info(&domain::Address::new(q.address.clone())).await {
+        Ok(Some(source)) => Ok(ResponseJson(json!({
+            "address": q.address,
+            "name": source.name,
+            "verified": source.verified,
+            // The explorer returns the ABI as a JSON string; hand it back as JSON
+            "abi": source.abi.map(|abi| serde_json::from_str::<Value>(&abi).unwrap_or(Value::String(abi))),
+        }))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!("Contract info error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn handle_erc20_info(
+    State((toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
+    Query(q): Query<Erc20InfoQuery>,
+) -> Result<ResponseJson<Value>, StatusCode> {
+    if !domain::looks_like_address(&q.token) || q.holder.as_deref().is_some_and(|h| !domain::looks_like_address(h)) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let token = domain::Address::new(q.token);
+    let balance_req = q.holder.map(|holder| domain::Erc20BalanceRequest::new(token.clone(), domain::Address::new(holder)));
+    let balance = async {
+        match balance_req {
+            Some(req) => toolbox.erc20_balance_of(req).await.map(|r| Some(r.amount().to_string())),
+            None => Ok(None),
+        }
+    };
+    let (metadata, total_supply, balance) = tokio::join!(toolbox.erc20_metadata(&token), toolbox.erc20_total_supply(&token), balance);
+    match (metadata, total_supply, balance) {
+        (Ok(meta), Ok(total_supply), Ok(balance)) => {
+            let mut body = json!({
+                "token": token.as_str(),
+                "name": meta.name,
+                "symbol": meta.symbol,
+                "decimals": meta.decimals,
+                "total_supply": total_supply,
+            });
+            if let Some(balance) = balance {
+                body["balance"] = json!(balance);
+            }
+            Ok(ResponseJson(body))
+        }
+        (meta, supply, balance) => {
+            let errors = [meta.err(), supply.err(), balance.err()];
+            if errors.iter().flatten().any(|e| matches!(e.downcast_ref::<AdapterError>(), Some(AdapterError::NotAnErc20 { .. }))) {
+                warn!("{} is not an ERC-20 token", token.as_str());
+                return Err(StatusCode::UNPROCESSABLE_ENTITY);
+            }
+            error!("ERC20 info error for {}: {:?}", token.as_str(), errors.iter().flatten().collect::<Vec<_>>());
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Longest `Idempotency-Key` header accepted on `/send`
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// The optional `Idempotency-Key` header; present but empty, overlong or not text is a 400
+fn idempotency_key(headers: &HeaderMap) -> Result<Option<String>, StatusCode> {
+    let Some(value) = headers.get("idempotency-key") else { return Ok(None) };
+    match value.to_str() {
+        Ok(key) if !key.trim().is_empty() && key.len() <= MAX_IDEMPOTENCY_KEY_LEN => Ok(Some(key.trim().to_string())),
+        _ => {
+            warn!("Rejected send with an invalid Idempotency-Key");
+            Err(StatusCode::BAD_REQUEST)
+        }
+    }
+}
+
+/// Send ETH. With an `Idempotency-Key`, a repeat of a completed send from the same
+/// address replays its result instead of broadcasting again; a repeat while the first
+/// is still running is a 409, and reusing the key for a different send is a 422.
 async fn handle_send(
     State((toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
-    Json(payload): Json<Value>,
+    headers: HeaderMap,
+    Json(send_in): Json<SendIn>,
 ) -> Result<ResponseJson<Value>, StatusCode> {
-    let send_in: SendIn = serde_json::from_value(payload).map_err(|_| StatusCode::BAD_REQUEST)?;
-    
+    let tools = chain_tools(&toolbox, send_in.chain.as_deref())?;
+    let key = idempotency_key(&headers)?;
+    let chain = send_in.chain.clone();
+
     match send_in.try_into() {
         Ok(req) => {
-            match toolbox.send(req).await {
-                Ok(result) => Ok(ResponseJson(json!({ 
-                    "tx_hash": result.tx_hash(),
-                    "success": result.status().unwrap_or(false)
-                }))),
+            let req: domain::SendRequest = req;
+            let from = req.from().as_str().to_string();
+            if let Some(key) = &key {
+                let request = serde_json::to_string(&(&chain, &req)).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+                match toolbox.idempotent_sends().claim(&from, key, &request) {
+                    idempotency::Claim::Proceed => {}
+                    idempotency::Claim::Replay(result) => {
+                        info!("Replaying send for Idempotency-Key {} from {}", key, from);
+                        return Ok(ResponseJson(result));
+                    }
+                    idempotency::Claim::InFlight => return Err(StatusCode::CONFLICT),
+                    idempotency::Claim::Mismatch => return Err(StatusCode::UNPROCESSABLE_ENTITY),
+                }
+            }
+            let simulate = req.simulate();
+            // An unconfirmed broadcast keeps the key, so a retry gets its hash rather than a second broadcast
+            match unconfirmed_as_pending(tools.send(req).await) {
+                Ok(result) => {
+                    let body = json!(domain::SimResult::from_tx(&result, simulate));
+                    if let Some(key) = &key {
+                        toolbox.idempotent_sends().complete(&from, key, &body);
+                    }
+                    Ok(ResponseJson(body))
+                }
                 Err(e) => {
+                    if let Some(key) = &key {
+                        toolbox.idempotent_sends().release(&from, key);
+                    }
                     error!("Send error: {}", e);
-                    Err(StatusCode::INTERNAL_SERVER_ERROR)
+                    match e.downcast_ref::<AdapterError>() {
+                        Some(AdapterError::ForkBlockOnBroadcast { .. }) => Err(StatusCode::BAD_REQUEST),
+                        _ => Err(StatusCode::INTERNAL_SERVER_ERROR),
+                    }
                 }
             }
         }
@@ -164,33 +634,303 @@ async fn handle_send(
     }
 }
 
+/// A broadcast whose confirmations timed out is still in flight: report its hash like a
+/// missing receipt (`success: false`) instead of an error that loses it
+fn unconfirmed_as_pending(sent: anyhow::Result<domain::TxResult>) -> anyhow::Result<domain::TxResult> {
+    match sent {
+        Err(e) => match e.downcast_ref::<AdapterError>() {
+            Some(AdapterError::ConfirmationTimeout { tx_hash, .. }) => {
+                warn!("Send not confirmed in time: {}", e);
+                Ok(domain::TxResult::new(tx_hash.clone(), None, None))
+            }
+            _ => Err(e),
+        },
+        sent => sent,
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct SendRawIn { raw_tx: String }
+
+/// Broadcast a transaction the client signed itself; malformed hex or RLP is a 400
+async fn handle_send_raw(
+    State((toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
+    Json(payload): Json<SendRawIn>,
+) -> Result<ResponseJson<Value>, StatusCode> {
+    if let Err(e) = foundry_adapter::decode_raw_transaction(&payload.raw_tx) {
+        warn!("Rejected send_raw: {}", e);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    match toolbox.send_raw(&payload.raw_tx).await {
+        Ok(result) => Ok(ResponseJson(json!(domain::SimResult::from_tx(&result, false)))),
+        Err(e) => {
+            error!("Raw send error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Deploy a contract from a local key; simulates unless `simulate` is false. Bad hex is a 400
+async fn handle_deploy(
+    State((toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
+    Json(payload): Json<DeployIn>,
+) -> Result<ResponseJson<Value>, StatusCode> {
+    let req: domain::DeployRequest = payload.try_into().map_err(|e| {
+        warn!("Invalid deploy request: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+    if let Err(e) = foundry_adapter::creation_data(req.bytecode(), req.constructor_args()) {
+        warn!("Rejected deploy: {}", e);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    match toolbox.deploy_contract(&req).await {
+        Ok(result) => Ok(ResponseJson(json!(result))),
+        Err(e) => {
+            error!("Deploy error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Gas estimate for a `/send` body, without the gas cap; never signs or broadcasts
+async fn handle_estimate_gas(
+    State((toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
+    Json(send_in): Json<SendIn>,
+) -> Result<ResponseJson<Value>, StatusCode> {
+    let tools = chain_tools(&toolbox, send_in.chain.as_deref())?;
+    let req: domain::SendRequest = send_in.try_into().map_err(|e: anyhow::Error| {
+        error!("Invalid estimate_gas request: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+    match tools.estimate_send_gas(&req).await {
+        Ok(estimated_gas) => Ok(ResponseJson(json!({ "estimated_gas": estimated_gas }))),
+        Err(e) => {
+            error!("Gas estimation error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Simulate a send and hand back a ticket for broadcasting exactly the simulated
+/// transaction: gas limit, fees and type are fixed here, only the nonce is set at commit.
+/// A failed simulation (revert, insufficient funds, ...) yields `ok: false` and no ticket.
+async fn handle_prepare_send(
+    State((toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
+    Json(send_in): Json<SendIn>,
+) -> Result<ResponseJson<Value>, StatusCode> {
+    // Tickets carry no chain, so they are only issued for the primary one
+    if !toolbox.chains().is_primary(send_in.chain.as_deref()) {
+        warn!("Rejected prepare_send on non-primary chain {:?}", send_in.chain);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let req: domain::SendRequest = send_in.try_into().map_err(|e: anyhow::Error| {
+        error!("Invalid prepare_send request: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+    match toolbox.prepare_send(&req).await {
+        Ok((result, transfer)) => {
+            let ticket = toolbox.prepared_sends().insert(transfer);
+            Ok(ResponseJson(json!({
+                "ok": true,
+                "ticket": ticket,
+                "expires_in": toolbox.prepared_sends().ttl().as_secs(),
+                "simulation": domain::SimResult::from_tx(&result, true),
+            })))
+        }
+        Err(e) if matches!(e.downcast_ref::<AdapterError>(), Some(AdapterError::ForkBlockOnBroadcast { .. })) => {
+            warn!("Invalid prepare_send request: {}", e);
+            Err(StatusCode::BAD_REQUEST)
+        }
+        Err(e) => {
+            warn!("prepare_send simulation failed: {}", e);
+            Ok(ResponseJson(json!({ "ok": false, "ticket": null, "error": e.to_string() })))
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct CommitSendIn { ticket: String }
+
+async fn handle_commit_send(
+    State((toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
+    Json(payload): Json<CommitSendIn>,
+) -> Result<ResponseJson<Value>, StatusCode> {
+    let Some(transfer) = toolbox.prepared_sends().take(&payload.ticket) else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+    // The ticket is spent either way; an unconfirmed broadcast still hands back its hash
+    match unconfirmed_as_pending(toolbox.send_prepared(&transfer).await) {
+        Ok(result) => Ok(ResponseJson(json!(domain::SimResult::from_tx(&result, false)))),
+        Err(e) => {
+            error!("commit_send error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn handle_batch(
+    State((toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
+    Json(payload): Json<Value>,
+) -> Result<ResponseJson<Value>, StatusCode> {
+    let Value::Array(ops) = payload else {
+        return Err(StatusCode::BAD_REQUEST);
+    };
+    if ops.len() > batch::MAX_BATCH_OPS {
+        error!("Batch too large: {} ops (max {})", ops.len(), batch::MAX_BATCH_OPS);
+        return Err(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+    let results = batch::run_batch(toolbox.as_ref(), ops).await;
+    Ok(ResponseJson(json!({ "results": results })))
+}
+
+/// Preview an ordered bundle on a dev node and roll it back; per-step gas and success
+async fn handle_simulate(
+    State((toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
+    Json(payload): Json<SimulateIn>,
+) -> Result<ResponseJson<Value>, StatusCode> {
+    if payload.steps.is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    if payload.steps.len() > MAX_BUNDLE_STEPS {
+        error!("Bundle too large: {} steps (max {})", payload.steps.len(), MAX_BUNDLE_STEPS);
+        return Err(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+    let tools = chain_tools(&toolbox, payload.chain.as_deref())?;
+    let steps: Vec<domain::BundleStep> = payload.steps.into_iter().map(TryInto::try_into).collect::<anyhow::Result<_>>().map_err(|e| {
+        warn!("Invalid bundle step: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+    match tools.simulate_bundle(&steps).await {
+        Ok(results) => Ok(ResponseJson(json!({ "success": results.iter().all(|r| r.success), "steps": results }))),
+        Err(e) => Err(dev_method_status("Bundle preview", e)),
+    }
+}
+
+/// Save the dev node's state; 501 on nodes without `evm_snapshot`
+async fn handle_snapshot(
+    State((toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
+    Json(payload): Json<SnapshotIn>,
+) -> Result<ResponseJson<Value>, StatusCode> {
+    let tools = chain_tools(&toolbox, payload.chain.as_deref())?;
+    match tools.snapshot().await {
+        Ok(id) => Ok(ResponseJson(json!({ "id": id }))),
+        Err(e) => Err(dev_method_status("snapshot", e)),
+    }
+}
+
+/// Roll the dev node back to a `/snapshot` id; `reverted` is false for an unknown or used id
+async fn handle_revert(
+    State((toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
+    Json(payload): Json<RevertIn>,
+) -> Result<ResponseJson<Value>, StatusCode> {
+    let tools = chain_tools(&toolbox, payload.chain.as_deref())?;
+    match tools.revert(&payload.id).await {
+        Ok(reverted) => Ok(ResponseJson(json!({ "reverted": reverted }))),
+        Err(e) => Err(dev_method_status("revert", e)),
+    }
+}
+
+/// 501 when the node lacks Anvil's dev methods, 403 on a read-only server
+fn dev_method_status(what: &str, e: anyhow::Error) -> StatusCode {
+    match e.downcast_ref::<AdapterError>() {
+        Some(AdapterError::DevMethodUnsupported { .. }) => {
+            warn!("{} refused: {}", what, e);
+            StatusCode::NOT_IMPLEMENTED
+        }
+        Some(AdapterError::ReadOnly | AdapterError::ImpersonationDisabled) => {
+            warn!("{} refused: {}", what, e);
+            StatusCode::FORBIDDEN
+        }
+        _ => {
+            error!("{} error: {}", what, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
 async fn handle_token_lookup(
     State((_toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
-    Json(payload): Json<Value>,
+    Json(token_in): Json<TokenLookupIn>,
 ) -> Result<ResponseJson<Value>, StatusCode> {
-    let token_in: TokenLookupIn = serde_json::from_value(payload).map_err(|_| StatusCode::BAD_REQUEST)?;
-    
-    let mut client = external_api::TokenLookupClient::new("http://localhost:8080", 60);
-    match client.lookup_by_symbol(&token_in.symbol, &token_in.chain).await {
-        Ok(result) => {
-            let response = TokenLookupOut {
-                address: result.map(|t| t.address),
-                symbol: token_in.symbol,
-                chain: token_in.chain,
-            };
-            Ok(ResponseJson(json!({ 
-                "address": response.address,
-                "symbol": response.symbol,
-                "chain": response.chain
-            })))
+    // External API is optional (TOKEN_API_URL); the curated list keeps lookups working offline
+    let mut client = std::env::var("TOKEN_API_URL").ok().map(|url| external_api::TokenLookupClient::new(url, 60).with_timeouts(HttpTimeouts::from_env()));
+    let result = token_list::resolve(client.as_mut(), token_list::curated(), &token_in.symbol, &token_in.chain).await;
+    let response = TokenLookupOut {
+        address: result.map(|t| t.address),
+        symbol: token_in.symbol,
+        chain: token_in.chain,
+    };
+    Ok(ResponseJson(json!({ 
+        "address": response.address,
+        "symbol": response.symbol,
+        "chain": response.chain
+    })))
+}
+
+#[derive(serde::Deserialize)]
+struct EnsResolveQuery { name: String }
+
+async fn handle_ens_resolve(
+    State((toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
+    axum::extract::Query(q): axum::extract::Query<EnsResolveQuery>,
+) -> Result<ResponseJson<Value>, StatusCode> {
+    let name = q.name.trim();
+    if name.is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    match toolbox.resolve_ens(name).await {
+        Ok(address) => Ok(ResponseJson(json!({ "name": name, "address": address.map(|a| a.as_str().to_string()) }))),
+        Err(e) => {
+            error!("ENS resolve error: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
+    }
+}
+
+async fn handle_chat(
+    State((toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
+    Json(chat_in): Json<chat::ChatIn>,
+) -> Result<ResponseJson<Value>, StatusCode> {
+    let Some(agent) = toolbox.chat_agent() else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+    match agent.run(&toolbox, chat_in).await {
+        Ok(out) => Ok(ResponseJson(json!(out))),
         Err(e) => {
-            error!("Token lookup error: {}", e);
+            error!("Chat error: {}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
 
+#[derive(serde::Deserialize)]
+struct RpcIn {
+    method: String,
+    #[serde(default)]
+    params: Vec<Value>,
+}
+
+async fn handle_rpc(
+    State((toolbox, _sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
+    Json(rpc_in): Json<RpcIn>,
+) -> Result<ResponseJson<Value>, StatusCode> {
+    let config = toolbox.rpc_passthrough();
+    if !config.enabled {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    if !config.permits(&rpc_in.method) {
+        warn!("RPC passthrough refused method {}", rpc_in.method);
+        return Err(StatusCode::FORBIDDEN);
+    }
+    match toolbox.raw_rpc(&rpc_in.method, Value::Array(rpc_in.params)).await {
+        Ok(result) => Ok(ResponseJson(result)),
+        Err(e) => {
+            error!("RPC passthrough error for {}: {}", rpc_in.method, e);
+            Err(StatusCode::BAD_GATEWAY)
+        }
+    }
+}
+
 #[derive(serde::Deserialize)]
 struct SessionGetQuery { session_id: String }
 
@@ -198,6 +938,7 @@ async fn handle_session_get(
     State((_toolbox, sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
     Query(q): Query<SessionGetQuery>,
 ) -> Result<ResponseJson<Value>, StatusCode> {
+    check_session_id(&q.session_id)?;
     let data = sessions.get(&q.session_id);
     let turns: Vec<Value> = data.turns
         .iter()
@@ -216,6 +957,7 @@ async fn handle_session_append(
     State((_toolbox, sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
     Json(payload): Json<SessionAppendIn>,
 ) -> Result<ResponseJson<Value>, StatusCode> {
+    check_session_id(&payload.session_id)?;
     sessions.append(&payload.session_id, payload.role, payload.content);
     Ok(ResponseJson(json!({ "ok": true })))
 }
@@ -227,6 +969,7 @@ async fn handle_session_partial_get(
     State((_toolbox, sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
     Query(q): Query<SessionPartialGetQuery>,
 ) -> Result<ResponseJson<Value>, StatusCode> {
+    check_session_id(&q.session_id)?;
     let intent = sessions.get_partial_intent(&q.session_id);
     Ok(ResponseJson(json!({ "partial_intent": intent })))
 }
@@ -238,7 +981,576 @@ async fn handle_session_partial_set(
     State((_toolbox, sessions)): State<(Arc<ServerToolbox>, Arc<sessions::SessionStore>)>,
     Json(payload): Json<SessionPartialSetIn>,
 ) -> Result<ResponseJson<Value>, StatusCode> {
-    sessions.set_partial_intent(&payload.session_id, payload.intent);
+    check_session_id(&payload.session_id)?;
+    // Posting `null` clears it
+    sessions.set_partial_intent(&payload.session_id, Some(payload.intent).filter(|intent| !intent.is_null()));
     Ok(ResponseJson(json!({ "ok": true })))
 }
 
+fn check_session_id(session_id: &str) -> Result<(), StatusCode> {
+    if sessions::is_valid_session_id(session_id) {
+        Ok(())
+    } else {
+        warn!("Rejected session_id {:?}", session_id.chars().take(sessions::MAX_SESSION_ID_LEN).collect::<String>());
+        Err(StatusCode::BAD_REQUEST)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serve the real router on an ephemeral port; the RPC URL is never dialed by these routes.
+    async fn spawn_server() -> String {
+        let adapter = FoundryAdapter::new("http://127.0.0.1:1").await.unwrap();
+        spawn_server_with(ServerToolbox::new(adapter)).await
+    }
+
+    async fn spawn_server_with(toolbox: ServerToolbox) -> String {
+        let app = router(Arc::new(toolbox), Arc::new(sessions::SessionStore::new(3600, 50, 1000)));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn partial_intent_round_trips_over_http() {
+        let base = spawn_server().await;
+        let http = reqwest::Client::new();
+
+        let missing: Value = http.get(format!("{}/session/partial?session_id=s1", base)).send().await.unwrap().json().await.unwrap();
+        assert_eq!(missing, json!({ "partial_intent": null }));
+
+        let intent = json!({ "type": "SendNative", "to": "bob.eth", "amount_eth": null });
+        let set = http.post(format!("{}/session/partial", base))
+            .json(&json!({ "session_id": "s1", "intent": intent }))
+            .send().await.unwrap();
+        assert!(set.status().is_success());
+
+        let got: Value = http.get(format!("{}/session/partial?session_id=s1", base)).send().await.unwrap().json().await.unwrap();
+        assert_eq!(got["partial_intent"], intent);
+
+        http.post(format!("{}/session/partial", base))
+            .json(&json!({ "session_id": "s1", "intent": null }))
+            .send().await.unwrap();
+        let cleared: Value = http.get(format!("{}/session/partial?session_id=s1", base)).send().await.unwrap().json().await.unwrap();
+        assert_eq!(cleared, json!({ "partial_intent": null }));
+    }
+
+    #[tokio::test]
+    async fn version_reports_crate_version_and_build_info() {
+        let base = spawn_server().await;
+        let version: Value = reqwest::get(format!("{}/version", base)).await.unwrap().json().await.unwrap();
+        assert_eq!(version["version"], env!("CARGO_PKG_VERSION"));
+        assert!(version["git_sha"].as_str().is_some_and(|sha| !sha.is_empty()));
+        assert!(version["features"].is_array());
+    }
+
+    #[tokio::test]
+    async fn rpc_passthrough_forwards_allowlisted_methods() {
+        let rpc = httpmock::MockServer::start_async().await;
+        rpc.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_blockNumber"}"#);
+            then.status(200).json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": "0x1234" }));
+        });
+        let adapter = FoundryAdapter::new(rpc.base_url()).await.unwrap();
+        let config = rpc_passthrough::RpcPassthrough { enabled: true, allow_send: false };
+        let base = spawn_server_with(ServerToolbox::new(adapter).with_rpc_passthrough(config)).await;
+        let http = reqwest::Client::new();
+
+        let block: Value = http.post(format!("{}/rpc", base))
+            .json(&json!({ "method": "eth_blockNumber", "params": [] }))
+            .send().await.unwrap().json().await.unwrap();
+        assert_eq!(block, json!("0x1234"));
+
+        let refused = http.post(format!("{}/rpc", base))
+            .json(&json!({ "method": "eth_sendRawTransaction", "params": ["0x00"] }))
+            .send().await.unwrap();
+        assert_eq!(refused.status(), reqwest::StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn rpc_passthrough_is_off_by_default() {
+        let base = spawn_server().await;
+        let resp = reqwest::Client::new().post(format!("{}/rpc", base))
+            .json(&json!({ "method": "eth_blockNumber" }))
+            .send().await.unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn send_raw_rejects_malformed_transactions() {
+        let base = spawn_server().await;
+        let http = reqwest::Client::new();
+        for raw in ["0xnothex", "0xdeadbeef"] {
+            let resp = http.post(format!("{}/send_raw", base)).json(&json!({ "raw_tx": raw })).send().await.unwrap();
+            assert_eq!(resp.status(), reqwest::StatusCode::BAD_REQUEST, "{}", raw);
+        }
+    }
+
+    #[tokio::test]
+    async fn deploy_rejects_malformed_bytecode() {
+        let base = spawn_server().await;
+        let http = reqwest::Client::new();
+        let from = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+        for bytecode in ["0x", "0xnothex"] {
+            let resp = http.post(format!("{}/deploy", base)).json(&json!({ "from": from, "bytecode": bytecode })).send().await.unwrap();
+            assert_eq!(resp.status(), reqwest::StatusCode::BAD_REQUEST, "{}", bytecode);
+        }
+    }
+
+    #[tokio::test]
+    async fn requests_route_to_the_named_chain() {
+        let rpc_with_balance = |wei: &'static str| async move {
+            let server = httpmock::MockServer::start_async().await;
+            server.mock(|when, then| {
+                when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_getBalance"}"#);
+                then.status(200).json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": wei }));
+            });
+            server
+        };
+        let (mainnet, base) = (rpc_with_balance("0x1").await, rpc_with_balance("0x2").await);
+        let chains = chains::ChainRegistry::new("ethereum", FoundryAdapter::new(mainnet.base_url()).await.unwrap())
+            .with_chain("base", FoundryAdapter::new(base.base_url()).await.unwrap());
+        let api = spawn_server_with(ServerToolbox::with_chains(chains)).await;
+        let http = reqwest::Client::new();
+        let who = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+
+        for (chain, expected) in [(json!(null), "1"), (json!("base"), "2"), (json!("8453"), "2"), (json!("mainnet"), "1")] {
+            let body: Value = http.post(format!("{}/balance", api)).json(&json!({ "who": who, "chain": chain })).send().await.unwrap().json().await.unwrap();
+            assert_eq!(body["balance"], expected, "chain {}", chain);
+        }
+        let unknown = http.post(format!("{}/balance", api)).json(&json!({ "who": who, "chain": "optimism" })).send().await.unwrap();
+        assert_eq!(unknown.status(), reqwest::StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn bodies_are_size_limited_and_typed_before_handlers_run() {
+        let adapter = FoundryAdapter::new("http://127.0.0.1:1").await.unwrap();
+        let base = spawn_server_with(ServerToolbox::new(adapter).with_max_body_bytes(1024)).await;
+        let http = reqwest::Client::new();
+
+        let oversized = json!({ "who": "vitalik.eth", "padding": "x".repeat(2048) });
+        let resp = http.post(format!("{}/balance", base)).json(&oversized).send().await.unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::PAYLOAD_TOO_LARGE);
+
+        let wrong_shape = http.post(format!("{}/balance", base)).json(&json!({ "who": 42 })).send().await.unwrap();
+        assert_eq!(wrong_shape.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+        let missing_field = http.post(format!("{}/send", base)).json(&json!({ "from": "alice.eth" })).send().await.unwrap();
+        assert_eq!(missing_field.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+        let not_json = http.post(format!("{}/balance", base)).header("content-type", "application/json").body("{\"who\":").send().await.unwrap();
+        assert_eq!(not_json.status(), reqwest::StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn snapshot_on_a_regular_node_is_not_implemented() {
+        let rpc = httpmock::MockServer::start_async().await;
+        rpc.mock(|when, then| {
+            when.method(httpmock::Method::POST);
+            then.status(200).json_body(json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32601, "message": "the method does not exist/is not available" } }));
+        });
+        let api = spawn_server_with(ServerToolbox::new(FoundryAdapter::new(rpc.base_url()).await.unwrap())).await;
+        let http = reqwest::Client::new();
+        let snapshot = http.post(format!("{}/snapshot", api)).json(&json!({})).send().await.unwrap();
+        assert_eq!(snapshot.status(), reqwest::StatusCode::NOT_IMPLEMENTED);
+        let revert = http.post(format!("{}/revert", api)).json(&json!({ "id": "0x1" })).send().await.unwrap();
+        assert_eq!(revert.status(), reqwest::StatusCode::NOT_IMPLEMENTED);
+    }
+
+    #[tokio::test]
+    async fn erc20_approve_rejects_bad_input() {
+        let base = spawn_server().await;
+        let http = reqwest::Client::new();
+        let token = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+        let owner = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+        for (spender, amount) in [("bob.eth", "1"), ("0x70997970c51812dc3a010c7d01b50e0d17dc79c8", "1.5")] {
+            let resp = http.post(format!("{}/erc20_approve", base))
+                .json(&json!({ "token": token, "owner": owner, "spender": spender, "amount": amount }))
+                .send().await.unwrap();
+            assert_eq!(resp.status(), reqwest::StatusCode::BAD_REQUEST, "{} {}", spender, amount);
+        }
+    }
+
+    #[tokio::test]
+    async fn erc20_transfer_rejects_bad_addresses() {
+        let base = spawn_server().await;
+        let resp = reqwest::Client::new().post(format!("{}/erc20_transfer", base))
+            .json(&json!({ "token": "usdc", "from": "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266", "to": "0x70997970c51812dc3a010c7d01b50e0d17dc79c8", "amount": "1" }))
+            .send().await.unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn simulate_rejects_bad_or_oversized_bundles() {
+        let base = spawn_server().await;
+        let http = reqwest::Client::new();
+        let send = json!({ "op": "send", "from": "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266", "to": "0x70997970c51812dc3a010c7d01b50e0d17dc79c8", "amount_eth": "1" });
+        let bad_call = json!({ "op": "call", "from": "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266", "to": "0x70997970c51812dc3a010c7d01b50e0d17dc79c8", "data": "0xzz" });
+        let cases = [
+            (json!([]), reqwest::StatusCode::BAD_REQUEST),
+            (json!([send, bad_call]), reqwest::StatusCode::BAD_REQUEST),
+            (json!(vec![send; MAX_BUNDLE_STEPS + 1]), reqwest::StatusCode::PAYLOAD_TOO_LARGE),
+        ];
+        for (steps, status) in cases {
+            let resp = http.post(format!("{}/simulate", base)).json(&json!({ "steps": steps })).send().await.unwrap();
+            assert_eq!(resp.status(), status);
+        }
+    }
+
+    #[tokio::test]
+    async fn simulate_needs_impersonation_and_snapshots() {
+        let rpc = httpmock::MockServer::start_async().await;
+        rpc.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"evm_snapshot"}"#);
+            then.status(200).json_body(json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32601, "message": "the method evm_snapshot does not exist" } }));
+        });
+        let send = json!({ "op": "send", "from": "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266", "to": "0x70997970c51812dc3a010c7d01b50e0d17dc79c8", "amount_eth": "1" });
+        let http = reqwest::Client::new();
+        for (impersonation, status) in [(false, reqwest::StatusCode::FORBIDDEN), (true, reqwest::StatusCode::NOT_IMPLEMENTED)] {
+            let adapter = FoundryAdapter::new(rpc.base_url()).await.unwrap().with_impersonation(impersonation);
+            let base = spawn_server_with(ServerToolbox::new(adapter)).await;
+            let resp = http.post(format!("{}/simulate", base)).json(&json!({ "steps": [send] })).send().await.unwrap();
+            assert_eq!(resp.status(), status);
+        }
+    }
+
+    #[tokio::test]
+    async fn block_stream_needs_a_websocket_rpc() {
+        let base = spawn_server().await;
+        let resp = reqwest::get(format!("{}/blocks/stream", base)).await.unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::NOT_IMPLEMENTED);
+    }
+
+    #[tokio::test]
+    async fn chat_resolves_balance_query_end_to_end() {
+        let rpc = httpmock::MockServer::start_async().await;
+        rpc.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_getBalance"}"#);
+            then.status(200).json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": "0xde0b6b3a7640000" }));
+        });
+        let adapter = FoundryAdapter::new(rpc.base_url()).await.unwrap();
+        let parser = baml_client::parser::NlParser::new(Box::new(baml_client::provider::MockProvider::new()) as Box<dyn baml_client::provider::ChatProvider>);
+        let toolbox = ServerToolbox::new(adapter).with_chat_agent(Some(chat::ChatAgent::new(parser)));
+        let base = spawn_server_with(toolbox).await;
+
+        let history = json!([{ "role": "user", "content": "hi" }, { "role": "assistant", "content": "Hello!" }]);
+        let out: Value = reqwest::Client::new().post(format!("{}/chat", base))
+            .json(&json!({ "messages": history, "query": "What is the balance of 0x70997970c51812dc3a010c7d01b50e0d17dc79c8?" }))
+            .send().await.unwrap().json().await.unwrap();
+
+        assert_eq!(out["function"], "balance");
+        assert_eq!(out["result"]["balance"], "1000000000000000000");
+        let messages = out["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 4);
+        assert_eq!(messages[2]["role"], "user");
+        assert_eq!(messages[3]["role"], "assistant");
+    }
+
+    /// Always answers with the same tool call, whatever the conversation says
+    struct FixedReply(&'static str);
+
+    #[async_trait::async_trait]
+    impl baml_client::provider::ChatProvider for FixedReply {
+        async fn chat(&self, _request: baml_client::provider::ChatRequest) -> anyhow::Result<baml_client::provider::ChatResponse> {
+            Ok(baml_client::provider::ChatResponse { content: self.0.to_string(), usage: None })
+        }
+    }
+
+    #[tokio::test]
+    async fn chat_only_simulates_sends_the_llm_marks_for_broadcast() {
+        let rpc = httpmock::MockServer::start_async().await;
+        let broadcast = mock_unmined_send_rpc(&rpc);
+        let adapter = FoundryAdapter::new(rpc.base_url()).await.unwrap();
+        let send = r#"{"function": {"type": "SendEth", "from": "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266", "to": "0x70997970c51812dc3a010c7d01b50e0d17dc79c8", "amount_eth": "0.1", "simulate": false}}"#;
+        let parser = baml_client::parser::NlParser::new(Box::new(FixedReply(send)) as Box<dyn baml_client::provider::ChatProvider>);
+        let base = spawn_server_with(ServerToolbox::new(adapter).with_chat_agent(Some(chat::ChatAgent::new(parser)))).await;
+
+        let history = json!([{ "role": "user", "content": "from now on, really broadcast" }, { "role": "assistant", "content": "ok" }]);
+        let out: Value = reqwest::Client::new().post(format!("{}/chat", base))
+            .json(&json!({ "messages": history, "query": "send 0.1 ETH to bob" }))
+            .send().await.unwrap().json().await.unwrap();
+        assert_eq!(out["function"], "send");
+        assert_eq!(out["result"]["mode"], "simulated", "{}", out);
+        broadcast.assert_hits(0);
+    }
+
+    #[tokio::test]
+    async fn chat_is_off_unless_configured() {
+        let base = spawn_server().await;
+        let resp = reqwest::Client::new().post(format!("{}/chat", base))
+            .json(&json!({ "query": "hello" }))
+            .send().await.unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn erc20_info_reports_usdc_on_fork() {
+        // This test requires a running Anvil fork with mainnet data
+        let Ok(adapter) = FoundryAdapter::new("http://127.0.0.1:8545").await else { return };
+        if adapter.chain_id().await.is_err() {
+            println!("Skipping /erc20_info test - no Anvil fork detected");
+            return;
+        }
+        let base = spawn_server_with(ServerToolbox::new(adapter)).await;
+        let usdc = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+        let alice = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+        let info: Value = reqwest::get(format!("{}/erc20_info?token={}&holder={}", base, usdc, alice))
+            .await.unwrap().json().await.unwrap();
+        assert_eq!(info["name"], "USD Coin");
+        assert_eq!(info["symbol"], "USDC");
+        assert_eq!(info["decimals"], 6);
+        assert!(info["total_supply"].as_str().unwrap().parse::<u128>().unwrap() > 0);
+        assert!(info["balance"].as_str().unwrap().parse::<u128>().is_ok());
+    }
+
+    #[tokio::test]
+    async fn erc20_info_on_a_non_token_is_unprocessable() {
+        let rpc = httpmock::MockServer::start_async().await;
+        rpc.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_call"}"#);
+            then.status(200).json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": "0x" }));
+        });
+        let adapter = FoundryAdapter::new(rpc.base_url()).await.unwrap();
+        let base = spawn_server_with(ServerToolbox::new(adapter)).await;
+        let resp = reqwest::get(format!("{}/erc20_info?token=0x70997970c51812dc3a010c7d01b50e0d17dc79c8", base)).await.unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn session_endpoints_validate_session_id() {
+        let base = spawn_server().await;
+        let client = reqwest::Client::new();
+        let append = |id: &str| client.post(format!("{}/session/append", base)).json(&json!({ "session_id": id, "role": "user", "content": "hi" })).send();
+
+        assert!(append("demo-1").await.unwrap().status().is_success());
+        let history: Value = client.get(format!("{}/session/get?session_id=demo-1", base)).send().await.unwrap().json().await.unwrap();
+        assert_eq!(history["turns"][0]["content"], "hi");
+
+        assert_eq!(append("../../etc/passwd").await.unwrap().status(), reqwest::StatusCode::BAD_REQUEST);
+        let traversal = client.get(format!("{}/session/get?session_id=..%2F..%2Fetc%2Fpasswd", base)).send().await.unwrap();
+        assert_eq!(traversal.status(), reqwest::StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn commit_send_rejects_unknown_tickets() {
+        let base = spawn_server().await;
+        let resp = reqwest::Client::new().post(format!("{}/commit_send", base)).json(&json!({ "ticket": "nope" })).send().await.unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn prepare_then_commit_send_on_anvil() {
+        // This test requires a running Anvil node (fork or plain)
+        let Ok(adapter) = FoundryAdapter::new("http://127.0.0.1:8545").await else { return };
+        if adapter.chain_id().await.is_err() {
+            println!("Skipping prepare/commit test - no Anvil node detected");
+            return;
+        }
+        let base = spawn_server_with(ServerToolbox::new(adapter)).await;
+        let client = reqwest::Client::new();
+        let bob = "0x70997970c51812dc3a010c7d01b50e0d17dc79c8";
+        let prepared: Value = client
+            .post(format!("{}/prepare_send", base))
+            .json(&json!({ "from": "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266", "to": bob, "amount_eth": "0.01" }))
+            .send().await.unwrap().json().await.unwrap();
+        assert_eq!(prepared["ok"], true, "{}", prepared);
+        assert_eq!(prepared["simulation"]["mode"], "simulated");
+        assert!(prepared.get("balance_deltas").is_none());
+        let changes = prepared["simulation"]["balance_changes"].as_array().unwrap();
+        let received = changes.iter().find(|c| c["address"].as_str().unwrap().eq_ignore_ascii_case(bob)).unwrap();
+        assert_eq!(received["delta"], "+10000000000000000");
+
+        let commit = |ticket: Value| client.post(format!("{}/commit_send", base)).json(&json!({ "ticket": ticket })).send();
+        let sent: Value = commit(prepared["ticket"].clone()).await.unwrap().json().await.unwrap();
+        assert_eq!(sent["mode"], "broadcast");
+        assert!(sent["tx_hash"].as_str().unwrap().starts_with("0x"));
+        assert_eq!(commit(prepared["ticket"].clone()).await.unwrap().status(), reqwest::StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn idempotency_key_is_validated_and_freed_by_a_failed_send() {
+        let base = spawn_server().await;
+        let client = reqwest::Client::new();
+        let body = json!({ "from": "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266", "to": "0x70997970c51812dc3a010c7d01b50e0d17dc79c8", "amount_eth": "0.01", "simulate": false });
+        let send = |key: &str| client.post(format!("{}/send", base)).header("Idempotency-Key", key).json(&body).send();
+
+        assert_eq!(send(" ").await.unwrap().status(), reqwest::StatusCode::BAD_REQUEST);
+        assert_eq!(send(&"k".repeat(MAX_IDEMPOTENCY_KEY_LEN + 1)).await.unwrap().status(), reqwest::StatusCode::BAD_REQUEST);
+        // The RPC node is unreachable, so the send fails and the retry is attempted rather than a 409
+        assert_eq!(send("transfer-1").await.unwrap().status(), reqwest::StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(send("transfer-1").await.unwrap().status(), reqwest::StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    /// RPC node for a plain legacy transfer from Anvil account 0 that accepts the
+    /// broadcast (hash `UNMINED_TX_HASH`) but never mines it; returns the broadcast mock
+    fn mock_unmined_send_rpc(rpc: &httpmock::MockServer) -> httpmock::Mock<'_> {
+        for (method, result) in [
+            ("eth_chainId", json!("0x7a69")),
+            ("eth_getCode", json!("0x")),
+            ("eth_getBalance", json!("0x8ac7230489e80000")),
+            ("eth_getBlockByNumber", Value::Null),
+            ("eth_estimateGas", json!("0x5208")),
+            ("eth_gasPrice", json!("0x3b9aca00")),
+            ("eth_call", json!("0x")),
+            ("eth_getTransactionCount", json!("0x0")),
+            ("eth_getTransactionReceipt", Value::Null),
+        ] {
+            rpc.mock(|when, then| {
+                when.method(httpmock::Method::POST).json_body_partial(json!({ "method": method }).to_string());
+                then.status(200).json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": result }));
+            });
+        }
+        rpc.mock(|when, then| {
+            when.method(httpmock::Method::POST).json_body_partial(r#"{"method":"eth_sendRawTransaction"}"#);
+            then.status(200).json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": UNMINED_TX_HASH }));
+        })
+    }
+
+    const UNMINED_TX_HASH: &str = "0xabababababababababababababababababababababababababababababababab";
+
+    /// Server over `rpc` that gives up on confirmations quickly
+    async fn spawn_impatient_server(rpc: &httpmock::MockServer) -> String {
+        let adapter = FoundryAdapter::new(rpc.base_url())
+            .await
+            .unwrap()
+            .with_poll_interval(std::time::Duration::from_millis(10))
+            .with_confirmation_timeout(std::time::Duration::from_millis(200));
+        spawn_server_with(ServerToolbox::new(adapter)).await
+    }
+
+    #[tokio::test]
+    async fn unconfirmed_send_keeps_its_idempotency_key_and_replays_the_hash() {
+        let rpc = httpmock::MockServer::start_async().await;
+        let broadcast = mock_unmined_send_rpc(&rpc);
+        let base = spawn_impatient_server(&rpc).await;
+        let client = reqwest::Client::new();
+        let body = json!({ "from": "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266", "to": "0x70997970c51812dc3a010c7d01b50e0d17dc79c8", "amount_eth": "0.01", "simulate": false });
+        let send = || client.post(format!("{}/send", base)).header("Idempotency-Key", "slow-1").json(&body).send();
+
+        let first = send().await.unwrap();
+        assert_eq!(first.status(), reqwest::StatusCode::OK);
+        let first: Value = first.json().await.unwrap();
+        assert_eq!(first["tx_hash"], UNMINED_TX_HASH);
+        assert_eq!(first["success"], false);
+        let retry: Value = send().await.unwrap().json().await.unwrap();
+        assert_eq!(retry, first);
+        broadcast.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn unconfirmed_commit_send_returns_the_hash() {
+        let rpc = httpmock::MockServer::start_async().await;
+        let broadcast = mock_unmined_send_rpc(&rpc);
+        let base = spawn_impatient_server(&rpc).await;
+        let client = reqwest::Client::new();
+        let prepared: Value = client
+            .post(format!("{}/prepare_send", base))
+            .json(&json!({ "from": "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266", "to": "0x70997970c51812dc3a010c7d01b50e0d17dc79c8", "amount_eth": "0.01" }))
+            .send().await.unwrap().json().await.unwrap();
+        assert_eq!(prepared["ok"], true, "{}", prepared);
+
+        let sent = client.post(format!("{}/commit_send", base)).json(&json!({ "ticket": prepared["ticket"] })).send().await.unwrap();
+        assert_eq!(sent.status(), reqwest::StatusCode::OK);
+        let sent: Value = sent.json().await.unwrap();
+        assert_eq!(sent["tx_hash"], UNMINED_TX_HASH);
+        assert_eq!(sent["success"], false);
+        broadcast.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn repeated_idempotency_key_broadcasts_once_on_anvil() {
+        // This test requires a running Anvil node (fork or plain)
+        let Ok(adapter) = FoundryAdapter::new("http://127.0.0.1:8545").await else { return };
+        if adapter.chain_id().await.is_err() {
+            println!("Skipping idempotent send test - no Anvil node detected");
+            return;
+        }
+        let base = spawn_server_with(ServerToolbox::new(adapter)).await;
+        let client = reqwest::Client::new();
+        let from = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+        let nonce = || async {
+            let reply: Value = client
+                .post("http://127.0.0.1:8545")
+                .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": "eth_getTransactionCount", "params": [from, "latest"] }))
+                .send().await.unwrap().json().await.unwrap();
+            u64::from_str_radix(reply["result"].as_str().unwrap().trim_start_matches("0x"), 16).unwrap()
+        };
+        let key = format!("transfer-{}", std::process::id());
+        let body = json!({ "from": from, "to": "0x70997970c51812dc3a010c7d01b50e0d17dc79c8", "amount_eth": "0.01", "simulate": false });
+        let send = |body: &Value| client.post(format!("{}/send", base)).header("Idempotency-Key", &key).json(body).send();
+
+        let before = nonce().await;
+        let first: Value = send(&body).await.unwrap().json().await.unwrap();
+        let second: Value = send(&body).await.unwrap().json().await.unwrap();
+        assert_eq!(first["mode"], "broadcast", "{}", first);
+        assert_eq!(first["tx_hash"], second["tx_hash"]);
+        assert_eq!(nonce().await, before + 1, "the repeat must not broadcast");
+
+        let other = json!({ "from": from, "to": "0x70997970c51812dc3a010c7d01b50e0d17dc79c8", "amount_eth": "0.02", "simulate": false });
+        assert_eq!(send(&other).await.unwrap().status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[cfg(feature = "bonus_uniswap_v2")]
+    #[tokio::test]
+    async fn wrap_rejects_bad_bodies_before_touching_the_node() {
+        let base = spawn_server().await;
+        let client = reqwest::Client::new();
+        let wrap = |path: &str, body: Value| client.post(format!("{}{}", base, path)).json(&body).send();
+        let alice = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+        assert_eq!(wrap("/wrap", json!({ "from": "alice", "amount_eth": "1" })).await.unwrap().status(), reqwest::StatusCode::BAD_REQUEST);
+        assert_eq!(wrap("/wrap", json!({ "from": alice, "amount_eth": "lots" })).await.unwrap().status(), reqwest::StatusCode::BAD_REQUEST);
+        assert_eq!(wrap("/unwrap", json!({ "from": alice, "amount_eth": "0" })).await.unwrap().status(), reqwest::StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn contract_info_reports_verification_and_caches_the_abi() {
+        let base = spawn_server().await;
+        let weth = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+        let resp = reqwest::get(format!("{}/contract_info?address={}", base, weth)).await.unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::NOT_FOUND, "no explorer configured");
+
+        let explorer = httpmock::MockServer::start_async().await;
+        mock_source(&explorer, weth, json!({ "SourceCode": "contract WETH9 { }", "ABI": "[{\"type\":\"function\",\"name\":\"deposit\"}]", "ContractName": "WETH9" }));
+        let adapter = FoundryAdapter::new("http://127.0.0.1:1").await.unwrap();
+        let client = foundry_adapter::ExplorerClient::new("key".to_string()).with_base_url(explorer.url("/api"));
+        let toolbox = ServerToolbox::new(adapter).with_explorer(Some(client));
+        let info = toolbox.contract_info(&domain::Address::new(weth.to_string())).await.unwrap().unwrap();
+        assert!(info.verified);
+        assert!(toolbox.chains().primary().cached_abi(weth).unwrap().contains("deposit"));
+
+        let base = spawn_server_with(toolbox).await;
+        let info: Value = reqwest::get(format!("{}/contract_info?address={}", base, weth)).await.unwrap().json().await.unwrap();
+        assert_eq!(info["name"], "WETH9");
+        assert_eq!(info["verified"], true);
+        assert_eq!(info["abi"][0]["name"], "deposit");
+
+        let eoa = "0x28c6c06298d514db089934071355e5743bf21d60";
+        mock_source(&explorer, eoa, json!({ "SourceCode": "", "ABI": "Contract source code not verified", "ContractName": "" }));
+        let info: Value = reqwest::get(format!("{}/contract_info?address={}", base, eoa)).await.unwrap().json().await.unwrap();
+        assert_eq!(info, json!({ "address": eoa, "name": null, "verified": false, "abi": null }));
+        let resp = reqwest::get(format!("{}/contract_info?address=weth", base)).await.unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::BAD_REQUEST);
+    }
+
+    /// Mock an explorer's `getsourcecode` reply for `address`
+    fn mock_source(explorer: &httpmock::MockServer, address: &str, entry: Value) {
+        explorer.mock(|when, then| {
+            when.method(httpmock::Method::GET).path("/api").query_param("action", "getsourcecode").query_param("address", address);
+            then.status(200).json_body(json!({ "status": "1", "message": "OK", "result": [entry] }));
+        });
+    }
+
+    #[tokio::test]
+    async fn erc20_balances_rejects_oversized_requests() {
+        let base = spawn_server().await;
+        let holders = vec!["0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266"; MAX_ERC20_HOLDERS + 1];
+        let resp = reqwest::Client::new()
+            .post(format!("{}/erc20_balances", base))
+            .json(&json!({ "token": "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", "holders": holders }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use foundry_adapter::PreparedTransfer;
+
+/// How long a `/prepare_send` ticket can be committed; `PREPARED_SEND_TTL` overrides it
+pub const DEFAULT_PREPARED_SEND_TTL_SECS: u64 = 120;
+
+struct PreparedSend<T> {
+    tx: T,
+    prepared_at: Instant,
+}
+
+/// Simulated transactions (gas, fees and type already filled) waiting for `/commit_send`,
+/// keyed by an opaque ticket. Tickets are single-use and expire, so a broadcast always
+/// matches a recent preview.
+pub struct PreparedSends<T = PreparedTransfer> {
+    inner: Mutex<HashMap<String, PreparedSend<T>>>,
+    ttl: Duration,
+}
+
+impl<T> Default for PreparedSends<T> {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(DEFAULT_PREPARED_SEND_TTL_SECS))
+    }
+}
+
+impl<T> PreparedSends<T> {
+    pub fn new(ttl: Duration) -> Self {
+        Self { inner: Mutex::new(HashMap::new()), ttl }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Remember the prepared transaction and return its ticket.
+    pub fn insert(&self, tx: T) -> String {
+        let ticket = new_ticket();
+        let mut map = self.inner.lock().unwrap();
+        map.retain(|_, p| p.prepared_at.elapsed() < self.ttl);
+        map.insert(ticket.clone(), PreparedSend { tx, prepared_at: Instant::now() });
+        ticket
+    }
+
+    /// Claim the prepared transaction; `None` for unknown, expired or already-committed tickets.
+    pub fn take(&self, ticket: &str) -> Option<T> {
+        let prepared = self.inner.lock().unwrap().remove(ticket)?;
+        (prepared.prepared_at.elapsed() < self.ttl).then_some(prepared.tx)
+    }
+}
+
+/// 128 bits from the OS random source, as hex
+fn new_ticket() -> String {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).expect("OS random source unavailable");
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tickets_are_single_use() {
+        let store = PreparedSends::default();
+        let ticket = store.insert("0.5 to bob");
+        assert_eq!(ticket.len(), 32);
+        assert_ne!(ticket, store.insert("0.5 to bob"));
+
+        assert_eq!(store.take(&ticket), Some("0.5 to bob"));
+        assert!(store.take(&ticket).is_none(), "a ticket commits at most once");
+        assert!(store.take("unknown").is_none());
+    }
+
+    #[test]
+    fn expired_tickets_cannot_be_committed() {
+        let store = PreparedSends::new(Duration::ZERO);
+        let ticket = store.insert("0.5 to bob");
+        assert!(store.take(&ticket).is_none());
+    }
+}
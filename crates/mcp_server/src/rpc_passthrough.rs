@@ -0,0 +1,59 @@
+/// Read-only JSON-RPC methods `/rpc` forwards without further opt-in.
+const SAFE_METHODS: &[&str] = &[
+    "eth_blockNumber",
+    "eth_call",
+    "eth_chainId",
+    "eth_estimateGas",
+    "eth_feeHistory",
+    "eth_gasPrice",
+    "eth_getBalance",
+    "eth_getBlockByHash",
+    "eth_getBlockByNumber",
+    "eth_getCode",
+    "eth_getLogs",
+    "eth_getStorageAt",
+    "eth_getTransactionByHash",
+    "eth_getTransactionCount",
+    "eth_getTransactionReceipt",
+    "eth_maxPriorityFeePerGas",
+    "net_version",
+    "web3_clientVersion",
+];
+
+const SEND_RAW_TRANSACTION: &str = "eth_sendRawTransaction";
+
+/// Gate for the raw `POST /rpc` endpoint: off unless `ENABLE_RPC_PASSTHROUGH=1`, and
+/// broadcasting signed transactions additionally needs `RPC_PASSTHROUGH_ALLOW_SEND=1`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RpcPassthrough {
+    pub enabled: bool,
+    pub allow_send: bool,
+}
+
+impl RpcPassthrough {
+    pub fn from_env() -> Self {
+        let flag = |name: &str| std::env::var(name).ok().map(|v| v == "1").unwrap_or(false);
+        Self { enabled: flag("ENABLE_RPC_PASSTHROUGH"), allow_send: flag("RPC_PASSTHROUGH_ALLOW_SEND") }
+    }
+
+    pub fn permits(&self, method: &str) -> bool {
+        SAFE_METHODS.contains(&method) || (self.allow_send && method == SEND_RAW_TRANSACTION)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn send_raw_transaction_needs_explicit_opt_in() {
+        let reads_only = RpcPassthrough { enabled: true, allow_send: false };
+        assert!(reads_only.permits("eth_blockNumber"));
+        assert!(!reads_only.permits("eth_sendRawTransaction"));
+        assert!(!reads_only.permits("anvil_setBalance"));
+
+        let with_send = RpcPassthrough { enabled: true, allow_send: true };
+        assert!(with_send.permits("eth_sendRawTransaction"));
+        assert!(!with_send.permits("eth_sendTransaction"));
+    }
+}
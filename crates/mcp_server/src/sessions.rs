@@ -1,5 +1,20 @@
 use std::{collections::HashMap, sync::RwLock, time::{Duration, Instant}};
 
+/// Store defaults when `SESSION_TTL`, `SESSION_MAX_TURNS` or `SESSION_MAX_SESSIONS` are unset
+pub const DEFAULT_SESSION_TTL_SECS: u64 = 3600;
+pub const DEFAULT_SESSION_MAX_TURNS: usize = 50;
+pub const DEFAULT_SESSION_MAX_SESSIONS: usize = 1000;
+
+/// Longest accepted `session_id`; ids are map keys (and may become file names), so keep them bounded
+pub const MAX_SESSION_ID_LEN: usize = 128;
+
+/// `[A-Za-z0-9_-]{1,128}`: no separators, dots or whitespace that could escape a key or path.
+pub fn is_valid_session_id(session_id: &str) -> bool {
+    !session_id.is_empty()
+        && session_id.len() <= MAX_SESSION_ID_LEN
+        && session_id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+}
+
 #[derive(Clone, Debug)]
 pub struct ChatTurn {
     pub role: String,
@@ -27,6 +42,21 @@ impl SessionStore {
         Self { inner: RwLock::new(HashMap::new()), ttl: Duration::from_secs(ttl_seconds), max_turns_per_session, max_sessions }
     }
 
+    /// Store sized from `SESSION_TTL` (seconds), `SESSION_MAX_TURNS` and `SESSION_MAX_SESSIONS`
+    pub fn from_env() -> Self {
+        Self::from_vars(|var| std::env::var(var).ok())
+    }
+
+    /// Unset or unparseable values fall back to the defaults.
+    pub fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let num = |name: &str, default: u64| var(name).and_then(|v| v.trim().parse::<u64>().ok()).unwrap_or(default);
+        Self::new(
+            num("SESSION_TTL", DEFAULT_SESSION_TTL_SECS),
+            num("SESSION_MAX_TURNS", DEFAULT_SESSION_MAX_TURNS as u64) as usize,
+            num("SESSION_MAX_SESSIONS", DEFAULT_SESSION_MAX_SESSIONS as u64) as usize,
+        )
+    }
+
     pub fn get(&self, session_id: &str) -> SessionData {
         self.evict_expired();
         let mut map = self.inner.write().unwrap();
@@ -48,10 +78,11 @@ impl SessionStore {
         entry.updated_at = Instant::now();
     }
 
-    pub fn set_partial_intent(&self, session_id: &str, intent: serde_json::Value) {
+    /// `None` clears it once the intent is complete
+    pub fn set_partial_intent(&self, session_id: &str, intent: Option<serde_json::Value>) {
         let mut map = self.inner.write().unwrap();
         let entry = map.entry(session_id.to_string()).or_insert(SessionData { turns: Vec::new(), partial_intent: None, updated_at: Instant::now() });
-        entry.partial_intent = Some(intent);
+        entry.partial_intent = intent;
         entry.updated_at = Instant::now();
     }
 
@@ -67,4 +98,26 @@ impl SessionStore {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_values_size_the_store() {
+        let vars: HashMap<&str, &str> = [("SESSION_TTL", "120"), ("SESSION_MAX_TURNS", "10"), ("SESSION_MAX_SESSIONS", "lots")].into();
+        let store = SessionStore::from_vars(|name| vars.get(name).map(|v| v.to_string()));
+        assert_eq!(store.ttl, Duration::from_secs(120));
+        assert_eq!(store.max_turns_per_session, 10);
+        assert_eq!(store.max_sessions, DEFAULT_SESSION_MAX_SESSIONS);
+    }
 
+    #[test]
+    fn session_ids_are_restricted_to_a_safe_charset_and_length() {
+        assert!(is_valid_session_id("demo-session_01"));
+        assert!(is_valid_session_id(&"a".repeat(MAX_SESSION_ID_LEN)));
+        for bad in ["", "../../etc/passwd", "a/b", "with space", "dot.ted", "naïve"] {
+            assert!(!is_valid_session_id(bad), "{:?} should be rejected", bad);
+        }
+        assert!(!is_valid_session_id(&"a".repeat(MAX_SESSION_ID_LEN + 1)));
+    }
+}
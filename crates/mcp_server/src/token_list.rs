@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::external_api::{TokenInfo, TokenLookupClient};
+
+const BUNDLED_TOKEN_LIST: &str = include_str!("../data/token_list.json");
+
+#[derive(Deserialize)]
+struct TokenListFile {
+    tokens: Vec<TokenInfo>,
+}
+
+/// Offline symbol → address list for common tokens, keyed by canonical chain name.
+#[derive(Debug, Default)]
+pub struct CuratedTokenList {
+    tokens: HashMap<String, TokenInfo>,
+}
+
+impl CuratedTokenList {
+    pub fn from_json(raw: &str) -> Result<Self> {
+        let file: TokenListFile = serde_json::from_str(raw)?;
+        let mut tokens = HashMap::new();
+        for mut token in file.tokens {
+            token.chain = canonical_chain(&token.chain);
+            tokens.insert(Self::key(&token.symbol, &token.chain), token);
+        }
+        Ok(Self { tokens })
+    }
+
+    pub fn bundled() -> Self {
+        Self::from_json(BUNDLED_TOKEN_LIST).expect("bundled token list is valid JSON")
+    }
+
+    /// `TOKEN_LIST_PATH` replaces the bundled list; an unreadable file falls back to it.
+    pub fn from_env() -> Self {
+        let Ok(path) = std::env::var("TOKEN_LIST_PATH") else { return Self::bundled() };
+        match std::fs::read_to_string(&path).map_err(anyhow::Error::from).and_then(|raw| Self::from_json(&raw)) {
+            Ok(list) => list,
+            Err(e) => {
+                warn!("failed to load TOKEN_LIST_PATH {}: {}; using bundled list", path, e);
+                Self::bundled()
+            }
+        }
+    }
+
+    pub fn lookup(&self, symbol: &str, chain: &str) -> Option<TokenInfo> {
+        self.tokens.get(&Self::key(symbol, &canonical_chain(chain))).cloned()
+    }
+
+    fn key(symbol: &str, chain: &str) -> String {
+        format!("{}::{}", chain, symbol.to_uppercase())
+    }
+}
+
+/// Process-wide curated list, loaded on first use.
+pub fn curated() -> &'static CuratedTokenList {
+    static LIST: OnceLock<CuratedTokenList> = OnceLock::new();
+    LIST.get_or_init(CuratedTokenList::from_env)
+}
+
+/// Prefer the external API when it is configured and answers; otherwise use the curated list.
+pub async fn resolve(external: Option<&mut TokenLookupClient>, curated: &CuratedTokenList, symbol: &str, chain: &str) -> Option<TokenInfo> {
+    if let Some(client) = external {
+        match client.lookup_by_symbol(symbol, chain).await {
+            Ok(Some(info)) => return Some(info),
+            Ok(None) => {}
+            Err(e) => warn!("external token lookup failed for {} on {}: {}", symbol, chain, e),
+        }
+    }
+    curated.lookup(symbol, chain)
+}
+
+pub(crate) fn canonical_chain(chain: &str) -> String {
+    match chain.trim().to_lowercase().as_str() {
+        "mainnet" | "eth" | "1" => "ethereum".to_string(),
+        "arbitrum-one" | "arb" | "42161" => "arbitrum".to_string(),
+        "op" | "10" => "optimism".to_string(),
+        "8453" => "base".to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::{prelude::*, Method::GET};
+
+    #[tokio::test]
+    async fn resolves_usdc_on_mainnet_without_external_call() {
+        let list = CuratedTokenList::bundled();
+        let info = resolve(None, &list, "usdc", "mainnet").await.unwrap();
+        assert_eq!(info.address, "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+        assert_eq!(info.chain, "ethereum");
+    }
+
+    #[tokio::test]
+    async fn prefers_external_api_and_falls_back_to_curated() {
+        let server = MockServer::start();
+        let _usdc = server.mock(|when, then| {
+            when.method(GET).path("/tokens").query_param("symbol", "USDC");
+            then.status(200).json_body(serde_json::json!({
+                "address": "0x0000000000000000000000000000000000000001",
+                "symbol": "USDC",
+                "name": "USD Coin (external)",
+                "chain": "ethereum"
+            }));
+        });
+        let _dai = server.mock(|when, then| {
+            when.method(GET).path("/tokens").query_param("symbol", "DAI");
+            then.status(404);
+        });
+        let list = CuratedTokenList::bundled();
+        let mut client = TokenLookupClient::new(server.base_url(), 60);
+
+        let usdc = resolve(Some(&mut client), &list, "USDC", "ethereum").await.unwrap();
+        assert_eq!(usdc.address, "0x0000000000000000000000000000000000000001");
+        let dai = resolve(Some(&mut client), &list, "DAI", "ethereum").await.unwrap();
+        assert_eq!(dai.address, "0x6B175474E89094C44Da98b954EedeAC495271d0F");
+    }
+
+    #[test]
+    fn unknown_symbol_or_chain_is_none() {
+        let list = CuratedTokenList::bundled();
+        assert!(list.lookup("NOPE", "ethereum").is_none());
+        assert!(list.lookup("USDC", "solana").is_none());
+    }
+}
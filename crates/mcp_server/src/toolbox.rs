@@ -1,18 +1,208 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use domain::*;
-use foundry_adapter::FoundryAdapter;
+use foundry_adapter::{ContractSource, Erc20Metadata, ExplorerClient, FeeSuggestion, FoundryAdapter, PreparedTransfer};
+
+use crate::chains::ChainRegistry;
+use crate::chat::ChatAgent;
+use crate::idempotency::IdempotentSends;
+use crate::prepared::PreparedSends;
+use crate::rpc_passthrough::RpcPassthrough;
 
 pub struct ServerToolbox {
-    adapter: FoundryAdapter,
+    chains: ChainRegistry,
+    rpc_passthrough: RpcPassthrough,
+    chat: Option<ChatAgent>,
+    explorer: Option<ExplorerClient>,
+    prepared: PreparedSends,
+    idempotent: IdempotentSends,
+    max_body_bytes: usize,
 }
 
 impl ServerToolbox {
-    pub fn new(adapter: FoundryAdapter) -> Self { Self { adapter } }
+    /// A single-chain toolbox, named after the default primary chain
+    #[cfg(test)]
+    pub fn new(adapter: FoundryAdapter) -> Self { Self::with_chains(ChainRegistry::new(crate::chains::DEFAULT_PRIMARY_CHAIN, adapter)) }
+
+    pub fn with_chains(chains: ChainRegistry) -> Self { Self { chains, rpc_passthrough: RpcPassthrough::default(), chat: None, explorer: None, prepared: PreparedSends::default(), idempotent: IdempotentSends::default(), max_body_bytes: crate::dto::DEFAULT_MAX_BODY_BYTES } }
+
+    pub fn chains(&self) -> &ChainRegistry {
+        &self.chains
+    }
+
+    /// Tools bound to one configured chain; `None` is the primary chain
+    pub fn on_chain(&self, chain: Option<&str>) -> Result<ChainToolbox<'_>> {
+        Ok(ChainToolbox { adapter: self.chains.get(chain)? })
+    }
+
+    pub fn with_chat_agent(mut self, chat: Option<ChatAgent>) -> Self {
+        self.chat = chat;
+        self
+    }
+
+    pub fn chat_agent(&self) -> Option<&ChatAgent> {
+        self.chat.as_ref()
+    }
+
+    /// Explorer API for the primary chain, used by `/contract_info`
+    pub fn with_explorer(mut self, explorer: Option<ExplorerClient>) -> Self {
+        self.explorer = explorer;
+        self
+    }
+
+    /// Explorer source details for a contract on the primary chain; `None` without an
+    /// explorer. A verified ABI is cached on the adapter, e.g. for decoding revert errors.
+    pub async fn contract_info(&self, address: &Address) -> Result<Option<ContractSource>> {
+        let Some(explorer) = &self.explorer else { return Ok(None) };
+        let source = explorer.get_source_code(address.as_str()).await?;
+        if let Some(abi) = &source.abi {
+            self.chains.primary().cache_abi(address.as_str(), abi.clone(), source.verified);
+        }
+        Ok(Some(source))
+    }
+
+    /// How long `/prepare_send` tickets stay committable
+    pub fn with_prepared_send_ttl(mut self, ttl: std::time::Duration) -> Self {
+        self.prepared = PreparedSends::new(ttl);
+        self
+    }
+
+    /// Largest request body the router accepts
+    pub fn with_max_body_bytes(mut self, max_body_bytes: usize) -> Self {
+        self.max_body_bytes = max_body_bytes;
+        self
+    }
+
+    pub fn max_body_bytes(&self) -> usize {
+        self.max_body_bytes
+    }
+
+    pub fn prepared_sends(&self) -> &PreparedSends {
+        &self.prepared
+    }
+
+    /// How long `/send` results are replayed for a repeated `Idempotency-Key`
+    pub fn with_idempotency_ttl(mut self, ttl: std::time::Duration) -> Self {
+        self.idempotent = IdempotentSends::new(ttl);
+        self
+    }
+
+    pub fn idempotent_sends(&self) -> &IdempotentSends {
+        &self.idempotent
+    }
+
+    pub fn with_rpc_passthrough(mut self, rpc_passthrough: RpcPassthrough) -> Self {
+        self.rpc_passthrough = rpc_passthrough;
+        self
+    }
+
+    pub fn rpc_passthrough(&self) -> RpcPassthrough {
+        let mut config = self.rpc_passthrough;
+        // A read-only server never broadcasts, whatever the passthrough config says
+        config.allow_send &= !self.chains.primary().is_read_only();
+        config
+    }
+
+    pub async fn raw_rpc(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
+        Ok(self.chains.primary().raw_request(method, params).await?)
+    }
+
+    pub async fn resolve_ens(&self, name: &str) -> Result<Option<Address>> {
+        Ok(self.chains.primary().resolve_ens(name).await?)
+    }
+
+    pub async fn erc20_metadata(&self, token: &Address) -> Result<Erc20Metadata> {
+        Ok(self.chains.primary().erc20_metadata(token).await?)
+    }
+
+    /// Per-holder results in input order; only an invalid token fails the whole call
+    pub async fn erc20_balances_of(&self, token: &Address, holders: &[Address]) -> Result<Vec<Result<String>>> {
+        let results = self.chains.primary().erc20_balances_of(token, holders).await?;
+        Ok(results.into_iter().map(|r| r.map_err(Into::into)).collect())
+    }
+
+    /// Simulate a send on the primary chain and keep the filled transaction for `send_prepared`
+    pub async fn prepare_send(&self, req: &SendRequest) -> Result<(TxResult, PreparedTransfer)> {
+        Ok(self.chains.primary().prepare_send(req).await?)
+    }
+
+    pub async fn send_prepared(&self, prepared: &PreparedTransfer) -> Result<TxResult> {
+        Ok(self.chains.primary().send_prepared(prepared).await?)
+    }
+
+    pub async fn send_raw(&self, raw_tx_hex: &str) -> Result<TxResult> {
+        Ok(self.chains.primary().send_raw(raw_tx_hex).await?)
+    }
+
+    pub async fn deploy_contract(&self, req: &DeployRequest) -> Result<DeployResult> {
+        Ok(self.chains.primary().deploy_contract(req).await?)
+    }
+
+    pub async fn suggest_fees(&self) -> Result<FeeSuggestion> {
+        Ok(self.chains.primary().suggest_fees().await?)
+    }
+
+    /// Live block numbers; needs a ws:// or wss:// RPC_URL
+    pub async fn subscribe_blocks(&self) -> Result<impl futures_util::Stream<Item = u64> + Send + use<>> {
+        Ok(self.chains.primary().subscribe_blocks().await?)
+    }
+
+    pub fn supports_subscriptions(&self) -> bool {
+        self.chains.primary().is_ws()
+    }
+
+    pub async fn erc20_total_supply(&self, token: &Address) -> Result<String> {
+        Ok(self.chains.primary().erc20_total_supply(token).await?)
+    }
+}
+
+/// The `Toolbox` calls against a single chain's adapter
+pub struct ChainToolbox<'a> {
+    adapter: &'a FoundryAdapter,
+}
+
+impl ChainToolbox<'_> {
+    pub async fn estimate_send_gas(&self, req: &SendRequest) -> Result<u64> {
+        Ok(self.adapter.estimate_send_gas(req).await?)
+    }
+
+    pub async fn erc20_metadata(&self, token: &Address) -> Result<Erc20Metadata> {
+        Ok(self.adapter.erc20_metadata(token).await?)
+    }
+
+    pub async fn erc20_allowance(&self, token: &Address, owner: &Address, spender: &Address) -> Result<String> {
+        Ok(self.adapter.erc20_allowance(token, owner, spender).await?)
+    }
+
+    pub async fn erc20_approve(&self, token: &Address, owner: &Address, spender: &Address, amount: &str, simulate: bool) -> Result<TxResult> {
+        Ok(self.adapter.erc20_approve(token, owner, spender, amount, simulate).await?)
+    }
+
+    #[cfg(feature = "bonus_uniswap_v2")]
+    pub async fn wrap_eth(&self, from: &Address, amount_eth: &str, simulate: bool) -> Result<TxResult> {
+        Ok(self.adapter.wrap_eth(from, amount_eth, simulate).await?)
+    }
+
+    #[cfg(feature = "bonus_uniswap_v2")]
+    pub async fn unwrap_weth(&self, from: &Address, amount_eth: &str, simulate: bool) -> Result<TxResult> {
+        Ok(self.adapter.unwrap_weth(from, amount_eth, simulate).await?)
+    }
+
+    pub async fn simulate_bundle(&self, steps: &[BundleStep]) -> Result<Vec<BundleStepResult>> {
+        Ok(self.adapter.simulate_bundle(steps).await?)
+    }
+
+    pub async fn snapshot(&self) -> Result<String> {
+        Ok(self.adapter.snapshot().await?)
+    }
+
+    pub async fn revert(&self, id: &str) -> Result<bool> {
+        Ok(self.adapter.revert(id).await?)
+    }
 }
 
 #[async_trait]
-impl Toolbox for ServerToolbox {
+impl Toolbox for ChainToolbox<'_> {
     async fn balance(&self, req: BalanceRequest) -> Result<BalanceResponse> {
         let wei = self.adapter.get_balance(&req).await?;
         Ok(BalanceResponse::new(wei))
@@ -32,5 +222,40 @@ impl Toolbox for ServerToolbox {
         let result = self.adapter.send_eth(&req).await?;
         Ok(result)
     }
+
+    async fn call(&self, req: ContractCallRequest) -> Result<ContractCallResponse> {
+        let data = self.adapter.eth_call(&req).await?;
+        Ok(ContractCallResponse::new(data))
+    }
+
+    async fn erc20_transfer(&self, req: Erc20TransferRequest) -> Result<TxResult> {
+        Ok(self.adapter.erc20_transfer(&req).await?)
+    }
 }
 
+#[async_trait]
+impl Toolbox for ServerToolbox {
+    async fn balance(&self, req: BalanceRequest) -> Result<BalanceResponse> {
+        self.on_chain(None)?.balance(req).await
+    }
+
+    async fn code(&self, req: CodeRequest) -> Result<CodeResponse> {
+        self.on_chain(None)?.code(req).await
+    }
+
+    async fn erc20_balance_of(&self, req: Erc20BalanceRequest) -> Result<Erc20BalanceResponse> {
+        self.on_chain(None)?.erc20_balance_of(req).await
+    }
+
+    async fn send(&self, req: SendRequest) -> Result<TxResult> {
+        self.on_chain(None)?.send(req).await
+    }
+
+    async fn call(&self, req: ContractCallRequest) -> Result<ContractCallResponse> {
+        self.on_chain(None)?.call(req).await
+    }
+
+    async fn erc20_transfer(&self, req: Erc20TransferRequest) -> Result<TxResult> {
+        self.on_chain(None)?.erc20_transfer(req).await
+    }
+}
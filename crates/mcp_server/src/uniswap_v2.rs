@@ -1,4 +1,5 @@
 #[cfg(feature = "bonus_uniswap_v2")]
+#[allow(dead_code, clippy::module_inception)]
 pub mod uniswap_v2 {
     use domain::{Address, UniswapV2SwapRequest, UniswapV2SwapResponse};
 
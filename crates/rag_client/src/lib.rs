@@ -1,5 +1,9 @@
-use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Tokens past this point in a chunk are ignored by `embed`
+const MAX_EMBED_TOKENS: usize = 4096;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DocChunk {
@@ -7,24 +11,101 @@ pub struct DocChunk {
     pub text: String,
 }
 
+/// Turns text into vectors compared by cosine similarity. `fit` sees the whole corpus
+/// after every ingest, so corpus-aware scorers can rebuild their statistics.
+pub trait Embedder {
+    fn fit(&mut self, _corpus: &[DocChunk]) {}
+    fn embed(&self, text: &str) -> Vec<f32>;
+}
+
+/// Corpus-independent length-hash embedding; cheap but blind to term importance
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HashEmbedder;
+
+impl Embedder for HashEmbedder {
+    fn embed(&self, text: &str) -> Vec<f32> { embed(text) }
+}
+
+/// TF-IDF over the ingested corpus: rare terms such as a specific function name weigh
+/// more than words most chunks share. Query terms outside the vocabulary are ignored.
+#[derive(Debug, Default, Clone)]
+pub struct TfIdfEmbedder {
+    vocab: HashMap<String, usize>,
+    idf: Vec<f32>,
+}
+
+impl Embedder for TfIdfEmbedder {
+    fn fit(&mut self, corpus: &[DocChunk]) {
+        self.vocab.clear();
+        let mut df: Vec<usize> = Vec::new();
+        for chunk in corpus {
+            let mut seen = vec![false; df.len()];
+            for term in terms(&chunk.text) {
+                let next = self.vocab.len();
+                let idx = *self.vocab.entry(term).or_insert(next);
+                if idx == df.len() {
+                    df.push(0);
+                    seen.push(false);
+                }
+                if !seen[idx] {
+                    seen[idx] = true;
+                    df[idx] += 1;
+                }
+            }
+        }
+        // Smoothed so a term in every chunk still counts a little and none divides by zero
+        let n = corpus.len() as f32;
+        self.idf = df.into_iter().map(|d| ((1.0 + n) / (1.0 + d as f32)).ln() + 1.0).collect();
+    }
+
+    fn embed(&self, text: &str) -> Vec<f32> {
+        let mut v = vec![0.0; self.idf.len()];
+        for term in terms(text) {
+            if let Some(&idx) = self.vocab.get(&term) { v[idx] += self.idf[idx]; }
+        }
+        v
+    }
+}
+
+/// Lowercased alphanumeric runs, capped like `embed`
+fn terms(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .take(MAX_EMBED_TOKENS)
+        .map(str::to_lowercase)
+}
+
 #[derive(Default)]
-pub struct RagStore {
+pub struct RagStore<E: Embedder = HashEmbedder> {
     chunks: Vec<DocChunk>,
+    /// One per chunk, recomputed on ingest since a corpus-aware embedder refits then
+    vectors: Vec<Vec<f32>>,
+    embedder: E,
 }
 
 impl RagStore {
-    pub fn new() -> Self { Self { chunks: Vec::new() } }
+    pub fn new() -> Self { Self::with_embedder(HashEmbedder) }
+}
+
+impl<E: Embedder> RagStore<E> {
+    pub fn with_embedder(embedder: E) -> Self { Self { chunks: Vec::new(), vectors: Vec::new(), embedder } }
 
-    pub fn ingest(&mut self, chunks: Vec<DocChunk>) { self.chunks.extend(chunks); }
+    pub fn ingest(&mut self, chunks: Vec<DocChunk>) {
+        self.chunks.extend(chunks);
+        self.embedder.fit(&self.chunks);
+        self.vectors = self.chunks.iter().map(|c| self.embedder.embed(&c.text)).collect();
+    }
 
     pub fn top_k(&self, query: &str, k: usize) -> Vec<DocChunk> {
-        let q_vec = embed(query);
+        let q_vec = self.embedder.embed(query);
         let mut scored: Vec<(f32, &DocChunk)> = self
-            .chunks
+            .vectors
             .iter()
-            .map(|c| (cosine(&q_vec, &embed(&c.text)), c))
+            .zip(&self.chunks)
+            .map(|(v, c)| (cosine(&q_vec, v), c))
             .collect();
-        scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+        // Ties break on id so equal scores come back in a stable order
+        scored.sort_by(|a, b| score_desc(a.0, b.0).then_with(|| a.1.id.cmp(&b.1.id)));
         scored.into_iter().take(k).map(|(_, c)| c.clone()).collect()
     }
 }
@@ -32,11 +113,21 @@ impl RagStore {
 fn embed(text: &str) -> Vec<f32> {
     // Naive bag-of-words length-10 hash embedding to avoid extra deps
     let mut v = vec![0.0; 10];
-    for (i, t) in text.split_whitespace().enumerate() { v[i % 10] += (t.len() as f32).sqrt(); }
+    for (i, t) in text.split_whitespace().take(MAX_EMBED_TOKENS).enumerate() { v[i % 10] += (t.len() as f32).sqrt(); }
     v
 }
 
-fn cosine(a: &Vec<f32>, b: &Vec<f32>) -> f32 {
+/// Descending by score; NaN sorts last so a bad score never panics or outranks a real one.
+fn score_desc(a: f32, b: f32) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => b.partial_cmp(&a).unwrap_or(Ordering::Equal),
+    }
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
     let dot: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
     let na = (a.iter().map(|x| x * x).sum::<f32>()).sqrt();
     let nb = (b.iter().map(|x| x * x).sum::<f32>()).sqrt();
@@ -46,6 +137,7 @@ fn cosine(a: &Vec<f32>, b: &Vec<f32>) -> f32 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use anyhow::Result;
 
     #[test]
     fn test_top_k_returns_relevant_chunks() -> Result<()> {
@@ -59,4 +151,64 @@ mod tests {
         assert!(!res.is_empty());
         Ok(())
     }
+
+    #[test]
+    fn empty_query_scores_zero_without_panicking() {
+        let mut store = RagStore::new();
+        store.ingest(vec![DocChunk { id: "1".into(), text: "ENS resolution".into() }]);
+        assert_eq!(embed(""), vec![0.0; 10]);
+        assert_eq!(store.top_k("", 1).len(), 1);
+    }
+
+    #[test]
+    fn nan_scores_sort_last() {
+        let mut scores = [0.2, f32::NAN, 0.9, 0.5];
+        scores.sort_by(|a, b| score_desc(*a, *b));
+        assert_eq!(&scores[..3], &[0.9, 0.5, 0.2]);
+        assert!(scores[3].is_nan());
+    }
+
+    #[test]
+    fn equal_scores_are_ordered_by_id() {
+        let mut store = RagStore::new();
+        store.ingest(vec![
+            DocChunk { id: "b".into(), text: "swap tokens".into() },
+            DocChunk { id: "c".into(), text: "unrelated".into() },
+            DocChunk { id: "a".into(), text: "swap tokens".into() },
+        ]);
+        let ids: Vec<String> = store.top_k("swap tokens", 2).into_iter().map(|c| c.id).collect();
+        assert_eq!(ids, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn huge_chunk_is_capped_and_fast() {
+        let huge = "token ".repeat(2_000_000);
+        let start = std::time::Instant::now();
+        let v = embed(&huge);
+        assert!(start.elapsed() < std::time::Duration::from_secs(1));
+        assert_eq!(v, embed(&"token ".repeat(MAX_EMBED_TOKENS)));
+
+        let mut store = RagStore::new();
+        store.ingest(vec![
+            DocChunk { id: "huge".into(), text: huge },
+            DocChunk { id: "small".into(), text: "token".into() },
+        ]);
+        assert_eq!(store.top_k("token", 2).len(), 2);
+    }
+
+    #[test]
+    fn tf_idf_ranks_a_rare_term_above_common_words() {
+        let mut store = RagStore::with_embedder(TfIdfEmbedder::default());
+        store.ingest(vec![
+            DocChunk { id: "router".into(), text: "Router function swapExactETHForTokens".into() },
+            DocChunk { id: "common".into(), text: "how to add liquidity to the pool".into() },
+            DocChunk { id: "ens".into(), text: "how to resolve a name with the registry".into() },
+        ]);
+        // A later ingest refits the IDF table over the whole corpus
+        store.ingest(vec![DocChunk { id: "eip55".into(), text: "how the checksum is applied to an address".into() }]);
+
+        let ids: Vec<String> = store.top_k("how to use swapExactETHForTokens", 2).into_iter().map(|c| c.id).collect();
+        assert_eq!(ids, vec!["router", "common"]);
+        assert_eq!(store.top_k("unknown words only", 1).len(), 1, "out-of-vocabulary queries still return chunks");
+    }
 }
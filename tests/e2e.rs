@@ -2,6 +2,12 @@ use std::process::{Command, Stdio};
 use std::time::Duration;
 use tokio::time::sleep;
 use anyhow::Result;
+use serde_json::Value;
+
+/// Parse the single JSON document printed by `baml_client --json`
+fn parse_cli_json(stdout: &str) -> Option<Value> {
+    stdout.lines().rev().find_map(|line| serde_json::from_str::<Value>(line.trim()).ok())
+}
 
 #[tokio::test]
 async fn test_e2e_send_eth_from_alice_to_bob() {
@@ -69,6 +75,7 @@ async fn test_e2e_send_eth_from_alice_to_bob() {
         .args([
             "run", "-p", "baml_client", "--",
             "--mock", // Use mock provider for deterministic testing
+            "--json",
             "-q", "Send 1 ETH from Alice to Bob"
         ])
         .output();
@@ -98,14 +105,11 @@ async fn test_e2e_send_eth_from_alice_to_bob() {
     // Step 4: Verify the result
     match cli_result {
         Ok(output) => {
-            // Check that the output contains expected function and response
-            assert!(
-                output.contains("Function: send"),
-                "CLI should select 'send' function"
-            );
+            let json = parse_cli_json(&output).expect("CLI --json should print a JSON document");
+            assert_eq!(json["function"], "send", "CLI should select 'send' function");
             assert!(
-                output.contains("Response:"),
-                "CLI should output a response"
+                json["result"].get("tx_hash").is_some(),
+                "send response should include tx_hash"
             );
             
             println!("✅ E2E test passed: CLI successfully executed send command");
@@ -132,6 +136,7 @@ async fn test_e2e_balance_check() {
         .args([
             "run", "-p", "baml_client", "--",
             "--mock",
+            "--json",
             "-q", "What's vitalik.eth's balance?"
         ])
         .output();
@@ -147,13 +152,11 @@ async fn test_e2e_balance_check() {
             }
             
             if output.status.success() {
+                let json = parse_cli_json(&stdout).expect("CLI --json should print a JSON document");
+                assert_eq!(json["function"], "balance", "CLI should select 'balance' function");
                 assert!(
-                    stdout.contains("Function: balance"),
-                    "CLI should select 'balance' function"
-                );
-                assert!(
-                    stdout.contains("Response:"),
-                    "CLI should output a response"
+                    json["result"]["balance"].is_string(),
+                    "balance response should include a wei string"
                 );
                 println!("✅ E2E balance test passed");
             } else {
@@ -175,6 +178,7 @@ async fn test_e2e_code_check() {
         .args([
             "run", "-p", "baml_client", "--",
             "--mock",
+            "--json",
             "-q", "Check if 0x0000000000000000000000000000000000000000 has deployed code"
         ])
         .output();
@@ -190,13 +194,11 @@ async fn test_e2e_code_check() {
             }
             
             if output.status.success() {
+                let json = parse_cli_json(&stdout).expect("CLI --json should print a JSON document");
+                assert_eq!(json["function"], "code", "CLI should select 'code' function");
                 assert!(
-                    stdout.contains("Function: code"),
-                    "CLI should select 'code' function"
-                );
-                assert!(
-                    stdout.contains("Response:"),
-                    "CLI should output a response"
+                    json["result"]["deployed"].is_boolean(),
+                    "code response should include deployed flag"
                 );
                 println!("✅ E2E code check test passed");
             } else {